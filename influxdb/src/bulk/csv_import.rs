@@ -0,0 +1,514 @@
+//! Import of CSV files
+
+use chrono::DateTime;
+use futures::channel::mpsc;
+use futures::executor::block_on;
+use futures::{SinkExt, StreamExt};
+use std::io::Read;
+use std::ops::RangeInclusive;
+use std::thread;
+
+use crate::{Client, Error, Precision, Timestamp, Type, WriteQuery};
+
+/// How the values of the time column are formatted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// Date and time with offset, e.g. `2021-03-08T12:30:00Z`
+    Rfc3339,
+    /// Integer time since the unix epoch in the given precision, e.g. `1615206600` in seconds
+    Unix(Precision),
+}
+
+/// Type a column is converted to when it is written as a field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    Boolean,
+    Float,
+    SignedInteger,
+    UnsignedInteger,
+    Text,
+    /// The first type the value can be parsed as, trying booleans, signed integers, floats and
+    /// finally text
+    Infer,
+}
+
+#[derive(Debug, Clone)]
+enum MeasurementSource {
+    Fixed(String),
+    Column(String),
+}
+
+/// Maps the columns of a CSV file to the measurement, tags, fields and time of points
+///
+/// Columns are referred to by their name in the header row. Columns which are not mapped are
+/// ignored.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::bulk::{CsvMapping, FieldType, TimeFormat};
+/// use influxdb::Precision;
+///
+/// let mapping = CsvMapping::new("weather")
+///     .time_column("time", TimeFormat::Unix(Precision::Seconds))
+///     .tag("location")
+///     .field("temperature", FieldType::Float)
+///     .field("humidity", FieldType::Infer);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CsvMapping {
+    measurement: MeasurementSource,
+    time: Option<(String, TimeFormat)>,
+    tags: Vec<String>,
+    fields: Vec<(String, FieldType)>,
+}
+
+impl CsvMapping {
+    /// Creates a mapping writing all rows to `measurement`
+    pub fn new<S>(measurement: S) -> Self
+    where
+        S: Into<String>,
+    {
+        CsvMapping {
+            measurement: MeasurementSource::Fixed(measurement.into()),
+            time: None,
+            tags: Vec::new(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Creates a mapping taking the measurement of each row from `column`
+    pub fn measurement_column<S>(column: S) -> Self
+    where
+        S: Into<String>,
+    {
+        CsvMapping {
+            measurement: MeasurementSource::Column(column.into()),
+            ..CsvMapping::new("")
+        }
+    }
+
+    /// Sets the column holding the time of each row
+    ///
+    /// Without a time column, every row has to be mapped to a unique combination of measurement and
+    /// tags, as all points are written with the time the import started.
+    pub fn time_column<S>(mut self, column: S, format: TimeFormat) -> Self
+    where
+        S: Into<String>,
+    {
+        self.time = Some((column.into(), format));
+        self
+    }
+
+    /// Adds a column written as tag
+    pub fn tag<S>(mut self, column: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.tags.push(column.into());
+        self
+    }
+
+    /// Adds a column written as field of the given type
+    pub fn field<S>(mut self, column: S, field_type: FieldType) -> Self
+    where
+        S: Into<String>,
+    {
+        self.fields.push((column.into(), field_type));
+        self
+    }
+}
+
+/// Failure to import some lines of a CSV file
+#[derive(Debug)]
+pub struct ImportError {
+    /// Lines of the file which were not imported, a single line if it could not be mapped to a
+    /// point, the lines of the whole batch if writing it failed
+    pub lines: RangeInclusive<u64>,
+    pub error: Error,
+}
+
+/// Progress of a CSV import, passed to the progress callback after every batch and returned once
+/// the import finished
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Number of rows read, not counting the header
+    pub rows_read: u64,
+    /// Number of points the server accepted
+    pub points_written: u64,
+    pub errors: Vec<ImportError>,
+}
+
+/// A row of the file, as sent by the thread reading it
+enum Row {
+    Point(u64, WriteQuery),
+    Invalid(u64, Error),
+    /// The header row didn't match the mapping or the file couldn't be read, ends the import
+    Failed(Error),
+}
+
+/// Column indexes resolved from the header row
+struct Columns {
+    measurement: Option<usize>,
+    time: Option<(usize, TimeFormat)>,
+    tags: Vec<(String, usize)>,
+    fields: Vec<(String, usize, FieldType)>,
+}
+
+/// Streams the rows of a CSV file with a header row, and writes them as points in batches of
+/// `batch_size`
+///
+/// Rows which cannot be mapped to a point, e.g. because they are not valid UTF-8, and batches
+/// which cannot be written are reported in the [`errors`](crate::bulk::ImportReport::errors) of
+/// the returned report, and the import continues with the next row. Rows may have fewer or more
+/// columns than the header, missing columns are empty. `progress` is called after every batch. An
+/// error is only returned if the header row is missing a mapped column or reading the file fails.
+///
+/// The file is read on a thread of its own, as reading blocks, at most one batch ahead of the
+/// writes.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use influxdb::bulk::{import_csv, CsvMapping, FieldType, TimeFormat};
+/// use influxdb::{Client, Precision};
+/// use std::fs::File;
+///
+/// # #[async_std::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = Client::new("http://localhost:8086", "test");
+/// let mapping = CsvMapping::new("weather")
+///     .time_column("time", TimeFormat::Rfc3339)
+///     .tag("location")
+///     .field("temperature", FieldType::Float);
+///
+/// let report = import_csv(&client, File::open("weather.csv")?, &mapping, 5000, |report| {
+///     eprintln!("{} rows imported", report.rows_read)
+/// })
+/// .await?;
+/// for error in report.errors {
+///     eprintln!("lines {:?} failed: {}", error.lines, error.error);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn import_csv<R, F>(
+    client: &Client,
+    reader: R,
+    mapping: &CsvMapping,
+    batch_size: usize,
+    mut progress: F,
+) -> Result<ImportReport, Error>
+where
+    R: Read + Send + 'static,
+    F: FnMut(&ImportReport),
+{
+    let batch_size = batch_size.max(1);
+    let mut rows = read_rows(reader, mapping.clone(), batch_size);
+
+    let mut report = ImportReport::default();
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut first_line = None;
+    let mut last_line = 0;
+    loop {
+        let row = rows.next().await;
+        let more = row.is_some();
+        match row {
+            Some(Row::Point(line, point)) => {
+                report.rows_read += 1;
+                first_line.get_or_insert(line);
+                last_line = line;
+                batch.push(point);
+            }
+            Some(Row::Invalid(line, error)) => {
+                report.rows_read += 1;
+                report.errors.push(ImportError {
+                    lines: line..=line,
+                    error,
+                });
+            }
+            Some(Row::Failed(error)) => return Err(error),
+            None => {}
+        }
+
+        if batch.len() >= batch_size || (!more && !batch.is_empty()) {
+            let lines = first_line.take().unwrap_or(last_line)..=last_line;
+            match client.query(&batch).await {
+                Ok(_) => report.points_written += batch.len() as u64,
+                Err(error) => report.errors.push(ImportError { lines, error }),
+            }
+            batch.clear();
+            progress(&report);
+        }
+        if !more {
+            return Ok(report);
+        }
+    }
+}
+
+/// Reads and maps the rows on a thread of its own, which stops once the receiver is dropped
+fn read_rows<R>(reader: R, mapping: CsvMapping, buffer: usize) -> mpsc::Receiver<Row>
+where
+    R: Read + Send + 'static,
+{
+    let (mut sender, receiver) = mpsc::channel(buffer);
+    thread::spawn(move || {
+        let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+        let columns = match reader.headers().map_err(csv_error) {
+            Ok(headers) => resolve(&mapping, headers),
+            Err(error) => Err(error),
+        };
+        let columns = match columns {
+            Ok(columns) => columns,
+            Err(error) => {
+                let _ = block_on(sender.send(Row::Failed(error)));
+                return;
+            }
+        };
+        let started = Timestamp::now();
+
+        let mut record = csv::StringRecord::new();
+        loop {
+            let row = match reader.read_record(&mut record) {
+                Ok(false) => return,
+                Ok(true) => {
+                    let line = record.position().map_or(0, |position| position.line());
+                    match to_point(&mapping, &columns, &record, started) {
+                        Ok(point) => Row::Point(line, point),
+                        Err(error) => Row::Invalid(line, error),
+                    }
+                }
+                // reading again would most likely fail again
+                Err(error) if error.is_io_error() => Row::Failed(csv_error(error)),
+                Err(error) => {
+                    let line = error.position().map_or(0, |position| position.line());
+                    Row::Invalid(line, csv_error(error))
+                }
+            };
+            let failed = matches!(row, Row::Failed(_));
+            if block_on(sender.send(row)).is_err() || failed {
+                return;
+            }
+        }
+    });
+    receiver
+}
+
+fn resolve(mapping: &CsvMapping, headers: &csv::StringRecord) -> Result<Columns, Error> {
+    let index = |column: &str| {
+        headers
+            .iter()
+            .position(|header| header == column)
+            .ok_or_else(|| Error::DeserializationError {
+                error: format!("CSV file has no column {}", column),
+                source: None,
+            })
+    };
+
+    Ok(Columns {
+        measurement: match &mapping.measurement {
+            MeasurementSource::Fixed(_) => None,
+            MeasurementSource::Column(column) => Some(index(column)?),
+        },
+        time: match &mapping.time {
+            Some((column, format)) => Some((index(column)?, *format)),
+            None => None,
+        },
+        tags: mapping
+            .tags
+            .iter()
+            .map(|column| Ok((column.clone(), index(column)?)))
+            .collect::<Result<_, Error>>()?,
+        fields: mapping
+            .fields
+            .iter()
+            .map(|(column, field_type)| Ok((column.clone(), index(column)?, *field_type)))
+            .collect::<Result<_, Error>>()?,
+    })
+}
+
+fn to_point(
+    mapping: &CsvMapping,
+    columns: &Columns,
+    record: &csv::StringRecord,
+    default_time: Timestamp,
+) -> Result<WriteQuery, Error> {
+    // columns missing from short rows are empty
+    let value = |index: usize| record.get(index).unwrap_or_default();
+
+    let measurement = match (&mapping.measurement, columns.measurement) {
+        (_, Some(index)) => value(index),
+        (MeasurementSource::Fixed(measurement), None) => measurement.as_str(),
+        (MeasurementSource::Column(_), None) => unreachable!("column resolved from the header"),
+    };
+    let timestamp = match columns.time {
+        Some((index, format)) => parse_time(value(index), format)?,
+        None => default_time,
+    };
+
+    let mut point = WriteQuery::new(timestamp, measurement);
+    for (tag, index) in &columns.tags {
+        // empty tag values are not allowed by the line protocol
+        if !value(*index).is_empty() {
+            point = point.add_tag(tag.as_str(), value(*index));
+        }
+    }
+    for (field, index, field_type) in &columns.fields {
+        if let Some(value) = parse_field(value(*index), *field_type)? {
+            point = point.add_field(field.as_str(), value);
+        }
+    }
+    if point.fields().is_empty() {
+        return Err(Error::DeserializationError {
+            error: "row has no field values".to_string(),
+            source: None,
+        });
+    }
+    Ok(point)
+}
+
+fn parse_time(value: &str, format: TimeFormat) -> Result<Timestamp, Error> {
+    let invalid = || Error::DeserializationError {
+        error: format!("invalid time {}", value),
+        source: None,
+    };
+    match format {
+        TimeFormat::Rfc3339 => {
+            let nanos = DateTime::parse_from_rfc3339(value)
+                .map_err(|_| invalid())?
+                .timestamp_nanos_opt()
+                .filter(|nanos| *nanos >= 0)
+                .ok_or_else(invalid)?;
+            Ok(Timestamp::Nanoseconds(nanos as u128))
+        }
+        TimeFormat::Unix(precision) => {
+            let value = value.parse().map_err(|_| invalid())?;
+            Ok(match precision {
+                Precision::Nanoseconds => Timestamp::Nanoseconds(value),
+                Precision::Microseconds => Timestamp::Microseconds(value),
+                Precision::Milliseconds => Timestamp::Milliseconds(value),
+                Precision::Seconds => Timestamp::Seconds(value),
+                Precision::Minutes => Timestamp::Minutes(value),
+                Precision::Hours => Timestamp::Hours(value),
+            })
+        }
+    }
+}
+
+/// Parses a field value, empty values are skipped
+fn parse_field(value: &str, field_type: FieldType) -> Result<Option<Type>, Error> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let invalid = || Error::DeserializationError {
+        error: format!("invalid {:?} value {}", field_type, value),
+        source: None,
+    };
+    let value = match field_type {
+        FieldType::Boolean => Type::Boolean(value.parse().map_err(|_| invalid())?),
+        FieldType::Float => Type::Float(value.parse().map_err(|_| invalid())?),
+        FieldType::SignedInteger => Type::SignedInteger(value.parse().map_err(|_| invalid())?),
+        FieldType::UnsignedInteger => Type::UnsignedInteger(value.parse().map_err(|_| invalid())?),
+        FieldType::Text => Type::Text(value.to_string()),
+        FieldType::Infer => value
+            .parse()
+            .map(Type::Boolean)
+            .or_else(|_| value.parse().map(Type::SignedInteger))
+            .or_else(|_| value.parse().map(Type::Float))
+            .unwrap_or_else(|_| Type::Text(value.to_string())),
+    };
+    Ok(Some(value))
+}
+
+fn csv_error(error: csv::Error) -> Error {
+    Error::DeserializationError {
+        error: format!("invalid CSV: {}", error),
+        source: crate::error::source(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_rows, resolve, to_point, CsvMapping, FieldType, Row, TimeFormat};
+    use crate::{Precision, Query, Timestamp};
+    use futures::executor::block_on_stream;
+
+    #[test]
+    fn test_to_point() {
+        let csv = "time,city,temperature,note,station\n\
+                   1615206600,Berlin,12.5,cloudy,7\n\
+                   1615206660,,13,,\n\
+                   nope,Berlin,12.5,,\n";
+        let mapping = CsvMapping::new("weather")
+            .time_column("time", TimeFormat::Unix(Precision::Seconds))
+            .tag("city")
+            .field("temperature", FieldType::Float)
+            .field("note", FieldType::Text)
+            .field("station", FieldType::Infer);
+
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        let columns = resolve(&mapping, reader.headers().unwrap()).unwrap();
+        let points = reader
+            .records()
+            .map(|record| to_point(&mapping, &columns, &record.unwrap(), Timestamp::Seconds(0)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            points[0].as_ref().unwrap().build().unwrap().get(),
+            r#"weather,city=Berlin temperature=12.5,note="cloudy",station=7i 1615206600"#
+        );
+        assert_eq!(
+            points[1].as_ref().unwrap().build().unwrap().get(),
+            "weather temperature=13 1615206660"
+        );
+        assert!(points[2].is_err());
+    }
+
+    #[test]
+    fn test_missing_column() {
+        let mapping = CsvMapping::measurement_column("name").field("value", FieldType::Float);
+        let headers = csv::StringRecord::from(vec!["value"]);
+        assert!(resolve(&mapping, &headers).is_err());
+    }
+
+    #[test]
+    fn test_read_rows() {
+        let mut csv = b"time,temperature,note\n\
+                        1,12.5\n\
+                        2,13,windy,extra\n\
+                        3,"
+        .to_vec();
+        csv.extend_from_slice(&[0xff, b'\n']);
+        csv.extend_from_slice(b"nope,14,\n4,15,\n");
+        let mapping = CsvMapping::new("weather")
+            .time_column("time", TimeFormat::Unix(Precision::Seconds))
+            .field("temperature", FieldType::Float)
+            .field("note", FieldType::Text);
+
+        let rows = block_on_stream(read_rows(std::io::Cursor::new(csv), mapping, 1))
+            .map(|row| match row {
+                Row::Point(line, point) => Ok((line, point.build().unwrap().get())),
+                Row::Invalid(line, _) => Err(line),
+                Row::Failed(error) => panic!("import failed: {}", error),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            vec![
+                Ok((2, "weather temperature=12.5 1".to_string())),
+                Ok((3, r#"weather temperature=13,note="windy" 2"#.to_string())),
+                Err(4),
+                Err(5),
+                Ok((6, "weather temperature=15 4".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_rows_missing_column() {
+        let mapping = CsvMapping::new("weather").field("humidity", FieldType::Float);
+        let mut rows = block_on_stream(read_rows(&b"time,temperature\n1,12\n"[..], mapping, 1));
+        assert!(matches!(rows.next(), Some(Row::Failed(_))));
+        assert!(rows.next().is_none());
+    }
+}
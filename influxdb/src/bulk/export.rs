@@ -0,0 +1,235 @@
+//! Export of query results
+
+use futures::prelude::*;
+use serde_json::{Map, Value};
+use std::io::Write;
+
+use super::response::{chunk_series, Chunk, Series};
+use crate::{Client, Error, Query, ReadQuery};
+
+/// Number of rows the server sends per chunk
+const CHUNK_SIZE: &str = "10000";
+
+/// Format of exported rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Comma separated values, with the columns `name` and `tags` followed by the columns of the
+    /// series. A header row is written before the first row and whenever the columns change.
+    /// Tags are written as `key=value` pairs separated by commas.
+    Csv,
+    /// One JSON object per row, holding `name`, `tags` and the value of every column
+    JsonLines,
+}
+
+/// Streams the results of a query into `writer` and returns the number of exported rows
+///
+/// The query is sent with `chunked=true`, so the server streams its results and neither side
+/// holds all of them in memory. The rows are written to `writer` synchronously as each chunk
+/// arrives, wrap files in a [`BufWriter`](std::io::BufWriter) to avoid a system call per row.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use influxdb::bulk::{export, ExportFormat};
+/// use influxdb::{Client, ReadQuery};
+/// use std::fs::File;
+/// use std::io::BufWriter;
+///
+/// # #[async_std::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = Client::new("http://localhost:8086", "test");
+/// let query = ReadQuery::new("SELECT * FROM weather WHERE time > now() - 30d");
+/// let file = BufWriter::new(File::create("weather.csv")?);
+///
+/// let rows = export(&client, &query, file, ExportFormat::Csv).await?;
+/// eprintln!("exported {} rows", rows);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn export<W>(
+    client: &Client,
+    query: &ReadQuery,
+    mut writer: W,
+    format: ExportFormat,
+) -> Result<u64, Error>
+where
+    W: Write,
+{
+    let read_query = query.build()?.get();
+    let request = client
+        .with_headers(client.read_request(
+            read_query,
+            &[("chunked", "true"), ("chunk_size", CHUNK_SIZE)],
+        )?)
+        .build();
+    let mut response = client.send_request(request).await?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.body_string().await.unwrap_or_default();
+        return Err(Error::DatabaseError {
+            error: format!("influxdb error: \"{}\"", body),
+            status: Some(status.into()),
+            request_id: None,
+        });
+    }
+
+    let mut rows = 0;
+    let mut header = None;
+    let mut lines = response.lines();
+    while let Some(line) = lines.next().await {
+        let line = line.map_err(|err| Error::ConnectionError {
+            error: err.to_string(),
+            source: crate::error::source(err),
+        })?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let chunk: Chunk =
+            serde_json::from_str(&line).map_err(|err| Error::DeserializationError {
+                error: err.to_string(),
+                source: crate::error::source(err),
+            })?;
+        for series in chunk_series(chunk)? {
+            rows += write_series(&mut writer, &series, format, &mut header).map_err(io_error)?;
+        }
+    }
+    writer.flush().map_err(io_error)?;
+    Ok(rows)
+}
+
+/// Writes the rows of a series, `header` holds the columns of the last header row written
+fn write_series<W>(
+    writer: &mut W,
+    series: &Series,
+    format: ExportFormat,
+    header: &mut Option<Vec<String>>,
+) -> std::io::Result<u64>
+where
+    W: Write,
+{
+    match format {
+        ExportFormat::Csv => {
+            if header.as_ref() != Some(&series.columns) {
+                let names = ["name", "tags"].iter().copied();
+                write_csv_row(
+                    writer,
+                    names.chain(series.columns.iter().map(String::as_str)),
+                )?;
+                *header = Some(series.columns.clone());
+            }
+            let tags = series
+                .tags
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect::<Vec<_>>()
+                .join(",");
+            for row in &series.values {
+                let values = row.iter().map(|value| match value {
+                    Value::Null => String::new(),
+                    Value::String(value) => value.clone(),
+                    value => value.to_string(),
+                });
+                let prefix = vec![series.name.clone(), tags.clone()];
+                write_csv_row(writer, prefix.into_iter().chain(values))?;
+            }
+        }
+        ExportFormat::JsonLines => {
+            for row in &series.values {
+                let mut object = Map::new();
+                object.insert("name".to_string(), Value::from(series.name.as_str()));
+                if !series.tags.is_empty() {
+                    let tags = series
+                        .tags
+                        .iter()
+                        .map(|(key, value)| (key.clone(), Value::from(value.as_str())))
+                        .collect();
+                    object.insert("tags".to_string(), Value::Object(tags));
+                }
+                for (column, value) in series.columns.iter().zip(row) {
+                    object.insert(column.clone(), value.clone());
+                }
+                serde_json::to_writer(&mut *writer, &object)?;
+                writer.write_all(b"\n")?;
+            }
+        }
+    }
+    Ok(series.values.len() as u64)
+}
+
+fn write_csv_row<W, I, S>(writer: &mut W, values: I) -> std::io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    for (i, value) in values.into_iter().enumerate() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        let value = value.as_ref();
+        if value.contains([',', '"', '\n', '\r']) {
+            write!(writer, "\"{}\"", value.replace('"', "\"\""))?;
+        } else {
+            writer.write_all(value.as_bytes())?;
+        }
+    }
+    writer.write_all(b"\n")
+}
+
+fn io_error(err: std::io::Error) -> Error {
+    Error::IoError {
+        error: err.to_string(),
+        source: crate::error::source(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::response::{chunk_series, Chunk};
+    use super::{write_series, ExportFormat};
+
+    const CHUNKS: [&str; 2] = [
+        r#"{"results":[{"statement_id":0,"series":[{"name":"weather","tags":{"city":"Berlin"},"columns":["time","temperature","note"],"values":[["2021-03-08T12:30:00Z",12.5,"cloudy, cold"],["2021-03-08T12:31:00Z",13,null]]}],"partial":true}]}"#,
+        r#"{"results":[{"statement_id":0,"series":[{"name":"weather","tags":{"city":"Paris"},"columns":["time","temperature","note"],"values":[["2021-03-08T12:30:00Z",15,"sunny"]]}]}]}"#,
+    ];
+
+    fn export(format: ExportFormat) -> String {
+        let mut output = Vec::new();
+        let mut header = None;
+        for chunk in &CHUNKS {
+            let chunk: Chunk = serde_json::from_str(chunk).unwrap();
+            for series in chunk_series(chunk).unwrap() {
+                write_series(&mut output, &series, format, &mut header).unwrap();
+            }
+        }
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_export_csv() {
+        assert_eq!(
+            export(ExportFormat::Csv),
+            "name,tags,time,temperature,note\n\
+             weather,city=Berlin,2021-03-08T12:30:00Z,12.5,\"cloudy, cold\"\n\
+             weather,city=Berlin,2021-03-08T12:31:00Z,13,\n\
+             weather,city=Paris,2021-03-08T12:30:00Z,15,sunny\n"
+        );
+    }
+
+    #[test]
+    fn test_export_json_lines() {
+        assert_eq!(
+            export(ExportFormat::JsonLines).lines().next().unwrap(),
+            r#"{"name":"weather","note":"cloudy, cold","tags":{"city":"Berlin"},"temperature":12.5,"time":"2021-03-08T12:30:00Z"}"#
+        );
+    }
+
+    #[test]
+    fn test_export_error() {
+        let chunk: Chunk = serde_json::from_str(
+            r#"{"results":[{"statement_id":0,"error":"database not found: test"}]}"#,
+        )
+        .unwrap();
+        assert!(chunk_series(chunk).is_err());
+    }
+}
@@ -0,0 +1,17 @@
+//! Bulk transfers of points into and out of InfluxDB
+
+#[cfg(feature = "csv-import")]
+mod csv_import;
+#[cfg(feature = "use-serde")]
+mod export;
+#[cfg(feature = "use-serde")]
+mod replicate;
+#[cfg(feature = "use-serde")]
+mod response;
+
+#[cfg(feature = "csv-import")]
+pub use csv_import::{import_csv, CsvMapping, FieldType, ImportError, ImportReport, TimeFormat};
+#[cfg(feature = "use-serde")]
+pub use export::{export, ExportFormat};
+#[cfg(feature = "use-serde")]
+pub use replicate::replicate;
@@ -0,0 +1,217 @@
+//! Copying of points between servers
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::response::{chunk_series, Chunk, Series};
+use crate::query::influxql_term::InfluxQlTerm;
+use crate::{Client, Error, ReadQuery, TimeRange, Timestamp, Type, WriteQuery};
+
+/// Copies the points of a measurement within a time range from `source` to `destination` and
+/// returns the number of copied points
+///
+/// The points are read in time order, up to `batch_size` points per series at a time, and written
+/// to the database of `destination` with their original tags, field types and timestamps.
+///
+/// After every batch, `checkpoint` is called with the time up to which all points have been
+/// copied. An interrupted replication can be resumed by replicating again with a time range
+/// starting at the last checkpoint. Points at the checkpoint are then copied once more, which
+/// overwrites them with the same values.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use chrono::{Duration, Utc};
+/// use influxdb::bulk::replicate;
+/// use influxdb::{Client, TimeRange};
+///
+/// # #[async_std::main]
+/// # async fn main() -> Result<(), influxdb::Error> {
+/// let source = Client::new("http://old-server:8086", "telemetry");
+/// let destination = Client::new("http://new-server:8086", "telemetry");
+///
+/// let copied = replicate(
+///     &source,
+///     &destination,
+///     "cpu",
+///     &TimeRange::last(Duration::days(30)),
+///     10_000,
+///     |checkpoint| eprintln!("copied all points up to {}", checkpoint),
+/// )
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn replicate<F>(
+    source: &Client,
+    destination: &Client,
+    measurement: &str,
+    time_range: &TimeRange,
+    batch_size: usize,
+    mut checkpoint: F,
+) -> Result<u64, Error>
+where
+    F: FnMut(Timestamp),
+{
+    let measurement_term = InfluxQlTerm::Identifier(measurement).escape();
+    let field_types = field_types(source, &measurement_term).await?;
+    let batch_size = batch_size.max(1);
+
+    let mut copied = 0;
+    let mut after: Option<i64> = None;
+    loop {
+        let after_condition = after.map_or(String::new(), |after| format!(" AND time > {}", after));
+        let query = format!(
+            "SELECT * FROM {} WHERE ({}){} GROUP BY * ORDER BY time ASC LIMIT {}",
+            measurement_term, time_range, after_condition, batch_size
+        );
+        let request = source.read_request(query, &[("epoch", "ns")])?;
+        let body = source.send(request).await?;
+        let chunk: Chunk =
+            serde_json::from_str(&body).map_err(|err| Error::DeserializationError {
+                error: err.to_string(),
+                source: crate::error::source(err),
+            })?;
+        let series = chunk_series(chunk)?;
+
+        // series which hit the limit may have more points, all others are complete
+        let complete_until = series
+            .iter()
+            .filter(|series| series.values.len() >= batch_size)
+            .filter_map(|series| series.values.last().and_then(|row| row.first()?.as_i64()))
+            .min();
+        let mut points = Vec::new();
+        for series in &series {
+            for row in &series.values {
+                let point = to_point(measurement, series, row, &field_types)?;
+                if !matches!(complete_until, Some(until) if point.0 > until) {
+                    points.push(point);
+                }
+            }
+        }
+        let last = match complete_until.or_else(|| points.iter().map(|(time, _)| *time).max()) {
+            Some(last) => last,
+            None => return Ok(copied),
+        };
+
+        let points = points
+            .into_iter()
+            .map(|(_, point)| point)
+            .collect::<Vec<_>>();
+        if !points.is_empty() {
+            destination.query(&points).await?;
+            copied += points.len() as u64;
+        }
+        checkpoint(Timestamp::Nanoseconds(last as u128));
+        if complete_until.is_none() {
+            return Ok(copied);
+        }
+        after = Some(last);
+    }
+}
+
+/// Returns the types of the fields of a measurement, e.g. `float`
+async fn field_types(
+    client: &Client,
+    measurement_term: &str,
+) -> Result<HashMap<String, String>, Error> {
+    let body = client
+        .query(&ReadQuery::new(format!(
+            "SHOW FIELD KEYS FROM {}",
+            measurement_term
+        )))
+        .await?;
+    let chunk: Chunk = serde_json::from_str(&body).map_err(|err| Error::DeserializationError {
+        error: err.to_string(),
+        source: crate::error::source(err),
+    })?;
+    Ok(chunk_series(chunk)?
+        .into_iter()
+        .flat_map(|series| series.values)
+        .filter_map(|row| match (row.first(), row.get(1)) {
+            (Some(Value::String(key)), Some(Value::String(field_type))) => {
+                Some((key.clone(), field_type.clone()))
+            }
+            _ => None,
+        })
+        .collect())
+}
+
+/// Converts a row of a series into a point, returned with its time in nanoseconds
+fn to_point(
+    measurement: &str,
+    series: &Series,
+    row: &[Value],
+    field_types: &HashMap<String, String>,
+) -> Result<(i64, WriteQuery), Error> {
+    let invalid = |error: String| Error::DeserializationError {
+        error,
+        source: None,
+    };
+    let time = row
+        .first()
+        .and_then(Value::as_i64)
+        .filter(|time| *time >= 0)
+        .ok_or_else(|| invalid(format!("invalid time {:?}", row.first())))?;
+
+    let mut point = WriteQuery::new(Timestamp::Nanoseconds(time as u128), measurement);
+    for (key, value) in &series.tags {
+        // series without the tag are reported with an empty value
+        if !value.is_empty() {
+            point = point.add_tag(key.as_str(), value.as_str());
+        }
+    }
+    for (column, value) in series.columns.iter().zip(row).skip(1) {
+        if value.is_null() {
+            continue;
+        }
+        let field_type = field_types.get(column).map(String::as_str);
+        let field = match (field_type, value) {
+            (Some("float"), value) => value.as_f64().map(Type::Float),
+            (Some("integer"), value) => value.as_i64().map(Type::SignedInteger),
+            (Some("unsigned"), value) => value.as_u64().map(Type::UnsignedInteger),
+            (Some("boolean"), value) => value.as_bool().map(Type::Boolean),
+            (Some("string"), Value::String(value)) => Some(Type::Text(value.clone())),
+            _ => None,
+        }
+        .ok_or_else(|| invalid(format!("invalid value {} of field {}", value, column)))?;
+        point = point.add_field(column.as_str(), field);
+    }
+    Ok((time, point))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_point;
+    use crate::bulk::response::Series;
+    use crate::Query;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_to_point() {
+        let series: Series = serde_json::from_value(json!({
+            "name": "cpu",
+            "tags": {"host": "server01", "region": ""},
+            "columns": ["time", "idle", "cores", "busy", "note"],
+            "values": [[1615206600000000000i64, 12, 8, true, null]]
+        }))
+        .unwrap();
+        let field_types = vec![
+            ("idle", "float"),
+            ("cores", "integer"),
+            ("busy", "boolean"),
+            ("note", "string"),
+        ]
+        .into_iter()
+        .map(|(key, field_type)| (key.to_string(), field_type.to_string()))
+        .collect::<HashMap<_, _>>();
+
+        let (time, point) = to_point("cpu", &series, &series.values[0], &field_types).unwrap();
+        assert_eq!(time, 1615206600000000000);
+        assert_eq!(
+            point.build().unwrap().get(),
+            "cpu,host=server01 idle=12,cores=8i,busy=true 1615206600000000000"
+        );
+    }
+}
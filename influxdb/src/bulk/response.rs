@@ -0,0 +1,52 @@
+//! Results of queries, as far as the bulk transfers need them
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::Error;
+
+/// Result of a query, or a single chunk of the results of a chunked query
+#[derive(Deserialize)]
+pub(super) struct Chunk {
+    #[serde(default)]
+    pub(super) results: Vec<ChunkResult>,
+    pub(super) error: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub(super) struct ChunkResult {
+    #[serde(default)]
+    pub(super) series: Vec<Series>,
+    pub(super) error: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub(super) struct Series {
+    #[serde(default)]
+    pub(super) name: String,
+    #[serde(default)]
+    pub(super) tags: BTreeMap<String, String>,
+    pub(super) columns: Vec<String>,
+    #[serde(default)]
+    pub(super) values: Vec<Vec<Value>>,
+}
+
+/// Returns the series of all results of a chunk, or the first error it reports
+pub(super) fn chunk_series(chunk: Chunk) -> Result<Vec<Series>, Error> {
+    let results = chunk.results;
+    let error = chunk
+        .error
+        .or_else(|| results.iter().find_map(|result| result.error.clone()));
+    if let Some(error) = error {
+        return Err(Error::DatabaseError {
+            error,
+            status: None,
+            request_id: None,
+        });
+    }
+    Ok(results
+        .into_iter()
+        .flat_map(|result| result.series)
+        .collect())
+}
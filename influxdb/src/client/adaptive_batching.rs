@@ -0,0 +1,166 @@
+//! Batch sizes and flush intervals which adapt to the latency and errors of the server
+
+use std::time::Duration;
+
+use super::buffered_writer::WriterOptions;
+use super::writer_stats::WriterStats;
+
+/// Adaptive mode of a [`BufferedWriter`](crate::BufferedWriter), see
+/// [`WriterOptions::adaptive`](crate::WriterOptions::adaptive)
+///
+/// After each batch, the batch size grows by half while the server answers within the target
+/// latency, and is halved when it answers slower or fails, but stays between the minimum and the
+/// [maximum batch size](crate::WriterOptions::max_batch_size). After a failed batch, the flush
+/// interval is doubled up to the maximum flush interval, so a struggling server is written to less
+/// often, and returns to the [flush interval](crate::WriterOptions::flush_interval) of the options
+/// as batches succeed again.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::{AdaptiveBatching, WriterOptions};
+/// use std::time::Duration;
+///
+/// let options = WriterOptions::default()
+///     .max_batch_size(20_000)
+///     .adaptive(
+///         AdaptiveBatching::default()
+///             .target_latency(Duration::from_millis(200))
+///             .min_batch_size(500),
+///     );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdaptiveBatching {
+    target_latency: Duration,
+    min_batch_size: usize,
+    max_flush_interval: Duration,
+}
+
+impl Default for AdaptiveBatching {
+    fn default() -> Self {
+        AdaptiveBatching {
+            target_latency: Duration::from_millis(500),
+            min_batch_size: 100,
+            max_flush_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+impl AdaptiveBatching {
+    /// Sets the latency up to which batches grow, defaults to 500 milliseconds
+    pub fn target_latency(mut self, target_latency: Duration) -> Self {
+        self.target_latency = target_latency;
+        self
+    }
+
+    /// Sets the size batches don't shrink below, and which the writer starts with, defaults to 100
+    pub fn min_batch_size(mut self, min_batch_size: usize) -> Self {
+        self.min_batch_size = min_batch_size.max(1);
+        self
+    }
+
+    /// Sets the interval the flush interval is backed off to at most after failures, defaults to 30
+    /// seconds
+    pub fn max_flush_interval(mut self, max_flush_interval: Duration) -> Self {
+        self.max_flush_interval = max_flush_interval;
+        self
+    }
+}
+
+/// The batch size and the flush interval the writer currently uses
+#[derive(Debug)]
+pub(crate) struct BatchLimits {
+    pub(crate) batch_size: usize,
+    pub(crate) flush_interval: Duration,
+    max_batch_size: usize,
+    base_flush_interval: Duration,
+    adaptive: Option<AdaptiveBatching>,
+}
+
+impl BatchLimits {
+    pub(crate) fn new(options: &WriterOptions) -> Self {
+        let batch_size = match &options.adaptive {
+            Some(adaptive) => adaptive.min_batch_size.min(options.max_batch_size),
+            None => options.max_batch_size,
+        };
+        BatchLimits {
+            batch_size,
+            flush_interval: options.flush_interval,
+            max_batch_size: options.max_batch_size,
+            base_flush_interval: options.flush_interval,
+            adaptive: options.adaptive.clone(),
+        }
+    }
+
+    /// Returns empty statistics of a writer with these limits
+    pub(crate) fn stats(&self) -> WriterStats {
+        WriterStats::new(self.batch_size, self.flush_interval)
+    }
+
+    /// Adapts the limits to how long writing a batch took and whether it succeeded
+    pub(crate) fn observe(&mut self, latency: Duration, written: bool) {
+        let adaptive = match &self.adaptive {
+            Some(adaptive) => adaptive,
+            None => return,
+        };
+        let min_batch_size = adaptive.min_batch_size.min(self.max_batch_size);
+        if written && latency <= adaptive.target_latency {
+            self.batch_size = (self.batch_size + self.batch_size / 2 + 1).min(self.max_batch_size);
+        } else {
+            self.batch_size = (self.batch_size / 2).max(min_batch_size);
+        }
+        self.flush_interval = if written {
+            (self.flush_interval / 2).max(self.base_flush_interval)
+        } else {
+            (self.flush_interval * 2).min(adaptive.max_flush_interval.max(self.base_flush_interval))
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AdaptiveBatching, BatchLimits};
+    use crate::WriterOptions;
+    use std::time::Duration;
+
+    #[test]
+    fn test_fixed() {
+        let mut limits = BatchLimits::new(&WriterOptions::default());
+        limits.observe(Duration::from_secs(10), false);
+        assert_eq!(limits.batch_size, 5000);
+        assert_eq!(limits.flush_interval, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_adaptive() {
+        let options = WriterOptions::default().max_batch_size(1000).adaptive(
+            AdaptiveBatching::default()
+                .min_batch_size(100)
+                .target_latency(Duration::from_millis(100))
+                .max_flush_interval(Duration::from_secs(3)),
+        );
+        let mut limits = BatchLimits::new(&options);
+        assert_eq!(limits.batch_size, 100);
+
+        let fast = Duration::from_millis(10);
+        limits.observe(fast, true);
+        assert_eq!(limits.batch_size, 151);
+        for _ in 0..10 {
+            limits.observe(fast, true);
+        }
+        assert_eq!(limits.batch_size, 1000);
+
+        limits.observe(Duration::from_millis(200), true);
+        assert_eq!(limits.batch_size, 500);
+        assert_eq!(limits.flush_interval, Duration::from_secs(1));
+
+        for _ in 0..3 {
+            limits.observe(fast, false);
+        }
+        assert_eq!(limits.batch_size, 100);
+        assert_eq!(limits.flush_interval, Duration::from_secs(3));
+
+        limits.observe(fast, true);
+        assert_eq!(limits.flush_interval, Duration::from_millis(1500));
+    }
+}
@@ -0,0 +1,1021 @@
+//! Background batching of writes
+
+use futures::channel::{mpsc, oneshot};
+use futures::future::{select, Either};
+use futures::prelude::*;
+use futures_timer::Delay;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use super::adaptive_batching::BatchLimits;
+use super::sampling::Sampler;
+use super::writer_stats::WriterStats;
+use crate::query::Precision;
+use crate::{
+    AdaptiveBatching, BackgroundTask, Client, Error, Query, Sampling, ToPoint, WriteLimits,
+    WriteQuery,
+};
+
+/// Options of a [`BufferedWriter`](crate::BufferedWriter)
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::WriterOptions;
+/// use std::time::Duration;
+///
+/// let options = WriterOptions::default()
+///     .max_batch_size(1000)
+///     .flush_interval(Duration::from_millis(500))
+///     .task_name("metrics-writer");
+/// ```
+#[derive(Debug, Clone)]
+pub struct WriterOptions {
+    pub(crate) max_batch_size: usize,
+    pub(crate) max_queued_points: usize,
+    pub(crate) flush_interval: Duration,
+    pub(crate) task_name: String,
+    pub(crate) self_monitoring: Option<(String, Duration)>,
+    pub(crate) adaptive: Option<AdaptiveBatching>,
+    pub(crate) coalesce: Option<Duration>,
+    pub(crate) sampling: Option<Sampling>,
+}
+
+impl Default for WriterOptions {
+    fn default() -> Self {
+        WriterOptions {
+            max_batch_size: 5000,
+            max_queued_points: 100_000,
+            flush_interval: Duration::from_secs(1),
+            task_name: "influxdb-writer".to_string(),
+            self_monitoring: None,
+            adaptive: None,
+            coalesce: None,
+            sampling: None,
+        }
+    }
+}
+
+impl WriterOptions {
+    /// Sets the number of points after which the buffer is flushed, defaults to 5000
+    pub fn max_batch_size(mut self, max_batch_size: usize) -> Self {
+        self.max_batch_size = max_batch_size.max(1);
+        self
+    }
+
+    /// Sets how many points may wait to be written at most, defaults to 100 000
+    ///
+    /// Further points are dropped and counted as [`points_dropped`](crate::WriterStats::points_dropped)
+    /// of the statistics, so the writer doesn't run out of memory while the server is down.
+    pub fn max_queued_points(mut self, max_queued_points: usize) -> Self {
+        self.max_queued_points = max_queued_points.max(1);
+        self
+    }
+
+    /// Sets the interval after which the buffer is flushed, no matter how many points it holds,
+    /// defaults to one second
+    pub fn flush_interval(mut self, flush_interval: Duration) -> Self {
+        self.flush_interval = flush_interval;
+        self
+    }
+
+    /// Sets the name of the [`BackgroundTask`](crate::BackgroundTask) writing the points, defaults
+    /// to `influxdb-writer`
+    pub fn task_name<S>(mut self, task_name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.task_name = task_name.into();
+        self
+    }
+
+    /// Writes the [statistics](crate::BufferedWriter::stats) of the writer as a point of
+    /// `measurement` along with the other points, about every `interval`
+    ///
+    /// The point is tagged with the `writer` name of the task and has the counters of the
+    /// statistics as fields, as well as the histograms of batch sizes and flush latencies with the
+    /// fields `<name>_count`, `<name>_sum` and `<name>_le_<bound>` for the cumulative count of
+    /// each bucket. Statistics are written with the flushes of the writer, so they aren't written
+    /// more often than the flush interval.
+    pub fn self_monitoring<S>(mut self, measurement: S, interval: Duration) -> Self
+    where
+        S: Into<String>,
+    {
+        self.self_monitoring = Some((measurement.into(), interval));
+        self
+    }
+
+    /// Adapts the batch size and the flush interval to the latency and the errors of the server,
+    /// see [`AdaptiveBatching`](crate::AdaptiveBatching)
+    ///
+    /// The maximum batch size and the flush interval become the bounds of the adapted values.
+    pub fn adaptive(mut self, adaptive: AdaptiveBatching) -> Self {
+        self.adaptive = Some(adaptive);
+        self
+    }
+
+    /// Merges buffered points with the same measurement, tag set and `window` of time into one
+    /// point with the fields of all of them, when the buffer is flushed
+    ///
+    /// Meant for emitters which write many points of a few series, each with some of the fields,
+    /// e.g. one point per sensor reading. Fields of later points overwrite fields of the same name
+    /// of earlier ones. Merged points are timestamped with the start of their window, counted from
+    /// the unix epoch, points which weren't merged with others keep their timestamp. A window
+    /// shorter than the precision of a point only merges points with the same timestamp.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::WriterOptions;
+    /// use std::time::Duration;
+    ///
+    /// let options = WriterOptions::default().coalesce(Duration::from_secs(10));
+    /// ```
+    pub fn coalesce(mut self, window: Duration) -> Self {
+        self.coalesce = Some(window);
+        self
+    }
+
+    /// Samples the points of each series, for sources which write more points than are worth
+    /// storing, see [`Sampling`](crate::Sampling)
+    ///
+    /// Points which aren't kept count as [written](crate::BufferedWriter::write_acked) and as
+    /// [`points_sampled_out`](crate::WriterStats::points_sampled_out) of the statistics.
+    pub fn sampling(mut self, sampling: Sampling) -> Self {
+        self.sampling = Some(sampling);
+        self
+    }
+}
+
+type Reply = oneshot::Sender<Result<(), Error>>;
+
+pub(crate) enum Command {
+    Write(WriteQuery, Option<Reply>),
+    Flush(Reply),
+    Shutdown(Reply),
+}
+
+/// A buffered point, with the senders of the [`WriteAck`](crate::WriteAck)s of the points merged
+/// into it
+pub(crate) struct Buffered {
+    pub(crate) point: WriteQuery,
+    acks: Vec<Reply>,
+}
+
+impl Buffered {
+    pub(crate) fn new(point: WriteQuery, ack: Option<Reply>) -> Self {
+        Buffered {
+            point,
+            acks: ack.into_iter().collect(),
+        }
+    }
+}
+
+impl Borrow<WriteQuery> for Buffered {
+    fn borrow(&self) -> &WriteQuery {
+        &self.point
+    }
+}
+
+/// Future returned by [`BufferedWriter::write_acked`](crate::BufferedWriter::write_acked), which
+/// resolves once the batch containing the point was written
+///
+/// Dropping it doesn't affect writing the point.
+#[derive(Debug)]
+#[must_use = "the acknowledgement is only received when the future is awaited"]
+pub struct WriteAck {
+    receiver: oneshot::Receiver<Result<(), Error>>,
+}
+
+impl Future for WriteAck {
+    type Output = Result<(), Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.receiver
+            .poll_unpin(cx)
+            .map(|result| result.unwrap_or_else(|_| Err(stopped())))
+    }
+}
+
+/// Handle to buffer points, which are written in batches by the task returned alongside it by
+/// [`Client::buffered_writer`](crate::Client::buffered_writer)
+///
+/// Handles are cheap to clone. Once all of them are dropped, or one of them is
+/// [shut down](crate::BufferedWriter::shutdown), the buffer is flushed a last time and the task
+/// completes.
+#[derive(Debug, Clone)]
+pub struct BufferedWriter {
+    sender: mpsc::UnboundedSender<Command>,
+    stats: Arc<Mutex<WriterStats>>,
+    write_limits: Option<WriteLimits>,
+    max_queued_points: usize,
+}
+
+impl BufferedWriter {
+    /// Adds a point to the buffer
+    ///
+    /// Returns an error if the future writing the points has completed, if the point exceeds
+    /// the [write limits](crate::Client::with_write_limits) of the client, or if it was dropped
+    /// because [too many points](crate::WriterOptions::max_queued_points) wait to be written.
+    pub fn write(&self, point: WriteQuery) -> Result<(), Error> {
+        self.send_point(point, None)
+    }
+
+    /// Adds the point of a [`ToPoint`](crate::ToPoint) value to the buffer, e.g. of a trait
+    /// object of a collection of different metric types
+    ///
+    /// Returns an error if the future writing the points has completed.
+    pub fn write_point<P>(&self, point: &P) -> Result<(), Error>
+    where
+        P: ToPoint + ?Sized,
+    {
+        self.write(point.to_point())
+    }
+
+    /// Adds a point to the buffer and returns a [`WriteAck`](crate::WriteAck) which resolves once
+    /// the server accepted the batch containing it, or with the error writing the batch failed
+    ///
+    /// Returns an error if the future writing the points has completed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::{Client, Timestamp, WriterOptions};
+    /// use influxdb::InfluxDbWriteable;
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("http://localhost:8086", "test");
+    /// let (writer, task) = client.buffered_writer(WriterOptions::default());
+    /// async_std::task::spawn(task);
+    ///
+    /// let point = Timestamp::Seconds(0).into_query("orders").add_field("amount", 42);
+    /// writer.write_acked(point)?.await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_acked(&self, point: WriteQuery) -> Result<WriteAck, Error> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_point(point, Some(sender))?;
+        Ok(WriteAck { receiver })
+    }
+
+    /// Returns a snapshot of the statistics of the writer, e.g. how many points are waiting to be
+    /// written and how long writing the batches took
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::{Client, WriterOptions};
+    ///
+    /// let client = Client::new("http://localhost:8086", "test");
+    /// let (writer, task) = client.buffered_writer(WriterOptions::default());
+    ///
+    /// let stats = writer.stats();
+    /// println!(
+    ///     "{} points queued, {} failed, {:?} ms per flush",
+    ///     stats.queue_depth,
+    ///     stats.points_failed,
+    ///     stats.flush_latencies.mean()
+    /// );
+    /// ```
+    pub fn stats(&self) -> WriterStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Writes all points buffered so far and waits until the server accepted them
+    ///
+    /// Returns the first error if writing any of the batches failed.
+    pub async fn flush(&self) -> Result<(), Error> {
+        let (sender, receiver) = oneshot::channel();
+        self.send(Command::Flush(sender))?;
+        receiver.await.unwrap_or_else(|_| Err(stopped()))
+    }
+
+    /// Stops the task writing the points, after it wrote all points buffered so far
+    ///
+    /// Points written by other handles while shutting down are still written. Afterwards, writing
+    /// to any of the handles fails. Returns the first error if writing any of the batches failed.
+    pub async fn shutdown(&self) -> Result<(), Error> {
+        let (sender, receiver) = oneshot::channel();
+        self.send(Command::Shutdown(sender))?;
+        receiver.await.unwrap_or_else(|_| Err(stopped()))
+    }
+
+    /// Waits for `signal`, e.g. `tokio::signal::ctrl_c()`, then [shuts down](crate::BufferedWriter::shutdown)
+    /// the writer and waits until the buffered points were written, but no longer than `deadline`
+    ///
+    /// Spawn the returned future and wait for it before the process exits, so the last points
+    /// aren't lost. If the points weren't written within the deadline, the returned error is a
+    /// [timeout](crate::Error::is_timeout). The output of `signal` is ignored, so a signal handler
+    /// which failed to install shuts the writer down right away.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use futures::channel::oneshot;
+    /// use influxdb::{Client, WriterOptions};
+    /// use std::time::Duration;
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("http://localhost:8086", "test");
+    /// let (writer, task) = client.buffered_writer(WriterOptions::default());
+    /// async_std::task::spawn(task);
+    ///
+    /// // completed by the signal handler of the application, e.g. of Ctrl-C
+    /// let (shutdown, signal) = oneshot::channel::<()>();
+    /// let drained = async_std::task::spawn({
+    ///     let writer = writer.clone();
+    ///     async move { writer.flush_on_shutdown(signal, Duration::from_secs(5)).await }
+    /// });
+    ///
+    /// // ... write points until the application shuts down
+    /// # drop(shutdown);
+    /// drained.await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn flush_on_shutdown<F>(&self, signal: F, deadline: Duration) -> Result<(), Error>
+    where
+        F: Future,
+    {
+        signal.await;
+        let shutdown = self.shutdown();
+        futures::pin_mut!(shutdown);
+        match select(shutdown, Delay::new(deadline)).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(Error::ConnectionError {
+                error: format!(
+                    "buffered points were not written within {:?} of the shutdown",
+                    deadline
+                ),
+                source: crate::error::source(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "shutdown deadline elapsed",
+                )),
+            }),
+        }
+    }
+
+    fn send_point(&self, point: WriteQuery, ack: Option<Reply>) -> Result<(), Error> {
+        if let Some(limits) = &self.write_limits {
+            limits.check(&point)?;
+        }
+        // counted before it's sent, so the task can't record it as written before it was queued
+        {
+            let mut stats = self.stats.lock().unwrap();
+            if stats.queue_depth >= self.max_queued_points as u64 {
+                stats.points_dropped += 1;
+                return Err(Error::ConnectionError {
+                    error: format!(
+                        "buffered writer has {} points waiting to be written, the point was dropped",
+                        stats.queue_depth
+                    ),
+                    source: None,
+                });
+            }
+            stats.queue_depth += 1;
+        }
+        self.send(Command::Write(point, ack)).inspect_err(|_| {
+            let mut stats = self.stats.lock().unwrap();
+            stats.queue_depth = stats.queue_depth.saturating_sub(1);
+        })
+    }
+
+    fn send(&self, command: Command) -> Result<(), Error> {
+        self.sender.unbounded_send(command).map_err(|_| stopped())
+    }
+}
+
+#[cfg(test)]
+impl BufferedWriter {
+    /// Creates a writer without a task writing its points, which are received by the caller instead
+    pub(crate) fn detached() -> (Self, mpsc::UnboundedReceiver<Command>) {
+        let (sender, receiver) = mpsc::unbounded();
+        let limits = BatchLimits::new(&WriterOptions::default());
+        let stats = Arc::new(Mutex::new(limits.stats()));
+        let writer = BufferedWriter {
+            sender,
+            stats,
+            write_limits: None,
+            max_queued_points: WriterOptions::default().max_queued_points,
+        };
+        (writer, receiver)
+    }
+}
+
+fn stopped() -> Error {
+    Error::ConnectionError {
+        error: "buffered writer has stopped".to_string(),
+        source: None,
+    }
+}
+
+impl Client {
+    /// Creates a [`BufferedWriter`](crate::BufferedWriter) which collects points and writes them in batches
+    ///
+    /// The points are written by the returned [`BackgroundTask`](crate::BackgroundTask), which has
+    /// to be spawned on the runtime of the application. A batch is written once it reaches the maximum batch size, or when the flush
+    /// interval elapsed. Points of different databases, retention policies and precisions are
+    /// written in separate batches. Failed batches are dropped, use
+    /// [`flush`](crate::BufferedWriter::flush) or [`write_acked`](crate::BufferedWriter::write_acked)
+    /// to find out whether writing them succeeded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::{Client, Timestamp, WriterOptions};
+    /// use influxdb::InfluxDbWriteable;
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("http://localhost:8086", "test");
+    /// let (writer, task) = client.buffered_writer(WriterOptions::default());
+    /// async_std::task::spawn(task);
+    ///
+    /// for i in 0..10_000 {
+    ///     writer.write(Timestamp::Seconds(i).into_query("weather").add_field("temperature", 82))?;
+    /// }
+    /// writer.shutdown().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn buffered_writer(&self, options: WriterOptions) -> (BufferedWriter, BackgroundTask) {
+        let (sender, receiver) = mpsc::unbounded();
+        let limits = BatchLimits::new(&options);
+        let stats = Arc::new(Mutex::new(limits.stats()));
+        let name = options.task_name.clone();
+        let max_queued_points = options.max_queued_points;
+        let writer = Writer {
+            client: self.clone(),
+            stats: stats.clone(),
+            limits: Mutex::new(limits),
+            coalesce: options.coalesce,
+        };
+        let task = BackgroundTask::new(name, run(writer, options, receiver));
+        let writer = BufferedWriter {
+            sender,
+            stats,
+            write_limits: self.write_limits,
+            max_queued_points,
+        };
+        (writer, task)
+    }
+}
+
+/// The client the task writes with, the statistics it records and the limits of its batches
+struct Writer {
+    client: Client,
+    stats: Arc<Mutex<WriterStats>>,
+    limits: Mutex<BatchLimits>,
+    coalesce: Option<Duration>,
+}
+
+async fn run(
+    writer: Writer,
+    options: WriterOptions,
+    mut receiver: mpsc::UnboundedReceiver<Command>,
+) {
+    let mut buffer = Vec::new();
+    let mut sampler = options.sampling.clone().map(Sampler::new);
+    let mut timer = Delay::new(writer.flush_interval());
+    let mut last_report = Instant::now();
+    loop {
+        match select(receiver.next(), &mut timer).await {
+            Either::Left((Some(Command::Write(point, ack)), _)) => {
+                writer.buffer(&mut buffer, &mut sampler, Buffered::new(point, ack));
+                if buffer.len() >= writer.batch_size() {
+                    let _ = flush(&writer, &mut buffer).await;
+                    timer.reset(writer.flush_interval());
+                }
+            }
+            Either::Left((Some(Command::Flush(reply)), _)) => {
+                drain_sampler(&mut buffer, &mut sampler, true);
+                let _ = reply.send(flush(&writer, &mut buffer).await);
+            }
+            Either::Left((Some(Command::Shutdown(reply)), _)) => {
+                shutdown(&writer, buffer, sampler, receiver, reply).await;
+                return;
+            }
+            Either::Left((None, _)) => {
+                drain_sampler(&mut buffer, &mut sampler, true);
+                let _ = flush(&writer, &mut buffer).await;
+                return;
+            }
+            Either::Right(_) => {
+                if let Some((measurement, interval)) = &options.self_monitoring {
+                    if last_report.elapsed() >= *interval {
+                        last_report = Instant::now();
+                        buffer.push(writer.report(measurement, &options.task_name));
+                    }
+                }
+                drain_sampler(&mut buffer, &mut sampler, false);
+                let _ = flush(&writer, &mut buffer).await;
+                timer.reset(writer.flush_interval());
+            }
+        }
+    }
+}
+
+/// Adds the points the sampler kept of its reservoirs to the buffer, see [`Sampler::drain`]
+fn drain_sampler(buffer: &mut Vec<Buffered>, sampler: &mut Option<Sampler>, force: bool) {
+    if let Some(sampler) = sampler {
+        buffer.extend(sampler.drain(force));
+    }
+}
+
+impl Writer {
+    /// Adds the point to the buffer, or to the sampler if the writer samples its points
+    fn buffer(&self, buffer: &mut Vec<Buffered>, sampler: &mut Option<Sampler>, point: Buffered) {
+        let sampler = match sampler {
+            Some(sampler) => sampler,
+            None => return buffer.push(point),
+        };
+        let (kept, dropped) = sampler.offer(point);
+        buffer.extend(kept);
+        if let Some(dropped) = dropped {
+            self.stats.lock().unwrap().record_sampled_out(1);
+            for ack in dropped.acks {
+                let _ = ack.send(Ok(()));
+            }
+        }
+    }
+
+    fn batch_size(&self) -> usize {
+        self.limits.lock().unwrap().batch_size
+    }
+
+    fn flush_interval(&self) -> Duration {
+        self.limits.lock().unwrap().flush_interval
+    }
+
+    /// Records a batch which was sent, adapting the limits of the next batches
+    fn record_batch(&self, points: usize, latency: Duration, written: bool) {
+        let mut limits = self.limits.lock().unwrap();
+        limits.observe(latency, written);
+        let mut stats = self.stats.lock().unwrap();
+        stats.record_batch(points, latency, written);
+        stats.batch_size = limits.batch_size;
+        stats.flush_interval = limits.flush_interval;
+    }
+
+    /// Returns the point of the current statistics, which is queued like any other point
+    fn report(&self, measurement: &str, task_name: &str) -> Buffered {
+        let mut stats = self.stats.lock().unwrap();
+        stats.queue_depth += 1;
+        Buffered::new(stats.to_point(measurement, task_name), None)
+    }
+}
+
+/// Writes the buffer and the points still queued in the channel, then answers all callers waiting
+/// for the points to be written
+async fn shutdown(
+    writer: &Writer,
+    mut buffer: Vec<Buffered>,
+    mut sampler: Option<Sampler>,
+    mut receiver: mpsc::UnboundedReceiver<Command>,
+    reply: oneshot::Sender<Result<(), Error>>,
+) {
+    receiver.close();
+    let mut replies = vec![reply];
+    while let Some(command) = receiver.next().await {
+        match command {
+            Command::Write(point, ack) => {
+                writer.buffer(&mut buffer, &mut sampler, Buffered::new(point, ack))
+            }
+            Command::Flush(reply) | Command::Shutdown(reply) => replies.push(reply),
+        }
+    }
+    drain_sampler(&mut buffer, &mut sampler, true);
+
+    let result = flush(writer, &mut buffer).await;
+    for reply in replies {
+        let _ = reply.send(result.clone());
+    }
+}
+
+/// Writes and empties the buffer, in one batch per database, retention policy and precision, and
+/// acknowledges the points of each batch
+async fn flush(writer: &Writer, buffer: &mut Vec<Buffered>) -> Result<(), Error> {
+    if let Some(window) = writer.coalesce {
+        let merged = coalesce(buffer, window);
+        writer.stats.lock().unwrap().record_coalesced(merged);
+    }
+    let mut result = Ok(());
+    for batch in partition(buffer.drain(..)) {
+        let (points, acks): (Vec<_>, Vec<_>) = batch
+            .into_iter()
+            .map(|buffered| (buffered.point, buffered.acks))
+            .unzip();
+        let started = Instant::now();
+        let written = writer.client.query(&points).await.map(|_| ());
+        writer.record_batch(points.len(), started.elapsed(), written.is_ok());
+        for ack in acks.into_iter().flatten() {
+            let _ = ack.send(written.clone());
+        }
+        result = result.and(written);
+    }
+    result
+}
+
+/// Merges the points with the same measurement, tag set and window into the first of them,
+/// timestamped with the start of the window, and returns how many points were merged into others
+///
+/// Points which weren't merged with others keep their timestamp.
+fn coalesce(buffer: &mut Vec<Buffered>, window: Duration) -> usize {
+    let points = buffer.len();
+    let mut coalesced: Vec<Buffered> = Vec::with_capacity(points);
+    let mut series = HashMap::<_, usize>::new();
+    for mut buffered in buffer.drain(..) {
+        let timestamp = buffered.point.timestamp().align_to(window);
+        // points are merged if their timestamps denote the same instant, whatever their precision
+        let instant = timestamp.checked_nanos().ok_or(timestamp);
+        let key = (series_key(&buffered.point), instant);
+        match series.get(&key) {
+            Some(&index) => {
+                let merged = &mut coalesced[index];
+                let start = merged.point.timestamp().align_to(window);
+                *merged.point.timestamp_mut() = start;
+                for (field, value) in buffered.point.fields_mut().drain(..) {
+                    let fields = merged.point.fields_mut();
+                    match fields.iter_mut().find(|(existing, _)| *existing == field) {
+                        Some((_, existing)) => *existing = value,
+                        None => fields.push((field, value)),
+                    }
+                }
+                merged.acks.append(&mut buffered.acks);
+            }
+            None => {
+                series.insert(key, coalesced.len());
+                coalesced.push(buffered);
+            }
+        }
+    }
+    let merged = points - coalesced.len();
+    *buffer = coalesced;
+    merged
+}
+
+/// The database, retention policy, measurement and sorted tags of a point, which identify the
+/// series it is written to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct SeriesKey {
+    database: Option<String>,
+    retention_policy: Option<String>,
+    measurement: String,
+    tags: Vec<(String, String)>,
+}
+
+pub(crate) fn series_key(point: &WriteQuery) -> SeriesKey {
+    let mut tags = point
+        .tags()
+        .iter()
+        .map(|(key, value)| (key.clone(), value.to_string()))
+        .collect::<Vec<_>>();
+    tags.sort();
+    SeriesKey {
+        database: point.get_database().map(ToOwned::to_owned),
+        retention_policy: point.get_retention_policy().map(ToOwned::to_owned),
+        measurement: point.measurement().name().to_string(),
+        tags,
+    }
+}
+
+/// Splits points into batches which can be sent in a single request, keeping their order
+fn partition<T, I>(points: I) -> Vec<Vec<T>>
+where
+    T: Borrow<WriteQuery>,
+    I: IntoIterator<Item = T>,
+{
+    let mut batches: Vec<(BatchKey, Vec<T>)> = Vec::new();
+    for point in points {
+        let key = BatchKey::of(point.borrow());
+        match batches.iter_mut().find(|(batch_key, _)| *batch_key == key) {
+            Some((_, batch)) => batch.push(point),
+            None => batches.push((key, vec![point])),
+        }
+    }
+    batches.into_iter().map(|(_, batch)| batch).collect()
+}
+
+#[derive(PartialEq)]
+struct BatchKey {
+    database: Option<String>,
+    retention_policy: Option<String>,
+    precision: Precision,
+}
+
+impl BatchKey {
+    fn of(point: &WriteQuery) -> Self {
+        BatchKey {
+            database: point.get_database().map(ToOwned::to_owned),
+            retention_policy: point.get_retention_policy().map(ToOwned::to_owned),
+            precision: point.timestamp().precision(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{coalesce, partition, Buffered, BufferedWriter, Command};
+    use crate::{
+        Client, InfluxDbWriteable, Measurement, Query, Timestamp, WriteQuery, WriterOptions,
+    };
+    use futures::executor::block_on;
+
+    #[test]
+    fn test_partition() {
+        let point = |timestamp: Timestamp, measurement: Measurement| {
+            WriteQuery::new(timestamp, measurement).add_field("temperature", 82)
+        };
+        let batches = partition(vec![
+            point(Timestamp::Seconds(1), Measurement::new("weather")),
+            point(
+                Timestamp::Seconds(2),
+                Measurement::new("weather").retention_policy("two_weeks"),
+            ),
+            point(Timestamp::Milliseconds(3), Measurement::new("weather")),
+            Timestamp::Seconds(4)
+                .into_query("weather")
+                .add_field("temperature", 82),
+        ]);
+
+        let timestamps = batches
+            .iter()
+            .map(|batch| batch.iter().map(|p| p.timestamp()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            timestamps,
+            vec![
+                vec![Timestamp::Seconds(1), Timestamp::Seconds(4)],
+                vec![Timestamp::Seconds(2)],
+                vec![Timestamp::Milliseconds(3)],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_coalesce() {
+        use std::time::Duration;
+
+        let (ack, acked) = futures::channel::oneshot::channel();
+        let mut buffer = vec![
+            Buffered::new(
+                Timestamp::Seconds(61)
+                    .into_query("weather")
+                    .add_tag("city", "Berlin")
+                    .add_tag("station", 1)
+                    .add_field("temperature", 82),
+                Some(ack),
+            ),
+            Buffered::new(
+                Timestamp::Seconds(62)
+                    .into_query("weather")
+                    .add_tag("city", "Paris")
+                    .add_field("temperature", 75),
+                None,
+            ),
+            Buffered::new(
+                Timestamp::Milliseconds(119_000)
+                    .into_query("weather")
+                    .add_tag("station", 1)
+                    .add_tag("city", "Berlin")
+                    .add_field("humidity", 40)
+                    .add_field("temperature", 83),
+                None,
+            ),
+            Buffered::new(
+                Timestamp::Seconds(120)
+                    .into_query("weather")
+                    .add_tag("city", "Berlin")
+                    .add_tag("station", 1)
+                    .add_field("temperature", 84),
+                None,
+            ),
+        ];
+
+        assert_eq!(coalesce(&mut buffer, Duration::from_secs(60)), 1);
+        let lines = buffer
+            .iter()
+            .map(|buffered| buffered.point.build().unwrap().get())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                "weather,city=Berlin,station=1 temperature=83i,humidity=40i 60",
+                "weather,city=Paris temperature=75i 62",
+                "weather,city=Berlin,station=1 temperature=84i 120",
+            ]
+        );
+        for buffered in buffer.iter_mut() {
+            for ack in buffered.acks.drain(..) {
+                ack.send(Ok(())).unwrap();
+            }
+        }
+        assert!(block_on(acked).unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_coalesce_databases() {
+        use std::time::Duration;
+
+        let point = |measurement: Measurement, field: &str| {
+            Buffered::new(
+                WriteQuery::new(Timestamp::Seconds(1), measurement)
+                    .add_tag("city", "Berlin")
+                    .add_field(field, 82),
+                None,
+            )
+        };
+        let mut buffer = vec![
+            point(Measurement::new("weather").database("a"), "temperature"),
+            point(Measurement::new("weather").database("b"), "humidity"),
+            point(
+                Measurement::new("weather")
+                    .database("a")
+                    .retention_policy("two_weeks"),
+                "pressure",
+            ),
+            point(Measurement::new("weather").database("a"), "wind"),
+        ];
+
+        assert_eq!(coalesce(&mut buffer, Duration::from_secs(60)), 1);
+        let points = buffer
+            .iter()
+            .map(|buffered| {
+                let fields = buffered
+                    .point
+                    .fields()
+                    .iter()
+                    .map(|(field, _)| field.as_str());
+                (
+                    buffered.point.get_database().unwrap(),
+                    buffered.point.get_retention_policy(),
+                    fields.collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            points,
+            vec![
+                ("a", None, vec!["temperature", "wind"]),
+                ("b", None, vec!["humidity"]),
+                ("a", Some("two_weeks"), vec!["pressure"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_write_after_stop() {
+        let (writer, mut receiver) = BufferedWriter::detached();
+        let point = Timestamp::Seconds(1)
+            .into_query("weather")
+            .add_field("temperature", 82);
+        assert!(writer.write(point.clone()).is_ok());
+        match receiver.try_recv() {
+            Ok(Command::Write(received, None)) => assert_eq!(received, point),
+            _ => panic!("point was not buffered"),
+        }
+
+        drop(receiver);
+        assert!(writer.write(point).is_err());
+    }
+
+    #[test]
+    fn test_write_limits() {
+        use crate::WriteLimits;
+
+        let client = Client::new("http://127.0.0.1:1", "test")
+            .with_write_limits(WriteLimits::new().max_fields(1));
+        let (writer, _task) = client.buffered_writer(WriterOptions::default());
+        let point = Timestamp::Seconds(1)
+            .into_query("weather")
+            .add_field("temperature", 82);
+        assert!(writer.write(point.clone()).is_ok());
+        assert!(writer.write(point.add_field("humidity", 40)).is_err());
+        assert_eq!(writer.stats().queue_depth, 1);
+    }
+
+    #[test]
+    fn test_max_queued_points() {
+        let client = Client::new("http://127.0.0.1:1", "test");
+        let (writer, _task) = client.buffered_writer(WriterOptions::default().max_queued_points(2));
+        let point = Timestamp::Seconds(1)
+            .into_query("weather")
+            .add_field("temperature", 82);
+        for _ in 0..2 {
+            assert!(writer.write(point.clone()).is_ok());
+        }
+        assert!(writer.write(point).is_err());
+        let stats = writer.stats();
+        assert_eq!((stats.queue_depth, stats.points_dropped), (2, 1));
+    }
+
+    #[test]
+    fn test_shutdown() {
+        let client = Client::new("http://127.0.0.1:1", "test");
+        let (writer, task) = client.buffered_writer(WriterOptions::default().task_name("metrics"));
+        assert_eq!(task.name(), "metrics");
+
+        let task = std::thread::spawn(move || block_on(task));
+        assert!(block_on(writer.shutdown()).is_ok());
+        task.join().unwrap();
+
+        let point = Timestamp::Seconds(1)
+            .into_query("weather")
+            .add_field("temperature", 82);
+        assert!(writer.write(point).is_err());
+    }
+
+    #[test]
+    fn test_flush_on_shutdown() {
+        let (writer, mut receiver) = BufferedWriter::detached();
+        let (shutdown, signal) = futures::channel::oneshot::channel::<()>();
+        let drained = std::thread::spawn(move || {
+            block_on(writer.flush_on_shutdown(signal, std::time::Duration::from_millis(50)))
+        });
+        shutdown.send(()).unwrap();
+
+        // the shutdown is received, but never answered
+        let command = block_on(futures::StreamExt::next(&mut receiver));
+        assert!(matches!(command, Some(Command::Shutdown(_))));
+        let error = drained.join().unwrap().unwrap_err();
+        assert!(error.is_timeout(), "{:?}", error);
+    }
+
+    #[test]
+    fn test_stats() {
+        let client = Client::new("http://127.0.0.1:1", "test");
+        let (writer, task) = client.buffered_writer(WriterOptions::default());
+        let task = std::thread::spawn(move || block_on(task));
+
+        let point = Timestamp::Seconds(1)
+            .into_query("weather")
+            .add_field("temperature", 82);
+        writer.write(point.clone()).unwrap();
+        writer.write(point).unwrap();
+        assert!(block_on(writer.flush()).is_err());
+
+        let stats = writer.stats();
+        assert_eq!(stats.queue_depth, 0);
+        assert_eq!((stats.points_failed, stats.batches_failed), (2, 1));
+        assert_eq!(stats.batch_sizes.mean(), Some(2.0));
+        assert_eq!(stats.flush_latencies.count(), 1);
+
+        drop(writer);
+        task.join().unwrap();
+    }
+
+    #[test]
+    fn test_sampling() {
+        use crate::Sampling;
+
+        let client = Client::new("http://127.0.0.1:1", "test");
+        let (writer, task) =
+            client.buffered_writer(WriterOptions::default().sampling(Sampling::one_in(3)));
+        let task = std::thread::spawn(move || block_on(task));
+
+        let point = Timestamp::Seconds(1)
+            .into_query("weather")
+            .add_field("temperature", 82);
+        let acks = (0..3)
+            .map(|_| writer.write_acked(point.clone()).unwrap())
+            .collect::<Vec<_>>();
+        assert!(block_on(writer.flush()).is_err());
+        let acks = acks.into_iter().map(block_on).collect::<Vec<_>>();
+        assert!(acks[0].is_err());
+        assert!(acks[1].is_ok() && acks[2].is_ok());
+
+        let stats = writer.stats();
+        assert_eq!(stats.queue_depth, 0);
+        assert_eq!((stats.points_failed, stats.points_sampled_out), (1, 2));
+
+        drop(writer);
+        task.join().unwrap();
+    }
+
+    #[test]
+    fn test_write_acked() {
+        let (writer, mut receiver) = BufferedWriter::detached();
+        let point = Timestamp::Seconds(1)
+            .into_query("weather")
+            .add_field("temperature", 82);
+
+        let ack = writer.write_acked(point.clone()).unwrap();
+        match receiver.try_recv() {
+            Ok(Command::Write(_, Some(reply))) => reply.send(Ok(())).unwrap(),
+            _ => panic!("point was not buffered with an acknowledgement"),
+        }
+        assert!(block_on(ack).is_ok());
+
+        let ack = writer.write_acked(point).unwrap();
+        drop(receiver);
+        assert!(block_on(ack).is_err());
+    }
+}
@@ -0,0 +1,493 @@
+//! Builder which validates the configuration of a [`Client`](crate::Client) up front
+
+#[cfg(not(feature = "hyper-client"))]
+use std::convert::TryInto;
+use std::time::Duration;
+#[cfg(not(feature = "hyper-client"))]
+use surf::Config;
+use surf::{Client as SurfClient, Url};
+
+use futures::future::{self, Either};
+use futures_timer::Delay;
+use surf::middleware::{Middleware, Next};
+
+use crate::query::Precision;
+use crate::{Client, Error};
+
+/// Builder for a [`Client`](crate::Client), returned by [`Client::builder`](crate::Client::builder)
+///
+/// Unlike [`Client::new`](crate::Client::new), [`build`](crate::ClientBuilder::build) validates the
+/// configuration and returns an error instead of failing later, when the first query is sent.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::Client;
+/// use std::time::Duration;
+///
+/// let client = Client::builder()
+///     .url("http://localhost:8086/")
+///     .database("test")
+///     .auth("admin", "password")
+///     .timeout(Duration::from_secs(5))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(client.database_url(), "http://localhost:8086");
+/// assert!(Client::builder().url("localhost:8086").database("test").build().is_err());
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ClientBuilder {
+    url: Option<String>,
+    database: Option<String>,
+    auth: Option<(String, String)>,
+    retention_policy: Option<String>,
+    headers: Vec<(String, String)>,
+    precision: Option<Precision>,
+    max_precision: Option<Precision>,
+    chunked_threshold: Option<usize>,
+    max_split_depth: Option<u32>,
+    transport: Transport,
+}
+
+/// Options of the HTTP client and its connections
+#[derive(Debug, Default, Clone)]
+struct Transport {
+    timeout: Option<Duration>,
+    pool_size: Option<usize>,
+    http_keep_alive: Option<bool>,
+    tcp_nodelay: Option<bool>,
+    #[cfg(feature = "hyper-client")]
+    http2_only: bool,
+    #[cfg(feature = "hyper-client")]
+    pool_idle_timeout: Option<Duration>,
+    #[cfg(feature = "hyper-client")]
+    tcp_keepalive: Option<Duration>,
+}
+
+impl Client {
+    /// Returns a [`ClientBuilder`](crate::ClientBuilder) to configure a [`Client`](crate::Client)
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+}
+
+impl ClientBuilder {
+    /// Sets the URL where InfluxDB is running (ex. `http://localhost:8086`)
+    pub fn url<S>(mut self, url: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// Sets the database against which queries and writes will be run
+    pub fn database<S>(mut self, database: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.database = Some(database.into());
+        self
+    }
+
+    /// See [`Client::with_auth`](crate::Client::with_auth)
+    pub fn auth<S1, S2>(mut self, username: S1, password: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        self.auth = Some((username.into(), password.into()));
+        self
+    }
+
+    /// See [`Client::with_retention_policy`](crate::Client::with_retention_policy)
+    pub fn retention_policy<S>(mut self, retention_policy: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.retention_policy = Some(retention_policy.into());
+        self
+    }
+
+    /// See [`Client::with_header`](crate::Client::with_header)
+    pub fn header<S1, S2>(mut self, name: S1, value: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// See [`Client::with_user_agent`](crate::Client::with_user_agent)
+    pub fn user_agent<S>(self, user_agent: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.header("User-Agent", user_agent)
+    }
+
+    /// See [`Client::with_precision`](crate::Client::with_precision)
+    pub fn precision(mut self, precision: Precision) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// See [`Client::with_downscaling`](crate::Client::with_downscaling)
+    pub fn downscaling(mut self, max_precision: Precision) -> Self {
+        self.max_precision = Some(max_precision);
+        self
+    }
+
+    /// See [`Client::with_chunked_threshold`](crate::Client::with_chunked_threshold)
+    pub fn chunked_threshold(mut self, bytes: usize) -> Self {
+        self.chunked_threshold = Some(bytes);
+        self
+    }
+
+    /// See [`Client::with_max_split_depth`](crate::Client::with_max_split_depth)
+    pub fn max_split_depth(mut self, depth: u32) -> Self {
+        self.max_split_depth = Some(depth);
+        self
+    }
+
+    /// Sets the timeout of each request, including reading the response
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.transport.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how many connections per host are pooled, defaults to 50
+    ///
+    /// The curl and async-h1 backends open at most this many connections per host, hyper keeps at
+    /// most this many idle connections open.
+    pub fn pool_size(mut self, pool_size: usize) -> Self {
+        self.transport.pool_size = Some(pool_size);
+        self
+    }
+
+    /// Sets whether connections are kept open and reused, enabled by default
+    pub fn http_keep_alive(mut self, enabled: bool) -> Self {
+        self.transport.http_keep_alive = Some(enabled);
+        self
+    }
+
+    /// Sets whether `TCP_NODELAY` is set on connections, i.e. whether small writes are sent
+    /// without waiting to be combined with later ones
+    pub fn tcp_nodelay(mut self, enabled: bool) -> Self {
+        self.transport.tcp_nodelay = Some(enabled);
+        self
+    }
+
+    /// Sets whether requests are sent over HTTP/2 only, disabled by default
+    ///
+    /// Connections use HTTP/2 with prior knowledge, without negotiating it, so the server or the
+    /// proxy in front of it has to accept HTTP/2 right away. Only supported by the hyper backend.
+    #[cfg(feature = "hyper-client")]
+    pub fn http2_only(mut self, enabled: bool) -> Self {
+        self.transport.http2_only = enabled;
+        self
+    }
+
+    /// Sets how long idle connections are kept in the pool, defaults to 90 seconds
+    ///
+    /// Only supported by the hyper backend.
+    #[cfg(feature = "hyper-client")]
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.transport.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Enables TCP keepalive on connections, probing idle connections after `interval`
+    ///
+    /// Only supported by the hyper backend.
+    #[cfg(feature = "hyper-client")]
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.transport.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// Validates the configuration and builds the [`Client`](crate::Client)
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::UrlConstructionError`](crate::Error::UrlConstructionError) if the URL or
+    /// database is missing, or the URL is not an absolute `http` or `https` URL without query or
+    /// fragment. Returns an [`Error::ProtocolError`](crate::Error::ProtocolError) if the HTTP client
+    /// can't be configured, e.g. with a pool size of zero.
+    pub fn build(self) -> Result<Client, Error> {
+        let url =
+            validate_url(
+                self.url
+                    .as_deref()
+                    .ok_or_else(|| Error::UrlConstructionError {
+                        error: "missing url".to_string(),
+                    })?,
+            )?;
+        let database = self.database.ok_or_else(|| Error::UrlConstructionError {
+            error: "missing database".to_string(),
+        })?;
+
+        let mut client = Client::new(url, database);
+        if let Some((username, password)) = self.auth {
+            client = client.with_auth(username, password);
+        }
+        if let Some(retention_policy) = self.retention_policy {
+            client = client.with_retention_policy(retention_policy);
+        }
+        for (name, value) in self.headers {
+            client = client.with_header(name, value);
+        }
+        client.precision = self.precision;
+        client.max_precision = self.max_precision;
+        if let Some(chunked_threshold) = self.chunked_threshold {
+            client.chunked_threshold = chunked_threshold;
+        }
+        if let Some(max_split_depth) = self.max_split_depth {
+            client.max_split_depth = max_split_depth;
+        }
+        if let Some(surf_client) = self.transport.client()? {
+            client.client = surf_client;
+        }
+        Ok(client)
+    }
+}
+
+impl Transport {
+    fn is_default(&self) -> bool {
+        let default = self.timeout.is_none()
+            && self.pool_size.is_none()
+            && self.http_keep_alive.is_none()
+            && self.tcp_nodelay.is_none();
+        #[cfg(feature = "hyper-client")]
+        let default = default
+            && !self.http2_only
+            && self.pool_idle_timeout.is_none()
+            && self.tcp_keepalive.is_none();
+        default
+    }
+
+    /// Returns the configured HTTP client, or `None` to keep the default one
+    fn client(&self) -> Result<Option<SurfClient>, Error> {
+        if self.is_default() {
+            return Ok(None);
+        }
+        if self.pool_size == Some(0) {
+            return Err(Error::ProtocolError {
+                error: "the pool size has to be at least one".to_string(),
+            });
+        }
+        #[cfg(feature = "hyper-client")]
+        let client = self.hyper_client();
+        #[cfg(not(feature = "hyper-client"))]
+        let client = self.surf_client()?;
+        Ok(Some(match self.timeout {
+            Some(timeout) => client.with(Timeout(timeout)),
+            None => client,
+        }))
+    }
+
+    /// Builds a surf client from the configuration, leaving the timeout to the [`Timeout`]
+    /// middleware
+    #[cfg(not(feature = "hyper-client"))]
+    fn surf_client(&self) -> Result<SurfClient, Error> {
+        let mut config = Config::new().set_timeout(None);
+        if let Some(pool_size) = self.pool_size {
+            config = config.set_max_connections_per_host(pool_size);
+        }
+        if let Some(enabled) = self.http_keep_alive {
+            config = config.set_http_keep_alive(enabled);
+        }
+        if let Some(enabled) = self.tcp_nodelay {
+            config = config.set_tcp_no_delay(enabled);
+        }
+        TryInto::<SurfClient>::try_into(config).map_err(|err| Error::ProtocolError {
+            error: err.to_string(),
+        })
+    }
+
+    /// Builds a hyper client, as the configuration of surf doesn't cover the options of hyper
+    #[cfg(feature = "hyper-client")]
+    fn hyper_client(&self) -> SurfClient {
+        let mut http = hyper::client::HttpConnector::new();
+        http.enforce_http(false);
+        http.set_nodelay(self.tcp_nodelay.unwrap_or(false));
+        http.set_keepalive(self.tcp_keepalive);
+
+        let mut builder = hyper::Client::builder();
+        builder.http2_only(self.http2_only);
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder.pool_idle_timeout(timeout);
+        }
+        if self.http_keep_alive == Some(false) {
+            builder.pool_max_idle_per_host(0);
+        } else if let Some(pool_size) = self.pool_size {
+            builder.pool_max_idle_per_host(pool_size);
+        }
+        let connector = hyper_tls::HttpsConnector::new_with_connector(http);
+        let hyper_client = http_client::hyper::HyperClient::from_client(builder.build(connector));
+
+        SurfClient::with_http_client(hyper_client)
+    }
+}
+
+/// Fails requests which take longer than the timeout with an [`io::ErrorKind::TimedOut`] error,
+/// which [`Error::is_timeout`] recognises whatever the HTTP client
+///
+/// [`io::ErrorKind::TimedOut`]: std::io::ErrorKind::TimedOut
+#[derive(Debug)]
+struct Timeout(Duration);
+
+#[surf::utils::async_trait]
+impl Middleware for Timeout {
+    async fn handle(
+        &self,
+        request: surf::Request,
+        client: SurfClient,
+        next: Next<'_>,
+    ) -> surf::Result<surf::Response> {
+        match future::select(next.run(request, client), Delay::new(self.0)).await {
+            Either::Left((response, _)) => response,
+            Either::Right(_) => Err(surf::Error::new(
+                surf::StatusCode::RequestTimeout,
+                std::io::Error::new(std::io::ErrorKind::TimedOut, "request timed out"),
+            )),
+        }
+    }
+}
+
+/// Checks that the URL points to an InfluxDB server
+fn validate_url(url: &str) -> Result<String, Error> {
+    let invalid = |reason: &str| Error::UrlConstructionError {
+        error: format!("invalid url {}: {}", url, reason),
+    };
+    let parsed =
+        Url::parse(url).map_err(|err| invalid(&super::explain_url_error(url, &err.to_string())))?;
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        return Err(invalid("scheme has to be http or https"));
+    }
+    if !parsed.has_host() {
+        return Err(invalid("missing host"));
+    }
+    if parsed.query().is_some() || parsed.fragment().is_some() {
+        return Err(invalid("query and fragment are not supported"));
+    }
+    Ok(url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate_url;
+    use crate::client::test_server::unresponsive;
+    use crate::{Client, Precision};
+
+    #[test]
+    fn test_validate_url() {
+        assert_eq!(
+            validate_url("http://localhost:8086").unwrap(),
+            "http://localhost:8086"
+        );
+        assert_eq!(
+            validate_url("https://influx.example.com/proxy/").unwrap(),
+            "https://influx.example.com/proxy/"
+        );
+        assert!(validate_url("localhost:8086").is_err());
+        assert!(validate_url("ftp://localhost:8086").is_err());
+        assert!(validate_url("http://localhost:8086?db=test").is_err());
+        assert!(validate_url("not a url").is_err());
+        assert_eq!(
+            validate_url("http://[::1]:8086").unwrap(),
+            "http://[::1]:8086"
+        );
+        assert!(validate_url("http://[fe80::1%eth0]:8086")
+            .unwrap_err()
+            .to_string()
+            .contains("zone IDs"));
+    }
+
+    #[test]
+    fn test_build() {
+        let client = Client::builder()
+            .url("http://localhost:8086/")
+            .database("test")
+            .auth("admin", "password")
+            .retention_policy("two_weeks")
+            .user_agent("my-service/1.0")
+            .precision(Precision::Seconds)
+            .build()
+            .unwrap();
+
+        assert_eq!(client.database_url(), "http://localhost:8086");
+        assert_eq!(client.database_name(), "test");
+        assert_eq!(client.parameters.get("u").unwrap(), "admin");
+        assert_eq!(client.retention_policy(), Some("two_weeks"));
+        assert_eq!(client.headers.get("User-Agent").unwrap(), "my-service/1.0");
+        assert_eq!(client.precision(), Some(Precision::Seconds));
+    }
+
+    #[test]
+    fn test_build_transport() {
+        let builder = Client::builder()
+            .url("http://localhost:8086")
+            .database("test");
+        assert!(builder
+            .clone()
+            .pool_size(8)
+            .http_keep_alive(true)
+            .tcp_nodelay(true)
+            .build()
+            .is_ok());
+        assert!(builder.pool_size(0).build().is_err());
+    }
+
+    #[cfg(not(feature = "hyper-client"))]
+    #[async_std::test]
+    async fn test_timeout() {
+        use std::time::Duration;
+
+        let (_listener, url) = unresponsive();
+        let client = Client::builder()
+            .url(url)
+            .database("test")
+            .timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let error = client
+            .query(&crate::ReadQuery::new("SHOW DATABASES"))
+            .await
+            .unwrap_err();
+        assert!(error.is_timeout(), "{:?}", error);
+    }
+
+    #[cfg(feature = "hyper-client")]
+    #[tokio::test]
+    async fn test_hyper_timeout() {
+        use std::time::Duration;
+
+        let (_listener, url) = unresponsive();
+        let client = Client::builder()
+            .url(url)
+            .database("test")
+            .http2_only(false)
+            .pool_idle_timeout(Duration::from_secs(30))
+            .tcp_keepalive(Duration::from_secs(60))
+            .timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let error = client
+            .query(&crate::ReadQuery::new("SHOW DATABASES"))
+            .await
+            .unwrap_err();
+        assert!(error.is_timeout(), "{:?}", error);
+    }
+
+    #[test]
+    fn test_build_missing() {
+        assert!(Client::builder().database("test").build().is_err());
+        assert!(Client::builder()
+            .url("http://localhost:8086")
+            .build()
+            .is_err());
+    }
+}
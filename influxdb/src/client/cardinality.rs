@@ -0,0 +1,114 @@
+//! Series cardinality of databases and measurements
+
+use super::stats::parse_series;
+use crate::query::influxql_term::InfluxQlTerm;
+use crate::{Client, Error, ReadQuery};
+
+/// Number of series of a measurement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeasurementCardinality {
+    pub measurement: String,
+    pub series: u64,
+}
+
+impl Client {
+    /// Returns the estimated number of series in a database
+    ///
+    /// The estimation is cheap to compute, use
+    /// [`measurement_cardinalities`](crate::Client::measurement_cardinalities) for exact counts.
+    pub async fn series_cardinality(&self, database: &str) -> Result<u64, Error> {
+        let statement = format!(
+            "SHOW SERIES CARDINALITY ON {}",
+            InfluxQlTerm::Identifier(database).escape()
+        );
+        let counts = self.cardinalities(&statement).await?;
+        Ok(counts.iter().map(|(_, count)| count).sum())
+    }
+
+    /// Returns the exact number of series of every measurement in a database
+    ///
+    /// Counting the series exactly reads the whole index of the database, which can take a while
+    /// for databases with many series.
+    pub async fn measurement_cardinalities(
+        &self,
+        database: &str,
+    ) -> Result<Vec<MeasurementCardinality>, Error> {
+        let statement = format!(
+            "SHOW SERIES EXACT CARDINALITY ON {}",
+            InfluxQlTerm::Identifier(database).escape()
+        );
+        let counts = self.cardinalities(&statement).await?;
+        Ok(counts
+            .into_iter()
+            .map(|(measurement, series)| MeasurementCardinality {
+                measurement,
+                series,
+            })
+            .collect())
+    }
+
+    /// Returns the measurements of a database with more series than `threshold`, those with the
+    /// most series first
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::Client;
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("http://localhost:8086", "telegraf");
+    /// for cardinality in client.high_cardinality_measurements("telegraf", 100_000).await? {
+    ///     eprintln!("{} has {} series", cardinality.measurement, cardinality.series);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn high_cardinality_measurements(
+        &self,
+        database: &str,
+        threshold: u64,
+    ) -> Result<Vec<MeasurementCardinality>, Error> {
+        let mut cardinalities = self.measurement_cardinalities(database).await?;
+        cardinalities.retain(|cardinality| cardinality.series > threshold);
+        cardinalities.sort_by_key(|cardinality| std::cmp::Reverse(cardinality.series));
+        Ok(cardinalities)
+    }
+
+    /// Runs a cardinality statement and returns the count of every series of the result
+    async fn cardinalities(&self, statement: &str) -> Result<Vec<(String, u64)>, Error> {
+        let body = self.query(&ReadQuery::new(statement)).await?;
+        counts(&body)
+    }
+}
+
+/// Returns the name and count of every series, the count being its first value
+fn counts(body: &str) -> Result<Vec<(String, u64)>, Error> {
+    Ok(parse_series(body)?
+        .into_iter()
+        .map(|(name, _, values)| {
+            let count = values.values().find_map(|value| value.as_u64());
+            (name, count.unwrap_or_default())
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::counts;
+
+    #[test]
+    fn test_counts() {
+        let body = r#"{"results":[{"statement_id":0,"series":[
+            {"name":"cpu","columns":["count"],"values":[[120]]},
+            {"name":"mem","columns":["count"],"values":[[4]]}
+        ]}]}"#;
+        assert_eq!(
+            counts(body).unwrap(),
+            vec![("cpu".to_string(), 120), ("mem".to_string(), 4)]
+        );
+
+        let body = r#"{"results":[{"statement_id":0,"series":[{"columns":["cardinality estimation"],"values":[[124]]}]}]}"#;
+        assert_eq!(counts(body).unwrap(), vec![(String::new(), 124)]);
+    }
+}
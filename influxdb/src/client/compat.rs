@@ -0,0 +1,223 @@
+//! Writing to databases which accept the line protocol of InfluxDB
+
+use surf::http::auth::BasicAuth;
+use surf::{Body, RequestBuilder};
+
+use crate::{Client, Error};
+
+/// A backend which accepts writes in the line protocol of InfluxDB, see
+/// [`Client::with_compatibility`](crate::Client::with_compatibility)
+///
+/// Compatible backends only accept writes. Read queries fail with an
+/// [`InvalidQueryError`](crate::Error::InvalidQueryError) instead of being sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum Compatibility {
+    /// InfluxDB itself, the default
+    #[default]
+    InfluxDb,
+    /// [VictoriaMetrics](https://victoriametrics.com), single-node or the `vminsert` of a
+    /// cluster, e.g. with the URL `http://vminsert:8480/insert/0/`
+    ///
+    /// Points are written to `influx/write` with precisions VictoriaMetrics understands. The
+    /// database isn't sent, as VictoriaMetrics would add it as a `db` label to every series.
+    VictoriaMetrics,
+    /// [QuestDB](https://questdb.io), which accepts line protocol over HTTP
+    ///
+    /// The database and the retention policy aren't sent, points are written to the table named
+    /// after their measurement.
+    QuestDb,
+}
+
+impl Compatibility {
+    fn name(self) -> &'static str {
+        match self {
+            Compatibility::InfluxDb => "InfluxDB",
+            Compatibility::VictoriaMetrics => "VictoriaMetrics",
+            Compatibility::QuestDb => "QuestDB",
+        }
+    }
+
+    fn write_endpoint(self) -> &'static str {
+        match self {
+            Compatibility::VictoriaMetrics => "influx/write",
+            _ => "write",
+        }
+    }
+
+    /// Translates the precision modifier of InfluxDB
+    fn precision(self, precision: String) -> String {
+        match (self, precision.as_str()) {
+            (Compatibility::VictoriaMetrics, "n") => "ns".to_string(),
+            (Compatibility::VictoriaMetrics, "u") => "us".to_string(),
+            _ => precision,
+        }
+    }
+
+    /// Returns the message of a response with an error status
+    ///
+    /// VictoriaMetrics answers with plain text, QuestDB with a JSON object with a `message`.
+    pub(crate) fn error_message(self, body: &str) -> String {
+        let body = body.trim();
+        if let Compatibility::QuestDb = self {
+            #[cfg(feature = "use-serde")]
+            {
+                if let Ok(serde_json::Value::Object(object)) = serde_json::from_str(body) {
+                    if let Some(message) = object.get("message").and_then(|m| m.as_str()) {
+                        return format!("{} error: {}", self.name(), message);
+                    }
+                }
+            }
+        }
+        format!("{} error: {}", self.name(), body)
+    }
+}
+
+impl Client {
+    /// Adjusts writes to a database which accepts the line protocol of InfluxDB, see
+    /// [`Compatibility`](crate::Compatibility)
+    ///
+    /// Besides the endpoint and the precision of writes, credentials are sent as basic
+    /// authentication and responses with an error status are always errors, as compatible
+    /// backends don't report errors the way InfluxDB does.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Client, Compatibility};
+    ///
+    /// let client = Client::new("http://localhost:8428", "metrics")
+    ///     .with_compatibility(Compatibility::VictoriaMetrics);
+    ///
+    /// assert_eq!(client.compatibility(), Compatibility::VictoriaMetrics);
+    /// ```
+    pub fn with_compatibility(mut self, compatibility: Compatibility) -> Self {
+        self.compatibility = compatibility;
+        self
+    }
+
+    /// Returns the backend the client writes to
+    pub fn compatibility(&self) -> Compatibility {
+        self.compatibility
+    }
+
+    /// Builds the request which writes `body` with the given precision, database and retention
+    /// policy
+    pub(crate) fn write_request<B>(
+        &self,
+        body: B,
+        precision: String,
+        database: Option<&str>,
+        retention_policy: Option<&str>,
+    ) -> Result<RequestBuilder, Error>
+    where
+        B: Into<Body>,
+    {
+        let url = self.endpoint(self.compatibility.write_endpoint())?;
+        let mut parameters = self.write_parameters(
+            self.compatibility.precision(precision),
+            database,
+            retention_policy,
+        );
+        let mut auth = None;
+        if self.compatibility != Compatibility::InfluxDb {
+            for name in ["db", "rp"] {
+                parameters.remove(name);
+            }
+            if let (Some(username), Some(password)) =
+                (parameters.remove("u"), parameters.remove("p"))
+            {
+                auth = Some(BasicAuth::new(username, password));
+            }
+        }
+
+        let mut request_builder = self
+            .client
+            .post(url)
+            .body(body)
+            .query(&parameters)
+            .map_err(|err| Error::UrlConstructionError {
+                error: err.to_string(),
+            })?;
+        if let Some(auth) = auth {
+            request_builder = request_builder.header(auth.name(), auth.value());
+        }
+        Ok(request_builder)
+    }
+
+    /// Fails if the backend of the client doesn't support read queries
+    pub(crate) fn check_reads(&self) -> Result<(), Error> {
+        match self.compatibility {
+            Compatibility::InfluxDb => Ok(()),
+            compatibility => Err(Error::InvalidQueryError {
+                error: format!(
+                    "{} doesn't support InfluxQL read queries",
+                    compatibility.name()
+                ),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Compatibility;
+    use crate::{Client, InfluxDbWriteable, ReadQuery, Timestamp};
+
+    fn url(client: &Client) -> String {
+        let point = Timestamp::Nanoseconds(1)
+            .into_query("weather")
+            .add_field("temperature", 82);
+        let request = client.request(&point).unwrap().build();
+        request.url().to_string()
+    }
+
+    #[test]
+    fn test_victoria_metrics() {
+        let client = Client::new("http://vminsert:8480/insert/0", "metrics")
+            .with_auth("admin", "secret")
+            .with_compatibility(Compatibility::VictoriaMetrics);
+        assert_eq!(
+            url(&client),
+            "http://vminsert:8480/insert/0/influx/write?precision=ns"
+        );
+        let request = client
+            .request(
+                &Timestamp::Seconds(1)
+                    .into_query("weather")
+                    .add_field("a", 1),
+            )
+            .unwrap()
+            .build();
+        assert_eq!(
+            request.header("Authorization").unwrap().as_str(),
+            "Basic YWRtaW46c2VjcmV0"
+        );
+        assert!(client.request(&ReadQuery::new("SELECT 1")).is_err());
+    }
+
+    #[test]
+    fn test_influxdb() {
+        let client = Client::new("http://localhost:8086", "test");
+        assert_eq!(client.compatibility(), Compatibility::InfluxDb);
+        let url = url(&client);
+        assert!(url.starts_with("http://localhost:8086/write?"));
+        assert!(url.contains("db=test"));
+        assert!(url.contains("precision=n"));
+    }
+
+    #[test]
+    fn test_error_message() {
+        assert_eq!(
+            Compatibility::VictoriaMetrics.error_message("cannot parse line\n"),
+            "VictoriaMetrics error: cannot parse line"
+        );
+        #[cfg(feature = "use-serde")]
+        assert_eq!(
+            Compatibility::QuestDb.error_message(
+                r#"{"code":"invalid","message":"failed to parse line protocol","line":1}"#
+            ),
+            "QuestDB error: failed to parse line protocol"
+        );
+    }
+}
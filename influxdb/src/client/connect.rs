@@ -0,0 +1,119 @@
+//! Warm-up of the connection to the server, before the first write needs it
+
+use futures::channel::oneshot;
+use std::net::SocketAddr;
+use std::thread;
+use std::time::{Duration, Instant};
+use surf::Url;
+
+use crate::{Client, Error};
+
+/// The connection established by [`Client::connect`](crate::Client::connect)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connection {
+    /// Addresses the host of the client resolved to
+    pub addresses: Vec<SocketAddr>,
+    /// Build type reported by the server, empty if it doesn't report one
+    pub build: String,
+    /// Version reported by the server, empty if it doesn't report one
+    pub version: String,
+    /// How long the ping took, on the connection which was opened for it
+    pub round_trip: Duration,
+}
+
+impl Client {
+    /// Resolves the host of the client, connects to it and pings it, so the first write doesn't
+    /// wait for DNS, the TCP and TLS handshakes
+    ///
+    /// The connection is kept by the HTTP client for the following requests. Meant to be called
+    /// when a service starts, so a wrong URL or an unreachable server is reported right away.
+    /// Use [`verify_credentials`](crate::Client::verify_credentials) to check the credentials as
+    /// well.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::Client;
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("https://influxdb.example.com:8086", "telegraf");
+    /// let connection = client.connect().await?;
+    /// println!(
+    ///     "connected to InfluxDB {} at {:?}",
+    ///     connection.version, connection.addresses
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn connect(&self) -> Result<Connection, Error> {
+        let url = self.endpoint("ping")?;
+        let addresses = resolve(url).await?;
+        let start = Instant::now();
+        let (build, version) = self.ping().await?;
+        Ok(Connection {
+            addresses,
+            build,
+            version,
+            round_trip: start.elapsed(),
+        })
+    }
+}
+
+/// Resolves the host of a URL on a thread of its own, as resolving blocks
+pub(super) async fn resolve(url: Url) -> Result<Vec<SocketAddr>, Error> {
+    let host = url.host_str().unwrap_or_default().to_string();
+    let (sender, receiver) = oneshot::channel();
+    thread::spawn(move || sender.send(url.socket_addrs(|| None)));
+    let addresses = receiver
+        .await
+        .map_err(|_| Error::ConnectionError {
+            error: format!("could not resolve {}", host),
+            source: None,
+        })?
+        .map_err(|err| Error::ConnectionError {
+            error: format!("could not resolve {}: {}", host, err),
+            source: crate::error::source(err),
+        })?;
+    if addresses.is_empty() {
+        return Err(Error::ConnectionError {
+            error: format!("{} resolved to no addresses", host),
+            source: None,
+        });
+    }
+    Ok(addresses)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::test_server::{response, serve};
+    use crate::{Client, Error};
+    use futures::executor::block_on;
+
+    #[test]
+    fn test_connect() {
+        let (address, _) = serve(|_| {
+            response(
+                "204 No Content",
+                "X-Influxdb-Build: OSS\r\nX-Influxdb-Version: 1.8.10\r\n",
+                "",
+            )
+        });
+
+        let client = Client::new(format!("http://{}", address), "test");
+        let connection = block_on(client.connect()).unwrap();
+        assert_eq!(connection.addresses, vec![address]);
+        assert_eq!(connection.version, "1.8.10");
+        assert_eq!(connection.build, "OSS");
+        assert!(client.server_version().is_some());
+    }
+
+    #[test]
+    fn test_connect_unresolvable() {
+        let client = Client::new("http://influxdb.invalid:8086", "test");
+        assert!(matches!(
+            block_on(client.connect()),
+            Err(Error::ConnectionError { .. })
+        ));
+    }
+}
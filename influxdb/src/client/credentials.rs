@@ -0,0 +1,465 @@
+//! Credentials which are looked up for every request, so they can be rotated
+
+use futures::future::BoxFuture;
+use futures::prelude::*;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use surf::Request;
+
+use crate::{Client, Error};
+
+/// Credentials a request is authenticated with
+#[derive(Clone, PartialEq, Eq)]
+pub enum Credentials {
+    /// Username and password of an InfluxDB user, sent as the `u` and `p` query parameters
+    Basic { username: String, password: String },
+    /// API token, sent in an `Authorization: Token` header, which requires InfluxDB 1.8 or later
+    Token(String),
+}
+
+impl Credentials {
+    /// Creates the credentials of an InfluxDB user
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Credentials;
+    ///
+    /// let _credentials = Credentials::new("admin", "password");
+    /// ```
+    pub fn new<S1, S2>(username: S1, password: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        Credentials::Basic {
+            username: username.into(),
+            password: password.into(),
+        }
+    }
+
+    /// Creates the credentials of an API token
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Credentials;
+    ///
+    /// let _credentials = Credentials::token("my-token");
+    /// ```
+    pub fn token<S>(token: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Credentials::Token(token.into())
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Credentials::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .field("password", &"REDACTED")
+                .finish(),
+            Credentials::Token(_) => f.debug_tuple("Token").field(&"REDACTED").finish(),
+        }
+    }
+}
+
+/// Source of the credentials a [`Client`](crate::Client) authenticates with, consulted before
+/// every request
+///
+/// Implemented by [`Credentials`](crate::Credentials) which never change,
+/// [`EnvCredentials`](crate::EnvCredentials), [`FileCredentials`](crate::FileCredentials) and by
+/// closures returning a future, e.g. to fetch them from a secret store.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::{Client, Credentials, Error};
+///
+/// async fn fetch_from_vault() -> Result<Credentials, Error> {
+///     // a request to the secret store
+///     Ok(Credentials::new("app", "s3cr3t"))
+/// }
+///
+/// let _client = Client::new("http://localhost:8086", "test")
+///     .with_credentials_provider(fetch_from_vault);
+/// ```
+pub trait CredentialsProvider: Send + Sync {
+    /// Returns the credentials for the next request
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials, Error>>;
+}
+
+impl CredentialsProvider for Credentials {
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials, Error>> {
+        future::ready(Ok(self.clone())).boxed()
+    }
+}
+
+impl<F, Fut> CredentialsProvider for F
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Credentials, Error>> + Send + 'static,
+{
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials, Error>> {
+        self().boxed()
+    }
+}
+
+/// Reads the credentials from environment variables, by default `INFLUX_USERNAME` and
+/// `INFLUX_PASSWORD`
+#[derive(Debug, Clone)]
+pub struct EnvCredentials {
+    variables: Variables,
+}
+
+#[derive(Debug, Clone)]
+enum Variables {
+    Basic { username: String, password: String },
+    Token(String),
+}
+
+impl Default for EnvCredentials {
+    fn default() -> Self {
+        EnvCredentials::new("INFLUX_USERNAME", "INFLUX_PASSWORD")
+    }
+}
+
+impl EnvCredentials {
+    /// Reads the credentials from the given environment variables
+    pub fn new<S1, S2>(username_variable: S1, password_variable: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        EnvCredentials {
+            variables: Variables::Basic {
+                username: username_variable.into(),
+                password: password_variable.into(),
+            },
+        }
+    }
+
+    /// Reads an API token from the given environment variable, e.g. `INFLUX_TOKEN`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Client, EnvCredentials};
+    ///
+    /// let _client = Client::new("http://localhost:8086", "test")
+    ///     .with_credentials_provider(EnvCredentials::token("INFLUX_TOKEN"));
+    /// ```
+    pub fn token<S>(token_variable: S) -> Self
+    where
+        S: Into<String>,
+    {
+        EnvCredentials {
+            variables: Variables::Token(token_variable.into()),
+        }
+    }
+}
+
+impl CredentialsProvider for EnvCredentials {
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials, Error>> {
+        let variable = |name: &str| {
+            env::var(name).map_err(|err| Error::CredentialsError {
+                error: format!("{}: {}", name, err),
+            })
+        };
+        let credentials = match &self.variables {
+            Variables::Basic { username, password } => variable(username).and_then(|username| {
+                variable(password).map(|password| Credentials::new(username, password))
+            }),
+            Variables::Token(token) => variable(token).map(Credentials::Token),
+        };
+        future::ready(credentials).boxed()
+    }
+}
+
+/// Reads the credentials from a file containing `username:password` or an API token, e.g. a
+/// mounted secret
+///
+/// The file is read again whenever it was modified.
+#[derive(Debug)]
+pub struct FileCredentials {
+    path: PathBuf,
+    token: bool,
+    cached: Mutex<Option<(SystemTime, Credentials)>>,
+}
+
+impl FileCredentials {
+    /// Reads the credentials from a file whose first line is `username:password`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Client, FileCredentials};
+    ///
+    /// let _client = Client::new("http://localhost:8086", "test")
+    ///     .with_credentials_provider(FileCredentials::new("/run/secrets/influxdb"));
+    /// ```
+    pub fn new<P>(path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        FileCredentials {
+            path: path.into(),
+            token: false,
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Reads an API token from the first line of a file
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Client, FileCredentials};
+    ///
+    /// let _client = Client::new("http://localhost:8086", "test")
+    ///     .with_credentials_provider(FileCredentials::token("/run/secrets/influxdb-token"));
+    /// ```
+    pub fn token<P>(path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        FileCredentials {
+            token: true,
+            ..FileCredentials::new(path)
+        }
+    }
+
+    fn read(&self) -> Result<Credentials, Error> {
+        let io_error = |err: std::io::Error| Error::CredentialsError {
+            error: format!("{}: {}", self.path.display(), err),
+        };
+        let modified = fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(io_error)?;
+        let mut cached = self.cached.lock().unwrap();
+        if let Some((cached_modified, credentials)) = cached.as_ref() {
+            if *cached_modified == modified {
+                return Ok(credentials.clone());
+            }
+        }
+
+        let content = fs::read_to_string(&self.path).map_err(io_error)?;
+        let credentials = if self.token {
+            parse_token(&content)
+        } else {
+            parse_credentials(&content)
+        };
+        let credentials = credentials.ok_or_else(|| Error::CredentialsError {
+            error: format!(
+                "{}: expected {}",
+                self.path.display(),
+                if self.token {
+                    "a token"
+                } else {
+                    "username:password"
+                }
+            ),
+        })?;
+        *cached = Some((modified, credentials.clone()));
+        Ok(credentials)
+    }
+}
+
+impl CredentialsProvider for FileCredentials {
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials, Error>> {
+        future::ready(self.read()).boxed()
+    }
+}
+
+fn parse_credentials(content: &str) -> Option<Credentials> {
+    let line = content.lines().next()?.trim();
+    let separator = line.find(':')?;
+    Some(Credentials::new(&line[..separator], &line[separator + 1..]))
+}
+
+fn parse_token(content: &str) -> Option<Credentials> {
+    let token = content.lines().next()?.trim();
+    if token.is_empty() {
+        return None;
+    }
+    Some(Credentials::token(token))
+}
+
+/// Replaces the credentials in the query parameters of a request, or sets its `Authorization`
+/// header to a token
+pub(crate) fn authenticate(request: &mut Request, credentials: &Credentials) {
+    let url = AsMut::<surf::http::Request>::as_mut(request).url_mut();
+    let mut parameters = url
+        .query_pairs()
+        .filter(|(name, _)| name != "u" && name != "p")
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect::<Vec<_>>();
+    if let Credentials::Basic { username, password } = credentials {
+        parameters.push(("u".to_string(), username.clone()));
+        parameters.push(("p".to_string(), password.clone()));
+    }
+    if parameters.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(parameters);
+    }
+
+    if let Credentials::Token(token) = credentials {
+        request.insert_header("Authorization", format!("Token {}", token));
+    }
+}
+
+/// The credentials provider of a client
+#[derive(Clone)]
+pub(crate) struct Provider(Arc<dyn CredentialsProvider>);
+
+impl Provider {
+    pub(crate) async fn credentials(&self) -> Result<Credentials, Error> {
+        self.0.credentials().await
+    }
+}
+
+impl fmt::Debug for Provider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Provider")
+    }
+}
+
+impl Client {
+    /// Authenticates every request with the credentials the
+    /// [`CredentialsProvider`](crate::CredentialsProvider) returns for it, instead of those set
+    /// with [`with_auth`](crate::Client::with_auth)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Client, FileCredentials};
+    ///
+    /// let _client = Client::new("http://localhost:8086", "test")
+    ///     .with_credentials_provider(FileCredentials::new("/run/secrets/influxdb"));
+    /// ```
+    pub fn with_credentials_provider<P>(mut self, provider: P) -> Self
+    where
+        P: CredentialsProvider + 'static,
+    {
+        self.credentials_provider = Some(Provider(Arc::new(provider)));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        authenticate, parse_credentials, parse_token, Credentials, CredentialsProvider,
+        EnvCredentials, FileCredentials,
+    };
+    use crate::client::test_server::{response, serve};
+    use crate::Client;
+    use futures::executor::block_on;
+    use std::fs;
+
+    #[test]
+    fn test_authenticate() {
+        let mut request = surf::get("http://localhost:8086/query?db=test&u=admin&p=old").build();
+        authenticate(&mut request, &Credentials::new("admin", "n&w"));
+        assert_eq!(request.url().query(), Some("db=test&u=admin&p=n%26w"));
+        assert!(request.header("Authorization").is_none());
+
+        authenticate(&mut request, &Credentials::token("rotated"));
+        assert_eq!(request.url().query(), Some("db=test"));
+        assert_eq!(request.header("Authorization").unwrap(), "Token rotated");
+
+        let mut request = surf::get("http://localhost:8086/ping?u=admin&p=old").build();
+        authenticate(&mut request, &Credentials::token("rotated"));
+        assert_eq!(request.url().as_str(), "http://localhost:8086/ping");
+    }
+
+    #[test]
+    fn test_parse_credentials() {
+        assert_eq!(
+            parse_credentials("admin:pass:word\n"),
+            Some(Credentials::new("admin", "pass:word"))
+        );
+        assert_eq!(parse_credentials("admin"), None);
+        assert_eq!(parse_credentials(""), None);
+        assert_eq!(
+            parse_token(" my-token \n"),
+            Some(Credentials::token("my-token"))
+        );
+        assert_eq!(parse_token("\n"), None);
+    }
+
+    #[test]
+    fn test_file_credentials() {
+        let path =
+            std::env::temp_dir().join(format!("influxdb-credentials-{}", std::process::id()));
+        fs::write(&path, "admin:first\n").unwrap();
+        let provider = FileCredentials::new(&path);
+        assert_eq!(
+            block_on(provider.credentials()).unwrap(),
+            Credentials::new("admin", "first")
+        );
+
+        fs::remove_file(&path).unwrap();
+        assert!(block_on(provider.credentials()).is_err());
+    }
+
+    #[test]
+    fn test_token_credentials() {
+        let path = std::env::temp_dir().join(format!("influxdb-token-{}", std::process::id()));
+        fs::write(&path, "first\n").unwrap();
+        let provider = FileCredentials::token(&path);
+        assert_eq!(
+            block_on(provider.credentials()).unwrap(),
+            Credentials::token("first")
+        );
+        fs::remove_file(&path).unwrap();
+
+        let variable = format!("INFLUXDB_TEST_TOKEN_{}", std::process::id());
+        std::env::set_var(&variable, "from-env");
+        assert_eq!(
+            block_on(EnvCredentials::token(&variable).credentials()).unwrap(),
+            Credentials::token("from-env")
+        );
+        std::env::remove_var(&variable);
+        assert!(block_on(EnvCredentials::token(&variable).credentials()).is_err());
+        assert_eq!(
+            format!("{:?}", Credentials::token("secret")),
+            "Token(\"REDACTED\")"
+        );
+    }
+
+    #[test]
+    fn test_ping_is_authenticated() {
+        let (address, requests) =
+            serve(|_| response("204 No Content", "X-Influxdb-Version: 1.8.10\r\n", ""));
+
+        let client = Client::new(format!("http://{}", address), "test")
+            .with_credentials_provider(|| async { Ok(Credentials::token("rotated")) });
+        let (_, version) = block_on(client.ping()).unwrap();
+        assert_eq!(version, "1.8.10");
+        let head = requests.recv().unwrap().head.to_ascii_lowercase();
+        assert!(
+            head.contains("authorization: token rotated\r\n"),
+            "{}",
+            head
+        );
+    }
+
+    #[test]
+    fn test_closure() {
+        let provider = || async { Ok(Credentials::new("admin", "fetched")) };
+        assert_eq!(
+            block_on(provider.credentials()).unwrap(),
+            Credentials::new("admin", "fetched")
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Inspection of requests without sending them
+
+use std::fmt;
+
+use crate::query::QueryType;
+use crate::{Client, Error, Query};
+
+/// Parameters whose values are replaced by [`Client::dry_run`](crate::Client::dry_run)
+const REDACTED_PARAMETERS: [&str; 1] = ["p"];
+
+/// The request a query would be sent with, as returned by
+/// [`Client::dry_run`](crate::Client::dry_run)
+///
+/// Displays like an HTTP request, with the method and URL on the first line and the body, if
+/// any, after an empty line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DryRun {
+    /// HTTP method, `GET` or `POST`
+    pub method: String,
+    /// Full URL including all parameters, with the password redacted
+    pub url: String,
+    /// Body of the request, only used by write queries
+    pub body: Option<String>,
+}
+
+impl fmt::Display for DryRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.url)?;
+        if let Some(body) = &self.body {
+            write!(f, "\n\n{}", body)?;
+        }
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Returns the request the query would be sent with, without sending it
+    ///
+    /// Useful to debug escaping issues, or to snapshot test the generated requests.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Client, InfluxDbWriteable, Timestamp};
+    ///
+    /// let client = Client::new("http://localhost:8086", "test").with_auth("admin", "secret");
+    /// let query = Timestamp::Seconds(1)
+    ///     .into_query("weather")
+    ///     .add_tag("location", "us east")
+    ///     .add_field("temperature", 82);
+    ///
+    /// let request = client.dry_run(&query).unwrap();
+    /// assert_eq!(request.method, "POST");
+    /// assert_eq!(request.body.as_deref(), Some(r#"weather,location=us\ east temperature=82i 1"#));
+    /// assert!(!request.url.contains("secret"));
+    /// ```
+    pub fn dry_run<Q>(&self, q: &Q) -> Result<DryRun, Error>
+    where
+        Q: Query,
+    {
+        let request = self.with_headers(self.request(q)?).build();
+        let mut url = request.url().clone();
+        let parameters = url
+            .query_pairs()
+            .map(|(name, value)| {
+                let value = if REDACTED_PARAMETERS.contains(&name.as_ref()) {
+                    "REDACTED".into()
+                } else {
+                    value
+                };
+                (name.into_owned(), value.into_owned())
+            })
+            .collect::<Vec<_>>();
+        url.query_pairs_mut().clear().extend_pairs(parameters);
+
+        let body = match q.get_type() {
+            QueryType::WriteQuery(_) => Some(self.build_query(q)?.0.get()),
+            QueryType::ReadQuery => None,
+        };
+        Ok(DryRun {
+            method: request.method().to_string(),
+            url: url.to_string(),
+            body,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Client, Error, InfluxDbWriteable, ReadQuery, Timestamp, WriteQuery};
+
+    #[test]
+    fn test_dry_run_read() {
+        let client = Client::new("http://localhost:8086", "test").with_auth("admin", "secret");
+        let request = client
+            .dry_run(&ReadQuery::new("SELECT * FROM weather"))
+            .unwrap();
+        let url = request.url.parse::<surf::Url>().unwrap();
+        let mut parameters = url.query_pairs().into_owned().collect::<Vec<_>>();
+        parameters.sort();
+
+        assert_eq!(request.method, "GET");
+        assert_eq!(url.path(), "/query");
+        assert_eq!(
+            parameters,
+            vec![
+                ("db".to_string(), "test".to_string()),
+                ("p".to_string(), "REDACTED".to_string()),
+                ("q".to_string(), "SELECT * FROM weather".to_string()),
+                ("u".to_string(), "admin".to_string()),
+            ]
+        );
+        assert_eq!(request.body, None);
+    }
+
+    #[test]
+    fn test_dry_run_write_hooks() {
+        let client = Client::new("http://localhost:8086", "test")
+            .with_write_hook(|point: WriteQuery| Ok::<_, Error>(point.add_tag("region", "eu")));
+        let points = vec![
+            Timestamp::Seconds(1)
+                .into_query("weather")
+                .add_field("temperature", 82),
+            Timestamp::Seconds(2)
+                .into_query("weather")
+                .add_field("temperature", 83),
+        ];
+
+        let request = client.dry_run(&points).unwrap();
+        assert_eq!(
+            request.body.as_deref(),
+            Some("weather,region=eu temperature=82i 1\nweather,region=eu temperature=83i 2")
+        );
+    }
+
+    #[test]
+    fn test_dry_run_mixed_precisions() {
+        use crate::Precision;
+
+        // the hook makes the points finer than the precision of the batch
+        let client =
+            Client::new("http://localhost:8086", "test").with_write_hook(|point: WriteQuery| {
+                let timestamp = point.timestamp().to_precision(Precision::Milliseconds);
+                WriteQuery::from_parts(
+                    "weather",
+                    point.tags().to_vec(),
+                    point.fields().to_vec(),
+                    timestamp,
+                )
+            });
+        let points = vec![
+            Timestamp::Seconds(1)
+                .into_query("weather")
+                .add_field("temperature", 82),
+            Timestamp::Seconds(2)
+                .into_query("weather")
+                .add_field("temperature", 83),
+        ];
+
+        let request = client.dry_run(&points).unwrap();
+        assert!(request.url.contains("precision=ms"), "{}", request.url);
+        assert_eq!(
+            request.body.as_deref(),
+            Some("weather temperature=82i 1000\nweather temperature=83i 2000")
+        );
+    }
+}
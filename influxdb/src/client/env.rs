@@ -0,0 +1,202 @@
+//! Construction of clients configured like the official InfluxDB tooling
+
+use std::env;
+#[cfg(feature = "cli-config")]
+use std::path::Path;
+
+use crate::{Client, Error};
+
+impl Client {
+    /// Creates a client configured by the environment variables of the official InfluxDB tooling
+    ///
+    /// `INFLUX_HOST` and `INFLUX_DATABASE` are required. Requests are authenticated with
+    /// `INFLUX_TOKEN` if it is set, or with `INFLUX_USERNAME` and `INFLUX_PASSWORD`. `INFLUX_ORG`
+    /// is only needed by the API of InfluxDB 2 and ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::Client;
+    ///
+    /// // INFLUX_HOST=http://localhost:8086 INFLUX_DATABASE=test INFLUX_TOKEN=admin:password
+    /// let client = Client::from_env()?;
+    /// # Ok::<(), influxdb::Error>(())
+    /// ```
+    pub fn from_env() -> Result<Client, Error> {
+        from_variables(|name| env::var(name).ok().filter(|value| !value.is_empty()))
+    }
+
+    /// Creates a client configured by a configuration file of the `influx` CLI, usually
+    /// `~/.influxdbv2/configs`
+    ///
+    /// The active configuration is used, or the one named `default` if none is active. As the
+    /// file doesn't name a database, it is read from a `database` key of the configuration or
+    /// from `INFLUX_DATABASE`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::Client;
+    ///
+    /// let client = Client::from_config("/home/me/.influxdbv2/configs")?;
+    /// # Ok::<(), influxdb::Error>(())
+    /// ```
+    #[cfg(feature = "cli-config")]
+    pub fn from_config<P>(path: P) -> Result<Client, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|err| Error::IoError {
+            error: format!("{}: {}", path.display(), err),
+            source: crate::error::source(err),
+        })?;
+        cli_config::parse(&content, env::var("INFLUX_DATABASE").ok())
+    }
+}
+
+/// Configures a client from variables, looked up by name
+fn from_variables<F>(variable: F) -> Result<Client, Error>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let required = |name: &str| {
+        variable(name).ok_or_else(|| Error::ConfigError {
+            error: format!("{} is not set", name),
+            source: None,
+        })
+    };
+    let mut builder = Client::builder()
+        .url(required("INFLUX_HOST")?)
+        .database(required("INFLUX_DATABASE")?);
+    if let Some(token) = variable("INFLUX_TOKEN") {
+        builder = builder.header("Authorization", format!("Token {}", token));
+    } else if let Some(username) = variable("INFLUX_USERNAME") {
+        builder = builder.auth(username, variable("INFLUX_PASSWORD").unwrap_or_default());
+    }
+    builder.build()
+}
+
+#[cfg(feature = "cli-config")]
+mod cli_config {
+    use serde::Deserialize;
+    use std::collections::BTreeMap;
+
+    use crate::{Client, Error};
+
+    /// A configuration of the `influx` CLI
+    #[derive(Deserialize)]
+    struct Config {
+        url: String,
+        token: Option<String>,
+        #[serde(default)]
+        active: bool,
+        database: Option<String>,
+    }
+
+    pub(super) fn parse(content: &str, database: Option<String>) -> Result<Client, Error> {
+        let configs: BTreeMap<String, Config> =
+            toml::from_str(content).map_err(|err| Error::ConfigError {
+                error: format!("invalid influx CLI configuration: {}", err),
+                source: crate::error::source(err),
+            })?;
+        let config = configs
+            .values()
+            .find(|config| config.active)
+            .or_else(|| configs.get("default"))
+            .ok_or_else(|| Error::ConfigError {
+                error: "influx CLI configuration has neither an active nor a default configuration"
+                    .to_string(),
+                source: None,
+            })?;
+        let database = config
+            .database
+            .clone()
+            .or(database)
+            .ok_or_else(|| Error::ConfigError {
+                error: "INFLUX_DATABASE is not set".to_string(),
+                source: None,
+            })?;
+
+        let mut builder = Client::builder().url(&config.url).database(database);
+        if let Some(token) = &config.token {
+            builder = builder.header("Authorization", format!("Token {}", token));
+        }
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::from_variables;
+    use crate::Error;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_from_variables() {
+        let mut variables = HashMap::new();
+        variables.insert("INFLUX_HOST", "http://localhost:8086");
+        assert!(matches!(
+            from_variables(|name| variables.get(name).map(|v| v.to_string())),
+            Err(Error::ConfigError { .. })
+        ));
+
+        variables.insert("INFLUX_DATABASE", "telegraf");
+        variables.insert("INFLUX_USERNAME", "admin");
+        variables.insert("INFLUX_PASSWORD", "secret");
+        let client = from_variables(|name| variables.get(name).map(|v| v.to_string())).unwrap();
+        assert_eq!(client.database_url(), "http://localhost:8086");
+        assert_eq!(client.database_name(), "telegraf");
+        assert_eq!(client.parameters.get("u").unwrap(), "admin");
+
+        variables.insert("INFLUX_TOKEN", "admin:secret");
+        let client = from_variables(|name| variables.get(name).map(|v| v.to_string())).unwrap();
+        assert_eq!(client.parameters.get("u"), None);
+        assert_eq!(
+            client.headers.get("Authorization").unwrap(),
+            "Token admin:secret"
+        );
+    }
+
+    #[cfg(feature = "cli-config")]
+    #[test]
+    fn test_cli_config() {
+        let content = r#"
+[default]
+  url = "http://localhost:8086"
+  token = "default-token"
+  org = "acme"
+
+[staging]
+  url = "https://staging.example.com:8086"
+  token = "staging-token"
+  org = "acme"
+  active = true
+  database = "metrics"
+"#;
+        let client = super::cli_config::parse(content, None).unwrap();
+        assert_eq!(client.database_url(), "https://staging.example.com:8086");
+        assert_eq!(client.database_name(), "metrics");
+        assert_eq!(
+            client.headers.get("Authorization").unwrap(),
+            "Token staging-token"
+        );
+
+        match super::cli_config::parse("[default", None) {
+            Err(Error::ConfigError { source, .. }) => assert!(source.is_some()),
+            _ => panic!("invalid configuration was accepted"),
+        }
+        let content = "[staging]\nurl = \"http://localhost:8086\"\n";
+        assert!(matches!(
+            super::cli_config::parse(content, None),
+            Err(Error::ConfigError { source: None, .. })
+        ));
+        let content = "[default]\nurl = \"http://localhost:8086\"\n";
+        assert!(matches!(
+            super::cli_config::parse(content, None),
+            Err(Error::ConfigError { .. })
+        ));
+        let client = super::cli_config::parse(content, Some("test".to_string())).unwrap();
+        assert_eq!(client.database_name(), "test");
+    }
+}
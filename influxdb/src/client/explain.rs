@@ -0,0 +1,74 @@
+//! Running `EXPLAIN` and `EXPLAIN ANALYZE`
+
+use serde_json::Value;
+
+use crate::query::explain::{PlanNode, QueryPlan};
+use crate::{Client, Error, ReadQuery};
+
+impl Client {
+    /// Shows the plans of the statements of a query, one per statement, without running them
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::{Client, ReadQuery};
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("http://localhost:8086", "test");
+    /// let plans = client
+    ///     .explain(&ReadQuery::new("SELECT mean(usage_idle) FROM cpu"))
+    ///     .await?;
+    /// println!("reading {:?} series", plans[0].number_of_series());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn explain(&self, query: &ReadQuery) -> Result<Vec<QueryPlan>, Error> {
+        let rows = self.explain_rows(&query.explain()).await?;
+        Ok(rows.into_iter().map(QueryPlan::parse).collect())
+    }
+
+    /// Runs the statements of a query and shows their plans, one per statement, annotated with
+    /// the time spent and the data read by each step
+    pub async fn explain_analyze(&self, query: &ReadQuery) -> Result<Vec<PlanNode>, Error> {
+        let rows = self.explain_rows(&query.explain_analyze()).await?;
+        rows.into_iter()
+            .map(|rows| {
+                PlanNode::parse(rows).ok_or_else(|| Error::DeserializationError {
+                    error: "EXPLAIN ANALYZE returned an empty plan".to_string(),
+                    source: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the rows of the single column `EXPLAIN` statements return, per statement
+    async fn explain_rows(&self, query: &ReadQuery) -> Result<Vec<Vec<String>>, Error> {
+        let body = self.query(query).await?;
+        let response: Value =
+            serde_json::from_str(&body).map_err(|err| Error::DeserializationError {
+                error: err.to_string(),
+                source: crate::error::source(err),
+            })?;
+
+        let results = response["results"].as_array().cloned().unwrap_or_default();
+        results
+            .iter()
+            .map(|result| {
+                if let Some(error) = result["error"].as_str() {
+                    return Err(Error::DatabaseError {
+                        error: error.to_string(),
+                        status: None,
+                        request_id: None,
+                    });
+                }
+                let series = result["series"].as_array().cloned().unwrap_or_default();
+                Ok(series
+                    .iter()
+                    .flat_map(|series| series["values"].as_array().cloned().unwrap_or_default())
+                    .filter_map(|row| row[0].as_str().map(ToOwned::to_owned))
+                    .collect())
+            })
+            .collect()
+    }
+}
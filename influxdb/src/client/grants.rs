@@ -0,0 +1,237 @@
+//! Privileges of users, to check them before the first read or write
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::stats::parse_rows;
+use crate::query::influxql_term::InfluxQlTerm;
+use crate::{Client, Error, ReadQuery};
+
+/// Privilege of a user on a database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Privilege {
+    Read,
+    Write,
+    All,
+    NoPrivileges,
+}
+
+impl Privilege {
+    /// Returns whether a user with this privilege has `privilege` as well, e.g. `ALL PRIVILEGES`
+    /// include `WRITE`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Privilege;
+    ///
+    /// assert!(Privilege::All.includes(Privilege::Write));
+    /// assert!(!Privilege::Read.includes(Privilege::Write));
+    /// ```
+    pub fn includes(self, privilege: Privilege) -> bool {
+        match (self, privilege) {
+            (_, Privilege::NoPrivileges) | (Privilege::All, _) => true,
+            (granted, privilege) => granted == privilege,
+        }
+    }
+
+    fn parse(privilege: &str) -> Option<Self> {
+        match privilege {
+            "READ" => Some(Privilege::Read),
+            "WRITE" => Some(Privilege::Write),
+            "ALL PRIVILEGES" => Some(Privilege::All),
+            "NO PRIVILEGES" => Some(Privilege::NoPrivileges),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Privilege {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Privilege::Read => "READ",
+            Privilege::Write => "WRITE",
+            Privilege::All => "ALL PRIVILEGES",
+            Privilege::NoPrivileges => "NO PRIVILEGES",
+        })
+    }
+}
+
+/// Privilege of a user on a database, as returned by `SHOW GRANTS FOR`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grant {
+    pub database: String,
+    pub privilege: Privilege,
+}
+
+impl Client {
+    /// Returns the privileges of a user on all databases, by running `SHOW GRANTS FOR`
+    ///
+    /// Only admin users can show grants. Admin users have all privileges on all databases, which
+    /// aren't listed as grants.
+    pub async fn show_grants_for(&self, user: &str) -> Result<Vec<Grant>, Error> {
+        let statement = format!(
+            "SHOW GRANTS FOR {}",
+            InfluxQlTerm::Identifier(user).escape()
+        );
+        let body = self.query(&ReadQuery::new(statement)).await?;
+        parse_rows(&body)?.iter().map(grant).collect()
+    }
+
+    /// Checks that the server accepts the credentials of the client and that its user has
+    /// privileges on the database of the client, with a cheap `SHOW DATABASES`
+    ///
+    /// Meant to be called when a service starts, so missing credentials or privileges are reported
+    /// right away instead of by the first write. Fails with an
+    /// [`AuthenticationError`](crate::Error::AuthenticationError) or
+    /// [`AuthorizationError`](crate::Error::AuthorizationError) if the credentials are rejected
+    /// and with [`InsufficientPrivileges`](crate::Error::InsufficientPrivileges) if the user
+    /// can't see the database, because it has neither read nor write privileges on it or because
+    /// the database doesn't exist.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::Client;
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("http://localhost:8086", "telegraf").with_auth("telegraf", "s3cr3t");
+    /// client.verify_credentials().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn verify_credentials(&self) -> Result<(), Error> {
+        let body = self.query(&ReadQuery::new("SHOW DATABASES")).await?;
+        let databases = parse_rows(&body)?
+            .iter()
+            .map(|row| text(row, "name"))
+            .collect::<Result<Vec<_>, _>>()?;
+        check_visible(&databases, self.database_name())
+    }
+
+    /// Checks that `user` has `privilege` on the database of the client, e.g. that the user a
+    /// service is deployed with can write
+    ///
+    /// Fails with [`InsufficientPrivileges`](crate::Error::InsufficientPrivileges) otherwise. Like
+    /// [`show_grants_for`](crate::Client::show_grants_for), it needs a client of an admin user.
+    pub async fn require_privilege(&self, user: &str, privilege: Privilege) -> Result<(), Error> {
+        let database = self.database_name();
+        let granted = self
+            .show_grants_for(user)
+            .await?
+            .into_iter()
+            .find(|grant| grant.database == database)
+            .map_or(Privilege::NoPrivileges, |grant| grant.privilege);
+        if granted.includes(privilege) {
+            return Ok(());
+        }
+        let body = self.query(&ReadQuery::new("SHOW USERS")).await?;
+        let admin = parse_rows(&body)?.iter().any(|row| {
+            row.get("user").and_then(Value::as_str) == Some(user)
+                && row.get("admin").and_then(Value::as_bool) == Some(true)
+        });
+        if admin {
+            return Ok(());
+        }
+        Err(insufficient(user, database, granted, privilege))
+    }
+}
+
+fn grant(row: &BTreeMap<String, Value>) -> Result<Grant, Error> {
+    let privilege = text(row, "privilege")?;
+    Ok(Grant {
+        database: text(row, "database")?,
+        privilege: Privilege::parse(&privilege).ok_or_else(|| missing("privilege"))?,
+    })
+}
+
+fn check_visible(databases: &[String], database: &str) -> Result<(), Error> {
+    if databases.iter().any(|name| name == database) {
+        return Ok(());
+    }
+    Err(Error::InsufficientPrivileges {
+        error: format!(
+            "the user has no privileges on database `{}`, or it doesn't exist",
+            database
+        ),
+    })
+}
+
+fn insufficient(user: &str, database: &str, granted: Privilege, required: Privilege) -> Error {
+    let granted = match granted {
+        Privilege::NoPrivileges => "no privileges".to_string(),
+        granted => format!("{} privileges", granted),
+    };
+    Error::InsufficientPrivileges {
+        error: format!(
+            "user `{}` has {} on database `{}`, {} required",
+            user, granted, database, required
+        ),
+    }
+}
+
+fn missing(column: &str) -> Error {
+    Error::DeserializationError {
+        error: format!("missing or invalid column {}", column),
+        source: None,
+    }
+}
+
+fn text(row: &BTreeMap<String, Value>, column: &str) -> Result<String, Error> {
+    row.get(column)
+        .and_then(Value::as_str)
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| missing(column))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_visible, grant, insufficient, Grant, Privilege};
+    use crate::client::stats::parse_rows;
+    use crate::Error;
+
+    #[test]
+    fn test_grants() {
+        let body = r#"{"results":[{"statement_id":0,"series":[{"columns":["database","privilege"],"values":[
+            ["telegraf","WRITE"],["grafana","ALL PRIVILEGES"],["old","NO PRIVILEGES"]
+        ]}]}]}"#;
+        let grants = parse_rows(body)
+            .unwrap()
+            .iter()
+            .map(grant)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            grants[0],
+            Grant {
+                database: "telegraf".to_string(),
+                privilege: Privilege::Write,
+            }
+        );
+        assert_eq!(grants[1].privilege, Privilege::All);
+        assert_eq!(grants[2].privilege, Privilege::NoPrivileges);
+        assert!(grant(&Default::default()).is_err());
+    }
+
+    #[test]
+    fn test_privileges() {
+        assert!(Privilege::Write.includes(Privilege::NoPrivileges));
+        assert!(!Privilege::NoPrivileges.includes(Privilege::Read));
+        assert!(!Privilege::Write.includes(Privilege::Read));
+
+        let error = insufficient("app", "telegraf", Privilege::Read, Privilege::Write);
+        assert!(error.is_auth());
+        assert_eq!(
+            error.to_string(),
+            "insufficient privileges: user `app` has READ privileges on database `telegraf`, WRITE required"
+        );
+        assert!(check_visible(&["telegraf".to_string()], "telegraf").is_ok());
+        assert!(matches!(
+            check_visible(&[], "telegraf"),
+            Err(Error::InsufficientPrivileges { .. })
+        ));
+    }
+}
@@ -0,0 +1,251 @@
+//! Staggered connection attempts to all addresses of the host of the client, see
+//! [`Client::with_happy_eyeballs`](crate::Client::with_happy_eyeballs)
+
+use futures::channel::oneshot;
+use std::collections::VecDeque;
+use std::io;
+use std::net::{SocketAddr, TcpStream};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use surf::{Request, Url};
+
+use super::connect::resolve;
+use crate::{Client, Error};
+
+/// How long a single connection attempt may take
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Remembers the address of the host which accepted a connection, shared by clones of a client
+#[derive(Debug)]
+pub(crate) struct AddressSelector {
+    stagger: Duration,
+    healthy: Mutex<Option<SocketAddr>>,
+}
+
+impl AddressSelector {
+    fn new(stagger: Duration) -> Self {
+        AddressSelector {
+            stagger,
+            healthy: Mutex::new(None),
+        }
+    }
+
+    /// Returns a selector with the same stagger which hasn't remembered an address yet, e.g. for
+    /// another URL
+    pub(crate) fn fresh(&self) -> Self {
+        AddressSelector::new(self.stagger)
+    }
+
+    /// Returns whether requests to the URL are sent to a selected address
+    ///
+    /// With `https`, the HTTP client needs the host name to verify the certificate of the server.
+    pub(crate) fn applies(url: &Url) -> bool {
+        url.scheme() == "http" && url.domain().is_some()
+    }
+
+    /// Returns the remembered address, or races connections to all addresses of the host of the
+    /// URL and remembers the first one which accepts
+    pub(crate) async fn select(&self, url: &Url) -> Result<SocketAddr, Error> {
+        if let Some(address) = *self.healthy.lock().unwrap() {
+            return Ok(address);
+        }
+        let host = url.host_str().unwrap_or_default().to_string();
+        let addresses = interleave(resolve(url.clone()).await?);
+        let stagger = self.stagger;
+        let (sender, receiver) = oneshot::channel();
+        thread::spawn(move || sender.send(race(&addresses, stagger)));
+        let address = receiver
+            .await
+            .unwrap_or_else(|_| Err(io::Error::other("connection attempts were aborted")))
+            .map_err(|err| Error::ConnectionError {
+                error: format!("could not connect to {}: {}", host, err),
+                source: crate::error::source(err),
+            })?;
+        *self.healthy.lock().unwrap() = Some(address);
+        Ok(address)
+    }
+
+    /// Forgets the address if it is the remembered one, so the next request races again
+    pub(crate) fn forget(&self, address: SocketAddr) {
+        let mut healthy = self.healthy.lock().unwrap();
+        if *healthy == Some(address) {
+            *healthy = None;
+        }
+    }
+}
+
+/// Sends the request to the address, keeping the host and port of its URL in the `Host` header
+pub(crate) fn pin(request: &mut Request, address: SocketAddr) {
+    let url = AsMut::<surf::http::Request>::as_mut(request).url_mut();
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+    // only fail for URLs which can't have a host, which have no host name to begin with
+    let _ = url.set_ip_host(address.ip());
+    let _ = url.set_port(Some(address.port()));
+    request.insert_header("Host", host);
+}
+
+/// Orders the addresses alternating between IPv6 and IPv4, starting with the family of the first
+fn interleave(addresses: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let first_v6 = addresses.first().is_some_and(SocketAddr::is_ipv6);
+    let (mut preferred, mut other): (VecDeque<_>, VecDeque<_>) = addresses
+        .into_iter()
+        .partition(|address| address.is_ipv6() == first_v6);
+    let mut interleaved = Vec::with_capacity(preferred.len() + other.len());
+    while !preferred.is_empty() || !other.is_empty() {
+        interleaved.extend(preferred.pop_front());
+        interleaved.extend(other.pop_front());
+    }
+    interleaved
+}
+
+/// Connects to the addresses in order, starting the next attempt once the previous one failed or
+/// after `stagger`, and returns the first address which accepted a connection
+fn race(addresses: &[SocketAddr], stagger: Duration) -> io::Result<SocketAddr> {
+    let (sender, receiver) = mpsc::channel();
+    let (mut started, mut failed) = (0, 0);
+    let mut last_error = None;
+    loop {
+        let attempt = if let Some(address) = addresses.get(started).copied() {
+            let sender = sender.clone();
+            thread::spawn(move || {
+                let _ = sender
+                    .send(TcpStream::connect_timeout(&address, CONNECT_TIMEOUT).map(|_| address));
+            });
+            started += 1;
+            receiver.recv_timeout(stagger).ok()
+        } else if failed < started {
+            receiver.recv().ok()
+        } else {
+            return Err(last_error.unwrap_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, "no addresses to connect to")
+            }));
+        };
+        match attempt {
+            Some(Ok(address)) => return Ok(address),
+            Some(Err(error)) => {
+                failed += 1;
+                last_error = Some(error);
+            }
+            None => {}
+        }
+    }
+}
+
+impl Client {
+    /// Connects to all addresses the host name of the client resolves to, `stagger` apart, and
+    /// sends requests to the first one which accepts the connection
+    ///
+    /// Like Happy Eyeballs (RFC 8305), IPv6 and IPv4 addresses are tried alternately and the next
+    /// attempt starts as soon as the previous one failed. The address is remembered by the client
+    /// and its clones until a request to it can't connect or the [URL changes](Client::with_url),
+    /// the next request tries all addresses again. The `Host` header keeps the host name, e.g. for virtual hosts of a reverse proxy.
+    ///
+    /// Only `http` URLs with a host name are affected: with `https`, the HTTP client needs the host
+    /// name to verify the certificate and tries the addresses one after another itself.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Client;
+    /// use std::time::Duration;
+    ///
+    /// let client = Client::new("http://influxdb.internal:8086", "telegraf")
+    ///     .with_happy_eyeballs(Duration::from_millis(250));
+    /// ```
+    pub fn with_happy_eyeballs(mut self, stagger: Duration) -> Self {
+        self.address_selector = Some(Arc::new(AddressSelector::new(stagger)));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{interleave, race};
+    use crate::client::test_server::{response, serve};
+    use crate::{Client, ReadQuery};
+    use futures::executor::block_on;
+    use std::net::{SocketAddr, TcpListener};
+    use std::time::Duration;
+
+    /// Returns an address nothing listens on
+    fn closed() -> SocketAddr {
+        TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_interleave() {
+        let addresses = ["[::1]:1", "[::2]:1", "[::3]:1", "10.0.0.1:1", "10.0.0.2:1"]
+            .iter()
+            .map(|address| address.parse().unwrap())
+            .collect::<Vec<SocketAddr>>();
+        let interleaved = interleave(addresses.clone());
+        assert_eq!(
+            interleaved,
+            vec![
+                addresses[0],
+                addresses[3],
+                addresses[1],
+                addresses[4],
+                addresses[2]
+            ]
+        );
+        assert!(interleave(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_race() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let open = listener.local_addr().unwrap();
+        let stagger = Duration::from_secs(60);
+
+        // failed attempts don't wait for the stagger
+        assert_eq!(race(&[closed(), closed(), open], stagger).unwrap(), open);
+        assert!(race(&[closed()], stagger).is_err());
+        assert!(race(&[], stagger).is_err());
+    }
+
+    #[test]
+    fn test_happy_eyeballs() {
+        // the connection of the race is closed without a request
+        let (address, requests) = serve(|_| {
+            response(
+                "200 OK",
+                "Content-Type: application/json\r\n",
+                r#"{"results":[{"statement_id":0}]}"#,
+            )
+        });
+        let port = address.port();
+
+        // `localhost` may resolve to `::1` as well, where nothing listens
+        let client = Client::new(format!("http://localhost:{}", port), "test")
+            .with_happy_eyeballs(Duration::from_millis(50));
+        block_on(client.query(&ReadQuery::new("SHOW DATABASES"))).unwrap();
+        let request = requests.recv().unwrap().head.to_lowercase();
+        assert!(
+            request.contains(&format!("host: localhost:{}", port)),
+            "{}",
+            request
+        );
+
+        // the address isn't remembered for another URL
+        let healthy = |client: &Client| {
+            *client
+                .address_selector
+                .as_ref()
+                .unwrap()
+                .healthy
+                .lock()
+                .unwrap()
+        };
+        let moved = client.clone().with_url("http://localhost:1");
+        assert_eq!(healthy(&moved), None);
+        assert_eq!(healthy(&client).map(|address| address.port()), Some(port));
+    }
+}
@@ -0,0 +1,219 @@
+//! Background monitoring of the connection to the server
+
+use futures::channel::oneshot;
+use futures::future::{poll_fn, select, Either};
+use futures_timer::Delay;
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Poll, Waker};
+use std::time::Duration;
+
+use crate::{BackgroundTask, Client};
+
+/// How many pings in a row have to fail before the server is considered down
+const FAILURES_UNTIL_DOWN: u32 = 3;
+
+/// The health of the connection to the server, as observed by a [`HealthMonitor`](crate::HealthMonitor)
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Health {
+    /// The last ping succeeded
+    Healthy,
+    /// The last ping failed, but the server hasn't been unreachable for long
+    Degraded,
+    /// The last pings failed
+    Down,
+}
+
+#[derive(Debug)]
+struct State {
+    health: Health,
+    version: u64,
+    wakers: Vec<Waker>,
+    stop: Option<oneshot::Sender<()>>,
+}
+
+/// Handle to the current health of the connection, updated by the future returned alongside it by
+/// [`Client::health_monitor`](crate::Client::health_monitor)
+///
+/// The monitoring stops once all handles and [`HealthWatch`](crate::HealthWatch)es are dropped,
+/// or one of the handles is [stopped](crate::HealthMonitor::stop).
+#[derive(Clone, Debug)]
+pub struct HealthMonitor {
+    state: Arc<Mutex<State>>,
+}
+
+impl HealthMonitor {
+    /// Returns the health observed by the last ping
+    pub fn current(&self) -> Health {
+        self.state.lock().unwrap().health
+    }
+
+    /// Returns a [`HealthWatch`](crate::HealthWatch) which is notified about every change of the health
+    pub fn subscribe(&self) -> HealthWatch {
+        HealthWatch {
+            state: self.state.clone(),
+            version: self.state.lock().unwrap().version,
+        }
+    }
+
+    /// Stops the monitoring, completing the task which pings the server without waiting for the
+    /// next ping
+    pub fn stop(&self) {
+        if let Some(stop) = self.state.lock().unwrap().stop.take() {
+            let _ = stop.send(());
+        }
+    }
+}
+
+/// Subscription to the changes of the health of the connection
+#[derive(Debug)]
+pub struct HealthWatch {
+    state: Arc<Mutex<State>>,
+    version: u64,
+}
+
+impl HealthWatch {
+    /// Waits until the health changed since it was last seen by this watch and returns it
+    ///
+    /// Changes which happen in quick succession may be observed only once, with the latest health.
+    pub async fn changed(&mut self) -> Health {
+        poll_fn(|cx| {
+            let mut state = self.state.lock().unwrap();
+            if state.version != self.version {
+                self.version = state.version;
+                Poll::Ready(state.health)
+            } else {
+                if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                    state.wakers.push(cx.waker().clone());
+                }
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+impl Client {
+    /// Creates a [`HealthMonitor`](crate::HealthMonitor) which pings the server every `interval`
+    ///
+    /// The pings are sent by the returned [`BackgroundTask`](crate::BackgroundTask) named
+    /// `influxdb-health-monitor`, which has to be spawned on the runtime of the application. It
+    /// completes once all handles to the monitor are dropped, or the monitor is stopped.
+    ///
+    /// A failed ping marks the server as [`Degraded`](crate::Health::Degraded), three failed pings
+    /// in a row as [`Down`](crate::Health::Down).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::{Client, Health};
+    /// use std::time::Duration;
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() {
+    /// let client = Client::new("http://localhost:8086", "test");
+    /// let (monitor, probe) = client.health_monitor(Duration::from_secs(10));
+    /// async_std::task::spawn(probe);
+    ///
+    /// let mut watch = monitor.subscribe();
+    /// while watch.changed().await != Health::Down {}
+    /// eprintln!("InfluxDB is down");
+    /// # }
+    /// ```
+    pub fn health_monitor(&self, interval: Duration) -> (HealthMonitor, BackgroundTask) {
+        let (stop, stopped) = oneshot::channel();
+        let state = Arc::new(Mutex::new(State {
+            health: Health::Healthy,
+            version: 0,
+            wakers: Vec::new(),
+            stop: Some(stop),
+        }));
+        let monitor = HealthMonitor {
+            state: state.clone(),
+        };
+        let probe = probe(self.clone(), interval, Arc::downgrade(&state), stopped);
+        let task = BackgroundTask::new("influxdb-health-monitor".to_string(), probe);
+        (monitor, task)
+    }
+}
+
+/// Pings the server until `stopped` completes, which it also does once the state is dropped
+async fn probe(
+    client: Client,
+    interval: Duration,
+    state: Weak<Mutex<State>>,
+    mut stopped: oneshot::Receiver<()>,
+) {
+    let mut failures = 0;
+    loop {
+        failures = match client.ping().await {
+            Ok(_) => 0,
+            Err(_) => failures + 1,
+        };
+        let health = match failures {
+            0 => Health::Healthy,
+            failures if failures < FAILURES_UNTIL_DOWN => Health::Degraded,
+            _ => Health::Down,
+        };
+
+        match state.upgrade() {
+            Some(state) => update(&state, health),
+            None => return,
+        }
+        if let Either::Right(_) = select(Delay::new(interval), &mut stopped).await {
+            return;
+        }
+    }
+}
+
+fn update(state: &Mutex<State>, health: Health) {
+    let mut state = state.lock().unwrap();
+    if state.health != health {
+        state.health = health;
+        state.version += 1;
+        for waker in state.wakers.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{update, Health, HealthMonitor, State};
+    use crate::Client;
+    use futures::executor::block_on;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[test]
+    fn test_watch() {
+        let monitor = HealthMonitor {
+            state: Arc::new(Mutex::new(State {
+                health: Health::Healthy,
+                version: 0,
+                wakers: Vec::new(),
+                stop: None,
+            })),
+        };
+        let mut watch = monitor.subscribe();
+
+        update(&monitor.state, Health::Healthy);
+        update(&monitor.state, Health::Degraded);
+        assert_eq!(monitor.current(), Health::Degraded);
+        assert_eq!(block_on(watch.changed()), Health::Degraded);
+
+        let changed = std::thread::spawn(move || block_on(watch.changed()));
+        update(&monitor.state, Health::Down);
+        assert_eq!(changed.join().unwrap(), Health::Down);
+    }
+
+    #[tokio::test]
+    async fn test_stop() {
+        let client = Client::new("http://127.0.0.1:1", "test");
+        let (monitor, task) = client.health_monitor(Duration::from_secs(3600));
+        assert_eq!(task.name(), "influxdb-health-monitor");
+
+        monitor.stop();
+        task.await;
+        assert_eq!(monitor.current(), Health::Degraded);
+    }
+}
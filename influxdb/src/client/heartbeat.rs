@@ -0,0 +1,56 @@
+//! Periodic liveness points
+
+use futures_timer::Delay;
+use std::future::Future;
+use std::time::Duration;
+
+use crate::{Client, Timestamp, WriteQuery};
+
+/// Measurement heartbeats are written to
+const MEASUREMENT: &str = "heartbeat";
+
+impl Client {
+    /// Writes a heartbeat point every `interval`, so dashboards and alerts can tell whether the
+    /// application is alive
+    ///
+    /// The points are written by the returned future, which has to be spawned on the runtime of the
+    /// application and runs until it is dropped. Each point belongs to the `heartbeat` measurement,
+    /// is tagged with `name` and has the field `alive` set to `true`. Failed writes are ignored, the
+    /// next heartbeat is written regardless.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::Client;
+    /// use std::time::Duration;
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() {
+    /// let client = Client::new("http://localhost:8086", "test");
+    /// async_std::task::spawn(client.heartbeat("billing-service", Duration::from_secs(30)));
+    /// # }
+    /// ```
+    pub fn heartbeat<S>(
+        &self,
+        name: S,
+        interval: Duration,
+    ) -> impl Future<Output = ()> + Send + 'static
+    where
+        S: Into<String>,
+    {
+        let client = self.clone();
+        let name = name.into();
+        async move {
+            loop {
+                let _ = client.query(&heartbeat_point(&name)).await;
+                Delay::new(interval).await;
+            }
+        }
+    }
+}
+
+fn heartbeat_point(name: &str) -> WriteQuery {
+    WriteQuery::new(Timestamp::now(), MEASUREMENT)
+        .add_tag("name", name)
+        .add_field("alive", true)
+}
@@ -16,25 +16,130 @@
 //! ```
 
 use futures::prelude::*;
-use surf::{self, Client as SurfClient, StatusCode};
+use surf::{
+    self, http::Method, Client as SurfClient, Request, RequestBuilder, Response, StatusCode, Url,
+};
 
-use crate::query::QueryType;
-use crate::Error;
+mod adaptive_batching;
+pub(crate) mod buffered_writer;
+mod builder;
+#[cfg(feature = "use-serde")]
+mod cardinality;
+mod compat;
+mod connect;
+mod credentials;
+mod dry_run;
+mod dsn;
+mod env;
+#[cfg(feature = "use-serde")]
+mod explain;
+#[cfg(feature = "use-serde")]
+mod grants;
+mod happy_eyeballs;
+mod health;
+mod heartbeat;
+mod multi_write;
+mod partial_write;
+mod process_tags;
+mod query_cache;
+#[cfg(feature = "use-serde")]
+mod read_limits;
+pub(crate) mod request_id;
+mod sampling;
+mod scope;
+mod server_version;
+#[cfg(feature = "use-serde")]
+mod shards;
+mod shared;
+mod split_write;
+#[cfg(feature = "use-serde")]
+mod stats;
+mod stream_write;
+mod task;
+mod tenant;
+#[cfg(test)]
+mod test_server;
+mod time_guard;
+mod write;
+mod write_result;
+mod writer_stats;
+pub use adaptive_batching::AdaptiveBatching;
+pub use buffered_writer::{BufferedWriter, WriteAck, WriterOptions};
+pub use builder::ClientBuilder;
+#[cfg(feature = "use-serde")]
+pub use cardinality::MeasurementCardinality;
+pub use compat::Compatibility;
+pub use connect::Connection;
+pub use credentials::{Credentials, CredentialsProvider, EnvCredentials, FileCredentials};
+pub use dry_run::DryRun;
+#[cfg(feature = "use-serde")]
+pub use grants::{Grant, Privilege};
+pub use health::{Health, HealthMonitor, HealthWatch};
+pub use multi_write::MultiWrite;
+#[cfg(feature = "use-serde")]
+pub use read_limits::ReadLimits;
+pub use sampling::Sampling;
+pub use scope::Scope;
+pub use server_version::ServerVersion;
+#[cfg(feature = "use-serde")]
+pub use shards::{Shard, ShardGroup};
+pub use shared::SharedClient;
+#[cfg(feature = "use-serde")]
+pub use stats::{DiagnosticsSection, ServerDiagnostics, ServerStats, StatsModule};
+pub use task::BackgroundTask;
+pub use tenant::{TenantRoute, TenantRouter};
+pub use time_guard::TimeGuard;
+pub use write_result::WriteResult;
+pub use writer_stats::{WriterHistogram, WriterStats};
+
+use crate::query::write_hook::WriteHooks;
+use crate::query::write_limits::WriteLimits;
+use crate::query::write_query::batch_precision;
+use crate::query::{Precision, QueryType, ValidQuery};
 use crate::Query;
+use crate::{Error, NamingPolicy, Type, WriteHook};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 #[derive(Clone, Debug)]
 /// Internal Representation of a Client
+///
+/// Cloning a client is cheap, clones share its configuration and connections. To change the
+/// configuration of clients which are already shared, e.g. after rotating credentials, use a
+/// [`SharedClient`](crate::SharedClient).
 pub struct Client {
     pub(crate) url: Arc<String>,
+    pub(crate) base_url: Option<Url>,
     pub(crate) parameters: Arc<HashMap<&'static str, String>>,
+    pub(crate) headers: Arc<HashMap<String, String>>,
+    pub(crate) precision: Option<Precision>,
+    pub(crate) max_precision: Option<Precision>,
+    pub(crate) chunked_threshold: usize,
+    pub(crate) max_split_depth: u32,
+    pub(crate) server_version: Arc<Mutex<Option<ServerVersion>>>,
+    pub(crate) query_cache: Option<Arc<query_cache::QueryCache>>,
+    pub(crate) write_hooks: WriteHooks,
+    pub(crate) write_limits: Option<WriteLimits>,
+    pub(crate) credentials_provider: Option<credentials::Provider>,
+    pub(crate) address_selector: Option<Arc<happy_eyeballs::AddressSelector>>,
+    pub(crate) tenant_router: Option<Arc<TenantRouter>>,
+    pub(crate) tenant_route: Option<Arc<TenantRoute>>,
+    pub(crate) query_in_errors: bool,
+    pub(crate) request_id: Option<Arc<String>>,
+    pub(crate) compatibility: Compatibility,
+    pub(crate) time_guard: TimeGuard,
+    #[cfg(feature = "use-serde")]
+    pub(crate) read_limits: Option<ReadLimits>,
     pub(crate) client: SurfClient,
 }
 
 impl Client {
     /// Instantiates a new [`Client`](crate::Client)
     ///
+    /// The URL may contain a base path, e.g. if InfluxDB is running behind a reverse proxy.
+    /// Trailing slashes are removed. Use [`Client::builder`](crate::Client::builder) to validate the URL
+    /// up front, an invalid URL otherwise fails every request.
+    ///
     /// # Arguments
     ///
     ///  * `url`: The URL where InfluxDB is running (ex. `http://localhost:8086`).
@@ -52,11 +157,33 @@ impl Client {
         S1: Into<String>,
         S2: Into<String>,
     {
+        let url = url.into().trim_end_matches('/').to_string();
         let mut parameters = HashMap::<&str, String>::new();
         parameters.insert("db", database.into());
         Client {
-            url: Arc::new(url.into()),
+            // a trailing slash makes the last segment of the path a base path, e.g. behind a reverse proxy
+            base_url: Url::parse(&format!("{}/", url)).ok(),
+            url: Arc::new(url),
             parameters: Arc::new(parameters),
+            headers: Arc::new(HashMap::new()),
+            precision: None,
+            max_precision: None,
+            chunked_threshold: stream_write::DEFAULT_CHUNKED_THRESHOLD,
+            max_split_depth: split_write::DEFAULT_MAX_SPLIT_DEPTH,
+            server_version: Arc::new(Mutex::new(None)),
+            query_cache: None,
+            write_hooks: WriteHooks::default(),
+            write_limits: None,
+            credentials_provider: None,
+            address_selector: None,
+            tenant_router: None,
+            tenant_route: None,
+            query_in_errors: false,
+            request_id: None,
+            compatibility: Compatibility::InfluxDb,
+            time_guard: TimeGuard::Off,
+            #[cfg(feature = "use-serde")]
+            read_limits: None,
             client: SurfClient::new(),
         }
     }
@@ -87,12 +214,303 @@ impl Client {
         self
     }
 
+    /// Sets the database writes and queries of the [`Client`](crate::Client) use
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Client;
+    ///
+    /// let client = Client::new("http://localhost:8086", "test").with_database("production");
+    ///
+    /// assert_eq!(client.database_name(), "production");
+    /// ```
+    pub fn with_database<S>(mut self, database: S) -> Self
+    where
+        S: Into<String>,
+    {
+        let mut with_database = self.parameters.as_ref().clone();
+        with_database.insert("db", database.into());
+        self.parameters = Arc::new(with_database);
+        self
+    }
+
+    /// Sets the retention policy writes and queries of the [`Client`](crate::Client) use by default
+    ///
+    /// Without a retention policy, the default retention policy of the database is used.
+    /// A [`Measurement`](crate::Measurement) qualified with a retention policy takes precedence.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Client;
+    ///
+    /// let client = Client::new("http://localhost:8086", "test").with_retention_policy("two_weeks");
+    ///
+    /// assert_eq!(client.retention_policy(), Some("two_weeks"));
+    /// ```
+    pub fn with_retention_policy<S>(mut self, retention_policy: S) -> Self
+    where
+        S: Into<String>,
+    {
+        let mut with_retention_policy = self.parameters.as_ref().clone();
+        with_retention_policy.insert("rp", retention_policy.into());
+        self.parameters = Arc::new(with_retention_policy);
+        self
+    }
+
+    /// Sets the [`Precision`](crate::Precision) all timestamps of a write are converted to
+    ///
+    /// A single write can only carry timestamps of one precision. Without an explicit precision,
+    /// the points of a batch are written with the finest precision among them. Converting to a
+    /// coarser precision truncates the timestamps.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Client, Precision};
+    ///
+    /// let client = Client::new("http://localhost:8086", "test").with_precision(Precision::Milliseconds);
+    ///
+    /// assert_eq!(client.precision(), Some(Precision::Milliseconds));
+    /// ```
+    pub fn with_precision(mut self, precision: Precision) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// Downscales the timestamps of writes which are finer than the given [`Precision`](crate::Precision)
+    ///
+    /// This is lossy by design: timestamps are truncated, so points which only differ in the truncated
+    /// part of their timestamp end up as the same point. Writes which are already as coarse as
+    /// `max_precision` are sent unchanged. Millisecond precision is a good choice for most metrics,
+    /// it keeps payloads small without merging points written in quick succession.
+    ///
+    /// A precision set with [`with_precision`](crate::Client::with_precision) takes precedence.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Client, Precision};
+    ///
+    /// let client = Client::new("http://localhost:8086", "test").with_downscaling(Precision::Milliseconds);
+    ///
+    /// assert_eq!(client.max_precision(), Some(Precision::Milliseconds));
+    /// ```
+    pub fn with_downscaling(mut self, max_precision: Precision) -> Self {
+        self.max_precision = Some(max_precision);
+        self
+    }
+
+    /// Checks every point written by the [`Client`](crate::Client) against
+    /// [`WriteLimits`](crate::WriteLimits), e.g. the limits of the server
+    ///
+    /// A write with a point exceeding them fails before it is sent, and
+    /// [`BufferedWriter::write`](crate::BufferedWriter::write) fails right away instead of
+    /// failing the batch later. Points are checked after the [write hooks](crate::Client::with_write_hook)
+    /// were applied as well.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Client, InfluxDbWriteable, Timestamp, WriteLimits};
+    ///
+    /// let client = Client::new("http://localhost:8086", "test")
+    ///     .with_write_limits(WriteLimits::new().max_fields(200).max_line_length(1024 * 1024));
+    ///
+    /// let point = Timestamp::Seconds(1).into_query("weather").add_field("description", "x".repeat(2_000_000));
+    /// assert!(client.dry_run(&point).is_err());
+    /// ```
+    pub fn with_write_limits(mut self, limits: WriteLimits) -> Self {
+        self.write_limits = Some(limits);
+        self
+    }
+
+    /// Returns the limits of written points, if any
+    pub fn write_limits(&self) -> Option<WriteLimits> {
+        self.write_limits
+    }
+
+    /// Add a header which will be sent with every request made by the [`Client`](crate::Client)
+    ///
+    /// Setting a header with the same name twice replaces the previous value.
+    /// Header names and values must be ASCII.
+    ///
+    /// # Arguments
+    ///
+    /// * name: The name of the header (ex. `X-Scope-OrgID`).
+    /// * value: The value of the header.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Client;
+    ///
+    /// let _client = Client::new("http://localhost:8086", "test").with_header("X-Scope-OrgID", "tenant-1");
+    /// ```
+    pub fn with_header<S1, S2>(mut self, name: S1, value: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        let mut with_header = self.headers.as_ref().clone();
+        with_header.insert(name.into(), value.into());
+        self.headers = Arc::new(with_header);
+        self
+    }
+
+    /// Set the `User-Agent` header sent with every request made by the [`Client`](crate::Client)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Client;
+    ///
+    /// let _client = Client::new("http://localhost:8086", "test").with_user_agent("my-service/1.0");
+    /// ```
+    pub fn with_user_agent<S>(self, user_agent: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.with_header("User-Agent", user_agent)
+    }
+
+    /// Adds a [`WriteHook`](crate::WriteHook) which processes every point written by the
+    /// [`Client`](crate::Client)
+    ///
+    /// Hooks are applied in the order they were added.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Client, Error, WriteQuery};
+    ///
+    /// let _client = Client::new("http://localhost:8086", "test")
+    ///     .with_write_hook(|point: WriteQuery| Ok::<_, Error>(point.add_tag("region", "eu-west")));
+    /// ```
+    pub fn with_write_hook<H>(mut self, hook: H) -> Self
+    where
+        H: WriteHook + 'static,
+    {
+        self.write_hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// Enforces a [`NamingPolicy`](crate::NamingPolicy) on every point written by the
+    /// [`Client`](crate::Client)
+    ///
+    /// The policy is applied like a [`WriteHook`](crate::WriteHook), so points violating it fail
+    /// to be written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Client, NamingPolicy};
+    ///
+    /// let _client = Client::new("http://localhost:8086", "test")
+    ///     .with_naming_policy(NamingPolicy::new().snake_case().max_length(64));
+    /// ```
+    pub fn with_naming_policy(self, policy: NamingPolicy) -> Self {
+        self.with_write_hook(policy)
+    }
+
+    /// Adds tags to every point written by the [`Client`](crate::Client)
+    ///
+    /// Tags the point already has are kept, a default tag set again replaces the earlier value.
+    /// The tags are added before the [`WriteHook`](crate::WriteHook)s are applied.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Client;
+    ///
+    /// let client = Client::new("http://localhost:8086", "test")
+    ///     .with_default_tags([("host", "web-1"), ("env", "prod")]);
+    ///
+    /// assert_eq!(client.default_tags().len(), 2);
+    /// ```
+    pub fn with_default_tags<I, K, V>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<Type>,
+    {
+        for (key, value) in tags {
+            self.write_hooks.set_default_tag(key.into(), value.into());
+        }
+        self
+    }
+
+    /// Returns the tags added to every point written by the [`Client`](crate::Client)
+    pub fn default_tags(&self) -> &[(String, Type)] {
+        self.write_hooks.default_tags()
+    }
+
+    /// Adds the failed query to the errors of [`query`](crate::Client::query), see
+    /// [`Error::with_query`](crate::Error::with_query)
+    ///
+    /// Errors are then reported as [`QueryFailed`](crate::Error::QueryFailed), use
+    /// [`Error::inner`](crate::Error::inner) to match on the original error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Client;
+    ///
+    /// let client = Client::new("http://localhost:8086", "test").with_query_in_errors(true);
+    /// ```
+    pub fn with_query_in_errors(mut self, enabled: bool) -> Self {
+        self.query_in_errors = enabled;
+        self
+    }
+
+    /// Changes the URL where InfluxDB is running, e.g. to fail over to another server
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Client;
+    ///
+    /// let client = Client::new("http://localhost:8086", "test").with_url("http://replica:8086/");
+    ///
+    /// assert_eq!(client.database_url(), "http://replica:8086");
+    /// ```
+    pub fn with_url<S>(mut self, url: S) -> Self
+    where
+        S: Into<String>,
+    {
+        let url = url.into().trim_end_matches('/').to_string();
+        self.base_url = Url::parse(&format!("{}/", url)).ok();
+        self.url = Arc::new(url);
+        // the other server may run another version, and has other addresses
+        self.server_version = Arc::new(Mutex::new(None));
+        self.address_selector = self
+            .address_selector
+            .map(|selector| Arc::new(selector.fresh()));
+        self
+    }
+
     /// Returns the name of the database the client is using
     pub fn database_name(&self) -> &str {
         // safe to unwrap: we always set the database name in `Self::new`
         self.parameters.get("db").unwrap()
     }
 
+    /// Returns the retention policy the client is using, if it isn't the default one of the database
+    pub fn retention_policy(&self) -> Option<&str> {
+        self.parameters.get("rp").map(String::as_str)
+    }
+
+    /// Returns the precision timestamps of writes are converted to, if any
+    pub fn precision(&self) -> Option<Precision> {
+        self.precision
+    }
+
+    /// Returns the precision timestamps of writes are downscaled to, if any
+    pub fn max_precision(&self) -> Option<Precision> {
+        self.max_precision
+    }
+
     /// Returns the URL of the InfluxDB installation the client is using
     pub fn database_url(&self) -> &str {
         &self.url
@@ -100,28 +518,67 @@ impl Client {
 
     /// Pings the InfluxDB Server
     ///
-    /// Returns a tuple of build type and version number
+    /// Returns a tuple of build type and version number, which are empty if the server doesn't
+    /// report them, e.g. a [compatible backend](crate::Client::with_compatibility)
     pub async fn ping(&self) -> Result<(String, String), Error> {
-        let url = &format!("{}/ping", self.url);
-        let res = self
-            .client
-            .get(url)
-            .send()
-            .await
-            .map_err(|err| Error::ProtocolError {
-                error: format!("{}", err),
-            })?;
+        let url = self.endpoint("ping")?;
+        let request = self.with_headers(self.client.get(url)).build();
+        let res = self.send_request(request).await?;
 
-        let build = res.header("X-Influxdb-Build").unwrap().as_str();
-        let version = res.header("X-Influxdb-Version").unwrap().as_str();
+        let header = |name: &str| {
+            res.header(name)
+                .map_or_else(String::new, |value| value.as_str().to_owned())
+        };
 
-        Ok((build.to_owned(), version.to_owned()))
+        Ok((header("X-Influxdb-Build"), header("X-Influxdb-Version")))
+    }
+
+    /// Returns the version of the server, as reported by its last response
+    ///
+    /// The version is only known after the first request, e.g. a [`ping`](crate::Client::ping).
+    /// Clones of the client share the detected version.
+    ///
+    /// Once the version is known, the client fails writes of unsigned integer fields to servers
+    /// before InfluxDB 1.4, and requests authenticated with an `Authorization: Token` header to
+    /// servers before InfluxDB 1.8, instead of sending requests the server would reject.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::Client;
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("http://localhost:8086", "test");
+    /// client.ping().await?;
+    ///
+    /// if client.server_version().map_or(false, |v| v.supports_unsigned_integers()) {
+    ///     // write u64 fields
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn server_version(&self) -> Option<ServerVersion> {
+        *self.server_version.lock().unwrap()
+    }
+
+    /// Caches the version the server reported with a response
+    fn detect_server_version(&self, res: &surf::Response) {
+        if let Some(version) = res
+            .header("X-Influxdb-Version")
+            .and_then(|version| version.as_str().parse().ok())
+        {
+            *self.server_version.lock().unwrap() = Some(version);
+        }
     }
 
     /// Sends a [`ReadQuery`](crate::ReadQuery) or [`WriteQuery`](crate::WriteQuery) to the InfluxDB Server.
     ///
     /// A version capable of parsing the returned string is available under the [serde_integration](crate::integrations::serde_integration)
     ///
+    /// Writes return an empty string, [`write_query`](crate::Client::write_query) returns the
+    /// status, request ID and warnings of a write instead.
+    ///
     /// # Arguments
     ///
     ///  * `q`: Query of type [`ReadQuery`](crate::ReadQuery) or [`WriteQuery`](crate::WriteQuery)
@@ -155,76 +612,417 @@ impl Client {
     /// If the function can not finish the query,
     /// a [`Error`] variant will be returned.
     ///
+    /// If the server rejects some lines of a write, a [`PartialWriteError`] lists them with the
+    /// indexes of their points, so they can be dropped before retrying. The server writes all other
+    /// points of the batch.
+    ///
+    /// Batches which the server rejects as too large (`HTTP 413 Payload Too Large`) are split in
+    /// halves and written again, up to the [maximum split depth](crate::Client::with_max_split_depth).
+    /// Points which are still rejected are listed by a [`PartialWriteError`] as well.
+    ///
+    /// If [read limits](crate::Client::with_read_limits) are set, the result of a read query which
+    /// exceeds them is a [`ResultTooLarge`] error.
+    ///
+    /// With [`with_query_in_errors`](crate::Client::with_query_in_errors), errors report the
+    /// redacted query as well. Errors of the server report the ID of the request, see
+    /// [`Error::request_id`](crate::Error::request_id).
+    ///
     /// [`Error`]: enum.Error.html
+    /// [`ResultTooLarge`]: crate::Error::ResultTooLarge
+    /// [`PartialWriteError`]: crate::Error::PartialWriteError
     pub async fn query<'q, Q>(&self, q: &'q Q) -> Result<String, Error>
     where
         Q: Query,
     {
-        let query = q.build().map_err(|err| Error::InvalidQueryError {
-            error: err.to_string(),
-        })?;
+        self.query_with_request_id(q, &self.next_request_id()).await
+    }
+
+    /// Sends a query with the given request ID, see [`query`](crate::Client::query)
+    pub(crate) async fn query_with_request_id<Q>(
+        &self,
+        q: &Q,
+        request_id: &str,
+    ) -> Result<String, Error>
+    where
+        Q: Query,
+    {
+        self.send_query(q, request_id).await.map_err(|error| {
+            let error = error.with_request_id(request_id);
+            if self.query_in_errors {
+                error.with_query(q)
+            } else {
+                error
+            }
+        })
+    }
 
-        let request_builder = match q.get_type() {
-            QueryType::ReadQuery => {
-                let read_query = query.get();
-                let url = &format!("{}/query", &self.url);
-                let mut parameters = self.parameters.as_ref().clone();
-                parameters.insert("q", read_query.clone());
-
-                if read_query.contains("SELECT") || read_query.contains("SHOW") {
-                    self.client.get(url).query(&parameters)
-                } else {
-                    self.client.post(url).query(&parameters)
+    async fn send_query<Q>(&self, q: &Q, request_id: &str) -> Result<String, Error>
+    where
+        Q: Query,
+    {
+        #[cfg(feature = "use-serde")]
+        if let (Some(limits), QueryType::ReadQuery) = (self.read_limits, q.get_type()) {
+            return self.limited_read(q, limits, request_id).await;
+        }
+        let request = self.request(q)?.header(request_id::REQUEST_ID, request_id);
+        let (status, body) = self.send_with_status(request).await?;
+        if status == StatusCode::PayloadTooLarge {
+            if let Some(points) = q.points().filter(|points| points.len() > 1) {
+                if self.max_split_depth > 0 {
+                    return self.write_halves(points, request_id).await;
                 }
             }
-            QueryType::WriteQuery(precision) => {
-                let url = &format!("{}/write", &self.url);
-                let mut parameters = self.parameters.as_ref().clone();
-                parameters.insert("precision", precision);
+        }
+        match (check_body(body), q.get_type()) {
+            (Err(Error::DatabaseError { error, .. }), QueryType::WriteQuery(_))
+                if status == StatusCode::BadRequest =>
+            {
+                Err(self.rejected_write_error(q, error))
+            }
+            (Err(Error::DatabaseError { error, .. }), _) if !status.is_success() => {
+                Err(Error::DatabaseError {
+                    error,
+                    status: Some(status.into()),
+                    request_id: None,
+                })
+            }
+            (result, _) => result,
+        }
+    }
 
-                self.client.post(url).body(query.get()).query(&parameters)
+    /// Returns the error of a write the server rejected with `400 Bad Request`, listing the
+    /// rejected lines if the server only rejected some of them
+    pub(crate) fn rejected_write_error<Q>(&self, q: &Q, error: String) -> Error
+    where
+        Q: Query,
+    {
+        let rejected = match self.build_query(q) {
+            Ok((lines, _)) => partial_write::rejected_lines(&error, &lines.get()),
+            Err(err) => return err,
+        };
+        if rejected.is_empty() {
+            Error::DatabaseError {
+                error,
+                status: Some(400),
+                request_id: None,
             }
+        } else {
+            Error::PartialWriteError { error, rejected }
         }
-        .map_err(|err| Error::UrlConstructionError {
+    }
+
+    /// Builds the request which sends the query to the server
+    pub(crate) fn request<Q>(&self, q: &Q) -> Result<RequestBuilder, Error>
+    where
+        Q: Query,
+    {
+        let (query, precision) = self.build_query(q)?;
+        match q.get_type() {
+            QueryType::ReadQuery => self.read_request(query.get(), &[]),
+            QueryType::WriteQuery(query_precision) => self.write_request(
+                query.get(),
+                precision.map_or(query_precision, |p| p.to_string()),
+                q.get_database(),
+                q.get_retention_policy(),
+            ),
+        }
+    }
+
+    /// Builds the query, converting write queries to the precision the client is configured with,
+    /// which is returned alongside
+    fn build_query<Q>(&self, q: &Q) -> Result<(ValidQuery, Option<Precision>), Error>
+    where
+        Q: Query,
+    {
+        let precision = match q.get_type() {
+            QueryType::WriteQuery(precision) => self.precision.or_else(|| {
+                let max_precision = self.max_precision?;
+                Precision::from_modifier(&precision)
+                    .filter(|precision| *precision < max_precision)
+                    .map(|_| max_precision)
+            }),
+            QueryType::ReadQuery => None,
+        };
+        let hooked = match q.points() {
+            Some(points) if !self.write_hooks.is_empty() => Some(
+                points
+                    .iter()
+                    .map(|point| self.write_hooks.apply(point.clone()))
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            _ => None,
+        };
+        if let Some(points) = hooked.as_deref().or_else(|| q.points()) {
+            if let Some(limits) = &self.write_limits {
+                limits.check_all(points)?;
+            }
+            self.check_unsigned_integers(points)?;
+        }
+        let (query, precision) = match (precision, hooked) {
+            (Some(precision), Some(points)) => {
+                (points.build_with_precision(precision), Some(precision))
+            }
+            // hooks may change timestamps, so the write takes the precision of the hooked points
+            (None, Some(points)) => (points.build(), batch_precision(&points)),
+            (Some(precision), None) => (q.build_with_precision(precision), Some(precision)),
+            (None, None) => (q.build(), None),
+        };
+        let query = query.map_err(|err| Error::InvalidQueryError {
             error: err.to_string(),
         })?;
+        Ok((query, precision))
+    }
 
-        let request = request_builder.build();
-        let mut res = self
-            .client
-            .send(request)
-            .map_err(|err| Error::ConnectionError {
+    /// Builds the request which sends a read query, with additional parameters like `chunked`
+    pub(crate) fn read_request(
+        &self,
+        read_query: String,
+        extra_parameters: &[(&'static str, &str)],
+    ) -> Result<RequestBuilder, Error> {
+        self.check_reads()?;
+        let read_query = self.route_read(read_query)?;
+        let read_query = self.guard_time(read_query)?;
+        let url = self.endpoint("query")?;
+        let mut parameters = self.parameters.as_ref().clone();
+        for (name, value) in extra_parameters {
+            parameters.insert(name, value.to_string());
+        }
+        let method = if (read_query.contains("SELECT") || read_query.contains("SHOW"))
+            && !is_select_into(&read_query)
+        {
+            Method::Get
+        } else {
+            Method::Post
+        };
+        parameters.insert("q", read_query);
+
+        self.client
+            .request(method, url)
+            .query(&parameters)
+            .map_err(|err| Error::UrlConstructionError {
                 error: err.to_string(),
             })
-            .await?;
+    }
 
-        match res.status() {
-            StatusCode::Unauthorized => return Err(Error::AuthorizationError),
-            StatusCode::Forbidden => return Err(Error::AuthenticationError),
-            _ => {}
+    /// Returns the URL of an endpoint of the HTTP API, e.g. `query`
+    pub(crate) fn endpoint(&self, endpoint: &str) -> Result<Url, Error> {
+        self.base_url
+            .as_ref()
+            .ok_or_else(|| {
+                let reason = Url::parse(&self.url)
+                    .err()
+                    .map_or_else(|| "invalid url".to_string(), |err| err.to_string());
+                Error::UrlConstructionError {
+                    error: format!(
+                        "invalid url {}: {}",
+                        self.url,
+                        explain_url_error(&self.url, &reason)
+                    ),
+                }
+            })?
+            .join(endpoint)
+            .map_err(|err| Error::UrlConstructionError {
+                error: err.to_string(),
+            })
+    }
+
+    /// Returns the parameters of a write with the given precision, database and retention policy
+    pub(crate) fn write_parameters(
+        &self,
+        precision: String,
+        database: Option<&str>,
+        retention_policy: Option<&str>,
+    ) -> HashMap<&'static str, String> {
+        let mut parameters = self.parameters.as_ref().clone();
+        parameters.insert("precision", precision);
+        if let Some(database) = database {
+            parameters.insert("db", database.to_owned());
         }
+        if let Some(retention_policy) = retention_policy {
+            parameters.insert("rp", retention_policy.to_owned());
+        }
+        parameters
+    }
+
+    /// Sends a request with the configured default headers and returns the body of the response
+    pub(crate) async fn send(&self, request_builder: RequestBuilder) -> Result<String, Error> {
+        let (_, body) = self.send_with_status(request_builder).await?;
+        check_body(body)
+    }
+
+    /// Sends a request with the configured default headers and returns the status and the
+    /// unchecked body of the response
+    pub(crate) async fn send_with_status(
+        &self,
+        request_builder: RequestBuilder,
+    ) -> Result<(StatusCode, String), Error> {
+        let mut request = self.with_headers(request_builder).build();
+        // responses are cached per identity, so the request is authenticated first
+        self.authenticate(&mut request).await?;
+        let cache = match &self.query_cache {
+            Some(query_cache) if request.method() == Method::Get => {
+                let key = query_cache::key(&request);
+                if let Some(body) = query_cache.get(&key) {
+                    return Ok((StatusCode::Ok, body));
+                }
+                Some((query_cache, key))
+            }
+            _ => None,
+        };
 
+        let mut res = self.send_authenticated(request).await?;
         let s = res
             .body_string()
             .await
-            .map_err(|_| Error::DeserializationError {
+            .map_err(|err| Error::DeserializationError {
                 error: "response could not be converted to UTF-8".to_string(),
+                source: crate::error::surf_source(err),
             })?;
 
-        // todo: improve error parsing without serde
-        if s.contains("\"error\"") {
+        let status = res.status();
+        // compatible backends don't report errors in the body like InfluxDB, `413 Payload Too
+        // Large` is left to the caller, which may split the write
+        if self.compatibility != Compatibility::InfluxDb
+            && !status.is_success()
+            && status != StatusCode::PayloadTooLarge
+        {
             return Err(Error::DatabaseError {
-                error: format!("influxdb error: \"{}\"", s),
+                error: self.compatibility.error_message(&s),
+                status: Some(status.into()),
+                request_id: None,
             });
         }
 
-        Ok(s)
+        if let Some((query_cache, key)) = cache {
+            if status.is_success() && !s.contains("\"error\"") {
+                query_cache.insert(key, s.clone());
+            }
+        }
+
+        Ok((status, s))
+    }
+
+    /// Sends a request and returns the response, with the body not read yet
+    ///
+    /// Fails if the server rejected the credentials. Requests without a `Request-Id` header are sent
+    /// with a generated one, see [`with_request_id`](crate::Client::with_request_id).
+    pub(crate) async fn send_request(&self, mut request: Request) -> Result<Response, Error> {
+        self.authenticate(&mut request).await?;
+        self.send_authenticated(request).await
+    }
+
+    /// Applies the credentials of the [`CredentialsProvider`](crate::CredentialsProvider), if any,
+    /// and fails if the server is known not to support how the request is authenticated
+    async fn authenticate(&self, request: &mut Request) -> Result<(), Error> {
+        if let Some(provider) = &self.credentials_provider {
+            let credentials = provider.credentials().await?;
+            credentials::authenticate(request, &credentials);
+        }
+        self.check_token_auth(request)
+    }
+
+    /// Sends a request which was [authenticated](Client::authenticate) already
+    async fn send_authenticated(&self, mut request: Request) -> Result<Response, Error> {
+        if request.header(request_id::REQUEST_ID).is_none() {
+            request.insert_header(request_id::REQUEST_ID, self.next_request_id().as_str());
+        }
+        let pinned = match &self.address_selector {
+            Some(selector) if happy_eyeballs::AddressSelector::applies(request.url()) => {
+                let address = selector.select(request.url()).await?;
+                happy_eyeballs::pin(&mut request, address);
+                Some((selector, address))
+            }
+            _ => None,
+        };
+        #[cfg(feature = "tracing-layer")]
+        let span = tracing::debug_span!(
+            "influxdb_request",
+            request_id = request
+                .header(request_id::REQUEST_ID)
+                .map_or("", |request_id| request_id.as_str()),
+            method = %request.method(),
+            path = request.url().path(),
+        );
+        let send = self
+            .client
+            .send(request)
+            .map_err(|err| Error::ConnectionError {
+                error: err.to_string(),
+                source: crate::error::surf_source(err),
+            });
+        #[cfg(feature = "tracing-layer")]
+        let send = tracing::Instrument::instrument(send, span);
+        let res = send.await;
+        if let (Err(Error::ConnectionError { .. }), Some((selector, address))) = (&res, pinned) {
+            selector.forget(address);
+        }
+        let res = res?;
+        self.detect_server_version(&res);
+
+        match res.status() {
+            StatusCode::Unauthorized => Err(Error::AuthorizationError),
+            StatusCode::Forbidden => Err(Error::AuthenticationError),
+            _ => Ok(res),
+        }
+    }
+
+    /// Adds the configured default headers to a request
+    pub(crate) fn with_headers(&self, mut request_builder: RequestBuilder) -> RequestBuilder {
+        for (name, value) in self.headers.iter() {
+            request_builder = request_builder.header(name.as_str(), value.as_str());
+        }
+        request_builder
+    }
+}
+
+/// Returns an error if the body of a response reports one
+pub(crate) fn check_body(s: String) -> Result<String, Error> {
+    // todo: improve error parsing without serde
+    if s.contains("\"error\"") {
+        return Err(Error::DatabaseError {
+            error: format!("influxdb error: \"{}\"", s),
+            status: None,
+            request_id: None,
+        });
+    }
+
+    Ok(s)
+}
+
+/// Adds a hint to the reason a URL is invalid if its host looks like an IPv6 address the URL
+/// parser rejects
+pub(crate) fn explain_url_error(url: &str, reason: &str) -> String {
+    let authority = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default();
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    if host.starts_with('[') && host.contains('%') {
+        format!(
+            "{}, zone IDs of IPv6 addresses like `%eth0` are not supported, use a host name or an address which isn't link-local",
+            reason
+        )
+    } else if !host.starts_with('[') && host.matches(':').count() > 1 {
+        format!(
+            "{}, IPv6 addresses have to be enclosed in brackets like `http://[::1]:8086`",
+            reason
+        )
+    } else {
+        reason.to_string()
     }
 }
 
+/// `SELECT ... INTO` writes data and therefore has to be sent as `POST`
+pub(crate) fn is_select_into(read_query: &str) -> bool {
+    crate::ql::has_into_clause(read_query)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Client;
+    use super::{is_select_into, Client};
+    use crate::query::Precision;
 
     #[test]
     fn test_fn_database() {
@@ -233,6 +1031,67 @@ mod tests {
         assert_eq!(client.database_url(), "http://localhost:8068");
     }
 
+    #[test]
+    fn test_endpoint() {
+        let endpoint = |url: &str| {
+            Client::new(url, "database")
+                .endpoint("query")
+                .map(|url| url.to_string())
+        };
+
+        assert_eq!(
+            endpoint("http://localhost:8086").unwrap(),
+            "http://localhost:8086/query"
+        );
+        assert_eq!(
+            endpoint("http://localhost:8086/").unwrap(),
+            "http://localhost:8086/query"
+        );
+        assert_eq!(
+            endpoint("https://proxy.example.com/influx/").unwrap(),
+            "https://proxy.example.com/influx/query"
+        );
+        assert_eq!(
+            Client::new("http://localhost:8086//", "database").database_url(),
+            "http://localhost:8086"
+        );
+        assert!(endpoint("localhost 8086").is_err());
+    }
+
+    #[test]
+    fn test_ipv6_urls() {
+        let endpoint = |url: &str| {
+            Client::new(url, "database")
+                .endpoint("query")
+                .map(|url| url.to_string())
+        };
+
+        assert_eq!(
+            endpoint("http://[::1]:8086").unwrap(),
+            "http://[::1]:8086/query"
+        );
+        assert_eq!(endpoint("http://[::1]/").unwrap(), "http://[::1]/query");
+        assert_eq!(
+            endpoint("https://[2001:db8::1]:8086/influx").unwrap(),
+            "https://[2001:db8::1]:8086/influx/query"
+        );
+        assert_eq!(
+            Client::new("http://[::1]:8086/", "database").database_url(),
+            "http://[::1]:8086"
+        );
+
+        let error = endpoint("http://[fe80::1%25eth0]:8086")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("zone IDs"), "{}", error);
+        let error = endpoint("http://::1:8086").unwrap_err().to_string();
+        assert!(error.contains("enclosed in brackets"), "{}", error);
+        let error = endpoint("http://user:pass@[fe80::1%eth0]/influx")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("zone IDs"), "{}", error);
+    }
+
     #[test]
     fn test_with_auth() {
         let client = Client::new("http://localhost:8068", "database");
@@ -245,4 +1104,73 @@ mod tests {
         assert_eq!(with_auth.parameters.get("u").unwrap(), "username");
         assert_eq!(with_auth.parameters.get("p").unwrap(), "password");
     }
+
+    #[test]
+    fn test_with_retention_policy() {
+        let client = Client::new("http://localhost:8068", "database");
+        assert_eq!(client.retention_policy(), None);
+
+        let with_retention_policy = client.with_retention_policy("two_weeks");
+        assert_eq!(with_retention_policy.retention_policy(), Some("two_weeks"));
+        assert_eq!(with_retention_policy.database_name(), "database");
+    }
+
+    #[test]
+    fn test_with_precision() {
+        let client = Client::new("http://localhost:8068", "database");
+        assert_eq!(client.precision(), None);
+
+        let with_precision = client.with_precision(Precision::Seconds);
+        assert_eq!(with_precision.precision(), Some(Precision::Seconds));
+    }
+
+    #[test]
+    fn test_with_downscaling() {
+        let client = Client::new("http://localhost:8068", "database");
+        assert_eq!(client.max_precision(), None);
+
+        let with_downscaling = client.with_downscaling(Precision::Milliseconds);
+        assert_eq!(
+            with_downscaling.max_precision(),
+            Some(Precision::Milliseconds)
+        );
+        assert_eq!(with_downscaling.precision(), None);
+    }
+
+    #[test]
+    fn test_is_select_into() {
+        assert!(is_select_into(
+            r#"SELECT mean("value") INTO "db"."rp"."dest" FROM "cpu""#
+        ));
+        assert!(is_select_into("select *\ninto dest from cpu"));
+        assert!(!is_select_into(r#"SELECT "interval" FROM "cpu""#));
+        assert!(!is_select_into("SHOW DATABASES"));
+        assert!(!is_select_into(
+            "SELECT * FROM logs WHERE msg = 'go into it'"
+        ));
+        assert!(!is_select_into(
+            r#"SELECT "into" FROM "into" WHERE host =~ /into/"#
+        ));
+        assert!(!is_select_into("SELECT * FROM cpu -- into"));
+    }
+
+    #[test]
+    fn test_with_header() {
+        let client = Client::new("http://localhost:8068", "database");
+        assert!(client.headers.is_empty());
+
+        let with_header = client
+            .with_header("X-Scope-OrgID", "tenant-1")
+            .with_user_agent("my-service/1.0")
+            .with_header("X-Scope-OrgID", "tenant-2");
+        assert_eq!(with_header.headers.len(), 2);
+        assert_eq!(
+            with_header.headers.get("X-Scope-OrgID").unwrap(),
+            "tenant-2"
+        );
+        assert_eq!(
+            with_header.headers.get("User-Agent").unwrap(),
+            "my-service/1.0"
+        );
+    }
 }
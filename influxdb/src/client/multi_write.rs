@@ -0,0 +1,88 @@
+//! Fan-out of writes to several databases, retention policies or servers
+
+use futures::future::join_all;
+
+use crate::{Client, Error, Query};
+
+/// Writes the same points to several targets, e.g. a raw and a downsampled retention policy,
+/// or a production server and its mirror.
+///
+/// Each target is a [`Client`](crate::Client), configured with the database, retention policy
+/// and credentials the points should be written with. The writes are sent concurrently and
+/// don't depend on each other: a failing target doesn't prevent the others from being written.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use influxdb::{Client, MultiWrite, Timestamp};
+/// use influxdb::InfluxDbWriteable;
+///
+/// # #[async_std::main]
+/// # async fn main() {
+/// let client = Client::new("http://localhost:8086", "metrics");
+/// let multi_write = MultiWrite::new()
+///     .add_target(client.clone())
+///     .add_target(client.with_retention_policy("two_weeks"))
+///     .add_target(Client::new("http://mirror:8086", "metrics"));
+///
+/// let query = Timestamp::Hours(1).into_query("cpu").add_field("usage", 0.5);
+/// for (target, result) in multi_write.targets().iter().zip(multi_write.query(&query).await) {
+///     if let Err(err) = result {
+///         eprintln!("could not write to {}: {}", target.database_url(), err);
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MultiWrite {
+    targets: Vec<Client>,
+}
+
+impl MultiWrite {
+    /// Creates a new [`MultiWrite`](crate::MultiWrite) without any targets
+    pub fn new() -> Self {
+        MultiWrite::default()
+    }
+
+    /// Adds a target the points will be written to
+    pub fn add_target(mut self, client: Client) -> Self {
+        self.targets.push(client);
+        self
+    }
+
+    /// Returns the targets, in the order they were added
+    pub fn targets(&self) -> &[Client] {
+        &self.targets
+    }
+
+    /// Sends the [`WriteQuery`](crate::WriteQuery), or a batch of them, to all targets
+    ///
+    /// Returns the result of each target, in the order the targets were added.
+    pub async fn query<Q>(&self, q: &Q) -> Vec<Result<String, Error>>
+    where
+        Q: Query,
+    {
+        join_all(self.targets.iter().map(|client| client.query(q))).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MultiWrite;
+    use crate::Client;
+
+    #[test]
+    fn test_add_target() {
+        let client = Client::new("http://localhost:8068", "database");
+        let multi_write = MultiWrite::new()
+            .add_target(client.clone())
+            .add_target(client.with_retention_policy("two_weeks"));
+
+        assert_eq!(multi_write.targets().len(), 2);
+        assert_eq!(multi_write.targets()[0].retention_policy(), None);
+        assert_eq!(
+            multi_write.targets()[1].retention_policy(),
+            Some("two_weeks")
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Parsing of the lines rejected by partial writes
+
+use crate::error::RejectedLine;
+
+const UNABLE_TO_PARSE: &str = "unable to parse '";
+
+/// Returns the lines of `request_body` the server reported in `error` as unparsable
+///
+/// InfluxDB reports each rejected line as `unable to parse '<line>': <reason>`, separated by
+/// newlines, and follows the last one with ` dropped=<count>` for partial writes. The error is the
+/// raw body of the response, so the JSON escapes are undone first.
+pub(crate) fn rejected_lines(error: &str, request_body: &str) -> Vec<RejectedLine> {
+    let error = unescape_json(error);
+    let lines = request_body.lines().map(str::trim).collect::<Vec<_>>();
+    let mut used = vec![false; lines.len()];
+
+    let mut rejected = Vec::new();
+    for message in error.split('\n') {
+        let start = match message.find(UNABLE_TO_PARSE) {
+            Some(start) => start + UNABLE_TO_PARSE.len(),
+            None => continue,
+        };
+        // the line may contain `': ` itself, the reason never does
+        let (line, reason) = match message[start..].rfind("': ") {
+            Some(end) => (&message[start..start + end], &message[start + end + 3..]),
+            None => continue,
+        };
+        let reason = match reason.rfind(" dropped=") {
+            Some(dropped) => &reason[..dropped],
+            None => reason,
+        };
+        let reason = reason.trim_end_matches(['"', '}']);
+
+        let line = line.trim();
+        let index = (0..lines.len()).find(|index| !used[*index] && lines[*index] == line);
+        if let Some(index) = index {
+            used[index] = true;
+            rejected.push(RejectedLine {
+                index,
+                line: line.to_string(),
+                reason: reason.to_string(),
+            });
+        }
+    }
+    rejected.sort_by_key(|rejected| rejected.index);
+    rejected
+}
+
+/// Undoes the escapes of JSON strings, leaving other text untouched
+fn unescape_json(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some('u') => {
+                let code = chars.by_ref().take(4).collect::<String>();
+                let c = u32::from_str_radix(&code, 16).ok().and_then(char::from_u32);
+                unescaped.push(c.unwrap_or(char::REPLACEMENT_CHARACTER));
+            }
+            Some(c) => unescaped.push(c),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rejected_lines;
+    use crate::error::RejectedLine;
+
+    #[test]
+    fn test_rejected_lines() {
+        let request_body = "weather temperature=82 1\n\
+                            weather temperature= 2\n\
+                            weather,location=us\\ east note=\"it's': cold\",ok=tru 3\n\
+                            weather temperature=83 4";
+        let error = r#"influxdb error: "{"error":"partial write: unable to parse 'weather temperature= 2': missing field value\nunable to parse 'weather,location=us\\ east note=\"it's': cold\",ok=tru 3': invalid boolean dropped=0"}""#;
+
+        assert_eq!(
+            rejected_lines(error, request_body),
+            vec![
+                RejectedLine {
+                    index: 1,
+                    line: "weather temperature= 2".to_string(),
+                    reason: "missing field value".to_string(),
+                },
+                RejectedLine {
+                    index: 2,
+                    line: r#"weather,location=us\ east note="it's': cold",ok=tru 3"#.to_string(),
+                    reason: "invalid boolean".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_other_errors() {
+        let error = r#"{"error":"partial write: field type conflict: input field \"temperature\" on measurement \"weather\" is type float, already exists as type integer dropped=1"}"#;
+        assert!(rejected_lines(error, "weather temperature=82.5 1").is_empty());
+    }
+}
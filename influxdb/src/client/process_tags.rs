@@ -0,0 +1,82 @@
+//! Tags identifying the process which writes points
+
+use lazy_static::lazy_static;
+use std::env;
+use std::fs;
+
+use crate::Client;
+
+lazy_static! {
+    static ref PROCESS_TAGS: [(&'static str, String); 3] = [
+        ("host", hostname()),
+        ("pid", std::process::id().to_string()),
+        ("client_version", env!("CARGO_PKG_VERSION").to_string()),
+    ];
+}
+
+/// Returns the name of the host, or `unknown` if it cannot be determined
+fn hostname() -> String {
+    ["/proc/sys/kernel/hostname", "/etc/hostname"]
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .chain(env::var("HOSTNAME").ok())
+        .chain(env::var("COMPUTERNAME").ok())
+        .map(|hostname| hostname.trim().to_string())
+        .find(|hostname| !hostname.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+impl Client {
+    /// Adds tags identifying the process to every point written by the [`Client`](crate::Client)
+    ///
+    /// The tags are the `host` name, the `pid` of the process and the `client_version` of this
+    /// crate. They are determined once per process and added as
+    /// [default tags](crate::Client::with_default_tags), so tags of the point win.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Client;
+    ///
+    /// let client = Client::new("http://localhost:8086", "test").with_process_tags();
+    ///
+    /// let keys = client.default_tags().iter().map(|(key, _)| key.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(keys, vec!["host", "pid", "client_version"]);
+    /// ```
+    pub fn with_process_tags(self) -> Self {
+        self.with_default_tags(
+            PROCESS_TAGS
+                .iter()
+                .map(|(key, value)| (*key, value.as_str())),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Client, Type};
+
+    #[test]
+    fn test_process_tags() {
+        let client = Client::new("http://localhost:8086", "test").with_process_tags();
+        let tags = client.default_tags();
+
+        assert_eq!(tags.len(), 3);
+        assert_eq!(tags[0].0, "host");
+        assert_ne!(tags[0].1, Type::Text(String::new()));
+        assert_eq!(
+            tags[1],
+            (
+                "pid".to_string(),
+                Type::Text(std::process::id().to_string())
+            )
+        );
+        assert_eq!(
+            tags[2],
+            (
+                "client_version".to_string(),
+                Type::Text(env!("CARGO_PKG_VERSION").to_string())
+            )
+        );
+    }
+}
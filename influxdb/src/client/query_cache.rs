@@ -0,0 +1,203 @@
+//! In-memory cache of the responses to read queries
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use surf::Request;
+
+use crate::Client;
+
+/// Responses to `GET` requests, keyed by their URL which contains the query, and by who sent them
+#[derive(Debug)]
+pub(crate) struct QueryCache {
+    capacity: usize,
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, String)>>,
+}
+
+impl QueryCache {
+    fn new(capacity: usize, ttl: Duration) -> Self {
+        QueryCache {
+            capacity,
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<String> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some((inserted, body)) if inserted.elapsed() < self.ttl => Some(body.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(crate) fn insert(&self, key: String, body: String) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            let ttl = self.ttl;
+            entries.retain(|_, (inserted, _)| inserted.elapsed() < ttl);
+        }
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, (inserted, _))| *inserted)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(key, (Instant::now(), body));
+    }
+
+    fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+/// Returns the key of an authenticated request, its URL without the credentials and a hash of
+/// them, so clients with different credentials never share responses
+pub(crate) fn key(request: &Request) -> String {
+    let mut url = request.url().clone();
+    let mut credentials = Vec::new();
+    let parameters = request
+        .url()
+        .query_pairs()
+        .filter(|(name, value)| {
+            let credential = name == "u" || name == "p";
+            if credential {
+                credentials.push(format!("{}={}", name, value));
+            }
+            !credential
+        })
+        .collect::<Vec<_>>();
+    url.query_pairs_mut().clear().extend_pairs(parameters);
+
+    let mut hasher = DefaultHasher::new();
+    credentials.hash(&mut hasher);
+    request
+        .header("Authorization")
+        .map(|value| value.as_str())
+        .hash(&mut hasher);
+    format!("{} {:016x}", url, hasher.finish())
+}
+
+impl Client {
+    /// Caches the responses to up to `capacity` distinct read queries for `ttl`
+    ///
+    /// Meant for dashboards which send identical `SELECT`s over and over. Only queries sent as `GET`
+    /// are cached, i.e. no statements which modify data. Responses are cached per credentials, i.e.
+    /// the `u` and `p` parameters and the `Authorization` header. Writes don't invalidate the cache, use
+    /// [`invalidate_query_cache`](crate::Client::invalidate_query_cache) if they have to be visible
+    /// before the `ttl` expires. Clones of the client share the cache.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::{Client, Query};
+    /// use std::time::Duration;
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("http://localhost:8086", "test")
+    ///     .with_query_cache(100, Duration::from_secs(10));
+    /// let query = Query::raw_read_query("SELECT mean(usage) FROM cpu WHERE time > now() - 1h");
+    ///
+    /// let cached = client.query(&query).await?;
+    /// let fresh = client.without_query_cache().query(&query).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_query_cache(mut self, capacity: usize, ttl: Duration) -> Self {
+        self.query_cache = Some(Arc::new(QueryCache::new(capacity, ttl)));
+        self
+    }
+
+    /// Returns a client which bypasses the query cache, e.g. for a single query which needs fresh results
+    pub fn without_query_cache(&self) -> Self {
+        Client {
+            query_cache: None,
+            ..self.clone()
+        }
+    }
+
+    /// Removes all responses from the query cache
+    pub fn invalidate_query_cache(&self) {
+        if let Some(query_cache) = &self.query_cache {
+            query_cache.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{key, QueryCache};
+    use std::time::Duration;
+
+    #[test]
+    fn test_key() {
+        let request = |url: &str, authorization: Option<&str>| {
+            let mut request = surf::get(url).build();
+            if let Some(authorization) = authorization {
+                request.insert_header("Authorization", authorization);
+            }
+            key(&request)
+        };
+        let query = "http://localhost:8086/query?db=test&q=SHOW+DATABASES";
+
+        let admin = request(&format!("{}&u=admin&p=secret", query), None);
+        assert!(admin.starts_with(query), "{}", admin);
+        assert!(!admin.contains("secret"), "{}", admin);
+        assert_eq!(admin, request(&format!("{}&u=admin&p=secret", query), None));
+        assert_ne!(
+            admin,
+            request(&format!("{}&u=admin&p=revoked", query), None)
+        );
+        assert_ne!(admin, request(query, None));
+        assert_ne!(
+            request(query, Some("Token a")),
+            request(query, Some("Token b"))
+        );
+    }
+
+    #[test]
+    fn test_ttl() {
+        let cache = QueryCache::new(10, Duration::from_millis(20));
+        cache.insert("a".to_string(), "1".to_string());
+        assert_eq!(cache.get("a"), Some("1".to_string()));
+        assert_eq!(cache.get("b"), None);
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(cache.get("a"), None);
+    }
+
+    #[test]
+    fn test_capacity() {
+        let cache = QueryCache::new(2, Duration::from_secs(60));
+        for (key, body) in &[("a", "1"), ("b", "2"), ("a", "3"), ("c", "4")] {
+            cache.insert(key.to_string(), body.to_string());
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        // "b" is the oldest entry, as "a" was replaced
+        assert_eq!(cache.get("a"), Some("3".to_string()));
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("c"), Some("4".to_string()));
+
+        cache.clear();
+        assert_eq!(cache.get("b"), None);
+
+        let disabled = QueryCache::new(0, Duration::from_secs(60));
+        disabled.insert("a".to_string(), "1".to_string());
+        assert_eq!(disabled.get("a"), None);
+    }
+}
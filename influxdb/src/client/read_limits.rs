@@ -0,0 +1,337 @@
+//! Limits protecting applications from runaway read queries
+
+use futures::prelude::*;
+use futures_timer::Delay;
+use serde_json::Value;
+use std::io;
+use std::time::Duration;
+
+use super::check_body;
+use super::request_id::REQUEST_ID;
+use crate::{Client, Error, Query};
+
+/// Number of rows the server sends per chunk when no row limit is set
+const CHUNK_SIZE: u64 = 10_000;
+
+/// Limits of the results of read queries, see
+/// [`Client::with_read_limits`](crate::Client::with_read_limits)
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::{Client, ReadLimits};
+/// use std::time::Duration;
+///
+/// let client = Client::new("http://localhost:8086", "test").with_read_limits(
+///     ReadLimits::new()
+///         .max_rows(100_000)
+///         .max_bytes(16 * 1024 * 1024)
+///         .timeout(Duration::from_secs(10)),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReadLimits {
+    max_rows: Option<u64>,
+    max_bytes: Option<u64>,
+    timeout: Option<Duration>,
+}
+
+impl ReadLimits {
+    /// Creates limits which don't limit anything yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of rows of all series of a result
+    pub fn max_rows(mut self, max_rows: u64) -> Self {
+        self.max_rows = Some(max_rows);
+        self
+    }
+
+    /// Sets the maximum size of a response in bytes
+    pub fn max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Sets the time after which a read query is aborted, including reading its result
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+impl Client {
+    /// Limits the results of read queries sent with [`query`](crate::Client::query)
+    ///
+    /// Limited queries are sent with `chunked=true`, so the server streams their results. The
+    /// request is aborted as soon as a limit is exceeded, failing with
+    /// [`ResultTooLarge`](crate::Error::ResultTooLarge) and the number of rows and bytes received
+    /// until then. Complete results are merged into a single response, like the server returns
+    /// it for queries which aren't chunked.
+    pub fn with_read_limits(mut self, limits: ReadLimits) -> Self {
+        self.read_limits = Some(limits);
+        self
+    }
+
+    /// Returns the limits of read queries, if any
+    pub fn read_limits(&self) -> Option<ReadLimits> {
+        self.read_limits
+    }
+
+    /// Sends a read query with `limits`, see [`with_read_limits`](crate::Client::with_read_limits)
+    pub(crate) async fn limited_read<Q>(
+        &self,
+        q: &Q,
+        limits: ReadLimits,
+        request_id: &str,
+    ) -> Result<String, Error>
+    where
+        Q: Query,
+    {
+        let read = self.read_chunks(q, limits, request_id);
+        match limits.timeout {
+            Some(timeout) => {
+                futures::pin_mut!(read);
+                match future::select(read, Delay::new(timeout)).await {
+                    future::Either::Left((result, _)) => result,
+                    future::Either::Right(_) => Err(Error::ConnectionError {
+                        error: format!("read query timed out after {:?}", timeout),
+                        source: crate::error::source(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            "read timeout elapsed",
+                        )),
+                    }),
+                }
+            }
+            None => read.await,
+        }
+    }
+
+    async fn read_chunks<Q>(
+        &self,
+        q: &Q,
+        limits: ReadLimits,
+        request_id: &str,
+    ) -> Result<String, Error>
+    where
+        Q: Query,
+    {
+        let chunk_size = limits.max_rows.map_or(CHUNK_SIZE, |max_rows| {
+            max_rows.saturating_add(1).min(CHUNK_SIZE)
+        });
+        let request = self
+            .with_headers(
+                self.read_request(
+                    q.build()?.get(),
+                    &[("chunked", "true"), ("chunk_size", &chunk_size.to_string())],
+                )?
+                .header(REQUEST_ID, request_id),
+            )
+            .build();
+        let mut response = self.send_request(request).await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.body_string().await.unwrap_or_default();
+            return Err(Error::DatabaseError {
+                error: format!("influxdb error: \"{}\"", body),
+                status: Some(status.into()),
+                request_id: None,
+            });
+        }
+
+        let (mut rows, mut bytes) = (0, 0);
+        let mut results = Vec::new();
+        let mut pending = Vec::new();
+        let mut buffer = [0; 8192];
+        loop {
+            let read = response
+                .read(&mut buffer)
+                .await
+                .map_err(|err| Error::ConnectionError {
+                    error: err.to_string(),
+                    source: crate::error::source(err),
+                })?;
+            bytes += read as u64;
+            if matches!(limits.max_bytes, Some(max_bytes) if bytes > max_bytes) {
+                return Err(Error::ResultTooLarge { rows, bytes });
+            }
+            pending.extend_from_slice(&buffer[..read]);
+
+            // the server sends one chunk per line
+            let complete = match pending.iter().rposition(|byte| *byte == b'\n') {
+                Some(end) => pending.drain(..=end).collect::<Vec<_>>(),
+                None if read == 0 => std::mem::take(&mut pending),
+                None => continue,
+            };
+            for line in complete.split(|byte| *byte == b'\n') {
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+                let chunk: Value =
+                    serde_json::from_slice(line).map_err(|err| Error::DeserializationError {
+                        error: format!("could not parse chunk: {}", err),
+                        source: crate::error::source(err),
+                    })?;
+                rows += count_rows(&chunk);
+                if matches!(limits.max_rows, Some(max_rows) if rows > max_rows) {
+                    return Err(Error::ResultTooLarge { rows, bytes });
+                }
+                merge_chunk(&mut results, chunk)?;
+            }
+            if read == 0 {
+                break;
+            }
+        }
+
+        check_body(serde_json::json!({ "results": results }).to_string())
+    }
+}
+
+/// Returns the number of rows of all series of a chunk
+fn count_rows(chunk: &Value) -> u64 {
+    chunk["results"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|result| result["series"].as_array())
+        .flatten()
+        .filter_map(|series| series["values"].as_array())
+        .map(|values| values.len() as u64)
+        .sum()
+}
+
+/// Appends the results of a chunk to the results received before, continuing series which were
+/// split across chunks
+fn merge_chunk(results: &mut Vec<Value>, chunk: Value) -> Result<(), Error> {
+    let chunk = match chunk {
+        Value::Object(mut chunk) => {
+            if let Some(error) = chunk.remove("error") {
+                return Err(Error::DatabaseError {
+                    error: error
+                        .as_str()
+                        .map_or_else(|| error.to_string(), String::from),
+                    status: None,
+                    request_id: None,
+                });
+            }
+            chunk.remove("results")
+        }
+        _ => None,
+    };
+    for mut result in chunk
+        .and_then(|results| match results {
+            Value::Array(results) => Some(results),
+            _ => None,
+        })
+        .into_iter()
+        .flatten()
+    {
+        if let Some(result) = result.as_object_mut() {
+            result.remove("partial");
+        }
+        for series in result
+            .get_mut("series")
+            .and_then(Value::as_array_mut)
+            .into_iter()
+            .flatten()
+        {
+            if let Some(series) = series.as_object_mut() {
+                series.remove("partial");
+            }
+        }
+        let last = match results.last_mut() {
+            Some(last) if last["statement_id"] == result["statement_id"] => last,
+            _ => {
+                results.push(result);
+                continue;
+            }
+        };
+        if let Some(error) = result.get("error") {
+            last["error"] = error.clone();
+        }
+        let series = match result.get_mut("series").map(Value::take) {
+            Some(Value::Array(series)) => series,
+            _ => continue,
+        };
+        if !last["series"].is_array() {
+            last["series"] = Value::Array(Vec::new());
+        }
+        let merged = last["series"].as_array_mut().unwrap();
+        for mut series in series {
+            match merged.last_mut() {
+                Some(previous)
+                    if previous["name"] == series["name"]
+                        && previous["tags"] == series["tags"]
+                        && previous["columns"] == series["columns"] =>
+                {
+                    if let (Some(values), Value::Array(more)) =
+                        (previous["values"].as_array_mut(), series["values"].take())
+                    {
+                        values.extend(more);
+                    }
+                }
+                _ => merged.push(series),
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count_rows, merge_chunk, ReadLimits};
+    use crate::Client;
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[test]
+    fn test_read_limits() {
+        let client = Client::new("http://localhost:8086", "test");
+        assert_eq!(client.read_limits(), None);
+        let limits = ReadLimits::new()
+            .max_rows(10)
+            .timeout(Duration::from_secs(1));
+        assert_eq!(client.with_read_limits(limits).read_limits(), Some(limits));
+    }
+
+    #[cfg(not(feature = "hyper-client"))]
+    #[async_std::test]
+    async fn test_read_timeout() {
+        let (_listener, url) = crate::client::test_server::unresponsive();
+        let client = Client::new(url, "test")
+            .with_read_limits(ReadLimits::new().timeout(Duration::from_millis(100)));
+        let error = client
+            .query(&crate::ReadQuery::new("SELECT * FROM cpu"))
+            .await
+            .unwrap_err();
+        assert!(error.is_timeout(), "{:?}", error);
+    }
+
+    #[test]
+    fn test_merge_chunks() {
+        let chunks = vec![
+            json!({"results":[{"statement_id":0,"series":[{"name":"cpu","columns":["time","load"],"values":[[1,0.5],[2,0.7]],"partial":true}],"partial":true}]}),
+            json!({"results":[{"statement_id":0,"series":[{"name":"cpu","columns":["time","load"],"values":[[3,0.9]]},{"name":"mem","columns":["time","used"],"values":[[1,42]]}]}]}),
+            json!({"results":[{"statement_id":1,"error":"measurement not found"}]}),
+        ];
+        assert_eq!(chunks.iter().map(count_rows).sum::<u64>(), 4);
+
+        let mut results = Vec::new();
+        for chunk in chunks {
+            merge_chunk(&mut results, chunk).unwrap();
+        }
+        assert_eq!(
+            json!({ "results": results }),
+            json!({"results":[
+                {"statement_id":0,"series":[
+                    {"name":"cpu","columns":["time","load"],"values":[[1,0.5],[2,0.7],[3,0.9]]},
+                    {"name":"mem","columns":["time","used"],"values":[[1,42]]}
+                ]},
+                {"statement_id":1,"error":"measurement not found"}
+            ]})
+        );
+
+        assert!(merge_chunk(&mut results, json!({"error":"query timeout"})).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+//! IDs correlating requests with the logs and traces of the server
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::Client;
+
+/// The header InfluxDB reads the ID of a request from and answers with
+pub(crate) const REQUEST_ID: &str = "Request-Id";
+
+impl Client {
+    /// Sends `request_id` as the ID of all requests of the [`Client`](crate::Client)
+    ///
+    /// Without a request ID, a random one is generated for every request. InfluxDB logs the ID and
+    /// sends it back in its `Request-Id` header, errors of the server report it as well, see
+    /// [`Error::request_id`](crate::Error::request_id). Clients are cheap to clone, so a clone can
+    /// reuse the ID of an incoming request to correlate both.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Client;
+    ///
+    /// let client = Client::new("http://localhost:8086", "test")
+    ///     .with_request_id("2f2c5bd6-4e47-4b4e-9a3c-7c8d0b9e6a1f");
+    /// ```
+    pub fn with_request_id<S>(mut self, request_id: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.request_id = Some(Arc::new(request_id.into()));
+        self
+    }
+
+    /// Returns the ID the next request is sent with
+    pub(crate) fn next_request_id(&self) -> String {
+        match &self.request_id {
+            Some(request_id) => request_id.to_string(),
+            None => generate(),
+        }
+    }
+}
+
+/// Generates a random ID in the format of a version 4 UUID, like the IDs InfluxDB generates
+pub(crate) fn generate() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    // the keys of `RandomState` are random, so are the hashes of the counter
+    let random = |salt: u64| {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(count);
+        hasher.write_u64(salt);
+        hasher.finish()
+    };
+    let (high, low) = (random(0), random(1));
+    format!(
+        "{:08x}-{:04x}-4{:03x}-{:04x}-{:012x}",
+        high >> 32,
+        (high >> 16) & 0xffff,
+        high & 0x0fff,
+        0x8000 | (low >> 48) & 0x3fff,
+        low & 0xffff_ffff_ffff,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::generate;
+    use crate::{Client, Error};
+
+    #[test]
+    fn test_generate() {
+        let id = generate();
+        assert_eq!(id.len(), 36);
+        assert_eq!(id.as_bytes()[14], b'4');
+        assert!(matches!(id.as_bytes()[19], b'8' | b'9' | b'a' | b'b'));
+        assert_ne!(generate(), id);
+    }
+
+    #[test]
+    fn test_request_id() {
+        let client = Client::new("http://localhost:8086", "test");
+        assert_ne!(client.next_request_id(), client.next_request_id());
+        let client = client.with_request_id("abc");
+        assert_eq!(client.next_request_id(), "abc");
+
+        let error = Error::DatabaseError {
+            error: "database not found".to_string(),
+            status: Some(404),
+            request_id: None,
+        }
+        .with_request_id("abc")
+        .with_request_id("def");
+        assert_eq!(error.request_id(), Some("abc"));
+        assert_eq!(Error::AuthorizationError.request_id(), None);
+    }
+}
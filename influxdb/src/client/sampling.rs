@@ -0,0 +1,252 @@
+//! Client-side sampling of the points of high-frequency sources, see
+//! [`WriterOptions::sampling`](crate::WriterOptions::sampling)
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant};
+
+use super::buffered_writer::{series_key, Buffered, SeriesKey};
+
+/// How a [`BufferedWriter`](crate::BufferedWriter) samples the points of each series, see
+/// [`WriterOptions::sampling`](crate::WriterOptions::sampling)
+///
+/// A series is a measurement with a tag set. Kept points get the sample rate as a float field,
+/// `sample_rate` by default, the number of points of the series each of them stands for. Queries
+/// rescale counts and sums with it, e.g. `SELECT sum("sample_rate") FROM "requests"` estimates the
+/// number of requests.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::{Sampling, WriterOptions};
+/// use std::time::Duration;
+///
+/// let options = WriterOptions::default().sampling(Sampling::one_in(10));
+/// let options = WriterOptions::default().sampling(
+///     Sampling::reservoir(100, Duration::from_secs(10)).rate_field("weight"),
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sampling {
+    policy: Policy,
+    rate_field: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Policy {
+    OneIn(u64),
+    Reservoir { size: usize, interval: Duration },
+}
+
+impl Sampling {
+    /// Keeps the first and then every `n`th point of each series
+    pub fn one_in(n: u64) -> Self {
+        Sampling::new(Policy::OneIn(n.max(1)))
+    }
+
+    /// Keeps up to `size` points of each series per `interval`, chosen at random
+    ///
+    /// The kept points are buffered until the interval ended, or the writer is flushed or shut
+    /// down. As the buffer is checked when it's flushed, intervals end on the next
+    /// [flush interval](crate::WriterOptions::flush_interval) at the earliest.
+    pub fn reservoir(size: usize, interval: Duration) -> Self {
+        Sampling::new(Policy::Reservoir {
+            size: size.max(1),
+            interval,
+        })
+    }
+
+    /// Sets the name of the field with the sample rate, defaults to `sample_rate`
+    pub fn rate_field<S>(mut self, rate_field: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.rate_field = rate_field.into();
+        self
+    }
+
+    fn new(policy: Policy) -> Self {
+        Sampling {
+            policy,
+            rate_field: "sample_rate".to_string(),
+        }
+    }
+}
+
+/// The points of a series seen in the current interval, and the ones kept of them
+#[derive(Default)]
+struct Reservoir {
+    seen: u64,
+    kept: Vec<Buffered>,
+}
+
+/// Applies a [`Sampling`](crate::Sampling) to the points of a writer
+pub(super) struct Sampler {
+    sampling: Sampling,
+    /// Points skipped per series since the last kept one, with [`Policy::OneIn`]
+    counts: HashMap<SeriesKey, u64>,
+    reservoirs: HashMap<SeriesKey, Reservoir>,
+    /// Order in which the series of the reservoirs were first seen
+    series: Vec<SeriesKey>,
+    interval_start: Instant,
+    random: RandomState,
+    draws: u64,
+}
+
+impl Sampler {
+    pub(super) fn new(sampling: Sampling) -> Self {
+        Sampler {
+            sampling,
+            counts: HashMap::new(),
+            reservoirs: HashMap::new(),
+            series: Vec::new(),
+            interval_start: Instant::now(),
+            random: RandomState::new(),
+            draws: 0,
+        }
+    }
+
+    /// Returns the point if it is kept right away, and the point which was dropped, either the
+    /// offered one or one it replaced in the reservoir of its series
+    pub(super) fn offer(&mut self, buffered: Buffered) -> (Option<Buffered>, Option<Buffered>) {
+        let key = series_key(&buffered.point);
+        match self.sampling.policy {
+            Policy::OneIn(n) => {
+                let skipped = self.counts.entry(key).or_insert(0);
+                let keep = *skipped == 0;
+                *skipped = (*skipped + 1) % n;
+                if keep {
+                    (Some(self.with_rate(buffered, n as f64)), None)
+                } else {
+                    (None, Some(buffered))
+                }
+            }
+            Policy::Reservoir { size, .. } => {
+                if !self.reservoirs.contains_key(&key) {
+                    self.series.push(key.clone());
+                }
+                let draw = self.draw();
+                let reservoir = self.reservoirs.entry(key).or_default();
+                reservoir.seen += 1;
+                if reservoir.kept.len() < size {
+                    reservoir.kept.push(buffered);
+                    return (None, None);
+                }
+                // algorithm R: the nth point replaces a kept one with a probability of size / n
+                let index = (draw % reservoir.seen) as usize;
+                match reservoir.kept.get_mut(index) {
+                    Some(kept) => (None, Some(std::mem::replace(kept, buffered))),
+                    None => (None, Some(buffered)),
+                }
+            }
+        }
+    }
+
+    /// Returns the points kept in the reservoirs if the interval ended, or `force` is set, with
+    /// their sample rate
+    pub(super) fn drain(&mut self, force: bool) -> Vec<Buffered> {
+        let interval = match self.sampling.policy {
+            Policy::Reservoir { interval, .. } => interval,
+            Policy::OneIn(_) => return Vec::new(),
+        };
+        if !force && self.interval_start.elapsed() < interval {
+            return Vec::new();
+        }
+        self.interval_start = Instant::now();
+        let mut points = Vec::new();
+        for key in std::mem::take(&mut self.series) {
+            if let Some(reservoir) = self.reservoirs.remove(&key) {
+                let rate = reservoir.seen as f64 / reservoir.kept.len() as f64;
+                for buffered in reservoir.kept {
+                    points.push(self.with_rate(buffered, rate));
+                }
+            }
+        }
+        points
+    }
+
+    /// Adds the field with the sample rate to the point
+    fn with_rate(&self, mut buffered: Buffered, rate: f64) -> Buffered {
+        buffered
+            .point
+            .fields_mut()
+            .push((self.sampling.rate_field.clone(), rate.into()));
+        buffered
+    }
+
+    /// Returns a random number, the keys of `RandomState` are random, so are the hashes
+    fn draw(&mut self) -> u64 {
+        self.draws += 1;
+        let mut hasher = self.random.build_hasher();
+        hasher.write_u64(self.draws);
+        hasher.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Sampler, Sampling};
+    use crate::client::buffered_writer::Buffered;
+    use crate::{InfluxDbWriteable, Query, Timestamp};
+    use std::time::Duration;
+
+    fn point(second: u128, host: &str) -> Buffered {
+        Buffered::new(
+            Timestamp::Seconds(second)
+                .into_query("requests")
+                .add_tag("host", host)
+                .add_field("latency", 12),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_one_in() {
+        let mut sampler = Sampler::new(Sampling::one_in(3));
+        let kept = (0..7)
+            .flat_map(|second| vec![point(second, "a"), point(second, "b")])
+            .filter_map(|buffered| sampler.offer(buffered).0)
+            .map(|buffered| buffered.point.build().unwrap().get())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kept,
+            vec![
+                "requests,host=a latency=12i,sample_rate=3 0",
+                "requests,host=b latency=12i,sample_rate=3 0",
+                "requests,host=a latency=12i,sample_rate=3 3",
+                "requests,host=b latency=12i,sample_rate=3 3",
+                "requests,host=a latency=12i,sample_rate=3 6",
+                "requests,host=b latency=12i,sample_rate=3 6",
+            ]
+        );
+        assert!(sampler.drain(true).is_empty());
+    }
+
+    #[test]
+    fn test_reservoir() {
+        let mut sampler =
+            Sampler::new(Sampling::reservoir(2, Duration::from_secs(60)).rate_field("weight"));
+        let mut dropped = 0;
+        for second in 0..10 {
+            let (kept, dropped_point) = sampler.offer(point(second, "a"));
+            assert!(kept.is_none());
+            dropped += dropped_point.into_iter().count();
+        }
+        sampler.offer(point(0, "b"));
+        assert_eq!(dropped, 8);
+        assert!(sampler.drain(false).is_empty());
+
+        let lines = sampler
+            .drain(true)
+            .into_iter()
+            .map(|buffered| buffered.point.build().unwrap().get())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[..2]
+            .iter()
+            .all(|line| line.starts_with("requests,host=a latency=12i,weight=5 ")));
+        assert_eq!(lines[2], "requests,host=b latency=12i,weight=1 0");
+        assert!(sampler.drain(true).is_empty());
+    }
+}
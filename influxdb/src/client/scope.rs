@@ -0,0 +1,132 @@
+//! Clients scoped to a database, retention policy and tags, for layering large applications
+
+use std::ops::Deref;
+
+use crate::{Client, Type};
+
+/// A [`Client`](crate::Client) with its own database, retention policy and default tags, created
+/// by [`Client::scope`](crate::Client::scope)
+///
+/// Dereferences to the scoped client, so queries and writes issued through the scope use its
+/// defaults, while the client it was created from is left unchanged. Parts of an application can
+/// be handed a scope instead of configuring a client of their own, and scope it further.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::{Client, Type};
+///
+/// let client = Client::new("http://localhost:8086", "test");
+/// let scope = client
+///     .scope()
+///     .database("metrics")
+///     .rp("two_weeks")
+///     .tag("env", "prod");
+/// let billing = scope.scope().tag("service", "billing");
+///
+/// assert_eq!(billing.database_name(), "metrics");
+/// assert_eq!(billing.retention_policy(), Some("two_weeks"));
+/// assert_eq!(billing.default_tags().len(), 2);
+/// assert_eq!(client.database_name(), "test");
+/// assert!(client.default_tags().is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scope {
+    client: Client,
+}
+
+impl Scope {
+    /// Sets the database of the scope
+    pub fn database<S>(mut self, database: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.client = self.client.with_database(database);
+        self
+    }
+
+    /// Sets the retention policy of the scope
+    ///
+    /// Named after the `rp` parameter, so it doesn't shadow
+    /// [`Client::retention_policy`](crate::Client::retention_policy) on the scope.
+    pub fn rp<S>(mut self, retention_policy: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.client = self.client.with_retention_policy(retention_policy);
+        self
+    }
+
+    /// Adds a tag to every point written through the scope, tags of the point win
+    pub fn tag<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<Type>,
+    {
+        self.client = self.client.with_default_tags(vec![(key, value)]);
+        self
+    }
+
+    /// Returns the scoped client
+    pub fn into_client(self) -> Client {
+        self.client
+    }
+}
+
+impl Deref for Scope {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        &self.client
+    }
+}
+
+impl From<Scope> for Client {
+    fn from(scope: Scope) -> Self {
+        scope.client
+    }
+}
+
+impl Client {
+    /// Returns a [`Scope`](crate::Scope) starting with the database, retention policy and
+    /// default tags of this client
+    pub fn scope(&self) -> Scope {
+        Scope {
+            client: self.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Client, InfluxDbWriteable, Timestamp};
+
+    #[test]
+    fn test_scope() {
+        let client = Client::new("http://localhost:8086", "test")
+            .with_default_tags(vec![("env", "prod"), ("region", "eu")]);
+        let scope = client
+            .scope()
+            .database("metrics")
+            .rp("two_weeks")
+            .tag("region", "us");
+        let point = Timestamp::Seconds(1)
+            .into_query("weather")
+            .add_field("temperature", 82);
+
+        let request = scope.dry_run(&point).unwrap();
+        assert!(request.url.contains("db=metrics"), "{}", request.url);
+        assert!(request.url.contains("rp=two_weeks"), "{}", request.url);
+        assert_eq!(
+            request.body.as_deref(),
+            Some("weather,env=prod,region=us temperature=82i 1")
+        );
+        let request = client.dry_run(&point).unwrap();
+        assert!(request.url.contains("db=test"), "{}", request.url);
+        assert_eq!(
+            request.body.as_deref(),
+            Some("weather,env=prod,region=eu temperature=82i 1")
+        );
+        assert_eq!(Client::from(scope).database_name(), "metrics");
+    }
+}
@@ -0,0 +1,181 @@
+//! Version of the InfluxDB server, as reported with every response
+
+use std::fmt;
+use std::str::FromStr;
+use surf::Request;
+
+use crate::{Client, Error, Type, WriteQuery};
+
+/// Version of the InfluxDB server the [`Client`](crate::Client) is talking to
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::ServerVersion;
+///
+/// let version: ServerVersion = "1.8.10".parse().unwrap();
+///
+/// assert!(version.at_least(1, 4));
+/// assert!(version.supports_token_auth());
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Copy, Clone)]
+pub struct ServerVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl ServerVersion {
+    /// Returns whether the server is at least of version `major.minor`
+    pub fn at_least(&self, major: u32, minor: u32) -> bool {
+        (self.major, self.minor) >= (major, minor)
+    }
+
+    /// Returns whether the server accepts unsigned integer fields, introduced in InfluxDB 1.4
+    pub fn supports_unsigned_integers(&self) -> bool {
+        self.at_least(1, 4)
+    }
+
+    /// Returns whether the server accepts `Authorization: Token` headers, introduced in InfluxDB 1.8
+    pub fn supports_token_auth(&self) -> bool {
+        self.at_least(1, 8)
+    }
+}
+
+impl FromStr for ServerVersion {
+    type Err = Error;
+
+    /// Parses versions like `1.8.10`, `v2.0.4` or `1.7.0~rc0`
+    fn from_str(version: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::ProtocolError {
+            error: format!("invalid server version: {}", version),
+        };
+        let mut parts = version
+            .trim()
+            .trim_start_matches('v')
+            .split(|c: char| !c.is_ascii_digit())
+            .map(|part| part.parse::<u32>().map_err(|_| invalid()));
+
+        Ok(ServerVersion {
+            major: parts.next().ok_or_else(invalid)??,
+            minor: parts.next().unwrap_or(Ok(0))?,
+            patch: parts.next().unwrap_or(Ok(0))?,
+        })
+    }
+}
+
+impl fmt::Display for ServerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl Client {
+    /// Fails writes of unsigned integer fields if the server is known not to support them, instead
+    /// of having them rejected as invalid lines
+    pub(crate) fn check_unsigned_integers(&self, points: &[WriteQuery]) -> Result<(), Error> {
+        let version = match self.server_version() {
+            Some(version) if !version.supports_unsigned_integers() => version,
+            _ => return Ok(()),
+        };
+        let unsigned = points
+            .iter()
+            .flat_map(|point| point.fields())
+            .find(|(_, value)| matches!(value, Type::UnsignedInteger(_)));
+        match unsigned {
+            Some((field, _)) => Err(Error::InvalidQueryError {
+                error: format!(
+                    "field {:?} is an unsigned integer, which InfluxDB {} doesn't support before 1.4",
+                    field, version
+                ),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Fails requests authenticated with a token if the server is known not to support tokens,
+    /// instead of having them rejected as unauthorized
+    pub(crate) fn check_token_auth(&self, request: &Request) -> Result<(), Error> {
+        let token = request
+            .header("Authorization")
+            .is_some_and(|value| value.as_str().starts_with("Token "));
+        match self.server_version() {
+            Some(version) if token && !version.supports_token_auth() => {
+                Err(Error::CredentialsError {
+                    error: format!(
+                        "InfluxDB {} doesn't support token authentication before 1.8, use a username and password",
+                        version
+                    ),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ServerVersion;
+    use crate::{Client, Error, InfluxDbWriteable, ReadQuery, Timestamp};
+
+    #[test]
+    fn test_parse() {
+        let version = |s: &str| s.parse::<ServerVersion>().ok();
+        let expected = |major, minor, patch| {
+            Some(ServerVersion {
+                major,
+                minor,
+                patch,
+            })
+        };
+
+        assert_eq!(version("1.8.10"), expected(1, 8, 10));
+        assert_eq!(version("v2.0.4"), expected(2, 0, 4));
+        assert_eq!(version("1.7.0~rc0"), expected(1, 7, 0));
+        assert_eq!(version("1.3"), expected(1, 3, 0));
+        assert_eq!(version("unknown"), None);
+        assert_eq!(version(""), None);
+    }
+
+    #[test]
+    fn test_features() {
+        let version = |s: &str| s.parse::<ServerVersion>().unwrap();
+
+        assert!(!version("1.3.9").supports_unsigned_integers());
+        assert!(version("1.4.0").supports_unsigned_integers());
+        assert!(!version("1.7.10").supports_token_auth());
+        assert!(version("1.8.0").supports_token_auth());
+        assert!(version("2.0.0").at_least(1, 8));
+        assert_eq!(version("1.8.10").to_string(), "1.8.10");
+    }
+
+    #[test]
+    fn test_version_checks() {
+        let client = Client::new("http://localhost:8086", "test")
+            .with_header("Authorization", "Token secret");
+        let points = vec![Timestamp::Seconds(1)
+            .into_query("requests")
+            .add_field("count", 7u64)];
+        let request = client
+            .with_headers(client.request(&ReadQuery::new("SHOW DATABASES")).unwrap())
+            .build();
+
+        // nothing is checked as long as the version is unknown
+        assert!(client.request(&points).is_ok());
+        assert!(client.check_token_auth(&request).is_ok());
+
+        *client.server_version.lock().unwrap() = Some("1.3.9".parse().unwrap());
+        assert!(matches!(
+            client.request(&points),
+            Err(Error::InvalidQueryError { .. })
+        ));
+        assert!(matches!(
+            client.check_token_auth(&request),
+            Err(Error::CredentialsError { .. })
+        ));
+
+        *client.server_version.lock().unwrap() = Some("1.8.10".parse().unwrap());
+        assert!(client.request(&points).is_ok());
+        assert!(client.check_token_auth(&request).is_ok());
+    }
+}
@@ -0,0 +1,193 @@
+//! Shards and shard groups of the server
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use super::stats::parse_rows;
+use crate::{Client, Error, ReadQuery};
+
+/// A shard, as returned by `SHOW SHARDS`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shard {
+    pub id: u64,
+    pub database: String,
+    pub retention_policy: String,
+    /// ID of the [`ShardGroup`](crate::ShardGroup) the shard belongs to
+    pub shard_group: u64,
+    /// Start of the time range of the points in the shard, inclusive
+    pub start_time: DateTime<Utc>,
+    /// End of the time range of the points in the shard, exclusive
+    pub end_time: DateTime<Utc>,
+    /// When the shard is deleted by the retention policy enforcement
+    pub expiry_time: DateTime<Utc>,
+    /// IDs of the data nodes storing the shard, empty for open source InfluxDB
+    pub owners: Vec<u64>,
+}
+
+impl Shard {
+    /// Returns whether the shard is past its expiry time at `now`
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expiry_time <= now
+    }
+}
+
+/// A shard group, as returned by `SHOW SHARD GROUPS`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardGroup {
+    pub id: u64,
+    pub database: String,
+    pub retention_policy: String,
+    /// Start of the time range of the points in the shard group, inclusive
+    pub start_time: DateTime<Utc>,
+    /// End of the time range of the points in the shard group, exclusive
+    pub end_time: DateTime<Utc>,
+    /// When the shard group is deleted by the retention policy enforcement
+    pub expiry_time: DateTime<Utc>,
+}
+
+impl ShardGroup {
+    /// Returns whether the shard group is past its expiry time at `now`
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expiry_time <= now
+    }
+}
+
+impl Client {
+    /// Returns all shards of all databases, by running `SHOW SHARDS`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use chrono::Utc;
+    /// use influxdb::Client;
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("http://localhost:8086", "test");
+    /// for shard in client.shards().await? {
+    ///     if shard.is_expired(Utc::now()) {
+    ///         println!("shard {} of {} awaits deletion", shard.id, shard.database);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn shards(&self) -> Result<Vec<Shard>, Error> {
+        let body = self.query(&ReadQuery::new("SHOW SHARDS")).await?;
+        parse_rows(&body)?.iter().map(shard).collect()
+    }
+
+    /// Returns all shard groups of all databases, by running `SHOW SHARD GROUPS`
+    pub async fn shard_groups(&self) -> Result<Vec<ShardGroup>, Error> {
+        let body = self.query(&ReadQuery::new("SHOW SHARD GROUPS")).await?;
+        parse_rows(&body)?.iter().map(shard_group).collect()
+    }
+}
+
+fn shard(row: &BTreeMap<String, Value>) -> Result<Shard, Error> {
+    Ok(Shard {
+        id: number(row, "id")?,
+        database: text(row, "database")?,
+        retention_policy: text(row, "retention_policy")?,
+        shard_group: number(row, "shard_group")?,
+        start_time: time(row, "start_time")?,
+        end_time: time(row, "end_time")?,
+        expiry_time: time(row, "expiry_time")?,
+        owners: row
+            .get("owners")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|owner| owner.trim().parse().ok())
+            .collect(),
+    })
+}
+
+fn shard_group(row: &BTreeMap<String, Value>) -> Result<ShardGroup, Error> {
+    Ok(ShardGroup {
+        id: number(row, "id")?,
+        database: text(row, "database")?,
+        retention_policy: text(row, "retention_policy")?,
+        start_time: time(row, "start_time")?,
+        end_time: time(row, "end_time")?,
+        expiry_time: time(row, "expiry_time")?,
+    })
+}
+
+fn missing(column: &str) -> Error {
+    Error::DeserializationError {
+        error: format!("missing or invalid column {}", column),
+        source: None,
+    }
+}
+
+fn number(row: &BTreeMap<String, Value>, column: &str) -> Result<u64, Error> {
+    row.get(column)
+        .and_then(Value::as_u64)
+        .ok_or_else(|| missing(column))
+}
+
+fn text(row: &BTreeMap<String, Value>, column: &str) -> Result<String, Error> {
+    row.get(column)
+        .and_then(Value::as_str)
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| missing(column))
+}
+
+fn time(row: &BTreeMap<String, Value>, column: &str) -> Result<DateTime<Utc>, Error> {
+    row.get(column)
+        .and_then(Value::as_str)
+        .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| missing(column))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{shard, shard_group};
+    use crate::client::stats::parse_rows;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_shards() {
+        let body = r#"{"results":[{"statement_id":0,"series":[
+            {"name":"_internal","columns":["id","database","retention_policy","shard_group","start_time","end_time","expiry_time","owners"],"values":[
+                [1,"_internal","monitor",1,"2021-03-08T00:00:00Z","2021-03-09T00:00:00Z","2021-03-16T00:00:00Z",""]
+            ]},
+            {"name":"telegraf","columns":["id","database","retention_policy","shard_group","start_time","end_time","expiry_time","owners"],"values":[
+                [2,"telegraf","autogen",2,"2021-03-01T00:00:00Z","2021-03-08T00:00:00Z","2021-03-08T00:00:00Z","4,5"]
+            ]}
+        ]}]}"#;
+        let shards = parse_rows(body)
+            .unwrap()
+            .iter()
+            .map(shard)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(shards.len(), 2);
+        assert_eq!(shards[0].retention_policy, "monitor");
+        assert_eq!(shards[0].owners, Vec::<u64>::new());
+        assert_eq!(shards[1].owners, vec![4, 5]);
+        let now = Utc.with_ymd_and_hms(2021, 3, 10, 0, 0, 0).unwrap();
+        assert!(!shards[0].is_expired(now));
+        assert!(shards[1].is_expired(now));
+    }
+
+    #[test]
+    fn test_shard_groups() {
+        let body = r#"{"results":[{"statement_id":0,"series":[{"name":"shard groups","columns":["id","database","retention_policy","start_time","end_time","expiry_time"],"values":[
+            [1,"_internal","monitor","2021-03-08T00:00:00Z","2021-03-09T00:00:00Z","2021-03-16T00:00:00Z"]
+        ]}]}]}"#;
+        let groups = parse_rows(body).unwrap();
+        let group = shard_group(&groups[0]).unwrap();
+
+        assert_eq!(group.id, 1);
+        assert_eq!(
+            group.end_time,
+            Utc.with_ymd_and_hms(2021, 3, 9, 0, 0, 0).unwrap()
+        );
+        assert!(shard_group(&Default::default()).is_err());
+    }
+}
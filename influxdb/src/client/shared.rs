@@ -0,0 +1,103 @@
+//! Client shared by the handlers of an application, whose configuration can be replaced at runtime
+
+use std::sync::{Arc, RwLock};
+
+use crate::Client;
+
+/// Handle to a [`Client`](crate::Client) shared by several tasks, e.g. in the state of a web
+/// application, whose configuration can be replaced while they are running
+///
+/// Handles are cheap to clone. Replacing the client, e.g. after credentials were rotated, affects
+/// all handles. Queries which are already running keep using the client they started with.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use influxdb::{Client, ReadQuery, SharedClient};
+///
+/// # #[async_std::main]
+/// # async fn main() -> Result<(), influxdb::Error> {
+/// let shared = SharedClient::new(Client::new("http://localhost:8086", "test").with_auth("app", "old"));
+/// let handler = shared.clone();
+///
+/// shared.update(|client| client.with_auth("app", "rotated"));
+/// handler.client().query(&ReadQuery::new("SHOW MEASUREMENTS")).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct SharedClient {
+    client: Arc<RwLock<Client>>,
+}
+
+impl SharedClient {
+    /// Creates a handle sharing the client
+    pub fn new(client: Client) -> Self {
+        SharedClient {
+            client: Arc::new(RwLock::new(client)),
+        }
+    }
+
+    /// Returns the current client
+    ///
+    /// Cloning a [`Client`](crate::Client) only clones references to its configuration, so
+    /// this is cheap enough to be called for every query.
+    pub fn client(&self) -> Client {
+        self.client
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Replaces the client of all handles
+    pub fn replace(&self, client: Client) {
+        *self
+            .client
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = client;
+    }
+
+    /// Replaces the client of all handles with a reconfigured one, e.g. with new credentials or
+    /// [another URL](crate::Client::with_url)
+    pub fn update<F>(&self, f: F)
+    where
+        F: FnOnce(Client) -> Client,
+    {
+        let mut client = self
+            .client
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *client = f(client.clone());
+    }
+}
+
+impl From<Client> for SharedClient {
+    fn from(client: Client) -> Self {
+        SharedClient::new(client)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SharedClient;
+    use crate::Client;
+
+    #[test]
+    fn test_update() {
+        let shared = SharedClient::new(Client::new("http://localhost:8086", "test"));
+        let handle = shared.clone();
+        let client = handle.client();
+
+        shared.update(|client| {
+            client
+                .with_auth("admin", "rotated")
+                .with_url("http://replica:8086")
+        });
+        assert_eq!(handle.client().database_url(), "http://replica:8086");
+        assert_eq!(handle.client().parameters.get("p").unwrap(), "rotated");
+        assert_eq!(client.database_url(), "http://localhost:8086");
+
+        shared.replace(Client::new("http://localhost:8086", "other"));
+        assert_eq!(handle.client().database_name(), "other");
+    }
+}
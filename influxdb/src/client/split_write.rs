@@ -0,0 +1,217 @@
+//! Writes of batches which are split when the server rejects them as too large
+
+use std::ops::Range;
+use surf::StatusCode;
+
+use crate::client::{check_body, request_id};
+use crate::error::RejectedLine;
+use crate::{Client, Error, WriteQuery};
+
+/// How often a rejected batch is halved at most, i.e. into up to 256 batches
+pub(crate) const DEFAULT_MAX_SPLIT_DEPTH: u32 = 8;
+
+impl Client {
+    /// Sets how often a batch which the server rejected as too large (`HTTP 413 Payload Too
+    /// Large`) is halved, before giving up on its points
+    ///
+    /// Defaults to 8, i.e. a batch is split into at most 256 batches. Applies to the writes of
+    /// [`query`](crate::Client::query) and to [`write_split`](crate::Client::write_split), a
+    /// depth of 0 disables splitting.
+    pub fn with_max_split_depth(mut self, depth: u32) -> Self {
+        self.max_split_depth = depth;
+        self
+    }
+
+    /// Writes a batch of points, splitting it in halves and retrying them recursively whenever
+    /// the server rejects a batch as too large (`HTTP 413 Payload Too Large`)
+    ///
+    /// [`query`](crate::Client::query) splits batches the same way, but fails with a
+    /// [`PartialWriteError`](crate::Error::PartialWriteError) if points are still rejected. This
+    /// returns them instead, e.g. to write them to a dead letter queue.
+    ///
+    /// Returns the points which were still rejected at the [maximum split depth](crate::Client::with_max_split_depth),
+    /// or on their own. Any other error aborts the write; batches written before that are not rolled back.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::{Client, Timestamp};
+    /// use influxdb::InfluxDbWriteable;
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("http://localhost:8086", "test");
+    /// let points = (0..1_000_000)
+    ///     .map(|i| Timestamp::Seconds(i).into_query("weather").add_field("temperature", 20))
+    ///     .collect::<Vec<_>>();
+    ///
+    /// let rejected = client.write_split(&points).await?;
+    /// assert!(rejected.is_empty(), "{} points were too large", rejected.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "points which were rejected as too large are returned"]
+    pub async fn write_split(&self, points: &[WriteQuery]) -> Result<Vec<WriteQuery>, Error> {
+        let pending = vec![(0..points.len(), 0)];
+        let rejected = self
+            .write_ranges(points, pending, &self.next_request_id())
+            .await?;
+        Ok(rejected
+            .into_iter()
+            .map(|index| points[index].clone())
+            .collect())
+    }
+
+    /// Writes the halves of a batch which the server rejected as too large, failing with a
+    /// [`PartialWriteError`](crate::Error::PartialWriteError) which lists the points that were
+    /// still rejected
+    pub(crate) async fn write_halves(
+        &self,
+        points: &[WriteQuery],
+        request_id: &str,
+    ) -> Result<String, Error> {
+        let mut pending = Vec::new();
+        push_halves(&mut pending, 0..points.len(), 1);
+        let rejected = self.write_ranges(points, pending, request_id).await?;
+        if rejected.is_empty() {
+            return Ok(String::new());
+        }
+        Err(Error::PartialWriteError {
+            error: format!(
+                "{} of {} points were rejected as too large",
+                rejected.len(),
+                points.len()
+            ),
+            rejected: rejected
+                .into_iter()
+                .map(|index| {
+                    let line = self.build_query(&points[index])?.0.get();
+                    Ok(RejectedLine {
+                        index,
+                        line,
+                        reason: "too large".to_string(),
+                    })
+                })
+                .collect::<Result<_, Error>>()?,
+        })
+    }
+
+    /// Writes the ranges of points, halving the ones which are rejected as too large, and returns
+    /// the indexes of the points which were still rejected
+    async fn write_ranges(
+        &self,
+        points: &[WriteQuery],
+        mut pending: Vec<(Range<usize>, u32)>,
+        request_id: &str,
+    ) -> Result<Vec<usize>, Error> {
+        let mut rejected = Vec::new();
+        while let Some((range, depth)) = pending.pop() {
+            let batch = points[range.clone()].to_vec();
+            let request_builder = self
+                .request(&batch)?
+                .header(request_id::REQUEST_ID, request_id);
+            let (status, body) = self.send_with_status(request_builder).await?;
+            if status != StatusCode::PayloadTooLarge {
+                check_body(body)?;
+            } else if range.len() > 1 && depth < self.max_split_depth {
+                push_halves(&mut pending, range, depth + 1);
+            } else {
+                rejected.extend(range);
+            }
+        }
+        Ok(rejected)
+    }
+}
+
+/// Splits the range of points in halves, pushed in reverse, so they are written in order
+fn push_halves(pending: &mut Vec<(Range<usize>, u32)>, range: Range<usize>, depth: u32) {
+    let middle = range.start + range.len() / 2;
+    pending.push((middle..range.end, depth));
+    pending.push((range.start..middle, depth));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::test_server::{response, serve};
+    use crate::{Client, Error, InfluxDbWriteable, Timestamp, WriteQuery};
+    use futures::executor::block_on;
+    use std::sync::mpsc::{self, Receiver};
+
+    /// Serves writes, rejecting bodies of more than `limit` bytes as too large, and returns the
+    /// URL and the bodies of the accepted writes
+    fn serve_writes(limit: usize) -> (String, Receiver<String>) {
+        let (sender, accepted) = mpsc::channel();
+        let (address, _) = serve(move |request| {
+            if request.body.len() > limit {
+                response(
+                    "413 Payload Too Large",
+                    "",
+                    r#"{"error":"Request Entity Too Large"}"#,
+                )
+            } else {
+                let _ = sender.send(request.body.clone());
+                response("204 No Content", "", "")
+            }
+        });
+        (format!("http://{}", address), accepted)
+    }
+
+    fn points(values: &[usize]) -> Vec<WriteQuery> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(second, length)| {
+                Timestamp::Seconds(second as u128)
+                    .into_query("logs")
+                    .add_field("message", "x".repeat(*length))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_batch_over_limit() {
+        let (url, accepted) = serve_writes(100);
+        let client = Client::new(url, "test");
+        let points = points(&[10; 8]);
+
+        assert_eq!(block_on(client.query(&points)).unwrap(), "");
+        let lines = accepted.try_iter().collect::<Vec<_>>().join("\n");
+        assert_eq!(lines.lines().count(), 8);
+        assert!(
+            lines.starts_with("logs message=\"xxxxxxxxxx\" 0\n"),
+            "{}",
+            lines
+        );
+        assert!(lines.ends_with(" 7"), "{}", lines);
+
+        let rejected = block_on(client.write_split(&points)).unwrap();
+        assert!(rejected.is_empty());
+        assert_eq!(accepted.try_iter().count(), 4);
+    }
+
+    #[test]
+    fn test_line_over_limit() {
+        let (url, accepted) = serve_writes(100);
+        let client = Client::new(url, "test");
+        let points = points(&[10, 200, 10]);
+
+        match block_on(client.query(&points)) {
+            Err(Error::PartialWriteError { rejected, .. }) => {
+                assert_eq!(rejected.len(), 1);
+                assert_eq!(rejected[0].index, 1);
+                assert!(rejected[0].line.starts_with("logs message=\"xxx"));
+            }
+            result => panic!("line over the limit was not rejected: {:?}", result),
+        }
+        assert_eq!(accepted.try_iter().count(), 2);
+
+        let rejected = block_on(client.write_split(&points)).unwrap();
+        assert_eq!(rejected, vec![points[1].clone()]);
+
+        // single points and writes without splitting fail as they did before
+        let error = block_on(client.query(&points[1])).unwrap_err();
+        assert_eq!(error.status(), Some(413));
+        let error = block_on(client.clone().with_max_split_depth(0).query(&points)).unwrap_err();
+        assert_eq!(error.status(), Some(413));
+    }
+}
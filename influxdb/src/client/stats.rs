@@ -0,0 +1,358 @@
+//! Runtime statistics and diagnostics of the server
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
+
+use crate::query::explain::parse_go_duration;
+use crate::{Client, Error, ReadQuery};
+
+/// Statistics of one module of the server, e.g. `httpd`, as returned by `SHOW STATS`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsModule {
+    /// Name of the module, e.g. `runtime` or `database`
+    pub name: String,
+    /// Tags identifying the instance of the module, e.g. `database` for `database` modules
+    pub tags: BTreeMap<String, String>,
+    /// The counters and gauges of the module, by name
+    pub values: BTreeMap<String, Value>,
+}
+
+impl StatsModule {
+    /// Returns a numeric value of the module
+    pub fn get(&self, key: &str) -> Option<f64> {
+        self.values.get(key)?.as_f64()
+    }
+}
+
+/// Runtime statistics of the server, as returned by [`Client::stats`](crate::Client::stats)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerStats {
+    /// When the statistics were collected, according to the local clock
+    pub collected_at: SystemTime,
+    /// Statistics of all modules, in the order the server returned them
+    pub modules: Vec<StatsModule>,
+}
+
+impl ServerStats {
+    /// Returns all instances of a module, e.g. one `database` module per database
+    pub fn modules_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a StatsModule> {
+        self.modules
+            .iter()
+            .filter(move |module| module.name == name)
+    }
+
+    /// Returns the sum of a value over all instances of a module
+    fn sum(&self, module: &str, key: &str) -> Option<f64> {
+        self.modules_named(module)
+            .map(|module| module.get(key))
+            .fold(None, |sum, value| match (sum, value) {
+                (sum, None) => sum,
+                (sum, Some(value)) => Some(sum.unwrap_or(0.0) + value),
+            })
+    }
+
+    /// Returns the number of series in all databases
+    pub fn series_count(&self) -> Option<u64> {
+        self.sum("database", "numSeries").map(|count| count as u64)
+    }
+
+    /// Returns the number of measurements in all databases
+    pub fn measurement_count(&self) -> Option<u64> {
+        self.sum("database", "numMeasurements")
+            .map(|count| count as u64)
+    }
+
+    /// Returns the number of points written through the HTTP API since the server started
+    pub fn points_written(&self) -> Option<u64> {
+        self.sum("httpd", "pointsWrittenOK")
+            .map(|count| count as u64)
+    }
+
+    /// Returns the number of queries received through the HTTP API since the server started
+    pub fn queries(&self) -> Option<u64> {
+        self.sum("httpd", "queryReq").map(|count| count as u64)
+    }
+
+    /// Returns the memory allocated by the server, in bytes
+    pub fn heap_allocated(&self) -> Option<u64> {
+        self.sum("runtime", "HeapAlloc").map(|bytes| bytes as u64)
+    }
+
+    /// Returns the points written per second between `earlier` statistics and these
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::Client;
+    /// use std::time::Duration;
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("http://localhost:8086", "test");
+    /// let earlier = client.stats().await?;
+    /// async_std::task::sleep(Duration::from_secs(10)).await;
+    /// let stats = client.stats().await?;
+    ///
+    /// println!("{:?} points/s", stats.write_throughput(&earlier));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_throughput(&self, earlier: &ServerStats) -> Option<f64> {
+        let elapsed = self
+            .collected_at
+            .duration_since(earlier.collected_at)
+            .ok()?;
+        let written = self
+            .points_written()?
+            .checked_sub(earlier.points_written()?)?;
+        if elapsed == Duration::from_secs(0) {
+            return None;
+        }
+        Some(written as f64 / elapsed.as_secs_f64())
+    }
+}
+
+/// A section of the diagnostics of the server, e.g. `build`, as returned by `SHOW DIAGNOSTICS`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiagnosticsSection {
+    /// Name of the section, e.g. `build` or `system`
+    pub name: String,
+    /// The values of the section, by name
+    pub values: BTreeMap<String, Value>,
+}
+
+/// Diagnostics of the server, as returned by [`Client::diagnostics`](crate::Client::diagnostics)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerDiagnostics {
+    /// All sections, in the order the server returned them
+    pub sections: Vec<DiagnosticsSection>,
+}
+
+impl ServerDiagnostics {
+    /// Returns a value of a section
+    pub fn get(&self, section: &str, key: &str) -> Option<&Value> {
+        self.sections
+            .iter()
+            .find(|candidate| candidate.name == section)?
+            .values
+            .get(key)
+    }
+
+    fn get_str(&self, section: &str, key: &str) -> Option<&str> {
+        self.get(section, key)?.as_str()
+    }
+
+    /// Returns the version of the server, e.g. `1.8.10`
+    pub fn version(&self) -> Option<&str> {
+        self.get_str("build", "Version")
+    }
+
+    /// Returns the commit the server was built from
+    pub fn commit(&self) -> Option<&str> {
+        self.get_str("build", "Commit")
+    }
+
+    /// Returns the branch the server was built from
+    pub fn branch(&self) -> Option<&str> {
+        self.get_str("build", "Branch")
+    }
+
+    /// Returns the version of Go the server was built with
+    pub fn go_version(&self) -> Option<&str> {
+        self.get_str("runtime", "version")
+    }
+
+    /// Returns the host name of the server
+    pub fn hostname(&self) -> Option<&str> {
+        self.get_str("network", "hostname")
+    }
+
+    /// Returns the process ID of the server
+    pub fn pid(&self) -> Option<u64> {
+        self.get("system", "PID")?.as_u64()
+    }
+
+    /// Returns how long the server has been running
+    pub fn uptime(&self) -> Option<Duration> {
+        parse_go_duration(self.get_str("system", "uptime")?)
+    }
+}
+
+impl Client {
+    /// Returns the runtime statistics of the server, by running `SHOW STATS`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::Client;
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("http://localhost:8086", "test");
+    /// let stats = client.stats().await?;
+    /// println!("{:?} series", stats.series_count());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn stats(&self) -> Result<ServerStats, Error> {
+        let collected_at = SystemTime::now();
+        let modules = self
+            .show_series("SHOW STATS")
+            .await?
+            .into_iter()
+            .map(|(name, tags, values)| StatsModule { name, tags, values })
+            .collect();
+        Ok(ServerStats {
+            collected_at,
+            modules,
+        })
+    }
+
+    /// Returns the diagnostics of the server, like its build and uptime, by running
+    /// `SHOW DIAGNOSTICS`
+    pub async fn diagnostics(&self) -> Result<ServerDiagnostics, Error> {
+        let sections = self
+            .show_series("SHOW DIAGNOSTICS")
+            .await?
+            .into_iter()
+            .map(|(name, _, values)| DiagnosticsSection { name, values })
+            .collect();
+        Ok(ServerDiagnostics { sections })
+    }
+
+    /// Runs a statement returning single row series and returns their names, tags and values
+    #[allow(clippy::type_complexity)]
+    async fn show_series(
+        &self,
+        statement: &str,
+    ) -> Result<Vec<(String, BTreeMap<String, String>, BTreeMap<String, Value>)>, Error> {
+        let body = self.query(&ReadQuery::new(statement)).await?;
+        parse_series(&body)
+    }
+}
+
+/// Returns the names, tags and first row of the series of a response
+#[allow(clippy::type_complexity)]
+pub(super) fn parse_series(
+    body: &str,
+) -> Result<Vec<(String, BTreeMap<String, String>, BTreeMap<String, Value>)>, Error> {
+    Ok(result_series(body)?
+        .iter()
+        .map(|series| {
+            let name = series["name"].as_str().unwrap_or_default().to_string();
+            let tags = series["tags"]
+                .as_object()
+                .map(|tags| {
+                    tags.iter()
+                        .map(|(key, value)| {
+                            (key.clone(), value.as_str().unwrap_or_default().to_string())
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            let values = rows(series).into_iter().next().unwrap_or_default();
+            (name, tags, values)
+        })
+        .collect())
+}
+
+/// Returns all rows of all series of a response, by column
+pub(super) fn parse_rows(body: &str) -> Result<Vec<BTreeMap<String, Value>>, Error> {
+    Ok(result_series(body)?.iter().flat_map(rows).collect())
+}
+
+/// Returns the series of the first result of a response, or the error it reports
+fn result_series(body: &str) -> Result<Vec<Value>, Error> {
+    let response: Value =
+        serde_json::from_str(body).map_err(|err| Error::DeserializationError {
+            error: err.to_string(),
+            source: crate::error::source(err),
+        })?;
+    let result = &response["results"][0];
+    if let Some(error) = result["error"].as_str() {
+        return Err(Error::DatabaseError {
+            error: error.to_string(),
+            status: None,
+            request_id: None,
+        });
+    }
+    Ok(result["series"].as_array().cloned().unwrap_or_default())
+}
+
+fn rows(series: &Value) -> Vec<BTreeMap<String, Value>> {
+    let columns = series["columns"].as_array().cloned().unwrap_or_default();
+    let rows = series["values"].as_array().cloned().unwrap_or_default();
+    rows.into_iter()
+        .map(|row| {
+            let row = row.as_array().cloned().unwrap_or_default();
+            columns
+                .iter()
+                .zip(row)
+                .filter_map(|(column, value)| Some((column.as_str()?.to_string(), value)))
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_series, DiagnosticsSection, ServerDiagnostics, ServerStats, StatsModule};
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_stats() {
+        let body = r#"{"results":[{"statement_id":0,"series":[
+            {"name":"httpd","tags":{"bind":":8086"},"columns":["pointsWrittenOK","queryReq"],"values":[[1200,35]]},
+            {"name":"database","tags":{"database":"_internal"},"columns":["numMeasurements","numSeries"],"values":[[12,230]]},
+            {"name":"database","tags":{"database":"telegraf"},"columns":["numMeasurements","numSeries"],"values":[[8,70]]}
+        ]}]}"#;
+        let modules = parse_series(body)
+            .unwrap()
+            .into_iter()
+            .map(|(name, tags, values)| StatsModule { name, tags, values })
+            .collect::<Vec<_>>();
+        let earlier = ServerStats {
+            collected_at: SystemTime::UNIX_EPOCH,
+            modules: Vec::new(),
+        };
+        let mut stats = ServerStats {
+            collected_at: SystemTime::UNIX_EPOCH + Duration::from_secs(10),
+            modules,
+        };
+
+        assert_eq!(stats.series_count(), Some(300));
+        assert_eq!(stats.measurement_count(), Some(20));
+        assert_eq!(stats.queries(), Some(35));
+        assert_eq!(stats.modules_named("database").count(), 2);
+        assert_eq!(stats.write_throughput(&earlier), None);
+
+        let earlier = stats.clone();
+        stats.collected_at += Duration::from_secs(10);
+        stats.modules[0]
+            .values
+            .insert("pointsWrittenOK".to_string(), 2200.into());
+        assert_eq!(stats.write_throughput(&earlier), Some(100.0));
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let body = r#"{"results":[{"statement_id":0,"series":[
+            {"name":"build","columns":["Branch","Build Time","Commit","Version"],"values":[["1.8","","688e697c51","1.8.10"]]},
+            {"name":"system","columns":["PID","currentTime","started","uptime"],"values":[[1,"2021-03-08T12:30:00Z","2021-03-08T11:00:00Z","1h30m0.5s"]]}
+        ]}]}"#;
+        let diagnostics = ServerDiagnostics {
+            sections: parse_series(body)
+                .unwrap()
+                .into_iter()
+                .map(|(name, _, values)| DiagnosticsSection { name, values })
+                .collect(),
+        };
+
+        assert_eq!(diagnostics.version(), Some("1.8.10"));
+        assert_eq!(diagnostics.commit(), Some("688e697c51"));
+        assert_eq!(diagnostics.pid(), Some(1));
+        assert_eq!(diagnostics.uptime(), Some(Duration::from_millis(5_400_500)));
+        assert_eq!(diagnostics.hostname(), None);
+    }
+}
@@ -0,0 +1,247 @@
+//! Writes of batches too large to be built into a single `String`
+
+use futures::io::{AsyncRead, BufReader};
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use surf::Body;
+
+use crate::query::write_hook::WriteHooks;
+use crate::query::Precision;
+use crate::{Client, Error, Query, WriteQuery};
+
+/// Batches up to this size are sent in a single body, larger ones are streamed
+pub(crate) const DEFAULT_CHUNKED_THRESHOLD: usize = 1024 * 1024;
+
+impl Client {
+    /// Sets the size in bytes above which [`write_stream`](crate::Client::write_stream) streams
+    /// the batch with `Transfer-Encoding: chunked` instead of sending it in a single body
+    ///
+    /// Defaults to 1 MiB.
+    pub fn with_chunked_threshold(mut self, bytes: usize) -> Self {
+        self.chunked_threshold = bytes;
+        self
+    }
+
+    /// Writes a batch of points, which is streamed to the server if it exceeds the
+    /// [chunked threshold](crate::Client::with_chunked_threshold)
+    ///
+    /// Points are only built while the request is sent, so memory stays bounded even when
+    /// backfilling millions of points. All points have to be written to the same database and
+    /// retention policy. Their timestamps are converted to the [precision](crate::Client::with_precision)
+    /// of the client or, if it has none, to the precision of the first point.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::{Client, Timestamp};
+    /// use influxdb::InfluxDbWriteable;
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("http://localhost:8086", "test");
+    /// let points = (0..10_000_000).map(|i| {
+    ///     Timestamp::Seconds(i)
+    ///         .into_query("weather")
+    ///         .add_field("temperature", 20)
+    /// });
+    /// client.write_stream(points).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn write_stream<I>(&self, points: I) -> Result<String, Error>
+    where
+        I: IntoIterator<Item = WriteQuery>,
+        I::IntoIter: Send + Sync + Unpin + 'static,
+    {
+        let mut points = points.into_iter();
+        let first = points.next().ok_or_else(|| Error::InvalidQueryError {
+            error: "batch cannot be empty".to_string(),
+        })?;
+        let precision = self.precision.unwrap_or_else(|| match self.max_precision {
+            Some(max_precision) => first.timestamp().precision().max(max_precision),
+            None => first.timestamp().precision(),
+        });
+        let database = first.get_database().map(str::to_owned);
+        let retention_policy = first.get_retention_policy().map(str::to_owned);
+
+        let mut lines = LineProtocolReader::new(first, points, precision, self.write_hooks.clone());
+        let mut body = Vec::new();
+        while body.len() <= self.chunked_threshold {
+            match lines.next_line() {
+                Some(line) => body.extend(line?),
+                None => {
+                    let request_builder = self.write_request(
+                        body,
+                        precision.to_string(),
+                        database.as_deref(),
+                        retention_policy.as_deref(),
+                    )?;
+                    return self.send(request_builder).await;
+                }
+            }
+        }
+
+        lines.buffer = body;
+        let error = lines.error.clone();
+        // without a length, the body is sent with `Transfer-Encoding: chunked`
+        let request_builder = self.write_request(
+            Body::from_reader(BufReader::new(lines), None),
+            precision.to_string(),
+            database.as_deref(),
+            retention_policy.as_deref(),
+        )?;
+        let result = self.send(request_builder).await;
+
+        // an invalid point aborts the upload, report it instead of the failed connection
+        let invalid_point = error.lock().unwrap().take();
+        match invalid_point {
+            Some(error) => Err(error),
+            None => result,
+        }
+    }
+}
+
+/// Encodes points into line protocol while they are read
+struct LineProtocolReader<I> {
+    first: Option<WriteQuery>,
+    points: I,
+    precision: Precision,
+    database: Option<String>,
+    retention_policy: Option<String>,
+    buffer: Vec<u8>,
+    position: usize,
+    error: Arc<Mutex<Option<Error>>>,
+    hooks: WriteHooks,
+}
+
+impl<I> LineProtocolReader<I>
+where
+    I: Iterator<Item = WriteQuery>,
+{
+    fn new(first: WriteQuery, points: I, precision: Precision, hooks: WriteHooks) -> Self {
+        LineProtocolReader {
+            database: first.get_database().map(ToOwned::to_owned),
+            retention_policy: first.get_retention_policy().map(ToOwned::to_owned),
+            first: Some(first),
+            points,
+            precision,
+            buffer: Vec::new(),
+            position: 0,
+            error: Arc::new(Mutex::new(None)),
+            hooks,
+        }
+    }
+
+    /// Returns the next point as a line terminated by a newline
+    fn next_line(&mut self) -> Option<Result<Vec<u8>, Error>> {
+        let point = self.first.take().or_else(|| self.points.next())?;
+        if point.get_database() != self.database.as_deref()
+            || point.get_retention_policy() != self.retention_policy.as_deref()
+        {
+            return Some(Err(Error::InvalidQueryError {
+                error: "all queries of a batch must target the same database and retention policy"
+                    .to_string(),
+            }));
+        }
+        let point = match self.hooks.apply(point) {
+            Ok(point) => point,
+            Err(err) => return Some(Err(err)),
+        };
+        Some(point.build_with_precision(self.precision).map(|line| {
+            let mut line = line.get().into_bytes();
+            line.push(b'\n');
+            line
+        }))
+    }
+}
+
+impl<I> AsyncRead for LineProtocolReader<I>
+where
+    I: Iterator<Item = WriteQuery> + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        while this.position == this.buffer.len() {
+            match this.next_line() {
+                Some(Ok(line)) => {
+                    this.buffer = line;
+                    this.position = 0;
+                }
+                Some(Err(err)) => {
+                    let io_error = io::Error::new(io::ErrorKind::InvalidData, err.to_string());
+                    *this.error.lock().unwrap() = Some(err);
+                    return Poll::Ready(Err(io_error));
+                }
+                None => return Poll::Ready(Ok(0)),
+            }
+        }
+
+        let read = buf.len().min(this.buffer.len() - this.position);
+        buf[..read].copy_from_slice(&this.buffer[this.position..this.position + read]);
+        this.position += read;
+        Poll::Ready(Ok(read))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LineProtocolReader;
+    use crate::query::write_hook::WriteHooks;
+    use crate::query::Precision;
+    use crate::{Error, InfluxDbWriteable, Measurement, Timestamp, WriteQuery};
+    use futures::executor::block_on;
+    use futures::io::AsyncReadExt;
+
+    #[test]
+    fn test_reader() {
+        let first = Timestamp::Seconds(1)
+            .into_query("weather")
+            .add_field("temperature", 82);
+        let points = (2..4).map(|i| {
+            Timestamp::Milliseconds(i * 1000)
+                .into_query("weather")
+                .add_field("temperature", 65)
+        });
+
+        let mut reader =
+            LineProtocolReader::new(first, points, Precision::Seconds, WriteHooks::default());
+        reader.buffer = b"weather temperature=70i 0\n".to_vec();
+        let mut body = String::new();
+        block_on(reader.read_to_string(&mut body)).unwrap();
+
+        assert_eq!(
+            body,
+            "weather temperature=70i 0\nweather temperature=82i 1\nweather temperature=65i 2\nweather temperature=65i 3\n"
+        );
+    }
+
+    #[test]
+    fn test_reader_invalid_point() {
+        let first = Timestamp::Seconds(1)
+            .into_query("weather")
+            .add_field("temperature", 82);
+        let points = vec![WriteQuery::new(
+            Timestamp::Seconds(2),
+            Measurement::new("weather").retention_policy("two_weeks"),
+        )
+        .add_field("temperature", 65)]
+        .into_iter();
+
+        let mut reader =
+            LineProtocolReader::new(first, points, Precision::Seconds, WriteHooks::default());
+        let error = reader.error.clone();
+        let mut body = String::new();
+
+        assert!(block_on(reader.read_to_string(&mut body)).is_err());
+        assert!(matches!(
+            *error.lock().unwrap(),
+            Some(Error::InvalidQueryError { .. })
+        ));
+    }
+}
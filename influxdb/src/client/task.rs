@@ -0,0 +1,69 @@
+//! Named futures of background tasks
+
+use futures::future::BoxFuture;
+use futures::prelude::*;
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Future of a background task of the client, e.g. the one writing the points of a
+/// [`BufferedWriter`](crate::BufferedWriter)
+///
+/// The task has to be spawned on the runtime of the application, whose join handle tells when it
+/// completed. Its [`name`](crate::BackgroundTask::name) identifies it in tools like `tokio-console`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use influxdb::{Client, WriterOptions};
+///
+/// # #[async_std::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = Client::new("http://localhost:8086", "test");
+/// let (writer, task) = client.buffered_writer(WriterOptions::default());
+/// let handle = async_std::task::Builder::new()
+///     .name(task.name().to_string())
+///     .spawn(task)?;
+///
+/// writer.shutdown().await?;
+/// handle.await;
+/// # Ok(())
+/// # }
+/// ```
+pub struct BackgroundTask {
+    name: String,
+    future: BoxFuture<'static, ()>,
+}
+
+impl BackgroundTask {
+    pub(crate) fn new<F>(name: String, future: F) -> Self
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        BackgroundTask {
+            name,
+            future: future.boxed(),
+        }
+    }
+
+    /// Returns the name of the task
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Future for BackgroundTask {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        self.future.poll_unpin(cx)
+    }
+}
+
+impl fmt::Debug for BackgroundTask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BackgroundTask")
+            .field("name", &self.name)
+            .finish()
+    }
+}
@@ -0,0 +1,430 @@
+//! Routing of writes and queries of tenants to their databases and measurements
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::{ql, Client, Error, Type, WriteQuery};
+
+const TENANT_PLACEHOLDER: &str = "{tenant}";
+
+/// Maps tenant ids to the database, retention policy and measurement prefix their data is stored
+/// with, see [`Client::for_tenant`](crate::Client::for_tenant)
+///
+/// Tenants added with [`add_tenant`](TenantRouter::add_tenant) use their own route. All other
+/// tenants are routed with templates, in which `{tenant}` is replaced with the tenant id. Without
+/// a database template, unknown tenants are rejected.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::{TenantRoute, TenantRouter};
+///
+/// let router = TenantRouter::new()
+///     .database_template("metrics_{tenant}")
+///     .measurement_prefix_template("{tenant}_")
+///     .add_tenant("acme", TenantRoute::new().database("acme_dedicated"));
+///
+/// let route = router.route("globex")?;
+/// assert_eq!(route.get_database(), Some("metrics_globex"));
+/// assert_eq!(route.measurement("cpu"), "globex_cpu");
+///
+/// let route = router.route("acme")?;
+/// assert_eq!(route.get_database(), Some("acme_dedicated"));
+/// assert_eq!(route.measurement("cpu"), "cpu");
+/// # Ok::<(), influxdb::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TenantRouter {
+    database_template: Option<String>,
+    measurement_prefix_template: Option<String>,
+    retention_policy: Option<String>,
+    tenant_tag: Option<String>,
+    tenants: HashMap<String, TenantRoute>,
+}
+
+/// Where the data of a single tenant is stored
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TenantRoute {
+    database: Option<String>,
+    retention_policy: Option<String>,
+    measurement_prefix: Option<String>,
+}
+
+impl TenantRouter {
+    /// Creates a router without any tenants
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the database of tenants without their own route, e.g. `metrics_{tenant}`
+    pub fn database_template<S>(mut self, template: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.database_template = Some(template.into());
+        self
+    }
+
+    /// Sets the measurement prefix of tenants without their own route, e.g. `{tenant}_`
+    ///
+    /// The character after `{tenant}` separates the tenant id from the measurement, so tenant ids
+    /// which contain it are rejected. Otherwise, tenant `a` writing `b_cpu` and tenant `a_b`
+    /// writing `cpu` would both write `a_b_cpu`.
+    pub fn measurement_prefix_template<S>(mut self, template: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.measurement_prefix_template = Some(template.into());
+        self
+    }
+
+    /// Sets the retention policy of tenants without their own retention policy
+    pub fn retention_policy<S>(mut self, retention_policy: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.retention_policy = Some(retention_policy.into());
+        self
+    }
+
+    /// Adds a tag with the given key and the tenant id to every point written for a tenant
+    ///
+    /// Points which already have the tag with another value are rejected, so a tenant can't write
+    /// points attributed to another one.
+    pub fn tenant_tag<S>(mut self, key: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.tenant_tag = Some(key.into());
+        self
+    }
+
+    /// Routes a tenant with its own route instead of the templates
+    pub fn add_tenant<S>(mut self, tenant: S, route: TenantRoute) -> Self
+    where
+        S: Into<String>,
+    {
+        self.tenants.insert(tenant.into(), route);
+        self
+    }
+
+    /// Returns the route of a tenant
+    ///
+    /// Tenant ids have to consist of ASCII letters, digits, `-` and `_`, so they can't change the
+    /// meaning of the names they are substituted into. Tenants routed with the templates mustn't
+    /// contain the separator of the
+    /// [measurement prefix template](TenantRouter::measurement_prefix_template).
+    pub fn route(&self, tenant: &str) -> Result<TenantRoute, Error> {
+        if tenant.is_empty()
+            || !tenant
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(Error::InvalidQueryError {
+                error: format!("invalid tenant id {:?}", tenant),
+            });
+        }
+
+        let mut route = match self.tenants.get(tenant) {
+            Some(route) => route.clone(),
+            None => {
+                let database =
+                    self.database_template
+                        .as_ref()
+                        .ok_or_else(|| Error::InvalidQueryError {
+                            error: format!("unknown tenant {:?}", tenant),
+                        })?;
+                TenantRoute {
+                    database: Some(database.replace(TENANT_PLACEHOLDER, tenant)),
+                    retention_policy: None,
+                    measurement_prefix: match &self.measurement_prefix_template {
+                        Some(template) => Some(measurement_prefix(template, tenant)?),
+                        None => None,
+                    },
+                }
+            }
+        };
+        if route.retention_policy.is_none() {
+            route.retention_policy = self.retention_policy.clone();
+        }
+        Ok(route)
+    }
+}
+
+/// Substitutes the tenant id into the measurement prefix template, unless the id contains the
+/// character which follows `{tenant}` in the template
+fn measurement_prefix(template: &str, tenant: &str) -> Result<String, Error> {
+    let separator = template
+        .find(TENANT_PLACEHOLDER)
+        .and_then(|start| template[start + TENANT_PLACEHOLDER.len()..].chars().next());
+    if let Some(separator) = separator.filter(|separator| tenant.contains(*separator)) {
+        return Err(Error::InvalidQueryError {
+            error: format!(
+                "tenant id {:?} contains {:?}, which separates it from the measurement",
+                tenant, separator
+            ),
+        });
+    }
+    Ok(template.replace(TENANT_PLACEHOLDER, tenant))
+}
+
+impl TenantRoute {
+    /// Creates a route which keeps the database and measurements of the client
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the database of the tenant
+    pub fn database<S>(mut self, database: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.database = Some(database.into());
+        self
+    }
+
+    /// Sets the retention policy of the tenant
+    pub fn retention_policy<S>(mut self, retention_policy: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.retention_policy = Some(retention_policy.into());
+        self
+    }
+
+    /// Sets the prefix of the measurements of the tenant
+    pub fn measurement_prefix<S>(mut self, prefix: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.measurement_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Returns the database of the tenant, if it has its own
+    pub fn get_database(&self) -> Option<&str> {
+        self.database.as_deref()
+    }
+
+    /// Returns the retention policy of the tenant, if it has its own
+    pub fn get_retention_policy(&self) -> Option<&str> {
+        self.retention_policy.as_deref()
+    }
+
+    /// Returns the prefix of the measurements of the tenant, if they are prefixed
+    pub fn get_measurement_prefix(&self) -> Option<&str> {
+        self.measurement_prefix.as_deref()
+    }
+
+    /// Returns the name a measurement of the tenant is stored with
+    ///
+    /// Names which already start with the prefix are returned as they are, so measurements aren't
+    /// prefixed twice.
+    pub fn measurement(&self, name: &str) -> String {
+        match self.measurement_prefix.as_deref() {
+            Some(prefix) if !name.starts_with(prefix) => format!("{}{}", prefix, name),
+            _ => name.to_string(),
+        }
+    }
+}
+
+impl Client {
+    /// Sets the [`TenantRouter`](crate::TenantRouter) used by
+    /// [`for_tenant`](crate::Client::for_tenant)
+    pub fn with_tenant_router(mut self, router: TenantRouter) -> Self {
+        self.tenant_router = Some(Arc::new(router));
+        self
+    }
+
+    /// Returns a client scoped to a tenant of the [`TenantRouter`](crate::TenantRouter)
+    ///
+    /// Queries and writes of the returned client use the database and retention policy of the
+    /// tenant, and the measurements they read and write are prefixed, see
+    /// [`TenantRoute::measurement`]. In read queries, the measurements of `FROM` and `INTO` clauses
+    /// are prefixed, read queries with regexes as sources fail as the regexes might match the
+    /// measurements of other tenants. Points qualified with another database are rejected, as are
+    /// points with another value of the [tenant tag](crate::TenantRouter::tenant_tag). The scoped
+    /// client can't switch to another tenant.
+    ///
+    /// Scoping is a convenience for routing, not an isolation boundary: raw queries can still
+    /// reference other databases. Use InfluxDB users with per-database privileges to isolate
+    /// tenants.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Client, TenantRouter};
+    ///
+    /// let client = Client::new("http://localhost:8086", "shared")
+    ///     .with_tenant_router(TenantRouter::new().database_template("metrics_{tenant}"));
+    ///
+    /// let acme = client.for_tenant("acme")?;
+    /// assert_eq!(acme.database_name(), "metrics_acme");
+    /// assert_eq!(acme.tenant_route().unwrap().get_database(), Some("metrics_acme"));
+    /// assert!(acme.for_tenant("globex").is_err());
+    /// # Ok::<(), influxdb::Error>(())
+    /// ```
+    pub fn for_tenant(&self, tenant: &str) -> Result<Client, Error> {
+        let router = self
+            .tenant_router
+            .as_ref()
+            .ok_or_else(|| Error::InvalidQueryError {
+                error: "the client has no tenant router".to_string(),
+            })?;
+        let route = router.route(tenant)?;
+
+        let mut client = self.clone();
+        client.tenant_router = None;
+        if let Some(database) = route.get_database() {
+            client = client.with_database(database);
+        }
+        if let Some(retention_policy) = route.get_retention_policy() {
+            client = client.with_retention_policy(retention_policy);
+        }
+        let route = Arc::new(route);
+        client.tenant_route = Some(route.clone());
+
+        let tenant_tag = router
+            .tenant_tag
+            .clone()
+            .map(|key| (key, Type::from(tenant)));
+        let database = client.database_name().to_string();
+        Ok(client.with_write_hook(move |mut point: WriteQuery| {
+            if let Some(other) = point.measurement().get_database() {
+                if other != database {
+                    return Err(Error::InvalidQueryError {
+                        error: format!(
+                            "tenant can not write to database {:?}, only to {:?}",
+                            other, database
+                        ),
+                    });
+                }
+            }
+            if let Some((key, tenant)) = &tenant_tag {
+                match point.tags().iter().find(|(tag, _)| tag == key) {
+                    Some((_, value)) if value != tenant => {
+                        return Err(Error::InvalidQueryError {
+                            error: format!(
+                                "tenant {} can not write points tagged {}={}",
+                                tenant, key, value
+                            ),
+                        });
+                    }
+                    Some(_) => {}
+                    None => point.tags_mut().push((key.clone(), tenant.clone())),
+                }
+            }
+            let name = route.measurement(point.measurement().name());
+            Ok(point.rename_measurement(name))
+        }))
+    }
+
+    /// Returns the route of the tenant the client is scoped to with
+    /// [`for_tenant`](crate::Client::for_tenant)
+    pub fn tenant_route(&self) -> Option<&TenantRoute> {
+        self.tenant_route.as_deref()
+    }
+
+    /// Prefixes the measurements of the read query with the measurement prefix of the tenant the
+    /// client is scoped to
+    pub(crate) fn route_read(&self, read_query: String) -> Result<String, Error> {
+        match self
+            .tenant_route
+            .as_ref()
+            .and_then(|route| route.get_measurement_prefix())
+        {
+            Some(prefix) => Ok(ql::prefix_measurements(&read_query, prefix)?),
+            None => Ok(read_query),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TenantRoute, TenantRouter};
+    use crate::{Client, InfluxDbWriteable, Measurement, Query, ReadQuery, Timestamp, WriteQuery};
+
+    #[test]
+    fn test_route() {
+        let router = TenantRouter::new()
+            .retention_policy("thirty_days")
+            .add_tenant(
+                "acme",
+                TenantRoute::new()
+                    .database("acme")
+                    .measurement_prefix("acme_"),
+            );
+        let route = router.route("acme").unwrap();
+        assert_eq!(route.get_retention_policy(), Some("thirty_days"));
+        assert_eq!(route.measurement("cpu"), "acme_cpu");
+        assert_eq!(route.measurement("acme_cpu"), "acme_cpu");
+
+        assert!(router.route("globex").is_err());
+        assert!(router.route("").is_err());
+        assert!(TenantRouter::new()
+            .database_template("metrics_{tenant}")
+            .route("acme\" OR 1")
+            .is_err());
+
+        let router = TenantRouter::new()
+            .database_template("shared")
+            .measurement_prefix_template("{tenant}_")
+            .add_tenant("a_c", TenantRoute::new().measurement_prefix("a_c."));
+        assert_eq!(router.route("a").unwrap().measurement("b_cpu"), "a_b_cpu");
+        assert!(router.route("a_b").is_err());
+        assert_eq!(router.route("a-b").unwrap().measurement("cpu"), "a-b_cpu");
+        assert_eq!(router.route("a_c").unwrap().measurement("cpu"), "a_c.cpu");
+    }
+
+    #[test]
+    fn test_for_tenant() {
+        let client = Client::new("http://localhost:8086", "shared").with_tenant_router(
+            TenantRouter::new()
+                .database_template("metrics_{tenant}")
+                .measurement_prefix_template("{tenant}_")
+                .tenant_tag("tenant"),
+        );
+        assert!(Client::new("http://localhost:8086", "shared")
+            .for_tenant("acme")
+            .is_err());
+
+        let acme = client.for_tenant("acme").unwrap();
+        assert_eq!(acme.database_name(), "metrics_acme");
+        let point = Timestamp::Seconds(1)
+            .into_query("cpu")
+            .add_field("load", 0.5);
+        let point = acme.write_hooks.apply(point).unwrap();
+        assert_eq!(point.build().unwrap(), "acme_cpu,tenant=acme load=0.5 1");
+        let point = acme.write_hooks.apply(point).unwrap();
+        assert_eq!(point.build().unwrap(), "acme_cpu,tenant=acme load=0.5 1");
+
+        let impersonating = Timestamp::Seconds(1)
+            .into_query("cpu")
+            .add_tag("tenant", "globex")
+            .add_field("load", 0.5);
+        assert!(acme.write_hooks.apply(impersonating).is_err());
+
+        let escaping = WriteQuery::new(
+            Timestamp::Seconds(1),
+            Measurement::new("cpu").database("metrics_globex"),
+        )
+        .add_field("load", 0.5);
+        assert!(acme.write_hooks.apply(escaping).is_err());
+
+        let read = |query: &str| {
+            let request = acme.request(&ReadQuery::new(query)).ok()?.build();
+            let (_, q) = request.url().query_pairs().find(|(name, _)| name == "q")?;
+            Some(q.into_owned())
+        };
+        assert_eq!(
+            read("SELECT load FROM cpu, acme_mem").as_deref(),
+            Some(r#"SELECT load FROM "acme_cpu", acme_mem"#)
+        );
+        assert_eq!(read("SELECT load FROM /.*/"), None);
+        assert_eq!(
+            acme.tenant_route().unwrap().get_measurement_prefix(),
+            Some("acme_")
+        );
+        assert!(client.tenant_route().is_none());
+    }
+}
@@ -0,0 +1,92 @@
+//! HTTP server for the tests of the client
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+
+/// Request received by the server
+#[derive(Debug, Clone)]
+pub(crate) struct Request {
+    /// Request line and headers, as they were sent
+    pub(crate) head: String,
+    pub(crate) body: String,
+}
+
+/// Serves HTTP/1.1 on a local port, answering each request with the response returned by
+/// `respond`, and returns the address of the server and the requests it received
+///
+/// Connections are kept alive and served on threads of their own. Connections which are closed
+/// without a request are ignored.
+pub(crate) fn serve<F>(respond: F) -> (SocketAddr, Receiver<Request>)
+where
+    F: Fn(&Request) -> String + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let (sender, receiver) = mpsc::channel();
+    let respond = Arc::new(respond);
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = BufReader::new(stream.unwrap());
+            let (sender, respond) = (sender.clone(), Arc::clone(&respond));
+            thread::spawn(move || {
+                while let Some(request) = read_request(&mut stream) {
+                    let response = respond(&request);
+                    // received before it is answered, so the test sees it once the client returns
+                    let _ = sender.send(request);
+                    if stream.get_mut().write_all(response.as_bytes()).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    });
+    (address, receiver)
+}
+
+/// Returns a listener which accepts connections, but never answers, and its URL
+pub(crate) fn unresponsive() -> (TcpListener, String) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    (listener, url)
+}
+
+/// Formats a response, `headers` are terminated with `\r\n` each
+pub(crate) fn response(status: &str, headers: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\n{}Content-Length: {}\r\n\r\n{}",
+        status,
+        headers,
+        body.len(),
+        body
+    )
+}
+
+fn read_request(stream: &mut BufReader<TcpStream>) -> Option<Request> {
+    let mut head = String::new();
+    loop {
+        let start = head.len();
+        if stream.read_line(&mut head).ok()? == 0 {
+            return None;
+        }
+        if &head[start..] == "\r\n" {
+            break;
+        }
+    }
+    let length = head
+        .lines()
+        .find_map(|line| {
+            let line = line.to_ascii_lowercase();
+            line.strip_prefix("content-length:")
+                .and_then(|length| length.trim().parse().ok())
+        })
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    stream.read_exact(&mut body).ok()?;
+    Some(Request {
+        head,
+        body: String::from_utf8(body).unwrap(),
+    })
+}
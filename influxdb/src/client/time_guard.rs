@@ -0,0 +1,96 @@
+//! Guarding the server against `SELECT` statements which read all points
+
+use chrono::Duration;
+
+use crate::{ql, Client, Error};
+
+/// How a [`Client`](crate::Client) treats `SELECT` statements without a condition on `time`, see
+/// [`Client::with_time_guard`](crate::Client::with_time_guard)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeGuard {
+    /// Sends them as they are, the default
+    #[default]
+    Off,
+    /// Fails them with an [`InvalidQueryError`](crate::Error::InvalidQueryError) instead of
+    /// sending them
+    Reject,
+    /// Restricts them to the points newer than the duration before the current time of the server
+    Last(Duration),
+}
+
+impl Client {
+    /// Guards the server against `SELECT` statements of read queries which have no condition on
+    /// `time`, which read every shard of their measurements
+    ///
+    /// Queries are checked with the [InfluxQL validator](crate::ql), so guarded queries which it
+    /// considers invalid fail without being sent.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use chrono::Duration;
+    /// use influxdb::{Client, TimeGuard};
+    ///
+    /// let client = Client::new("http://localhost:8086", "test")
+    ///     .with_time_guard(TimeGuard::Last(Duration::hours(24)));
+    ///
+    /// assert_eq!(client.time_guard(), TimeGuard::Last(Duration::hours(24)));
+    /// ```
+    pub fn with_time_guard(mut self, time_guard: TimeGuard) -> Self {
+        self.time_guard = time_guard;
+        self
+    }
+
+    /// Returns how `SELECT` statements without a condition on `time` are treated
+    pub fn time_guard(&self) -> TimeGuard {
+        self.time_guard
+    }
+
+    /// Rejects or amends the read query according to the time guard of the client
+    pub(crate) fn guard_time(&self, read_query: String) -> Result<String, Error> {
+        match self.time_guard {
+            TimeGuard::Off => Ok(read_query),
+            TimeGuard::Reject => {
+                ql::require_time_condition(&read_query)?;
+                Ok(read_query)
+            }
+            TimeGuard::Last(duration) => Ok(ql::bound_time(&read_query, duration)?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TimeGuard;
+    use crate::{Client, ReadQuery};
+    use chrono::Duration;
+
+    fn query(client: &Client, query: &str) -> Option<String> {
+        let request = client.request(&ReadQuery::new(query)).ok()?.build();
+        let (_, q) = request.url().query_pairs().find(|(name, _)| name == "q")?;
+        Some(q.into_owned())
+    }
+
+    #[test]
+    fn test_time_guard() {
+        let client = Client::new("http://localhost:8086", "test");
+        assert_eq!(client.time_guard(), TimeGuard::Off);
+        assert_eq!(
+            query(&client, "SELECT * FROM cpu").as_deref(),
+            Some("SELECT * FROM cpu")
+        );
+
+        let client = client.with_time_guard(TimeGuard::Reject);
+        assert_eq!(query(&client, "SELECT * FROM cpu"), None);
+        assert_eq!(
+            query(&client, "SHOW DATABASES").as_deref(),
+            Some("SHOW DATABASES")
+        );
+
+        let client = client.with_time_guard(TimeGuard::Last(Duration::days(1)));
+        assert_eq!(
+            query(&client, "SELECT * FROM cpu").as_deref(),
+            Some("SELECT * FROM cpu WHERE time > now() - 1d")
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! Writing [`InfluxDbWriteable`](crate::InfluxDbWriteable) values in one call
+
+use crate::{Client, Error, InfluxDbWriteable, WriteQuery, WriteResult};
+
+impl Client {
+    /// Writes a value, e.g. of a struct deriving [`InfluxDbWriteable`](crate::InfluxDbWriteable),
+    /// as a point of `measurement`
+    ///
+    /// A shorthand for [`into_query`](crate::InfluxDbWriteable::into_query) followed by
+    /// [`write_query`](crate::Client::write_query).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use chrono::{DateTime, Utc};
+    /// use influxdb::{Client, InfluxDbWriteable, Timestamp};
+    ///
+    /// #[derive(InfluxDbWriteable)]
+    /// struct WeatherReading {
+    ///     time: DateTime<Utc>,
+    ///     temperature: i32,
+    ///     #[influxdb(tag)]
+    ///     location: String,
+    /// }
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("http://localhost:8086", "test");
+    /// let reading = WeatherReading {
+    ///     time: Timestamp::Hours(1).into(),
+    ///     temperature: 82,
+    ///     location: "us-midwest".to_string(),
+    /// };
+    /// client.write("weather", reading).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn write<S, W>(&self, measurement: S, point: W) -> Result<WriteResult, Error>
+    where
+        S: Into<String>,
+        W: InfluxDbWriteable,
+    {
+        self.write_query(&point.into_query(measurement)).await
+    }
+
+    /// Writes all values of `points` as points of `measurement`, in a single batch
+    ///
+    /// Returns `None` without sending a request if there aren't any points.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use chrono::{DateTime, Utc};
+    /// use influxdb::{Client, InfluxDbWriteable, Timestamp};
+    ///
+    /// #[derive(InfluxDbWriteable)]
+    /// struct WeatherReading {
+    ///     time: DateTime<Utc>,
+    ///     temperature: i32,
+    /// }
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("http://localhost:8086", "test");
+    /// let readings = (1..=3).map(|hour| WeatherReading {
+    ///     time: Timestamp::Hours(hour).into(),
+    ///     temperature: 82,
+    /// });
+    /// client.write_all("weather", readings).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn write_all<S, I>(
+        &self,
+        measurement: S,
+        points: I,
+    ) -> Result<Option<WriteResult>, Error>
+    where
+        S: Into<String>,
+        I: IntoIterator,
+        I::Item: InfluxDbWriteable,
+    {
+        let measurement = measurement.into();
+        let batch = points
+            .into_iter()
+            .map(|point| point.into_query(measurement.as_str()))
+            .collect::<Vec<WriteQuery>>();
+        if batch.is_empty() {
+            return Ok(None);
+        }
+        self.write_query(&batch).await.map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Client, Error, Timestamp};
+    use futures::executor::block_on;
+
+    #[test]
+    fn test_write_all() {
+        let client = Client::new("http://127.0.0.1:1", "test");
+        let empty = block_on(client.write_all("weather", Vec::<Timestamp>::new()));
+        assert_eq!(empty.unwrap(), None);
+
+        // a timestamp alone is a point without fields, which is rejected before it is sent
+        let result = block_on(client.write_all("weather", vec![Timestamp::Seconds(1)]));
+        assert!(matches!(result, Err(Error::InvalidQueryError { .. })));
+    }
+}
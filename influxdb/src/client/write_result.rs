@@ -0,0 +1,174 @@
+//! Typed results of writes
+
+use super::check_body;
+use super::request_id::REQUEST_ID;
+use crate::query::QueryType;
+use crate::{Client, Compatibility, Error, Query};
+
+/// Result of a successful write, see [`Client::write_query`](crate::Client::write_query)
+///
+/// InfluxDB answers successful writes with `204 No Content`. Other successful statuses, e.g. of
+/// proxies in front of InfluxDB, and messages the server sent along are kept as warnings instead
+/// of being dropped silently.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteResult {
+    /// The HTTP status of the response, usually `204`
+    pub status: u16,
+    /// The ID of the request, as the server reported it in its `Request-Id` header or as it was sent,
+    /// see [`Client::with_request_id`](crate::Client::with_request_id)
+    pub request_id: Option<String>,
+    /// Messages from the `X-Influxdb-Error` header and the body of the response
+    pub warnings: Vec<String>,
+}
+
+impl WriteResult {
+    /// Returns whether the server answered with `204 No Content` and didn't report any warnings
+    pub fn is_clean(&self) -> bool {
+        self.status == 204 && self.warnings.is_empty()
+    }
+}
+
+impl Client {
+    /// Sends a [`WriteQuery`](crate::WriteQuery), or a batch of them, and returns the typed
+    /// [`WriteResult`](crate::WriteResult) of the write
+    ///
+    /// Unlike [`query`](crate::Client::query), responses with an error status are always errors,
+    /// even if their body doesn't look like an error of InfluxDB.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::{Client, InfluxDbWriteable, Timestamp};
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("http://localhost:8086", "test");
+    /// let point = Timestamp::Seconds(1).into_query("weather").add_field("temperature", 82);
+    ///
+    /// let result = client.write_query(&point).await?;
+    /// for warning in &result.warnings {
+    ///     eprintln!("write {:?}: {}", result.request_id, warning);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn write_query<Q>(&self, q: &Q) -> Result<WriteResult, Error>
+    where
+        Q: Query,
+    {
+        if let QueryType::ReadQuery = q.get_type() {
+            return Err(Error::InvalidQueryError {
+                error: "read queries can not be sent with write_query".to_string(),
+            });
+        }
+        let sent_id = self.next_request_id();
+        let request = self
+            .with_headers(self.request(q)?.header(REQUEST_ID, sent_id.as_str()))
+            .build();
+        let mut response = self.send_request(request).await?;
+        let status = response.status();
+        let header = |name: &str| {
+            response
+                .header(name)
+                .map(|value| value.as_str().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let request_id = header(REQUEST_ID)
+            .or_else(|| header("X-Request-Id"))
+            .unwrap_or(sent_id);
+        let influx_error = header("X-Influxdb-Error");
+        let body = response
+            .body_string()
+            .await
+            .map_err(|err| Error::DeserializationError {
+                error: "response could not be converted to UTF-8".to_string(),
+                source: crate::error::surf_source(err),
+            })?;
+
+        if !status.is_success() {
+            let error = match check_body(body) {
+                Err(Error::DatabaseError { error, .. }) => error,
+                Err(err) => return Err(err.with_request_id(&request_id)),
+                Ok(body) if self.compatibility != Compatibility::InfluxDb => {
+                    self.compatibility.error_message(&body)
+                }
+                Ok(body) => influx_error.unwrap_or_else(|| {
+                    format!("influxdb error: {} \"{}\"", u16::from(status), body.trim())
+                }),
+            };
+            return Err(match u16::from(status) {
+                400 => self.rejected_write_error(q, error),
+                status => Error::DatabaseError {
+                    error,
+                    status: Some(status),
+                    request_id: None,
+                },
+            }
+            .with_request_id(&request_id));
+        }
+
+        let mut warnings = influx_error.into_iter().collect::<Vec<_>>();
+        warnings.extend(body_message(&body));
+        Ok(WriteResult {
+            status: status.into(),
+            request_id: Some(request_id),
+            warnings,
+        })
+    }
+}
+
+/// Returns the message of the body of a successful write, e.g. the `message` of the JSON bodies of
+/// InfluxDB 2.x
+fn body_message(body: &str) -> Option<String> {
+    let body = body.trim();
+    if body.is_empty() {
+        return None;
+    }
+    #[cfg(feature = "use-serde")]
+    {
+        if let Ok(serde_json::Value::Object(object)) = serde_json::from_str(body) {
+            let message = ["message", "error", "warning"]
+                .iter()
+                .find_map(|key| object.get(*key).and_then(serde_json::Value::as_str));
+            if let Some(message) = message {
+                return Some(message.to_string());
+            }
+        }
+    }
+    Some(body.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{body_message, WriteResult};
+
+    #[test]
+    fn test_body_message() {
+        assert_eq!(body_message(""), None);
+        assert_eq!(body_message(" \n"), None);
+        assert_eq!(
+            body_message("<html>accepted</html>"),
+            Some("<html>accepted</html>".to_string())
+        );
+        #[cfg(feature = "use-serde")]
+        assert_eq!(
+            body_message(r#"{"code":"ok","message":"partial data dropped by retention"}"#),
+            Some("partial data dropped by retention".to_string())
+        );
+    }
+
+    #[test]
+    fn test_is_clean() {
+        let result = WriteResult {
+            status: 204,
+            request_id: Some("5e5c2fa8".to_string()),
+            warnings: vec![],
+        };
+        assert!(result.is_clean());
+        assert!(!WriteResult {
+            status: 200,
+            ..result
+        }
+        .is_clean());
+    }
+}
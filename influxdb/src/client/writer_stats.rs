@@ -0,0 +1,196 @@
+//! Statistics of the batches written by a [`BufferedWriter`](crate::BufferedWriter)
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::metrics::Buckets;
+use crate::{Timestamp, WriteQuery};
+
+/// Upper bounds of the buckets of batch sizes, in points
+const BATCH_SIZE_BOUNDS: [f64; 7] = [1.0, 10.0, 100.0, 1000.0, 5000.0, 10000.0, 50000.0];
+/// Upper bounds of the buckets of flush latencies, in milliseconds
+const LATENCY_BOUNDS: [f64; 8] = [1.0, 5.0, 10.0, 50.0, 100.0, 500.0, 1000.0, 5000.0];
+
+/// Counts of observations in buckets with fixed upper bounds, e.g. of the sizes of batches
+///
+/// Shares its buckets with [`Histogram`](crate::metrics::Histogram), and writes the same fields,
+/// prefixed with the name of the statistic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WriterHistogram(Buckets);
+
+impl WriterHistogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        WriterHistogram(Buckets::new(Arc::from(bounds)))
+    }
+
+    pub(crate) fn observe(&mut self, value: f64) {
+        self.0.observe(value);
+    }
+
+    /// Returns the number of observations
+    pub fn count(&self) -> u64 {
+        self.0.count()
+    }
+
+    /// Returns the sum of all observations
+    pub fn sum(&self) -> f64 {
+        self.0.sum()
+    }
+
+    /// Returns the mean of all observations, if there are any
+    pub fn mean(&self) -> Option<f64> {
+        match self.count() {
+            0 => None,
+            count => Some(self.sum() / count as f64),
+        }
+    }
+
+    /// Returns the upper bound of each bucket with the number of observations in it, the last
+    /// bucket is unbounded
+    pub fn buckets(&self) -> impl Iterator<Item = (f64, u64)> + '_ {
+        self.0.buckets()
+    }
+
+    /// Adds the fields of the histogram with the name as prefix, e.g. `batch_size_le_100`
+    fn add_fields(&self, point: WriteQuery, name: &str) -> WriteQuery {
+        self.0.add_fields(point, &format!("{}_", name))
+    }
+}
+
+/// Snapshot of the statistics of a [`BufferedWriter`](crate::BufferedWriter), returned by
+/// [`BufferedWriter::stats`](crate::BufferedWriter::stats)
+///
+/// The counters start when the writer is created and are shared by all of its handles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WriterStats {
+    /// Points which were handed to the writer, but weren't written or dropped yet
+    pub queue_depth: u64,
+    /// Points which the server accepted
+    pub points_written: u64,
+    /// Points of batches which failed and were dropped
+    pub points_failed: u64,
+    /// Points which were [merged](crate::WriterOptions::coalesce) into other points before they
+    /// were written
+    pub points_coalesced: u64,
+    /// Points which weren't kept by the [sampling](crate::WriterOptions::sampling) of the writer
+    pub points_sampled_out: u64,
+    /// Points which were dropped because [too many points](crate::WriterOptions::max_queued_points)
+    /// were waiting to be written
+    pub points_dropped: u64,
+    /// Batches which the server accepted
+    pub batches_written: u64,
+    /// Batches which failed
+    pub batches_failed: u64,
+    /// Number of points per batch, of written and failed batches
+    pub batch_sizes: WriterHistogram,
+    /// Time it took to send a batch and receive the answer of the server, in milliseconds
+    pub flush_latencies: WriterHistogram,
+    /// Number of points from which the buffer is flushed, which changes in the
+    /// [adaptive mode](crate::WriterOptions::adaptive)
+    pub batch_size: usize,
+    /// Interval after which the buffer is flushed, which changes in the
+    /// [adaptive mode](crate::WriterOptions::adaptive)
+    pub flush_interval: Duration,
+}
+
+impl WriterStats {
+    pub(crate) fn new(batch_size: usize, flush_interval: Duration) -> Self {
+        WriterStats {
+            queue_depth: 0,
+            points_written: 0,
+            points_failed: 0,
+            points_coalesced: 0,
+            points_sampled_out: 0,
+            points_dropped: 0,
+            batches_written: 0,
+            batches_failed: 0,
+            batch_sizes: WriterHistogram::new(&BATCH_SIZE_BOUNDS),
+            flush_latencies: WriterHistogram::new(&LATENCY_BOUNDS),
+            batch_size,
+            flush_interval,
+        }
+    }
+
+    /// Records a batch which was sent, successfully or not
+    pub(crate) fn record_batch(&mut self, points: usize, latency: Duration, written: bool) {
+        let points = points as u64;
+        self.queue_depth = self.queue_depth.saturating_sub(points);
+        if written {
+            self.points_written += points;
+            self.batches_written += 1;
+        } else {
+            self.points_failed += points;
+            self.batches_failed += 1;
+        }
+        self.batch_sizes.observe(points as f64);
+        self.flush_latencies.observe(latency.as_secs_f64() * 1000.0);
+    }
+
+    /// Records points which were merged into others, and won't be written on their own
+    pub(crate) fn record_coalesced(&mut self, points: usize) {
+        let points = points as u64;
+        self.queue_depth = self.queue_depth.saturating_sub(points);
+        self.points_coalesced += points;
+    }
+
+    /// Records points which were dropped by sampling
+    pub(crate) fn record_sampled_out(&mut self, points: usize) {
+        let points = points as u64;
+        self.queue_depth = self.queue_depth.saturating_sub(points);
+        self.points_sampled_out += points;
+    }
+
+    /// Returns the statistics as a point of `measurement`, tagged with the name of the task of the
+    /// writer
+    pub(crate) fn to_point(&self, measurement: &str, task_name: &str) -> WriteQuery {
+        let point = WriteQuery::new(Timestamp::now(), measurement)
+            .add_tag("writer", task_name)
+            .add_field("queue_depth", self.queue_depth)
+            .add_field("points_written", self.points_written)
+            .add_field("points_failed", self.points_failed)
+            .add_field("points_coalesced", self.points_coalesced)
+            .add_field("points_sampled_out", self.points_sampled_out)
+            .add_field("points_dropped", self.points_dropped)
+            .add_field("batches_written", self.batches_written)
+            .add_field("batches_failed", self.batches_failed)
+            .add_field("batch_size_limit", self.batch_size as u64)
+            .add_field("flush_interval_ms", self.flush_interval.as_millis() as u64);
+        let point = self.batch_sizes.add_fields(point, "batch_size");
+        self.flush_latencies.add_fields(point, "flush_latency_ms")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WriterStats;
+    use crate::Query;
+    use std::time::Duration;
+
+    #[test]
+    fn test_record_batch() {
+        let mut stats = WriterStats {
+            queue_depth: 120,
+            ..WriterStats::new(5000, Duration::from_secs(1))
+        };
+        stats.record_batch(100, Duration::from_millis(20), true);
+        stats.record_batch(20, Duration::from_millis(2), false);
+
+        assert_eq!(stats.queue_depth, 0);
+        assert_eq!((stats.points_written, stats.batches_written), (100, 1));
+        assert_eq!((stats.points_failed, stats.batches_failed), (20, 1));
+        assert_eq!(stats.batch_sizes.count(), 2);
+        assert_eq!(stats.batch_sizes.mean(), Some(60.0));
+        assert_eq!(
+            stats.batch_sizes.buckets().take(4).collect::<Vec<_>>(),
+            vec![(1.0, 0), (10.0, 0), (100.0, 2), (1000.0, 0)]
+        );
+        assert_eq!(stats.flush_latencies.buckets().nth(1), Some((5.0, 1)));
+
+        let line = stats.to_point("writer_stats", "metrics").build().unwrap();
+        let line = line.get();
+        assert!(line.starts_with("writer_stats,writer=metrics queue_depth=0i,points_written=100i"));
+        assert!(line.contains(",batch_size_count=2i,batch_size_sum=120,"));
+        assert!(line.contains(",batch_size_le_10=0i,batch_size_le_100=2i,"));
+        assert!(line.contains(",flush_latency_ms_le_inf=2i "));
+    }
+}
@@ -1,8 +1,44 @@
 //! Errors that might happen in the crate
 
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+use std::ops::Deref;
+use std::sync::Arc;
 use thiserror::Error;
 
-#[derive(Debug, Error)]
+use crate::{Query, QueryType};
+
+/// The underlying error of an [`Error`], e.g. of the HTTP client, of `serde_json` or of the file
+/// system
+///
+/// It is shared, so errors stay cheap to clone, and dereferences to the original error, which
+/// [`source`](std::error::Error::source) returns as well.
+#[derive(Clone)]
+pub struct ErrorSource(Arc<dyn StdError + Send + Sync>);
+
+impl Deref for ErrorSource {
+    type Target = dyn StdError + Send + Sync + 'static;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+impl fmt::Debug for ErrorSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for ErrorSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+#[non_exhaustive]
 pub enum Error {
     #[error("query is invalid: {error}")]
     /// Error happens when a query is invalid
@@ -18,11 +54,33 @@ pub enum Error {
 
     #[error("http protocol error: {error}")]
     /// Error happens when Serde cannot deserialize the response
-    DeserializationError { error: String },
+    DeserializationError {
+        error: String,
+        source: Option<ErrorSource>,
+    },
 
     #[error("InfluxDB encountered the following error: {error}")]
-    /// Error which has happened inside InfluxDB
-    DatabaseError { error: String },
+    /// Error which has happened inside InfluxDB, with the HTTP status of the response if it wasn't
+    /// successful and the ID of the request, see [`Error::request_id`]
+    DatabaseError {
+        error: String,
+        status: Option<u16>,
+        request_id: Option<String>,
+    },
+
+    #[error("InfluxDB rejected {} lines of the write: {error}", rejected.len())]
+    /// Error happens when InfluxDB fails to parse some lines of a write. The lines are listed with
+    /// the index of the point they belong to.
+    PartialWriteError {
+        error: String,
+        rejected: Vec<RejectedLine>,
+    },
+
+    #[error("query result exceeded the read limits after {rows} rows and {bytes} bytes")]
+    /// Error happens when the result of a read query exceeds the
+    /// [`ReadLimits`](crate::ReadLimits) of the client. The request was aborted after receiving
+    /// the given number of rows and bytes.
+    ResultTooLarge { rows: u64, bytes: u64 },
 
     #[error("authentication error. No or incorrect credentials")]
     /// Error happens when no or incorrect credentials are used. `HTTP 401 Unauthorized`
@@ -32,7 +90,432 @@ pub enum Error {
     /// Error happens when the supplied user is not authorized. `HTTP 403 Forbidden`
     AuthorizationError,
 
+    #[error("insufficient privileges: {error}")]
+    /// Error happens when a user lacks privileges on a database, as found by
+    /// [`Client::verify_credentials`](crate::Client::verify_credentials) or
+    /// [`Client::require_privilege`](crate::Client::require_privilege)
+    InsufficientPrivileges { error: String },
+
     #[error("connection error: {error}")]
     /// Error happens when HTTP request fails
-    ConnectionError { error: String },
+    ConnectionError {
+        error: String,
+        source: Option<ErrorSource>,
+    },
+
+    #[error("credentials error: {error}")]
+    /// Error happens when a [`CredentialsProvider`](crate::CredentialsProvider) cannot provide credentials
+    CredentialsError { error: String },
+
+    #[error("configuration error: {error}")]
+    /// Error happens when a client cannot be configured from the environment or a configuration
+    /// file of the `influx` CLI, e.g. because a variable is missing or the file is invalid
+    ConfigError {
+        error: String,
+        source: Option<ErrorSource>,
+    },
+
+    #[error("time is out of the range of timestamps: {error}")]
+    /// Error happens when a time before the unix epoch, or too far after it, is converted into a
+    /// [`Timestamp`](crate::Timestamp)
+    TimestampOutOfRange { error: String },
+
+    #[error("I/O error: {error}")]
+    /// Error happens when reading or writing a local file fails
+    IoError {
+        error: String,
+        source: Option<ErrorSource>,
+    },
+
+    #[error("{source} (query: {query})")]
+    /// Error of a query, reported together with the query. Values in the query are redacted and
+    /// long queries are truncated, see [`Error::with_query`]. The original error is the
+    /// [`source`](std::error::Error::source) of this error and returned by [`Error::inner`].
+    QueryFailed { query: String, source: ErrorSource },
+}
+
+impl Error {
+    /// Adds the query which failed to the error, so it shows up in error reports
+    ///
+    /// String literals of read queries and field values of writes are redacted, only the first
+    /// lines of a batch are kept and long lines are truncated. Errors which already report their
+    /// query are returned unchanged. See
+    /// [`Client::with_query_in_errors`](crate::Client::with_query_in_errors) to add the query to
+    /// all errors of a client.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Error, ReadQuery};
+    ///
+    /// let query = ReadQuery::new("SELECT * FROM weather WHERE city = 'berlin'");
+    /// let error = Error::AuthorizationError.with_query(&query);
+    ///
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "authorization error. User not authorized (query: SELECT * FROM weather WHERE city = '***')"
+    /// );
+    /// assert!(matches!(error.inner(), Error::AuthorizationError));
+    /// ```
+    pub fn with_query<Q>(self, q: &Q) -> Self
+    where
+        Q: Query + ?Sized,
+    {
+        if matches!(self, Error::QueryFailed { .. }) {
+            return self;
+        }
+        let query = match q.build() {
+            Ok(query) => query.get(),
+            Err(_) => return self,
+        };
+        let query = match q.get_type() {
+            QueryType::ReadQuery => redact_read(&query),
+            QueryType::WriteQuery(_) => redact_write(&query),
+        };
+        Error::QueryFailed {
+            query,
+            source: ErrorSource(Arc::new(self)),
+        }
+    }
+
+    /// Returns the error without the query added by [`with_query`](crate::Error::with_query), so
+    /// it can be matched on
+    pub fn inner(&self) -> &Error {
+        match self {
+            Error::QueryFailed { source, .. } => source.downcast_ref().unwrap_or(self),
+            _ => self,
+        }
+    }
+
+    /// Returns the ID of the request the server failed, if the error was reported by the server
+    pub fn request_id(&self) -> Option<&str> {
+        match self.inner() {
+            Error::DatabaseError { request_id, .. } => request_id.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Records the ID of the request which caused the error, unless it already has one
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    pub(crate) fn with_request_id(mut self, id: &str) -> Self {
+        if let Error::DatabaseError { request_id, .. } = &mut self {
+            request_id.get_or_insert_with(|| id.to_string());
+        }
+        self
+    }
+
+    /// Returns whether a request or a read timed out, i.e. whether the error was caused by an
+    /// [`io::Error`] of kind [`TimedOut`](io::ErrorKind::TimedOut)
+    ///
+    /// This covers the [request timeout](crate::ClientBuilder::timeout), the timeout of
+    /// [read limits](crate::ReadLimits::timeout) and the deadline of
+    /// [`flush_on_shutdown`](crate::BufferedWriter::flush_on_shutdown).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Error;
+    /// use std::io;
+    ///
+    /// let error = Error::ConnectionError {
+    ///     error: "failed to connect to timeout-db:8086".to_string(),
+    ///     source: None,
+    /// };
+    /// assert!(!error.is_timeout());
+    /// let error = Error::from(io::Error::new(io::ErrorKind::TimedOut, "deadline elapsed"));
+    /// assert!(error.is_timeout());
+    /// ```
+    pub fn is_timeout(&self) -> bool {
+        let mut source = StdError::source(self);
+        while let Some(error) = source {
+            if let Some(error) = error.downcast_ref::<io::Error>() {
+                if error.kind() == io::ErrorKind::TimedOut {
+                    return true;
+                }
+            }
+            source = error.source();
+        }
+        false
+    }
+
+    /// Returns whether the server rejected the credentials of the client or didn't authorize its
+    /// user, or the user lacks privileges
+    pub fn is_auth(&self) -> bool {
+        matches!(
+            self.inner(),
+            Error::AuthenticationError
+                | Error::AuthorizationError
+                | Error::InsufficientPrivileges { .. }
+        )
+    }
+
+    /// Returns the HTTP status of the response which caused the error, if there was one
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Error;
+    ///
+    /// let error = Error::DatabaseError {
+    ///     error: "database not found: weather".to_string(),
+    ///     status: Some(404),
+    ///     request_id: None,
+    /// };
+    /// assert_eq!(error.status(), Some(404));
+    /// assert_eq!(Error::AuthorizationError.status(), Some(401));
+    /// ```
+    pub fn status(&self) -> Option<u16> {
+        // The client reports `401 Unauthorized` as `AuthorizationError` and `403 Forbidden` as
+        // `AuthenticationError`, so the status is the one of the response rather than the one the
+        // docs of the variants name
+        match self.inner() {
+            Error::AuthorizationError => Some(401),
+            Error::AuthenticationError => Some(403),
+            Error::PartialWriteError { .. } => Some(400),
+            Error::DatabaseError { status, .. } => *status,
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::IoError {
+            error: error.to_string(),
+            source: source(error),
+        }
+    }
+}
+
+#[cfg(feature = "use-serde")]
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Error::DeserializationError {
+            error: error.to_string(),
+            source: source(error),
+        }
+    }
+}
+
+/// Lines of a write which are kept in an error report
+const MAX_LINES: usize = 3;
+/// Characters of a line which are kept in an error report
+const MAX_LINE_LENGTH: usize = 200;
+
+/// Replaces the contents of string literals, which might be sensitive, e.g. passwords
+fn redact_read(query: &str) -> String {
+    let mut redacted = String::with_capacity(query.len());
+    let mut chars = query.chars();
+    while let Some(c) = chars.next() {
+        redacted.push(c);
+        if c == '\'' {
+            redacted.push_str("***'");
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => {
+                        chars.next();
+                    }
+                    '\'' => break,
+                    _ => {}
+                }
+            }
+        }
+    }
+    truncate(&redacted)
+}
+
+/// Keeps the series and field keys of the first lines of a write, replacing the field values
+fn redact_write(query: &str) -> String {
+    let lines = query.lines().filter(|line| !line.is_empty());
+    let mut redacted = lines
+        .clone()
+        .take(MAX_LINES)
+        .map(|line| truncate(&redact_line(line)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let more = lines.count().saturating_sub(MAX_LINES);
+    if more > 0 {
+        redacted.push_str(&format!("\n... {} more lines", more));
+    }
+    redacted
+}
+
+fn redact_line(line: &str) -> String {
+    let mut redacted = String::with_capacity(line.len());
+    // 0: series key, 1: fields, 2: timestamp
+    let mut section = 0;
+    let (mut escaped, mut quoted, mut in_value) = (false, false, false);
+    for c in line.chars() {
+        let special = !escaped && !quoted;
+        match c {
+            ' ' if special => {
+                section += 1;
+                in_value = false;
+            }
+            ',' if special && section == 1 => in_value = false,
+            '=' if special && section == 1 && !in_value => {
+                in_value = true;
+                redacted.push_str("=***");
+                continue;
+            }
+            '"' if !escaped && section == 1 => quoted = !quoted,
+            _ => {}
+        }
+        escaped = !escaped && c == '\\';
+        if !in_value {
+            redacted.push(c);
+        }
+    }
+    redacted
+}
+
+fn truncate(query: &str) -> String {
+    match query.char_indices().nth(MAX_LINE_LENGTH) {
+        Some((end, _)) => format!("{}...", &query[..end]),
+        None => query.to_string(),
+    }
+}
+
+/// Shares an error as the [`ErrorSource`] of an [`Error`]
+#[cfg_attr(not(any(feature = "client", feature = "use-serde")), allow(dead_code))]
+pub(crate) fn source<E>(error: E) -> Option<ErrorSource>
+where
+    E: StdError + Send + Sync + 'static,
+{
+    Some(ErrorSource(Arc::new(error)))
+}
+
+/// Shares the error of the HTTP client as the [`ErrorSource`] of an [`Error`]
+#[cfg(feature = "client")]
+pub(crate) fn surf_source(error: surf::Error) -> Option<ErrorSource> {
+    // boxing the inner error hides its type, so I/O errors are unwrapped for `is_timeout`
+    match error.downcast::<io::Error>() {
+        Ok(error) => source(error),
+        Err(error) => {
+            let error: Box<dyn StdError + Send + Sync> = error.into_inner().into();
+            Some(ErrorSource(Arc::from(error)))
+        }
+    }
+}
+
+/// A line of a write which InfluxDB failed to parse
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedLine {
+    /// Index of the point in the batch
+    pub index: usize,
+    /// The line as it was sent
+    pub line: String,
+    /// Why the server rejected the line, e.g. `invalid boolean`
+    pub reason: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{redact_read, redact_write, source, Error};
+    use crate::{InfluxDbWriteable, Timestamp};
+    use std::error::Error as StdError;
+    use std::io;
+
+    #[test]
+    fn test_source() {
+        let err = io::Error::new(io::ErrorKind::InvalidData, "not UTF-8");
+        let error = Error::IoError {
+            error: err.to_string(),
+            source: source(err),
+        };
+        let source = error.source().unwrap();
+        assert!(source.downcast_ref::<io::Error>().is_some());
+        assert!(Error::AuthenticationError.source().is_none());
+    }
+
+    #[test]
+    fn test_is_timeout() {
+        let error = Error::ConnectionError {
+            error: "request failed".to_string(),
+            source: source(io::Error::new(io::ErrorKind::TimedOut, "deadline")),
+        };
+        assert!(error.is_timeout());
+        let error = Error::IoError {
+            error: "file not found".to_string(),
+            source: source(io::Error::new(io::ErrorKind::NotFound, "missing")),
+        };
+        assert!(!error.is_timeout());
+        assert!(!Error::AuthorizationError.is_timeout());
+        let error = Error::ConnectionError {
+            error: "connection to http://timeout-db:8086 timed out".to_string(),
+            source: None,
+        };
+        assert!(!error.is_timeout());
+    }
+
+    #[test]
+    fn test_status() {
+        assert!(Error::AuthenticationError.is_auth());
+        assert_eq!(Error::AuthenticationError.status(), Some(403));
+        let error = Error::PartialWriteError {
+            error: "partial write".to_string(),
+            rejected: Vec::new(),
+        };
+        assert_eq!(error.status(), Some(400));
+        assert!(!error.is_auth());
+        let error = Error::DatabaseError {
+            error: "error parsing query".to_string(),
+            status: None,
+            request_id: None,
+        };
+        assert_eq!(error.status(), None);
+    }
+
+    #[test]
+    fn test_with_query() {
+        let write = Timestamp::Seconds(1)
+            .into_query("weather")
+            .add_field("temperature", 82);
+        let error = Error::DatabaseError {
+            error: "field type conflict".to_string(),
+            status: Some(400),
+            request_id: None,
+        }
+        .with_query(&write);
+        assert_eq!(
+            error.to_string(),
+            "InfluxDB encountered the following error: field type conflict (query: weather temperature=*** 1)"
+        );
+        assert_eq!(error.status(), Some(400));
+        assert!(matches!(
+            error.source().unwrap().downcast_ref(),
+            Some(Error::DatabaseError { .. })
+        ));
+        assert_eq!(
+            error.clone().with_query(&write).to_string(),
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn test_redact_read() {
+        assert_eq!(
+            redact_read("CREATE USER admin WITH PASSWORD 'it\\'s secret'"),
+            "CREATE USER admin WITH PASSWORD '***'"
+        );
+        assert_eq!(
+            redact_read(&"x".repeat(300)),
+            format!("{}...", "x".repeat(200))
+        );
+    }
+
+    #[test]
+    fn test_redact_write() {
+        assert_eq!(
+            redact_write(
+                "weather,city=new\\ york temperature=82i,note=\"a, b=c \\\"d\\\"\",ok=true 1\nweather temperature=1 2"
+            ),
+            "weather,city=new\\ york temperature=***,note=***,ok=*** 1\nweather temperature=*** 2"
+        );
+        let batch = ["weather temperature=1"; 5].join("\n");
+        assert_eq!(
+            redact_write(&batch),
+            "weather temperature=***\nweather temperature=***\nweather temperature=***\n... 2 more lines"
+        );
+    }
 }
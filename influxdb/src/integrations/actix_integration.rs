@@ -0,0 +1,174 @@
+//! Actix Web Integration for InfluxDB. Provides a middleware recording the duration, status and
+//! route of every request handled by an [`actix_web::App`] as a point of the `http_requests`
+//! measurement, which is written through a [`BufferedWriter`](crate::BufferedWriter).
+//!
+//! ```rust,no_run
+//! use actix_web::{web, App, HttpServer};
+//! use influxdb::integrations::actix_integration::InfluxDbMetrics;
+//! use influxdb::{Client, WriterOptions};
+//!
+//! # fn main() {
+//! let client = Client::new("http://localhost:8086", "metrics");
+//! let (writer, task) = client.buffered_writer(WriterOptions::default());
+//! async_std::task::spawn(task);
+//!
+//! let server = HttpServer::new(move || {
+//!     App::new()
+//!         .wrap(InfluxDbMetrics::new(writer.clone()))
+//!         .route("/users/{id}", web::get().to(|| async { "Hello" }))
+//! });
+//! # }
+//! ```
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use futures::future::{ready, LocalBoxFuture, Ready};
+use std::rc::Rc;
+use std::time::Instant;
+
+use super::http_metrics::{request_point, DEFAULT_MEASUREMENT};
+use crate::BufferedWriter;
+
+/// Middleware factory recording the requests handled by an [`actix_web::App`]
+#[derive(Debug, Clone)]
+pub struct InfluxDbMetrics {
+    writer: BufferedWriter,
+    measurement: Rc<str>,
+}
+
+impl InfluxDbMetrics {
+    /// Creates a middleware factory writing its points with `writer`
+    pub fn new(writer: BufferedWriter) -> Self {
+        InfluxDbMetrics {
+            writer,
+            measurement: DEFAULT_MEASUREMENT.into(),
+        }
+    }
+
+    /// Sets the measurement of request points, defaults to `http_requests`
+    pub fn with_measurement<S>(mut self, measurement: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.measurement = measurement.into().into();
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for InfluxDbMetrics
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = InfluxDbMetricsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(InfluxDbMetricsMiddleware {
+            service: Rc::new(service),
+            writer: self.writer.clone(),
+            measurement: self.measurement.clone(),
+        }))
+    }
+}
+
+/// Middleware created by [`InfluxDbMetrics`]
+#[derive(Debug)]
+pub struct InfluxDbMetricsMiddleware<S> {
+    service: Rc<S>,
+    writer: BufferedWriter,
+    measurement: Rc<str>,
+}
+
+impl<S, B> Service<ServiceRequest> for InfluxDbMetricsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        let started = Instant::now();
+        let method = request.method().to_string();
+        let route = request.match_pattern();
+        let writer = self.writer.clone();
+        let measurement = self.measurement.clone();
+        let service = self.service.clone();
+
+        Box::pin(async move {
+            let result = service.call(request).await;
+            let status = match &result {
+                Ok(response) => response.status(),
+                Err(error) => error.as_response_error().status_code(),
+            };
+            let point = request_point(
+                &measurement,
+                &method,
+                route.as_deref(),
+                status.as_u16(),
+                started.elapsed(),
+            );
+            // a full or stopped writer drops the point instead of failing the request
+            let _ = writer.write(point);
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InfluxDbMetrics;
+    use crate::client::buffered_writer::Command;
+    use crate::{BufferedWriter, Type};
+    use actix_web::test::{call_service, init_service, TestRequest};
+    use actix_web::{web, App, HttpResponse};
+
+    #[test]
+    fn test_request_point() {
+        let (writer, mut receiver) = BufferedWriter::detached();
+        actix_web::rt::System::new().block_on(async move {
+            let app = init_service(
+                App::new()
+                    .wrap(InfluxDbMetrics::new(writer).with_measurement("requests"))
+                    .route("/users/{id}", web::get().to(|| async { "Hello" }))
+                    .route("/fail", web::post().to(HttpResponse::InternalServerError)),
+            )
+            .await;
+
+            let response =
+                call_service(&app, TestRequest::get().uri("/users/42").to_request()).await;
+            assert_eq!(response.status(), 200);
+            let response = call_service(&app, TestRequest::post().uri("/fail").to_request()).await;
+            assert_eq!(response.status(), 500);
+        });
+
+        let point = match receiver.try_recv() {
+            Ok(Command::Write(point, _)) => point,
+            _ => panic!("request was not recorded"),
+        };
+        assert_eq!(point.measurement().name(), "requests");
+        assert_eq!(
+            point.tags(),
+            &[
+                ("method".to_string(), Type::Text("GET".to_string())),
+                ("status".to_string(), Type::Text("200".to_string())),
+                ("route".to_string(), Type::Text("/users/{id}".to_string())),
+            ][..]
+        );
+        assert!(point.fields().iter().any(|(key, _)| key == "duration_ns"));
+
+        match receiver.try_recv() {
+            Ok(Command::Write(point, _)) => assert!(point
+                .tags()
+                .contains(&("status".to_string(), Type::Text("500".to_string())))),
+            _ => panic!("failed request was not recorded"),
+        }
+    }
+}
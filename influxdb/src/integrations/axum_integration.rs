@@ -0,0 +1,159 @@
+//! Axum Integration for InfluxDB. Provides a [`tower_layer::Layer`] recording the duration,
+//! status and route of every request handled by an [`axum::Router`] as a point of the
+//! `http_requests` measurement, which is written through a [`BufferedWriter`](crate::BufferedWriter).
+//!
+//! The layer has to be added with [`route_layer`](axum::Router::route_layer), as the matched route
+//! is only known after routing.
+//!
+//! ```rust,no_run
+//! use axum::{routing::get, Router};
+//! use influxdb::integrations::axum_integration::InfluxDbMetricsLayer;
+//! use influxdb::{Client, WriterOptions};
+//!
+//! # #[async_std::main]
+//! # async fn main() {
+//! let client = Client::new("http://localhost:8086", "metrics");
+//! let (writer, task) = client.buffered_writer(WriterOptions::default());
+//! async_std::task::spawn(task);
+//!
+//! let app: Router = Router::new()
+//!     .route("/users/{id}", get(|| async { "Hello" }))
+//!     .route_layer(InfluxDbMetricsLayer::new(writer));
+//! # }
+//! ```
+
+use axum::extract::{MatchedPath, Request};
+use axum::response::Response;
+use futures::future::BoxFuture;
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use super::http_metrics::{request_point, DEFAULT_MEASUREMENT};
+use crate::BufferedWriter;
+
+/// [`Layer`](tower_layer::Layer) wrapping services in [`InfluxDbMetrics`]
+#[derive(Debug, Clone)]
+pub struct InfluxDbMetricsLayer {
+    writer: BufferedWriter,
+    measurement: String,
+}
+
+impl InfluxDbMetricsLayer {
+    /// Creates a layer writing its points with `writer`
+    pub fn new(writer: BufferedWriter) -> Self {
+        InfluxDbMetricsLayer {
+            writer,
+            measurement: DEFAULT_MEASUREMENT.to_string(),
+        }
+    }
+
+    /// Sets the measurement of request points, defaults to `http_requests`
+    pub fn with_measurement<S>(mut self, measurement: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.measurement = measurement.into();
+        self
+    }
+}
+
+impl<S> Layer<S> for InfluxDbMetricsLayer {
+    type Service = InfluxDbMetrics<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        InfluxDbMetrics {
+            inner,
+            writer: self.writer.clone(),
+            measurement: self.measurement.clone(),
+        }
+    }
+}
+
+/// Service recording the requests handled by the wrapped service
+#[derive(Debug, Clone)]
+pub struct InfluxDbMetrics<S> {
+    inner: S,
+    writer: BufferedWriter,
+    measurement: String,
+}
+
+impl<S> Service<Request> for InfluxDbMetrics<S>
+where
+    S: Service<Request, Response = Response>,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let started = Instant::now();
+        let method = request.method().to_string();
+        let route = request
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str().to_string());
+        let writer = self.writer.clone();
+        let measurement = self.measurement.clone();
+        let response = self.inner.call(request);
+
+        Box::pin(async move {
+            let response = response.await?;
+            let point = request_point(
+                &measurement,
+                &method,
+                route.as_deref(),
+                response.status().as_u16(),
+                started.elapsed(),
+            );
+            // a full or stopped writer drops the point instead of failing the request
+            let _ = writer.write(point);
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InfluxDbMetricsLayer;
+    use crate::client::buffered_writer::Command;
+    use crate::{BufferedWriter, Type};
+    use axum::body::Body;
+    use axum::extract::Request;
+    use axum::routing::get;
+    use axum::Router;
+    use tower_service::Service;
+
+    #[test]
+    fn test_request_point() {
+        let (writer, mut receiver) = BufferedWriter::detached();
+        let mut app: Router = Router::new()
+            .route("/users/{id}", get(|| async { "Hello" }))
+            .route_layer(InfluxDbMetricsLayer::new(writer));
+
+        let request = Request::get("/users/42").body(Body::empty()).unwrap();
+        let response = futures::executor::block_on(app.call(request)).unwrap();
+        assert_eq!(response.status(), 200);
+
+        let point = match receiver.try_recv() {
+            Ok(Command::Write(point, _)) => point,
+            _ => panic!("request was not recorded"),
+        };
+        assert_eq!(point.measurement().name(), "http_requests");
+        assert_eq!(
+            point.tags(),
+            &[
+                ("method".to_string(), Type::Text("GET".to_string())),
+                ("status".to_string(), Type::Text("200".to_string())),
+                ("route".to_string(), Type::Text("/users/{id}".to_string())),
+            ][..]
+        );
+        assert!(point.fields().iter().any(|(key, _)| key == "duration_ns"));
+    }
+}
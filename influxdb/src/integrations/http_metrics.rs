@@ -0,0 +1,31 @@
+//! Points shared by the HTTP middleware integrations
+
+use std::time::Duration;
+
+use crate::{Timestamp, WriteQuery};
+
+/// Measurement of request points, unless configured otherwise
+pub(crate) const DEFAULT_MEASUREMENT: &str = "http_requests";
+
+/// Creates the point of a handled request
+///
+/// The route is the pattern matched by the router, e.g. `/users/{id}`, never the requested path,
+/// which would create one series per user. Requests not matching any route are tagged without it.
+pub(crate) fn request_point(
+    measurement: &str,
+    method: &str,
+    route: Option<&str>,
+    status: u16,
+    duration: Duration,
+) -> WriteQuery {
+    let mut point = WriteQuery::new(Timestamp::now(), measurement)
+        .add_tag("method", method)
+        .add_tag("status", status.to_string());
+    if let Some(route) = route {
+        point = point.add_tag("route", route);
+    }
+    point.add_field(
+        "duration_ns",
+        duration.as_nanos().min(i64::MAX as u128) as i64,
+    )
+}
@@ -0,0 +1,222 @@
+//! Prometheus Integration for InfluxDB. Converts metric families of the
+//! [`prometheus`](https://docs.rs/prometheus) crate into points, so the crate can serve as the
+//! storage side of a small bridge service.
+//!
+//! Each metric becomes a point of a measurement named after its family, tagged with its labels.
+//! The fields depend on the type of the metric, following the format of Telegraf's Prometheus input:
+//!
+//! - counters, gauges and untyped metrics have a single field, named `counter`, `gauge` or `value`
+//! - summaries have the fields `sum`, `count` and one field per quantile, e.g. `0.99`
+//! - histograms have the fields `sum`, `count` and one field per bucket upper bound, e.g. `0.5` or `+Inf`
+//!
+//! ```rust,no_run
+//! use influxdb::{Client, Timestamp};
+//! use prometheus::{Counter, Registry};
+//!
+//! # #[async_std::main]
+//! # async fn main() -> Result<(), influxdb::Error> {
+//! let registry = Registry::new();
+//! let requests = Counter::new("http_requests_total", "Number of HTTP requests").unwrap();
+//! registry.register(Box::new(requests.clone())).unwrap();
+//! requests.inc();
+//!
+//! let client = Client::new("http://localhost:8086", "metrics");
+//! client
+//!     .write_metric_families(&registry.gather(), Timestamp::now())
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A bridge which gathers metrics continuously writes them through a
+//! [`BufferedWriter`](crate::BufferedWriter) instead, which batches the points of several
+//! gatherings and retries failed writes:
+//!
+//! ```rust,no_run
+//! use influxdb::{Client, Timestamp, WriterOptions};
+//! use prometheus::Registry;
+//!
+//! # #[async_std::main]
+//! # async fn main() -> Result<(), influxdb::Error> {
+//! let registry = Registry::new();
+//! let client = Client::new("http://localhost:8086", "metrics");
+//! let (writer, task) = client.buffered_writer(WriterOptions::default());
+//! async_std::task::spawn(task);
+//!
+//! writer.write_metric_families(&registry.gather(), Timestamp::now())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use prometheus::proto::{MetricFamily, MetricType};
+
+use crate::{BufferedWriter, Client, Error, Timestamp, WriteQuery};
+
+/// Converts metric families into one point per metric
+///
+/// Metrics without a timestamp of their own are written with `timestamp`.
+pub fn metric_families_to_points(
+    families: &[MetricFamily],
+    timestamp: Timestamp,
+) -> Vec<WriteQuery> {
+    let mut points = Vec::new();
+    for family in families {
+        for metric in family.get_metric() {
+            let timestamp = match metric.get_timestamp_ms() {
+                ms if ms > 0 => Timestamp::Milliseconds(ms as u128),
+                _ => timestamp,
+            };
+            let mut point = WriteQuery::new(timestamp, family.get_name());
+            for label in metric.get_label() {
+                point = point.add_tag(label.get_name(), label.get_value());
+            }
+
+            point = match family.get_field_type() {
+                MetricType::COUNTER => point.add_field("counter", metric.get_counter().get_value()),
+                MetricType::GAUGE => point.add_field("gauge", metric.get_gauge().get_value()),
+                MetricType::UNTYPED => {
+                    // untyped metrics are only produced when parsing the protobuf format
+                    #[allow(deprecated)]
+                    let value = metric.get_untyped().get_value();
+                    point.add_field("value", value)
+                }
+                MetricType::SUMMARY => {
+                    let summary = metric.get_summary();
+                    let mut point = point
+                        .add_field("sum", summary.get_sample_sum())
+                        .add_field("count", summary.get_sample_count());
+                    for quantile in summary.get_quantile() {
+                        point = point
+                            .add_field(quantile.get_quantile().to_string(), quantile.get_value());
+                    }
+                    point
+                }
+                MetricType::HISTOGRAM => {
+                    let histogram = metric.get_histogram();
+                    let mut point = point
+                        .add_field("sum", histogram.get_sample_sum())
+                        .add_field("count", histogram.get_sample_count());
+                    for bucket in histogram.get_bucket() {
+                        point = point.add_field(
+                            upper_bound(bucket.get_upper_bound()),
+                            bucket.get_cumulative_count(),
+                        );
+                    }
+                    // the implicit `+Inf` bucket is usually not exposed
+                    if !histogram
+                        .get_bucket()
+                        .iter()
+                        .any(|bucket| bucket.get_upper_bound() == f64::INFINITY)
+                    {
+                        point = point.add_field("+Inf", histogram.get_sample_count());
+                    }
+                    point
+                }
+            };
+            points.push(point);
+        }
+    }
+    points
+}
+
+/// Formats the upper bound of a bucket like the `le` label of the Prometheus text format
+fn upper_bound(upper_bound: f64) -> String {
+    if upper_bound == f64::INFINITY {
+        "+Inf".to_string()
+    } else {
+        upper_bound.to_string()
+    }
+}
+
+impl Client {
+    /// Writes the metric families in a single batch, see [`metric_families_to_points`]
+    pub async fn write_metric_families(
+        &self,
+        families: &[MetricFamily],
+        timestamp: Timestamp,
+    ) -> Result<String, Error> {
+        let points = metric_families_to_points(families, timestamp);
+        if points.is_empty() {
+            return Ok(String::new());
+        }
+        self.query(&points).await
+    }
+}
+
+impl BufferedWriter {
+    /// Adds the points of the metric families to the buffer, see [`metric_families_to_points`]
+    ///
+    /// Returns an error if the future writing the points has completed.
+    pub fn write_metric_families(
+        &self,
+        families: &[MetricFamily],
+        timestamp: Timestamp,
+    ) -> Result<(), Error> {
+        metric_families_to_points(families, timestamp)
+            .into_iter()
+            .try_for_each(|point| self.write(point))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::metric_families_to_points;
+    use crate::client::buffered_writer::Command;
+    use crate::{BufferedWriter, Query, Timestamp};
+    use futures::executor::block_on_stream;
+    use prometheus::{Counter, Histogram, HistogramOpts, IntGaugeVec, Opts, Registry};
+
+    #[test]
+    fn test_metric_families_to_points() {
+        let registry = Registry::new();
+        let counter = Counter::new("requests", "Number of requests").unwrap();
+        let gauge =
+            IntGaugeVec::new(Opts::new("connections", "Open connections"), &["host"]).unwrap();
+        let histogram =
+            Histogram::with_opts(HistogramOpts::new("latency", "Latency").buckets(vec![0.5, 1.0]))
+                .unwrap();
+        registry.register(Box::new(counter.clone())).unwrap();
+        registry.register(Box::new(gauge.clone())).unwrap();
+        registry.register(Box::new(histogram.clone())).unwrap();
+
+        counter.inc_by(2.0);
+        gauge.with_label_values(&["a"]).set(3);
+        histogram.observe(0.7);
+
+        let lines = metric_families_to_points(&registry.gather(), Timestamp::Seconds(10))
+            .iter()
+            .map(|point| point.build().unwrap().get())
+            .collect::<Vec<String>>();
+
+        assert_eq!(
+            lines,
+            vec![
+                "connections,host=a gauge=3 10",
+                "latency sum=0.7,count=1i,0.5=0i,1=1i,+Inf=1i 10",
+                "requests counter=2 10",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_buffered_writer() {
+        let registry = Registry::new();
+        let counter = Counter::new("requests", "Number of requests").unwrap();
+        registry.register(Box::new(counter.clone())).unwrap();
+        counter.inc();
+
+        let (writer, receiver) = BufferedWriter::detached();
+        writer
+            .write_metric_families(&registry.gather(), Timestamp::Seconds(10))
+            .unwrap();
+        drop(writer);
+
+        let lines = block_on_stream(receiver)
+            .map(|command| match command {
+                Command::Write(point, None) => point.build().unwrap().get(),
+                _ => panic!("metrics were not written as points"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec!["requests counter=1 10"]);
+    }
+}
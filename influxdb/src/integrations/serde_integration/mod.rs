@@ -7,7 +7,7 @@
 //! `name`, InfluxDB provides alongside query results.
 //!
 //! ```rust,no_run
-//! use futures::prelude::*;
+//! # #[cfg(feature = "client")]
 //! use influxdb::{Client, Query};
 //! use serde::Deserialize;
 //!
@@ -22,6 +22,7 @@
 //!     weather: WeatherWithoutCityName,
 //! }
 //!
+//! # #[cfg(feature = "client")]
 //! # #[async_std::main]
 //! # async fn main() -> Result<(), influxdb::Error> {
 //! let client = Client::new("http://localhost:8086", "test");
@@ -44,16 +45,25 @@
 //!     .collect::<Vec<Weather>>();
 //! # Ok(())
 //! # }
+//! # #[cfg(not(feature = "client"))]
+//! # fn main() {}
 //! ```
 
 mod de;
+mod ser;
+pub mod time_column;
 
-use surf::StatusCode;
+pub use ser::PointSerializer;
 
 use serde::{de::DeserializeOwned, Deserialize};
 
-use crate::{Client, Error, Query, ReadQuery};
+#[cfg(feature = "client")]
+use crate::client::is_select_into;
+use crate::Error;
+#[cfg(feature = "client")]
+use crate::{Client, Measurement, Query, SelectQuery};
 
+#[cfg(feature = "client")]
 #[derive(Deserialize)]
 #[doc(hidden)]
 struct _DatabaseError {
@@ -67,27 +77,27 @@ pub struct DatabaseQueryResult {
 }
 
 impl DatabaseQueryResult {
-    pub fn deserialize_next<T: 'static>(&mut self) -> Result<Return<T>, Error>
+    pub fn deserialize_next<T>(&mut self) -> Result<Return<T>, Error>
     where
-        T: DeserializeOwned + Send,
+        T: DeserializeOwned + Send + 'static,
     {
         serde_json::from_value::<Return<T>>(self.results.remove(0)).map_err(|err| {
             Error::DeserializationError {
                 error: format!("could not deserialize: {}", err),
+                source: crate::error::source(err),
             }
         })
     }
 
-    pub fn deserialize_next_tagged<TAG, T: 'static>(
-        &mut self,
-    ) -> Result<TaggedReturn<TAG, T>, Error>
+    pub fn deserialize_next_tagged<TAG, T>(&mut self) -> Result<TaggedReturn<TAG, T>, Error>
     where
         TAG: DeserializeOwned + Send,
-        T: DeserializeOwned + Send,
+        T: DeserializeOwned + Send + 'static,
     {
         serde_json::from_value::<TaggedReturn<TAG, T>>(self.results.remove(0)).map_err(|err| {
             Error::DeserializationError {
                 error: format!("could not deserialize: {}", err),
+                source: crate::error::source(err),
             }
         })
     }
@@ -122,13 +132,17 @@ pub struct TaggedSeries<TAG, T> {
     pub values: Vec<T>,
 }
 
+#[cfg(feature = "client")]
 impl Client {
-    pub async fn json_query(&self, q: ReadQuery) -> Result<DatabaseQueryResult, Error> {
+    pub async fn json_query<Q>(&self, q: Q) -> Result<DatabaseQueryResult, Error>
+    where
+        Q: Query,
+    {
         let query = q.build().map_err(|err| Error::InvalidQueryError {
             error: format!("{}", err),
         })?;
 
-        let read_query = query.get();
+        let read_query = self.route_read(query.get())?;
         let read_query_lower = read_query.to_lowercase();
 
         if !read_query_lower.contains("select") && !read_query_lower.contains("show") {
@@ -140,46 +154,88 @@ impl Client {
             return Err(error);
         }
 
-        let url = &format!("{}/query", &self.url);
+        let url = self.endpoint("query")?;
+        let request_builder = if is_select_into(&read_query) {
+            self.client.post(url)
+        } else {
+            self.client.get(url)
+        };
         let mut parameters = self.parameters.as_ref().clone();
         parameters.insert("q", read_query);
-        let request = self
-            .client
-            .get(url)
-            .query(&parameters)
-            .map_err(|err| Error::UrlConstructionError {
-                error: err.to_string(),
-            })?
-            .build();
-
-        let mut res = self
-            .client
-            .send(request)
-            .await
-            .map_err(|err| Error::ConnectionError {
-                error: err.to_string(),
-            })?;
-
-        match res.status() {
-            StatusCode::Unauthorized => return Err(Error::AuthorizationError),
-            StatusCode::Forbidden => return Err(Error::AuthenticationError),
-            _ => {}
-        }
-
-        let body = res.body_bytes().await.map_err(|err| Error::ProtocolError {
-            error: err.to_string(),
-        })?;
+        let request_builder =
+            request_builder
+                .query(&parameters)
+                .map_err(|err| Error::UrlConstructionError {
+                    error: err.to_string(),
+                })?;
+        let (_, body) = self.send_with_status(request_builder).await?;
 
         // Try parsing InfluxDBs { "error": "error message here" }
-        if let Ok(error) = serde_json::from_slice::<_DatabaseError>(&body) {
-            return Err(Error::DatabaseError { error: error.error });
+        if let Ok(error) = serde_json::from_str::<_DatabaseError>(&body) {
+            return Err(Error::DatabaseError {
+                error: error.error,
+                status: None,
+                request_id: None,
+            });
         }
 
         // Json has another structure, let's try actually parsing it to the type we're deserializing
-        serde_json::from_slice::<DatabaseQueryResult>(&body).map_err(|err| {
+        serde_json::from_str::<DatabaseQueryResult>(&body).map_err(|err| {
             Error::DeserializationError {
                 error: format!("serde error: {}", err),
+                source: crate::error::source(err),
             }
         })
     }
+
+    /// Returns the newest point of each series of the measurement
+    ///
+    /// Series are grouped by the given tags, or by all tags if `group_by_tags` is empty.
+    /// Each returned [`TaggedSeries`] contains exactly one value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::Client;
+    /// use serde::Deserialize;
+    /// use std::collections::HashMap;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Cpu {
+    ///     time: String,
+    ///     usage: f64,
+    /// }
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("http://localhost:8086", "test");
+    /// for series in client.last_values::<HashMap<String, String>, Cpu, _>("cpu", &["host"]).await? {
+    ///     println!("{}: {}", series.tags["host"], series.values[0].usage);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn last_values<TAG, T, M>(
+        &self,
+        measurement: M,
+        group_by_tags: &[&str],
+    ) -> Result<Vec<TaggedSeries<TAG, T>>, Error>
+    where
+        TAG: DeserializeOwned + Send,
+        T: DeserializeOwned + Send + 'static,
+        M: Into<Measurement>,
+    {
+        // `ORDER BY time DESC LIMIT 1` instead of `last(*)` keeps the column names
+        // and the timestamp of the point instead of the start of the time range
+        let mut query = SelectQuery::new().from(measurement).order_desc().limit(1);
+        if group_by_tags.is_empty() {
+            query = query.group_by_all();
+        }
+        for tag in group_by_tags {
+            query = query.group_by(*tag);
+        }
+
+        let mut result = self.json_query(query).await?;
+        Ok(result.deserialize_next_tagged::<TAG, T>()?.series)
+    }
 }
@@ -0,0 +1,480 @@
+//! Serializer turning any `Serialize` struct into a point, as an alternative to the derive macro
+
+use serde::ser::{self, Impossible, Serialize, SerializeMap, SerializeStruct, Serializer};
+use std::fmt;
+
+use crate::{Error, Measurement, Timestamp, Type, WriteQuery};
+
+/// Configures how [`Serialize`](serde::Serialize) types are turned into points
+///
+/// Fields of the type become fields of the point, unless they are configured as tags or ignored.
+/// Fields which are `None` are skipped. Structs, maps with string keys and newtype structs around
+/// them can be serialized, nested values are not supported.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::integrations::serde_integration::PointSerializer;
+/// use influxdb::{Query, Timestamp};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct WeatherReading {
+///     humidity: i32,
+///     wind_strength: Option<u64>,
+///     location: String,
+/// }
+///
+/// let reading = WeatherReading {
+///     humidity: 30,
+///     wind_strength: Some(5),
+///     location: "Berlin".to_string(),
+/// };
+/// let query = PointSerializer::new("weather")
+///     .tag("location")
+///     .to_query(&reading, Timestamp::Hours(1))
+///     .unwrap();
+///
+/// assert_eq!(
+///     query.build().unwrap(),
+///     "weather,location=Berlin humidity=30i,wind_strength=5i 1"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct PointSerializer {
+    measurement: Measurement,
+    tags: Vec<String>,
+    ignored: Vec<String>,
+}
+
+impl PointSerializer {
+    /// Creates a [`PointSerializer`] writing points to the given measurement
+    pub fn new<M>(measurement: M) -> Self
+    where
+        M: Into<Measurement>,
+    {
+        PointSerializer {
+            measurement: measurement.into(),
+            tags: Vec::new(),
+            ignored: Vec::new(),
+        }
+    }
+
+    /// Writes the field with the given name as tag
+    pub fn tag<S>(mut self, field: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.tags.push(field.into());
+        self
+    }
+
+    /// Doesn't write the field with the given name
+    pub fn ignore<S>(mut self, field: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.ignored.push(field.into());
+        self
+    }
+
+    /// Serializes the value into a [`WriteQuery`](crate::WriteQuery)
+    pub fn to_query<T>(&self, value: &T, timestamp: Timestamp) -> Result<WriteQuery, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let entries =
+            value
+                .serialize(EntriesSerializer)
+                .map_err(|err| Error::InvalidQueryError {
+                    error: format!("could not serialize point: {}", err),
+                })?;
+
+        let mut query = WriteQuery::new(timestamp, self.measurement.clone());
+        for (key, value) in entries {
+            if self.ignored.contains(&key) {
+                continue;
+            }
+            query = if self.tags.contains(&key) {
+                query.add_tag(key, value)
+            } else {
+                query.add_field(key, value)
+            };
+        }
+        Ok(query)
+    }
+}
+
+#[derive(Debug)]
+struct SerializeError(String);
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for SerializeError {}
+
+impl ser::Error for SerializeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerializeError(msg.to_string())
+    }
+}
+
+fn unsupported<T>(what: &str) -> Result<T, SerializeError> {
+    Err(SerializeError(format!("{} is not supported", what)))
+}
+
+/// Serializes the top level struct or map into its entries
+struct EntriesSerializer;
+
+struct Entries {
+    entries: Vec<(String, Type)>,
+    next_key: Option<String>,
+}
+
+impl Entries {
+    fn push<T>(&mut self, key: String, value: &T) -> Result<(), SerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        if let Some(value) = value.serialize(ValueSerializer)? {
+            self.entries.push((key, value));
+        }
+        Ok(())
+    }
+}
+
+macro_rules! unsupported_entries {
+    ($($method:ident($($arg:ty),*) -> $ok:ty;)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ok, Self::Error> {
+                unsupported("a point which is not a struct or map")
+            }
+        )*
+    };
+}
+
+impl Serializer for EntriesSerializer {
+    type Ok = Vec<(String, Type)>;
+    type Error = SerializeError;
+    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = Entries;
+    type SerializeStruct = Entries;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+    unsupported_entries! {
+        serialize_bool(bool) -> Self::Ok;
+        serialize_i8(i8) -> Self::Ok;
+        serialize_i16(i16) -> Self::Ok;
+        serialize_i32(i32) -> Self::Ok;
+        serialize_i64(i64) -> Self::Ok;
+        serialize_u8(u8) -> Self::Ok;
+        serialize_u16(u16) -> Self::Ok;
+        serialize_u32(u32) -> Self::Ok;
+        serialize_u64(u64) -> Self::Ok;
+        serialize_f32(f32) -> Self::Ok;
+        serialize_f64(f64) -> Self::Ok;
+        serialize_char(char) -> Self::Ok;
+        serialize_str(&str) -> Self::Ok;
+        serialize_bytes(&[u8]) -> Self::Ok;
+        serialize_none() -> Self::Ok;
+        serialize_unit() -> Self::Ok;
+        serialize_unit_struct(&'static str) -> Self::Ok;
+        serialize_unit_variant(&'static str, u32, &'static str) -> Self::Ok;
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        unsupported("a point which is not a struct or map")
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(Entries {
+            entries: Vec::with_capacity(len.unwrap_or(0)),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+}
+
+impl SerializeStruct for Entries {
+    type Ok = Vec<(String, Type)>;
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.entries)
+    }
+}
+
+impl SerializeMap for Entries {
+    type Ok = Vec<(String, Type)>;
+    type Error = SerializeError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        match key.serialize(ValueSerializer)? {
+            Some(Type::Text(key)) => {
+                self.next_key = Some(key);
+                Ok(())
+            }
+            _ => unsupported("a map key which is not a string"),
+        }
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| SerializeError("value without key".to_string()))?;
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.entries)
+    }
+}
+
+/// Serializes a single field or tag value, `None` for missing values
+struct ValueSerializer;
+
+macro_rules! serialize_values {
+    ($($method:ident($arg:ty) => $variant:ident;)*) => {
+        $(
+            fn $method(self, v: $arg) -> Result<Self::Ok, Self::Error> {
+                Ok(Some(Type::$variant(v.into())))
+            }
+        )*
+    };
+}
+
+macro_rules! unsupported_values {
+    ($($method:ident($($arg:ty),*) -> $ok:ty;)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ok, Self::Error> {
+                unsupported("a nested value")
+            }
+        )*
+    };
+}
+
+impl Serializer for ValueSerializer {
+    type Ok = Option<Type>;
+    type Error = SerializeError;
+    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+    serialize_values! {
+        serialize_bool(bool) => Boolean;
+        serialize_i8(i8) => SignedInteger;
+        serialize_i16(i16) => SignedInteger;
+        serialize_i32(i32) => SignedInteger;
+        serialize_i64(i64) => SignedInteger;
+        serialize_u8(u8) => UnsignedInteger;
+        serialize_u16(u16) => UnsignedInteger;
+        serialize_u32(u32) => UnsignedInteger;
+        serialize_u64(u64) => UnsignedInteger;
+        serialize_f32(f32) => Float;
+        serialize_f64(f64) => Float;
+        serialize_str(&str) => Text;
+    }
+
+    unsupported_values! {
+        serialize_bytes(&[u8]) -> Self::Ok;
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Type::Text(v.to_string())))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Type::Text(variant.to_string())))
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        unsupported("a nested value")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PointSerializer;
+    use crate::{Query, Timestamp};
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Condition {
+        Sunny,
+    }
+
+    #[derive(Serialize)]
+    struct Weather {
+        temperature: f64,
+        humidity: Option<i32>,
+        raining: bool,
+        condition: Condition,
+        location: String,
+        station: u32,
+    }
+
+    #[test]
+    fn test_struct() {
+        let weather = Weather {
+            temperature: 21.5,
+            humidity: None,
+            raining: false,
+            condition: Condition::Sunny,
+            location: "Berlin".to_string(),
+            station: 7,
+        };
+        let query = PointSerializer::new("weather")
+            .tag("location")
+            .ignore("station")
+            .to_query(&weather, Timestamp::Seconds(1))
+            .unwrap();
+
+        assert_eq!(
+            query.build().unwrap(),
+            r#"weather,location=Berlin temperature=21.5,raining=false,condition="sunny" 1"#
+        );
+    }
+
+    #[test]
+    fn test_map() {
+        let mut values = BTreeMap::new();
+        values.insert("host", "a");
+        values.insert("status", "ok");
+        let query = PointSerializer::new("status")
+            .tag("host")
+            .to_query(&values, Timestamp::Seconds(1))
+            .unwrap();
+
+        assert_eq!(query.build().unwrap(), r#"status,host=a status="ok" 1"#);
+    }
+
+    #[test]
+    fn test_unsupported() {
+        #[derive(Serialize)]
+        struct Nested {
+            weather: Vec<i32>,
+        }
+
+        let serializer = PointSerializer::new("weather");
+        assert!(serializer
+            .to_query(&Nested { weather: vec![1] }, Timestamp::Seconds(1))
+            .is_err());
+        assert!(serializer.to_query(&5, Timestamp::Seconds(1)).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+//! Deserialization of the `time` column into time types
+//!
+//! InfluxDB returns times as RFC3339 strings, or as integers if the query requested an epoch.
+//! Fields deserialized with [`deserialize`] accept both and convert them into any type
+//! implementing [`FromTimeColumn`], e.g. `DateTime<Utc>`, `SystemTime` or, with the `time03`
+//! feature, `time::OffsetDateTime`. Integers are
+//! interpreted as nanoseconds since the unix epoch.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use chrono::{DateTime, Utc};
+//! use serde::Deserialize;
+//! use std::time::SystemTime;
+//!
+//! #[derive(Deserialize)]
+//! struct Weather {
+//!     #[serde(deserialize_with = "influxdb::integrations::serde_integration::time_column::deserialize")]
+//!     time: DateTime<Utc>,
+//!     temperature: i32,
+//! }
+//!
+//! #[derive(Deserialize)]
+//! struct WeatherSystemTime {
+//!     #[serde(deserialize_with = "influxdb::integrations::serde_integration::time_column::deserialize")]
+//!     time: SystemTime,
+//!     temperature: i32,
+//! }
+//!
+//! let weather: Weather =
+//!     serde_json::from_str(r#"{"time": "1970-01-01T00:00:01.5Z", "temperature": 82}"#).unwrap();
+//! assert_eq!(weather.time.timestamp_millis(), 1500);
+//!
+//! let weather: WeatherSystemTime =
+//!     serde_json::from_str(r#"{"time": 1500000000, "temperature": 82}"#).unwrap();
+//! assert_eq!(weather.time, SystemTime::UNIX_EPOCH + std::time::Duration::from_millis(1500));
+//! ```
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::de::{self, Deserializer, Visitor};
+use std::convert::TryFrom;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Time types the `time` column can be deserialized into
+pub trait FromTimeColumn: Sized {
+    /// Converts nanoseconds since the unix epoch, returns `None` if they are out of range
+    fn from_unix_nanos(nanos: i128) -> Option<Self>;
+}
+
+impl FromTimeColumn for DateTime<Utc> {
+    fn from_unix_nanos(nanos: i128) -> Option<Self> {
+        i64::try_from(nanos)
+            .ok()
+            .map(|nanos| Utc.timestamp_nanos(nanos))
+    }
+}
+
+impl FromTimeColumn for SystemTime {
+    fn from_unix_nanos(nanos: i128) -> Option<Self> {
+        let since_epoch = Duration::from_nanos(u64::try_from(nanos.unsigned_abs()).ok()?);
+        if nanos >= 0 {
+            UNIX_EPOCH.checked_add(since_epoch)
+        } else {
+            UNIX_EPOCH.checked_sub(since_epoch)
+        }
+    }
+}
+
+#[cfg(feature = "time03")]
+impl FromTimeColumn for ::time::OffsetDateTime {
+    fn from_unix_nanos(nanos: i128) -> Option<Self> {
+        ::time::OffsetDateTime::from_unix_timestamp_nanos(nanos).ok()
+    }
+}
+
+/// Deserializes an RFC3339 string or nanoseconds since the unix epoch, see the [module docs](self)
+pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromTimeColumn,
+{
+    let nanos = deserializer.deserialize_any(TimeColumnVisitor)?;
+    T::from_unix_nanos(nanos)
+        .ok_or_else(|| de::Error::custom(format!("time {}ns is out of range", nanos)))
+}
+
+struct TimeColumnVisitor;
+
+impl<'de> Visitor<'de> for TimeColumnVisitor {
+    type Value = i128;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an RFC3339 time or nanoseconds since the unix epoch")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let time = DateTime::parse_from_rfc3339(v).map_err(E::custom)?;
+        Ok(
+            i128::from(time.timestamp()) * 1_000_000_000
+                + i128::from(time.timestamp_subsec_nanos()),
+        )
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(i128::from(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(i128::from(v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::deserialize;
+    use chrono::{DateTime, TimeZone, Utc};
+    use serde::Deserialize;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Row<T: super::FromTimeColumn> {
+        #[serde(deserialize_with = "deserialize")]
+        time: T,
+    }
+
+    #[test]
+    fn test_rfc3339() {
+        let row: Row<DateTime<Utc>> =
+            serde_json::from_str(r#"{"time": "2021-03-08T12:30:00.000000001Z"}"#).unwrap();
+        assert_eq!(
+            row.time,
+            Utc.with_ymd_and_hms(2021, 3, 8, 12, 30, 0).unwrap() + chrono::Duration::nanoseconds(1)
+        );
+
+        let row: Row<SystemTime> =
+            serde_json::from_str(r#"{"time": "1969-12-31T23:59:59Z"}"#).unwrap();
+        assert_eq!(row.time, UNIX_EPOCH - Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_epoch() {
+        let row: Row<DateTime<Utc>> = serde_json::from_str(r#"{"time": 1500}"#).unwrap();
+        assert_eq!(row.time, Utc.timestamp_nanos(1500));
+
+        let row: Row<SystemTime> = serde_json::from_str(r#"{"time": -1500}"#).unwrap();
+        assert_eq!(row.time, UNIX_EPOCH - Duration::from_nanos(1500));
+    }
+
+    #[test]
+    #[cfg(feature = "time03")]
+    fn test_offset_date_time() {
+        use time::macros::datetime;
+
+        let row: Row<time::OffsetDateTime> =
+            serde_json::from_str(r#"{"time": "2021-03-08T12:30:00Z"}"#).unwrap();
+        assert_eq!(row.time, datetime!(2021-03-08 12:30 UTC));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(serde_json::from_str::<Row<SystemTime>>(r#"{"time": "yesterday"}"#).is_err());
+        assert!(serde_json::from_str::<Row<SystemTime>>(r#"{"time": true}"#).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+//! Tracing Integration for InfluxDB. Provides a [`tracing_subscriber::Layer`] recording the spans
+//! and events of an application as points, which are written through a
+//! [`BufferedWriter`](crate::BufferedWriter).
+//!
+//! - every closed span becomes a point of the `tracing_spans` measurement, with the field
+//!   `duration_ns` holding the time between the creation and the closing of the span
+//! - every event becomes a point of the `tracing_events` measurement, with the field `count` set to
+//!   one, so the number of events can be summed up per interval
+//!
+//! Both are tagged with the `name`, `target` and `level` of the span or event.
+//!
+//! ```rust,no_run
+//! use influxdb::integrations::tracing_integration::InfluxDbLayer;
+//! use influxdb::{Client, WriterOptions};
+//! use tracing_subscriber::layer::SubscriberExt;
+//!
+//! # #[async_std::main]
+//! # async fn main() {
+//! let client = Client::new("http://localhost:8086", "telemetry");
+//! let (writer, task) = client.buffered_writer(WriterOptions::default());
+//! async_std::task::spawn(task);
+//!
+//! tracing::subscriber::set_global_default(
+//!     tracing_subscriber::registry().with(InfluxDbLayer::new(writer)),
+//! )
+//! .unwrap();
+//! # }
+//! ```
+//!
+//! Spans and events of this crate and of the HTTP clients it uses aren't recorded by default, see
+//! [`InfluxDbLayer::ignore_target`], as writing the points would record new ones with every
+//! flush, so the writer would never go idle.
+
+use std::time::Instant;
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::{BufferedWriter, Timestamp, WriteQuery};
+
+/// Targets of the spans and events this crate and the HTTP clients it uses emit while writing
+const OWN_TARGETS: &[&str] = &[
+    "influxdb",
+    "surf",
+    "http_client",
+    "isahc",
+    "hyper",
+    "h2",
+    "async_h1",
+    "rustls",
+];
+
+/// [`Layer`](tracing_subscriber::Layer) writing spans and events as points
+#[derive(Debug, Clone)]
+pub struct InfluxDbLayer {
+    writer: BufferedWriter,
+    span_measurement: String,
+    event_measurement: String,
+    ignored_targets: Vec<String>,
+}
+
+impl InfluxDbLayer {
+    /// Creates a layer writing its points with `writer`
+    pub fn new(writer: BufferedWriter) -> Self {
+        InfluxDbLayer {
+            writer,
+            span_measurement: "tracing_spans".to_string(),
+            event_measurement: "tracing_events".to_string(),
+            ignored_targets: OWN_TARGETS
+                .iter()
+                .map(|target| target.to_string())
+                .collect(),
+        }
+    }
+
+    /// Doesn't record the spans and events of `target` and of its modules, e.g. `sqlx` ignores
+    /// `sqlx::query` as well
+    ///
+    /// The targets of this crate and of the HTTP clients it uses are ignored by default, see
+    /// [`record_all_targets`](InfluxDbLayer::record_all_targets).
+    pub fn ignore_target<S>(mut self, target: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.ignored_targets.push(target.into());
+        self
+    }
+
+    /// Records the spans and events of all targets, including the ones of this crate and of the
+    /// HTTP clients it uses, e.g. to trace the requests to the server with another writer
+    pub fn record_all_targets(mut self) -> Self {
+        self.ignored_targets.clear();
+        self
+    }
+
+    fn records(&self, metadata: &Metadata<'_>) -> bool {
+        let target = metadata.target();
+        !self.ignored_targets.iter().any(|ignored| {
+            target
+                .strip_prefix(ignored.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+        })
+    }
+
+    /// Sets the measurement of span points, defaults to `tracing_spans`
+    pub fn with_span_measurement<S>(mut self, measurement: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.span_measurement = measurement.into();
+        self
+    }
+
+    /// Sets the measurement of event points, defaults to `tracing_events`
+    pub fn with_event_measurement<S>(mut self, measurement: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.event_measurement = measurement.into();
+        self
+    }
+
+    fn point(&self, measurement: &str, metadata: &Metadata<'_>) -> WriteQuery {
+        WriteQuery::new(Timestamp::now(), measurement)
+            .add_tag("name", metadata.name())
+            .add_tag("target", metadata.target())
+            .add_tag("level", metadata.level().as_str())
+    }
+}
+
+/// Stored in the extensions of a span when it is created
+struct Opened(Instant);
+
+impl<S> Layer<S> for InfluxDbLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if !self.records(attrs.metadata()) {
+            return;
+        }
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(Opened(Instant::now()));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if !self.records(event.metadata()) {
+            return;
+        }
+        let point = self
+            .point(&self.event_measurement, event.metadata())
+            .add_field("count", 1i64);
+        // there is nowhere to report errors to, points of a full or stopped writer are dropped
+        let _ = self.writer.write(point);
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let span = match ctx.span(&id) {
+            Some(span) => span,
+            None => return,
+        };
+        let opened = match span.extensions().get::<Opened>() {
+            Some(Opened(opened)) => *opened,
+            None => return,
+        };
+        let duration = opened.elapsed().as_nanos().min(i64::MAX as u128) as i64;
+        let point = self
+            .point(&self.span_measurement, span.metadata())
+            .add_field("duration_ns", duration);
+        let _ = self.writer.write(point);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InfluxDbLayer;
+    use crate::client::buffered_writer::Command;
+    use crate::{BufferedWriter, Type};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_spans_and_events() {
+        let (writer, mut receiver) = BufferedWriter::detached();
+        let subscriber = tracing_subscriber::registry().with(InfluxDbLayer::new(writer));
+        tracing::subscriber::with_default(subscriber, || {
+            // the targets of this crate are ignored
+            tracing::info_span!(target: "app", "work")
+                .in_scope(|| tracing::warn!(target: "app", "hello"));
+        });
+
+        let mut points = Vec::new();
+        while let Ok(Command::Write(point, _)) = receiver.try_recv() {
+            points.push(point);
+        }
+        assert_eq!(points.len(), 2);
+
+        let event = &points[0];
+        assert_eq!(event.measurement().name(), "tracing_events");
+        assert!(event
+            .tags()
+            .iter()
+            .any(|(key, value)| key == "level" && *value == Type::Text("WARN".to_string())));
+        assert!(event.fields().iter().any(|(key, _)| key == "count"));
+
+        let span = &points[1];
+        assert_eq!(span.measurement().name(), "tracing_spans");
+        assert!(span
+            .tags()
+            .iter()
+            .any(|(key, value)| key == "name" && *value == Type::Text("work".to_string())));
+        assert!(span.fields().iter().any(|(key, _)| key == "duration_ns"));
+    }
+
+    #[test]
+    fn test_ignored_targets() {
+        let record = |layer: InfluxDbLayer| {
+            tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+                tracing::debug_span!(target: "influxdb::client", "influxdb_request").in_scope(
+                    || {
+                        tracing::debug!(target: "surf::middleware", "sending");
+                        tracing::info!(target: "influxdb_app", "written");
+                        tracing::info!(target: "sqlx::query", "select");
+                    },
+                );
+            });
+        };
+
+        let (writer, mut receiver) = BufferedWriter::detached();
+        record(InfluxDbLayer::new(writer).ignore_target("sqlx"));
+        let mut targets = Vec::new();
+        while let Ok(Command::Write(point, _)) = receiver.try_recv() {
+            targets.extend(
+                point
+                    .tags()
+                    .iter()
+                    .filter(|(key, _)| key == "target")
+                    .cloned(),
+            );
+        }
+        assert_eq!(
+            targets,
+            vec![("target".to_string(), Type::Text("influxdb_app".to_string()))]
+        );
+
+        let (writer, mut receiver) = BufferedWriter::detached();
+        record(InfluxDbLayer::new(writer).record_all_targets());
+        let mut points = 0;
+        while let Ok(Command::Write(_, _)) = receiver.try_recv() {
+            points += 1;
+        }
+        assert_eq!(points, 4);
+    }
+}
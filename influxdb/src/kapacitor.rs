@@ -0,0 +1,468 @@
+//! Client for the HTTP API of [Kapacitor](https://docs.influxdata.com/kapacitor/v1.6/working/api/),
+//! to define tasks and to record and replay data for testing them.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use influxdb::kapacitor::{KapacitorClient, TaskDefinition};
+//!
+//! # #[async_std::main]
+//! # async fn main() -> Result<(), influxdb::Error> {
+//! let kapacitor = KapacitorClient::new("http://localhost:9092").with_auth("admin", "secret");
+//! let task = TaskDefinition::stream(
+//!     "cpu_alert",
+//!     r#"stream |from().measurement('cpu') |alert().crit(lambda: "usage_idle" < 10)"#,
+//! )
+//! .dbrp("telegraf", "autogen")
+//! .enabled();
+//!
+//! kapacitor.define_task(&task).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use surf::http::auth::BasicAuth;
+use surf::http::Method;
+use surf::{Client as SurfClient, StatusCode, Url};
+
+use crate::Error;
+
+/// Whether a task processes a stream of points as they are written, or queries batches of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskType {
+    Stream,
+    Batch,
+}
+
+/// Whether a task is running
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    Enabled,
+    Disabled,
+}
+
+/// A database and retention policy a task reads from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DbRp {
+    pub db: String,
+    pub rp: String,
+}
+
+/// Definition of a task, passed to [`KapacitorClient::define_task`](crate::kapacitor::KapacitorClient::define_task)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TaskDefinition {
+    id: String,
+    #[serde(rename = "type")]
+    task_type: TaskType,
+    dbrps: Vec<DbRp>,
+    script: String,
+    status: TaskStatus,
+}
+
+impl TaskDefinition {
+    /// Creates a disabled stream task running a TICKscript
+    pub fn stream<S1, S2>(id: S1, script: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        TaskDefinition {
+            id: id.into(),
+            task_type: TaskType::Stream,
+            dbrps: Vec::new(),
+            script: script.into(),
+            status: TaskStatus::Disabled,
+        }
+    }
+
+    /// Creates a disabled batch task running a TICKscript
+    pub fn batch<S1, S2>(id: S1, script: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        TaskDefinition {
+            task_type: TaskType::Batch,
+            ..TaskDefinition::stream(id, script)
+        }
+    }
+
+    /// Adds a database and retention policy the task reads from
+    pub fn dbrp<S1, S2>(mut self, db: S1, rp: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        self.dbrps.push(DbRp {
+            db: db.into(),
+            rp: rp.into(),
+        });
+        self
+    }
+
+    /// Enables the task once it is defined
+    pub fn enabled(mut self) -> Self {
+        self.status = TaskStatus::Enabled;
+        self
+    }
+}
+
+/// A task as stored by Kapacitor
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Task {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub task_type: TaskType,
+    #[serde(default)]
+    pub dbrps: Vec<DbRp>,
+    #[serde(default)]
+    pub script: String,
+    pub status: TaskStatus,
+    /// Whether the task is currently processing data
+    #[serde(default)]
+    pub executing: bool,
+    /// The last error of the task, empty if there was none
+    #[serde(default)]
+    pub error: String,
+}
+
+/// Progress of a recording or a replay
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Running,
+    Finished,
+    Failed,
+}
+
+/// A recording of data, which can be replayed to test tasks
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Recording {
+    pub id: String,
+    pub status: Status,
+    /// Share of the recording which is done, between zero and one
+    #[serde(default)]
+    pub progress: f64,
+    /// Size of the recording in bytes
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub error: String,
+}
+
+/// A replay of a recording through a task
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Replay {
+    pub id: String,
+    pub task: String,
+    pub recording: String,
+    pub status: Status,
+    /// Share of the replay which is done, between zero and one
+    #[serde(default)]
+    pub progress: f64,
+    #[serde(default)]
+    pub error: String,
+}
+
+/// Speed at which a recording is replayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplayClock {
+    /// Replays the data as fast as possible
+    Fast,
+    /// Replays the data with the time that passed between the recorded points
+    Real,
+}
+
+#[derive(Deserialize)]
+struct Tasks {
+    tasks: Vec<Task>,
+}
+
+/// Client for the HTTP API of Kapacitor
+#[derive(Clone, Debug)]
+pub struct KapacitorClient {
+    url: String,
+    auth: Option<(String, String)>,
+    client: SurfClient,
+}
+
+impl KapacitorClient {
+    /// Creates a client for the Kapacitor running at `url`, e.g. `http://localhost:9092`
+    pub fn new<S>(url: S) -> Self
+    where
+        S: Into<String>,
+    {
+        KapacitorClient {
+            url: url.into().trim_end_matches('/').to_string(),
+            auth: None,
+            client: SurfClient::new(),
+        }
+    }
+
+    /// Authenticates requests with the given username and password
+    pub fn with_auth<S1, S2>(mut self, username: S1, password: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        self.auth = Some((username.into(), password.into()));
+        self
+    }
+
+    /// Returns the URL of the Kapacitor
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Checks whether Kapacitor is reachable
+    pub async fn ping(&self) -> Result<(), Error> {
+        self.send(Method::Get, "ping", None).await.map(|_| ())
+    }
+
+    /// Defines a new task, failing if a task with the same ID already exists
+    pub async fn define_task(&self, task: &TaskDefinition) -> Result<Task, Error> {
+        let body = to_json(task)?;
+        self.send_json(Method::Post, "tasks", Some(body)).await
+    }
+
+    /// Returns a task
+    pub async fn task(&self, id: &str) -> Result<Task, Error> {
+        self.send_json(Method::Get, &format!("tasks/{}", id), None)
+            .await
+    }
+
+    /// Returns all tasks
+    pub async fn tasks(&self) -> Result<Vec<Task>, Error> {
+        let tasks: Tasks = self.send_json(Method::Get, "tasks", None).await?;
+        Ok(tasks.tasks)
+    }
+
+    /// Replaces the TICKscript of a task
+    pub async fn update_script(&self, id: &str, script: &str) -> Result<Task, Error> {
+        let body = json!({ "script": script }).to_string();
+        self.send_json(Method::Patch, &format!("tasks/{}", id), Some(body))
+            .await
+    }
+
+    /// Enables a task, so it starts processing data
+    pub async fn enable_task(&self, id: &str) -> Result<Task, Error> {
+        self.set_status(id, TaskStatus::Enabled).await
+    }
+
+    /// Disables a task, so it stops processing data
+    pub async fn disable_task(&self, id: &str) -> Result<Task, Error> {
+        self.set_status(id, TaskStatus::Disabled).await
+    }
+
+    async fn set_status(&self, id: &str, status: TaskStatus) -> Result<Task, Error> {
+        let body = to_json(&json!({ "status": status }))?;
+        self.send_json(Method::Patch, &format!("tasks/{}", id), Some(body))
+            .await
+    }
+
+    /// Deletes a task
+    pub async fn delete_task(&self, id: &str) -> Result<(), Error> {
+        self.send(Method::Delete, &format!("tasks/{}", id), None)
+            .await
+            .map(|_| ())
+    }
+
+    /// Starts recording the stream of points a task reads until `stop`
+    pub async fn record_stream(&self, task: &str, stop: DateTime<Utc>) -> Result<Recording, Error> {
+        let body = json!({ "task": task, "stop": rfc3339(stop) }).to_string();
+        self.send_json(Method::Post, "recordings/stream", Some(body))
+            .await
+    }
+
+    /// Starts recording the batches a task queries between `start` and `stop`
+    pub async fn record_batch(
+        &self,
+        task: &str,
+        start: DateTime<Utc>,
+        stop: DateTime<Utc>,
+    ) -> Result<Recording, Error> {
+        let body = json!({ "task": task, "start": rfc3339(start), "stop": rfc3339(stop) });
+        self.send_json(Method::Post, "recordings/batch", Some(body.to_string()))
+            .await
+    }
+
+    /// Returns a recording, to check whether it finished
+    pub async fn recording(&self, id: &str) -> Result<Recording, Error> {
+        self.send_json(Method::Get, &format!("recordings/{}", id), None)
+            .await
+    }
+
+    /// Starts replaying a recording through a task
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use chrono::{Duration, Utc};
+    /// use influxdb::kapacitor::{KapacitorClient, ReplayClock, Status};
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let kapacitor = KapacitorClient::new("http://localhost:9092");
+    /// let stop = Utc::now();
+    /// let recording = kapacitor
+    ///     .record_batch("cpu_alert", stop - Duration::hours(1), stop)
+    ///     .await?;
+    /// while kapacitor.recording(&recording.id).await?.status == Status::Running {
+    ///     async_std::task::sleep(std::time::Duration::from_secs(1)).await;
+    /// }
+    ///
+    /// let replay = kapacitor.replay("cpu_alert", &recording.id, ReplayClock::Fast).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn replay(
+        &self,
+        task: &str,
+        recording: &str,
+        clock: ReplayClock,
+    ) -> Result<Replay, Error> {
+        let body = json!({ "task": task, "recording": recording, "clock": clock });
+        self.send_json(Method::Post, "replays", Some(body.to_string()))
+            .await
+    }
+
+    /// Returns a replay, to check whether it finished
+    pub async fn replay_status(&self, id: &str) -> Result<Replay, Error> {
+        self.send_json(Method::Get, &format!("replays/{}", id), None)
+            .await
+    }
+
+    async fn send_json<T>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<String>,
+    ) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let body = self.send(method, path, body).await?;
+        serde_json::from_str(&body).map_err(|err| Error::DeserializationError {
+            error: err.to_string(),
+            source: crate::error::source(err),
+        })
+    }
+
+    /// Sends a request to an endpoint below `/kapacitor/v1/` and returns the body of the response
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<String>,
+    ) -> Result<String, Error> {
+        let url = format!("{}/kapacitor/v1/{}", self.url, path);
+        let url = Url::parse(&url).map_err(|err| Error::UrlConstructionError {
+            error: err.to_string(),
+        })?;
+        let mut request = self.client.request(method, url);
+        if let Some((username, password)) = &self.auth {
+            let auth = BasicAuth::new(username, password);
+            request = request.header(auth.name(), auth.value());
+        }
+        if let Some(body) = body {
+            request = request
+                .body(body)
+                .header("Content-Type", "application/json");
+        }
+
+        let mut response = self
+            .client
+            .send(request)
+            .map_err(|err| Error::ConnectionError {
+                error: err.to_string(),
+                source: crate::error::surf_source(err),
+            })
+            .await?;
+        let status = response.status();
+        let body = response
+            .body_string()
+            .await
+            .map_err(|err| Error::DeserializationError {
+                error: "response could not be converted to UTF-8".to_string(),
+                source: crate::error::surf_source(err),
+            })?;
+        match status {
+            StatusCode::Unauthorized => Err(Error::AuthorizationError),
+            StatusCode::Forbidden => Err(Error::AuthenticationError),
+            status if status.is_success() => Ok(body),
+            _ => Err(Error::DatabaseError {
+                error: error_message(&body),
+                status: Some(status.into()),
+                request_id: None,
+            }),
+        }
+    }
+}
+
+fn to_json<T>(value: &T) -> Result<String, Error>
+where
+    T: Serialize,
+{
+    serde_json::to_string(value).map_err(|err| Error::InvalidQueryError {
+        error: err.to_string(),
+    })
+}
+
+fn rfc3339(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// Returns the message of an error response of Kapacitor, e.g. `{"error":"task not found"}`
+fn error_message(body: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|error| error["error"].as_str().map(ToOwned::to_owned))
+        .unwrap_or_else(|| body.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{error_message, Status, Task, TaskDefinition, TaskStatus, TaskType};
+
+    #[test]
+    fn test_task_definition() {
+        let task = TaskDefinition::batch("cpu_alert", "batch |query('SELECT 1')")
+            .dbrp("telegraf", "autogen")
+            .enabled();
+        assert_eq!(
+            serde_json::to_string(&task).unwrap(),
+            r#"{"id":"cpu_alert","type":"batch","dbrps":[{"db":"telegraf","rp":"autogen"}],"script":"batch |query('SELECT 1')","status":"enabled"}"#
+        );
+    }
+
+    #[test]
+    fn test_task() {
+        let task: Task = serde_json::from_str(
+            r#"{"link":{"rel":"self","href":"/kapacitor/v1/tasks/cpu_alert"},"id":"cpu_alert","type":"stream","dbrps":[{"db":"telegraf","rp":"autogen"}],"script":"stream","status":"enabled","executing":true,"error":"","stats":{}}"#,
+        )
+        .unwrap();
+        assert_eq!(task.task_type, TaskType::Stream);
+        assert_eq!(task.status, TaskStatus::Enabled);
+        assert!(task.executing);
+
+        let status: Status = serde_json::from_str(r#""finished""#).unwrap();
+        assert_eq!(status, Status::Finished);
+    }
+
+    #[test]
+    fn test_error_message() {
+        assert_eq!(
+            error_message(r#"{"error":"no task exists"}"#),
+            "no task exists"
+        );
+        assert_eq!(error_message("Bad Gateway"), "Bad Gateway");
+    }
+}
@@ -26,10 +26,12 @@
 //! For an example with using Serde deserialization, please refer to [serde_integration](crate::integrations::serde_integration)
 //!
 //! ```rust,no_run
+//! # #[cfg(feature = "client")]
 //! use influxdb::{Client, Query, Timestamp};
 //! use influxdb::InfluxDbWriteable;
 //! use chrono::{DateTime, Utc};
 //!
+//! # #[cfg(feature = "client")]
 //! #[async_std::main]
 //! // or #[tokio::main] if you prefer
 //! async fn main() {
@@ -62,6 +64,8 @@
 //!     assert!(read_result.is_ok(), "Read result was not ok");
 //!     println!("{}", read_result.unwrap());
 //! }
+//! # #[cfg(not(feature = "client"))]
+//! # fn main() {}
 //! ```
 //!
 //! For further examples, check out the Integration Tests in `tests/integration_tests.rs`
@@ -69,29 +73,54 @@
 //!
 //! # Choice of HTTP backend
 //!
-//! To communicate with InfluxDB, you can choose the HTTP backend to be used configuring the appropriate feature:
+//! To communicate with InfluxDB, you can choose the HTTP backend to be used configuring the appropriate feature.
+//! The `rustls` and `native-tls` features select the TLS backend of the default HTTP backend,
+//! enable only one of them:
 //!
-//! - **[hyper](https://github.com/hyperium/hyper)** (used by default)
+//! - **[async-h1](https://github.com/http-rs/async-h1)** with [rustls](https://github.com/ctz/rustls)
+//!   (used by default), which doesn't depend on OpenSSL, e.g. for static musl builds
 //!    ```toml
 //!    influxdb = { version = "0.4.0", features = ["derive"] }
 //!    ```
-//! - **[curl](https://github.com/alexcrichton/curl-rust)**, using [libcurl](https://curl.se/libcurl/)
+//! - **[async-h1](https://github.com/http-rs/async-h1)** with the TLS of the platform, OpenSSL on Linux
 //!    ```toml
-//!    influxdb = { version = "0.4.0", default-features = false, features = ["derive", "use-serde", "curl-client"] }
+//!    influxdb = { version = "0.4.0", default-features = false, features = ["derive", "use-serde", "native-tls"] }
 //!    ```
-//! - **[async-h1](https://github.com/http-rs/async-h1)** with native TLS (OpenSSL)
+//! - **[hyper](https://github.com/hyperium/hyper)**
 //!    ```toml
-//!    influxdb = { version = "0.4.0", default-features = false, features = ["derive", "use-serde", "h1-client"] }
+//!    influxdb = { version = "0.4.0", default-features = false, features = ["derive", "use-serde", "hyper-client"] }
 //!    ```
-//! - **[async-h1](https://github.com/http-rs/async-h1)** with [rustls](https://github.com/ctz/rustls)
+//! - **[curl](https://github.com/alexcrichton/curl-rust)**, using [libcurl](https://curl.se/libcurl/)
 //!    ```toml
-//!    influxdb = { version = "0.4.0", default-features = false, features = ["derive", "use-serde", "h1-client-rustls"] }
+//!    influxdb = { version = "0.4.0", default-features = false, features = ["derive", "use-serde", "curl-client"] }
 //!    ```
 //! - WebAssembly's `window.fetch`, via `web-sys` and **[wasm-bindgen](https://github.com/rustwasm/wasm-bindgen)**
 //!    ```toml
 //!    influxdb = { version = "0.4.0", default-features = false, features = ["derive", "use-serde", "wasm-client"] }
 //!    ```
 //!
+//! `rustls` and `native-tls` are aliases of the `h1-client-rustls` and `h1-client` features, which
+//! select the same backends.
+//!
+//! # Building points without a client
+//!
+//! Line protocol, query builders and `#[derive(InfluxDbWriteable)]` don't need the HTTP client.
+//! Disabling the default features leaves out the `client` feature and its dependencies, e.g. for
+//! agents which hand formatted points to another process:
+//!
+//! ```toml
+//! influxdb = { version = "0.4.0", default-features = false, features = ["derive"] }
+//! ```
+//!
+//! All HTTP backends, as well as the integrations and the bulk operations, enable the `client`
+//! feature.
+//!
+//! # Minimum Rust version
+//!
+//! The crate requires Rust 1.76 or newer, with the exception of the `arrow` and `polars` features.
+//! Their dependencies use the 2024 edition and need at least Rust 1.85, `polars` usually a recent
+//! stable release.
+//!
 //! # License
 //!
 //! [![License: MIT](https://img.shields.io/badge/License-MIT-yellow.svg)](https://opensource.org/licenses/MIT)
@@ -99,20 +128,75 @@
 #![allow(clippy::needless_doctest_main)]
 #![allow(clippy::needless_lifetimes)] // False positive in client/mod.rs query fn
 
+#[cfg(all(feature = "client", any(feature = "csv-import", feature = "use-serde")))]
+pub mod bulk;
+#[cfg(feature = "client")]
 mod client;
 mod error;
+#[cfg(feature = "kapacitor")]
+pub mod kapacitor;
+pub mod metrics;
+pub mod ql;
 mod query;
+#[cfg(feature = "use-serde")]
+pub mod query_result;
+pub mod transport;
 
-pub use client::Client;
-pub use error::Error;
+#[cfg(feature = "client")]
+pub use client::{
+    AdaptiveBatching, BackgroundTask, BufferedWriter, Client, ClientBuilder, Compatibility,
+    Connection, Credentials, CredentialsProvider, DryRun, EnvCredentials, FileCredentials, Health,
+    HealthMonitor, HealthWatch, MultiWrite, Sampling, Scope, ServerVersion, SharedClient,
+    TenantRoute, TenantRouter, TimeGuard, WriteAck, WriteResult, WriterHistogram, WriterOptions,
+    WriterStats,
+};
+#[cfg(all(feature = "client", feature = "use-serde"))]
+pub use client::{
+    DiagnosticsSection, Grant, MeasurementCardinality, Privilege, ReadLimits, ServerDiagnostics,
+    ServerStats, Shard, ShardGroup, StatsModule,
+};
+pub use error::{Error, ErrorSource, RejectedLine};
 pub use query::{
+    annotation::Annotation,
+    dedupe::DedupeStrategy,
+    explain::{PlanNode, QueryPlan},
+    functions,
+    functions::Expression,
+    naming_policy::NamingPolicy,
+    param::Param,
     read_query::ReadQuery,
+    select_query::{Condition, Fill, SelectQuery, TimeRange},
+    timestamp_generator::TimestampGenerator,
+    to_point::ToPoint,
+    visit,
+    write_hook::WriteHook,
+    write_limits::WriteLimits,
     write_query::{Type, WriteQuery},
-    InfluxDbWriteable, Query, QueryType, Timestamp, ValidQuery,
+    InfluxDbWriteable, Measurement, Precision, Query, QueryType, Timestamp, ValidQuery,
 };
-
+#[cfg(all(feature = "client", feature = "use-serde"))]
+pub use query_result::ChunkedResult;
 #[cfg(feature = "use-serde")]
+pub use query_result::{QueryResult, TimeJoin, Value};
+
+#[cfg(any(
+    feature = "use-serde",
+    feature = "prometheus-bridge",
+    feature = "tracing-layer",
+    feature = "actix-middleware",
+    feature = "axum-middleware"
+))]
 pub mod integrations {
+    #[cfg(feature = "actix-middleware")]
+    pub mod actix_integration;
+    #[cfg(feature = "axum-middleware")]
+    pub mod axum_integration;
+    #[cfg(any(feature = "actix-middleware", feature = "axum-middleware"))]
+    pub(crate) mod http_metrics;
+    #[cfg(feature = "prometheus-bridge")]
+    pub mod prometheus_integration;
     #[cfg(feature = "use-serde")]
     pub mod serde_integration;
+    #[cfg(feature = "tracing-layer")]
+    pub mod tracing_integration;
 }
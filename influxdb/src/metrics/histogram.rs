@@ -0,0 +1,335 @@
+//! Observations counted in buckets with fixed bounds
+
+use std::mem;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::{millis, normalize_quantiles, point, quantile_field, DEFAULT_QUANTILES};
+use crate::{ToPoint, Type, WriteQuery};
+
+/// Upper bounds of the buckets unless configured otherwise, for latencies in milliseconds
+const DEFAULT_BOUNDS: [f64; 13] = [
+    1.0, 2.5, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0,
+];
+
+/// Counts observations in buckets with fixed upper bounds, and writes them as a point with the
+/// fields `count`, `sum`, `le_<bound>` for the cumulative count of each bucket, like the
+/// histograms of Prometheus, and `p50`, `p95` and `p99` for the estimated quantiles
+///
+/// Quantiles are interpolated linearly within the bucket they fall into, so they are only as
+/// precise as the buckets, but observing is cheap and takes no memory. Use a
+/// [`Summary`](crate::metrics::Summary) for precise quantiles. Quantiles aren't written for
+/// intervals without observations.
+///
+/// [`take_point`](Histogram::take_point) returns the point of the observations since it was called
+/// the last time, so each point covers one interval.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::metrics::Histogram;
+/// use influxdb::Query;
+/// use std::time::Duration;
+///
+/// let latency = Histogram::new("http_latency")
+///     .add_tag("route", "/users")
+///     .buckets(&[10.0, 50.0, 100.0]);
+/// latency.observe_duration(Duration::from_millis(8));
+/// latency.observe(42.0);
+///
+/// let line = latency.take_point().build()?.get();
+/// assert!(line.starts_with(
+///     "http_latency,route=/users count=2i,sum=50,le_10=1i,le_50=2i,le_100=2i,le_inf=2i,p50=10,"
+/// ));
+/// # Ok::<(), influxdb::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Histogram {
+    measurement: String,
+    tags: Vec<(String, Type)>,
+    quantiles: Vec<f64>,
+    state: Mutex<State>,
+}
+
+/// Observations of the current interval
+#[derive(Debug, Clone, PartialEq)]
+struct State {
+    buckets: Buckets,
+    min: f64,
+    max: f64,
+}
+
+impl State {
+    fn new(buckets: Buckets) -> Self {
+        State {
+            buckets,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+/// Counts of observations in buckets with fixed upper bounds, shared by [`Histogram`] and the
+/// [statistics](crate::WriterHistogram) of the [`BufferedWriter`](crate::BufferedWriter)
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Buckets {
+    bounds: Arc<[f64]>,
+    /// One count per bound, and one of the observations above the last bound
+    counts: Vec<u64>,
+    sum: f64,
+}
+
+impl Buckets {
+    /// Creates empty buckets, `bounds` have to be finite and sorted
+    pub(crate) fn new(bounds: Arc<[f64]>) -> Self {
+        Buckets {
+            counts: vec![0; bounds.len() + 1],
+            bounds,
+            sum: 0.0,
+        }
+    }
+
+    /// Returns empty buckets with the same bounds
+    fn cleared(&self) -> Self {
+        Buckets::new(Arc::clone(&self.bounds))
+    }
+
+    pub(crate) fn observe(&mut self, value: f64) {
+        let bucket = self
+            .bounds
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(self.bounds.len());
+        self.counts[bucket] += 1;
+        self.sum += value;
+    }
+
+    pub(crate) fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    pub(crate) fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// Returns the upper bound of each bucket with the number of observations in it, the last
+    /// bucket is unbounded
+    pub(crate) fn buckets(&self) -> impl Iterator<Item = (f64, u64)> + '_ {
+        self.bounds
+            .iter()
+            .copied()
+            .chain(std::iter::once(f64::INFINITY))
+            .zip(self.counts.iter().copied())
+    }
+
+    /// Adds the count, the sum and the cumulative count of each bucket as fields, like the
+    /// histograms of Prometheus, e.g. `<prefix>count` and `<prefix>le_100`
+    pub(crate) fn add_fields(&self, mut point: WriteQuery, prefix: &str) -> WriteQuery {
+        point = point
+            .add_field(format!("{}count", prefix), self.count())
+            .add_field(format!("{}sum", prefix), self.sum);
+        let mut cumulative = 0;
+        for (bound, count) in self.buckets() {
+            cumulative += count;
+            let field = if bound.is_infinite() {
+                format!("{}le_inf", prefix)
+            } else {
+                format!("{}le_{}", prefix, bound)
+            };
+            point = point.add_field(field, cumulative);
+        }
+        point
+    }
+}
+
+impl Histogram {
+    /// Creates a histogram written as points of `measurement`, with buckets for latencies from 1
+    /// millisecond to 10 seconds
+    pub fn new<S>(measurement: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Histogram {
+            measurement: measurement.into(),
+            tags: Vec::new(),
+            quantiles: DEFAULT_QUANTILES.to_vec(),
+            state: Mutex::new(State::new(Buckets::new(Arc::from(&DEFAULT_BOUNDS[..])))),
+        }
+    }
+
+    /// Adds a tag to the points of the histogram
+    pub fn add_tag<S, I>(mut self, tag: S, value: I) -> Self
+    where
+        S: Into<String>,
+        I: Into<Type>,
+    {
+        self.tags.push((tag.into(), value.into()));
+        self
+    }
+
+    /// Sets the upper bounds of the buckets, observations above the last bound are counted in a
+    /// bucket of their own
+    ///
+    /// Discards the observations so far.
+    pub fn buckets(mut self, bounds: &[f64]) -> Self {
+        let mut bounds = bounds
+            .iter()
+            .copied()
+            .filter(|bound| bound.is_finite())
+            .collect::<Vec<_>>();
+        bounds.sort_by(f64::total_cmp);
+        bounds.dedup();
+        self.state = Mutex::new(State::new(Buckets::new(bounds.into())));
+        self
+    }
+
+    /// Sets the quantiles which are estimated, defaults to 0.5, 0.95 and 0.99
+    pub fn quantiles(mut self, quantiles: &[f64]) -> Self {
+        self.quantiles = normalize_quantiles(quantiles);
+        self
+    }
+
+    /// Records an observation, NaN and infinities are ignored
+    pub fn observe(&self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.buckets.observe(value);
+        state.min = state.min.min(value);
+        state.max = state.max.max(value);
+    }
+
+    /// Records a duration in milliseconds
+    pub fn observe_duration(&self, duration: Duration) {
+        self.observe(millis(duration));
+    }
+
+    /// Returns the number of observations in the current interval
+    pub fn count(&self) -> u64 {
+        self.state.lock().unwrap().buckets.count()
+    }
+
+    /// Returns the point of the observations since the last call and starts a new interval
+    pub fn take_point(&self) -> WriteQuery {
+        let state = {
+            let mut state = self.state.lock().unwrap();
+            let buckets = state.buckets.cleared();
+            mem::replace(&mut *state, State::new(buckets))
+        };
+        self.point_of(&state)
+    }
+
+    fn point_of(&self, state: &State) -> WriteQuery {
+        let count = state.buckets.count();
+        let mut point = state
+            .buckets
+            .add_fields(point(&self.measurement, &self.tags), "");
+        if count > 0 {
+            for quantile in &self.quantiles {
+                point = point.add_field(
+                    quantile_field(*quantile),
+                    self.estimate(state, count, *quantile),
+                );
+            }
+        }
+        point
+    }
+
+    /// Interpolates the quantile within the bucket it falls into, bounded by the smallest and the
+    /// largest observation
+    fn estimate(&self, state: &State, count: u64, quantile: f64) -> f64 {
+        let rank = quantile * count as f64;
+        let (mut cumulative, mut lower_bound) = (0, f64::NEG_INFINITY);
+        for (bound, bucket_count) in state.buckets.buckets() {
+            let below = cumulative;
+            cumulative += bucket_count;
+            if bucket_count == 0 || (cumulative as f64) < rank {
+                lower_bound = bound;
+                continue;
+            }
+            let lower = lower_bound.max(state.min);
+            let upper = bound.min(state.max);
+            let fraction = (rank - below as f64) / bucket_count as f64;
+            return lower + (upper - lower) * fraction;
+        }
+        state.max
+    }
+}
+
+impl ToPoint for Histogram {
+    /// Returns the point of the observations of the current interval, without starting a new one
+    fn to_point(&self) -> WriteQuery {
+        let state = self.state.lock().unwrap().clone();
+        self.point_of(&state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Histogram;
+    use crate::{Query, ToPoint, Type};
+
+    fn field(line: &str, name: &str) -> f64 {
+        line.split(' ')
+            .nth(1)
+            .unwrap()
+            .split(',')
+            .find_map(|field| field.strip_prefix(&format!("{}=", name)))
+            .unwrap()
+            .trim_end_matches('i')
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_histogram() {
+        let histogram = Histogram::new("latency")
+            .buckets(&[100.0, 10.0, 50.0, f64::INFINITY])
+            .quantiles(&[0.5, 0.9]);
+        for value in 1..=100 {
+            histogram.observe(f64::from(value));
+        }
+        histogram.observe(f64::NAN);
+        histogram.observe(f64::INFINITY);
+        histogram.observe(f64::NEG_INFINITY);
+        assert_eq!(histogram.count(), 100);
+
+        let line = histogram.to_point().build().unwrap().get();
+        assert!(
+            line.starts_with(
+                "latency count=100i,sum=5050,le_10=10i,le_50=50i,le_100=100i,le_inf=100i,"
+            ),
+            "{}",
+            line
+        );
+        assert!((field(&line, "p50") - 50.0).abs() < 1e-9, "{}", line);
+        assert!((field(&line, "p90") - 90.0).abs() < 1e-9, "{}", line);
+
+        histogram.take_point();
+        let line = histogram.take_point().build().unwrap().get();
+        assert!(
+            line.starts_with("latency count=0i,sum=0,le_10=0i,"),
+            "{}",
+            line
+        );
+        assert!(!line.contains("p50"), "{}", line);
+    }
+
+    #[test]
+    fn test_estimate_bounds() {
+        let histogram = Histogram::new("latency")
+            .add_tag("host", "a")
+            .buckets(&[10.0]);
+        histogram.observe(2.0);
+        histogram.observe(4.0);
+        histogram.observe(500.0);
+
+        let point = histogram.take_point();
+        assert_eq!(point.tags(), &[("host".to_string(), Type::from("a"))]);
+        let line = point.build().unwrap().get();
+        assert!(field(&line, "p50") >= 2.0 && field(&line, "p50") <= 10.0);
+        assert!(field(&line, "p99") > 10.0 && field(&line, "p99") <= 500.0);
+    }
+}
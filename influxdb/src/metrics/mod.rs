@@ -0,0 +1,74 @@
+//! Accumulators which aggregate observations, e.g. request latencies, and are written as one point
+//! per interval
+//!
+//! [`Histogram`](crate::metrics::Histogram) counts observations in buckets with fixed bounds and
+//! estimates quantiles from them, [`Summary`](crate::metrics::Summary) keeps a sample of the
+//! observations and calculates quantiles from it. Both are shared between threads by reference,
+//! e.g. in an `Arc`, and implement [`ToPoint`](crate::ToPoint), so they can be written by a
+//! [`BufferedWriter`](crate::BufferedWriter). They only depend on the standard library and are
+//! available without the `client` feature.
+//!
+//! A [`Registry`](crate::metrics::Registry) keeps them along with counters and gauges, and writes
+//! all of them at a fixed interval.
+
+mod histogram;
+mod registry;
+mod summary;
+
+#[cfg(feature = "client")]
+pub(crate) use histogram::Buckets;
+pub use histogram::Histogram;
+pub use registry::{Counter, Gauge, Registry};
+pub use summary::Summary;
+
+use std::time::Duration;
+
+use crate::{Timestamp, Type, WriteQuery};
+
+/// Quantiles written unless configured otherwise
+const DEFAULT_QUANTILES: [f64; 3] = [0.5, 0.95, 0.99];
+
+/// Returns the name of the field of a quantile, e.g. `p95` for `0.95` or `p99.9` for `0.999`
+fn quantile_field(quantile: f64) -> String {
+    format!("p{}", (quantile * 1000.0).round() / 10.0)
+}
+
+/// Returns the quantiles clamped to `0.0..=1.0`, sorted and without duplicates
+fn normalize_quantiles(quantiles: &[f64]) -> Vec<f64> {
+    let mut quantiles = quantiles
+        .iter()
+        .filter(|quantile| !quantile.is_nan())
+        .map(|quantile| quantile.clamp(0.0, 1.0))
+        .collect::<Vec<_>>();
+    quantiles.sort_by(f64::total_cmp);
+    quantiles.dedup();
+    quantiles
+}
+
+/// Returns a duration in milliseconds, the unit of latencies observed as durations
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Creates the point of an accumulator, timestamped with the current time
+fn point(measurement: &str, tags: &[(String, Type)]) -> WriteQuery {
+    let mut point = WriteQuery::new(Timestamp::now(), measurement);
+    point.tags_mut().extend_from_slice(tags);
+    point
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_quantiles, quantile_field};
+
+    #[test]
+    fn test_quantiles() {
+        assert_eq!(quantile_field(0.5), "p50");
+        assert_eq!(quantile_field(0.999), "p99.9");
+        assert_eq!(quantile_field(1.0), "p100");
+        assert_eq!(
+            normalize_quantiles(&[0.99, 0.5, 1.5, f64::NAN, 0.5]),
+            vec![0.5, 0.99, 1.0]
+        );
+    }
+}
@@ -0,0 +1,377 @@
+//! Counters, gauges and accumulators which are written together
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use super::{point, Histogram, Summary};
+use crate::{Type, WriteQuery};
+
+/// A count which only goes up, e.g. of handled requests, created by
+/// [`Registry::counter`](crate::metrics::Registry::counter)
+///
+/// Clones count into the same instrument. Written with the field `counter`.
+#[derive(Debug, Clone, Default)]
+pub struct Counter {
+    value: Arc<AtomicU64>,
+}
+
+impl Counter {
+    /// Adds one to the count
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    /// Adds `value` to the count
+    pub fn add(&self, value: u64) {
+        self.value.fetch_add(value, Ordering::Relaxed);
+    }
+
+    /// Returns the count
+    pub fn get(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+/// A value which goes up and down, e.g. of open connections, created by
+/// [`Registry::gauge`](crate::metrics::Registry::gauge)
+///
+/// Clones set the same instrument. Written with the float field `gauge`.
+#[derive(Debug, Clone, Default)]
+pub struct Gauge {
+    /// Bits of the `f64` value
+    value: Arc<AtomicU64>,
+}
+
+impl Gauge {
+    /// Sets the value
+    pub fn set(&self, value: f64) {
+        self.value.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Adds `value` to the value, which may be negative
+    pub fn add(&self, value: f64) {
+        let _ = self
+            .value
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                Some((f64::from_bits(bits) + value).to_bits())
+            });
+    }
+
+    /// Returns the value
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.value.load(Ordering::Relaxed))
+    }
+}
+
+/// A measurement with its tag set, sorted by tag key
+#[derive(Debug, Clone, PartialEq)]
+struct Series {
+    measurement: String,
+    tags: Vec<(String, Type)>,
+}
+
+impl Series {
+    fn new<S, I, K, V>(measurement: S, tags: I) -> Self
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<Type>,
+    {
+        let mut tags = tags
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect::<Vec<_>>();
+        tags.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Series {
+            measurement: measurement.into(),
+            tags,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Instruments {
+    counters: Vec<(Series, Counter)>,
+    gauges: Vec<(Series, Gauge)>,
+    histograms: Vec<(Series, Arc<Histogram>)>,
+    summaries: Vec<(Series, Arc<Summary>)>,
+}
+
+/// Returns the instrument of the series, registering a new one if there is none yet
+fn get_or_insert<T, F>(instruments: &mut Vec<(Series, T)>, series: Series, create: F) -> T
+where
+    T: Clone,
+    F: FnOnce(&Series) -> T,
+{
+    match instruments.iter().find(|(existing, _)| *existing == series) {
+        Some((_, instrument)) => instrument.clone(),
+        None => {
+            let instrument = create(&series);
+            instruments.push((series, instrument.clone()));
+            instrument
+        }
+    }
+}
+
+/// Metrics of an application which are written together, e.g. every ten seconds through a
+/// [`BufferedWriter`](crate::BufferedWriter) with [`report`](Registry::report)
+///
+/// Each instrument is identified by its measurement and tags, asking for the same one again returns
+/// the registered instrument, so it can be looked up where it's used instead of being passed
+/// around. Clones of a registry share the instruments.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::metrics::Registry;
+/// use influxdb::Query;
+///
+/// let registry = Registry::new();
+/// let requests = registry.counter("requests", vec![("route", "/users")]);
+/// requests.inc();
+/// registry.counter("requests", vec![("route", "/users")]).inc();
+/// registry.gauge("connections", Vec::<(String, String)>::new()).set(12.0);
+///
+/// let lines = registry
+///     .snapshot()
+///     .iter()
+///     .map(|point| point.build().map(|query| query.get()))
+///     .collect::<Result<Vec<_>, _>>()?;
+/// assert!(lines[0].starts_with("requests,route=/users counter=2i "));
+/// assert!(lines[1].starts_with("connections gauge=12 "));
+/// # Ok::<(), influxdb::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    instruments: Arc<Mutex<Instruments>>,
+}
+
+impl Registry {
+    /// Creates a registry without instruments
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    /// Returns the counter of the measurement with the tags
+    pub fn counter<S, I, K, V>(&self, measurement: S, tags: I) -> Counter
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<Type>,
+    {
+        let series = Series::new(measurement, tags);
+        let mut instruments = self.instruments.lock().unwrap();
+        get_or_insert(&mut instruments.counters, series, |_| Counter::default())
+    }
+
+    /// Returns the gauge of the measurement with the tags
+    pub fn gauge<S, I, K, V>(&self, measurement: S, tags: I) -> Gauge
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<Type>,
+    {
+        let series = Series::new(measurement, tags);
+        let mut instruments = self.instruments.lock().unwrap();
+        get_or_insert(&mut instruments.gauges, series, |_| Gauge::default())
+    }
+
+    /// Returns the [`Histogram`](crate::metrics::Histogram) of the measurement with the tags,
+    /// with the default buckets for latencies in milliseconds
+    pub fn histogram<S, I, K, V>(&self, measurement: S, tags: I) -> Arc<Histogram>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<Type>,
+    {
+        let series = Series::new(measurement, tags);
+        let mut instruments = self.instruments.lock().unwrap();
+        get_or_insert(&mut instruments.histograms, series, |series| {
+            let histogram = series.tags.iter().cloned().fold(
+                Histogram::new(&series.measurement),
+                |histogram, (key, value)| histogram.add_tag(key, value),
+            );
+            Arc::new(histogram)
+        })
+    }
+
+    /// Returns the [`Summary`](crate::metrics::Summary) of the measurement with the tags
+    pub fn summary<S, I, K, V>(&self, measurement: S, tags: I) -> Arc<Summary>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<Type>,
+    {
+        let series = Series::new(measurement, tags);
+        let mut instruments = self.instruments.lock().unwrap();
+        get_or_insert(&mut instruments.summaries, series, |series| {
+            let summary = series.tags.iter().cloned().fold(
+                Summary::new(&series.measurement),
+                |summary, (key, value)| summary.add_tag(key, value),
+            );
+            Arc::new(summary)
+        })
+    }
+
+    /// Returns the points of all instruments, in the order they were registered, counters first,
+    /// then gauges, histograms and summaries
+    ///
+    /// Histograms and summaries start a new interval, see
+    /// [`Histogram::take_point`](crate::metrics::Histogram::take_point).
+    pub fn snapshot(&self) -> Vec<WriteQuery> {
+        let instruments = self.instruments.lock().unwrap();
+        let counters = instruments.counters.iter().map(|(series, counter)| {
+            point(&series.measurement, &series.tags).add_field("counter", counter.get())
+        });
+        let gauges = instruments.gauges.iter().map(|(series, gauge)| {
+            point(&series.measurement, &series.tags).add_field("gauge", gauge.get())
+        });
+        let histograms = instruments
+            .histograms
+            .iter()
+            .map(|(_, histogram)| histogram.take_point());
+        let summaries = instruments
+            .summaries
+            .iter()
+            .map(|(_, summary)| summary.take_point());
+        counters
+            .chain(gauges)
+            .chain(histograms)
+            .chain(summaries)
+            .collect()
+    }
+}
+
+#[cfg(feature = "client")]
+impl Registry {
+    /// Writes a [snapshot](Registry::snapshot) of the instruments every `interval` with the
+    /// [`BufferedWriter`](crate::BufferedWriter)
+    ///
+    /// The points are written by the returned future, which has to be spawned on the runtime of the
+    /// application. It completes once the writer has stopped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::metrics::Registry;
+    /// use influxdb::{Client, WriterOptions};
+    /// use std::time::Duration;
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() {
+    /// let client = Client::new("http://localhost:8086", "telegraf");
+    /// let (writer, task) = client.buffered_writer(WriterOptions::default());
+    /// async_std::task::spawn(task);
+    ///
+    /// let registry = Registry::new();
+    /// async_std::task::spawn(registry.report(&writer, Duration::from_secs(10)));
+    ///
+    /// registry.counter("jobs", vec![("queue", "emails")]).inc();
+    /// # }
+    /// ```
+    pub fn report(
+        &self,
+        writer: &crate::BufferedWriter,
+        interval: std::time::Duration,
+    ) -> impl std::future::Future<Output = ()> + Send + 'static {
+        let registry = self.clone();
+        let writer = writer.clone();
+        async move {
+            loop {
+                futures_timer::Delay::new(interval).await;
+                for point in registry.snapshot() {
+                    if writer.write(point).is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Registry;
+    use crate::Query;
+    use std::time::Duration;
+
+    fn lines(registry: &Registry) -> Vec<String> {
+        registry
+            .snapshot()
+            .iter()
+            .map(|point| {
+                let line = point.build().unwrap().get();
+                // without the timestamp
+                line.rsplit_once(' ').unwrap().0.to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_registry() {
+        let registry = Registry::new();
+        let users = registry.counter("requests", vec![("route", "/users"), ("method", "GET")]);
+        users.add(2);
+        registry
+            .counter("requests", vec![("method", "GET"), ("route", "/users")])
+            .inc();
+        registry.counter("requests", vec![("route", "/orders")]);
+        let connections = registry.clone().gauge("connections", vec![("pool", "db")]);
+        connections.set(10.0);
+        connections.add(-2.5);
+        registry
+            .histogram("latency", vec![("route", "/users")])
+            .observe_duration(Duration::from_millis(3));
+        registry
+            .summary("query_time", Vec::<(String, String)>::new())
+            .observe(1.0);
+
+        let snapshot = lines(&registry);
+        assert_eq!(
+            &snapshot[..3],
+            &[
+                "requests,method=GET,route=/users counter=3i",
+                "requests,route=/orders counter=0i",
+                "connections,pool=db gauge=7.5",
+            ]
+        );
+        assert!(snapshot[3].starts_with("latency,route=/users count=1i,sum=3,"));
+        assert!(snapshot[4].starts_with("query_time count=1i,sum=1,"));
+
+        // counters and gauges keep their values, accumulators start a new interval
+        let snapshot = lines(&registry);
+        assert_eq!(snapshot[0], "requests,method=GET,route=/users counter=3i");
+        assert!(snapshot[3].starts_with("latency,route=/users count=0i,"));
+    }
+
+    #[cfg(feature = "client")]
+    #[test]
+    fn test_report() {
+        use crate::client::buffered_writer::Command;
+        use crate::BufferedWriter;
+        use futures::executor::block_on;
+        use futures::StreamExt;
+
+        let registry = Registry::new();
+        registry.counter("jobs", vec![("queue", "emails")]).inc();
+        let (writer, mut receiver) = BufferedWriter::detached();
+        let report = registry.report(&writer, Duration::from_millis(10));
+        drop(writer);
+        let reporter = std::thread::spawn(move || block_on(report));
+
+        match block_on(receiver.next()) {
+            Some(Command::Write(point, None)) => {
+                assert_eq!(point.fields()[0].0, "counter");
+            }
+            _ => panic!("snapshot was not written"),
+        }
+        // the reporter stops once the writer has
+        drop(receiver);
+        reporter.join().unwrap();
+    }
+}
@@ -0,0 +1,225 @@
+//! Quantiles calculated from a sample of the observations
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::mem;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::{millis, normalize_quantiles, point, quantile_field, DEFAULT_QUANTILES};
+use crate::{ToPoint, Type, WriteQuery};
+
+/// Keeps the observations of an interval, and writes them as a point with the fields `count`,
+/// `sum`, `min`, `max`, and `p50`, `p95` and `p99` for the quantiles
+///
+/// Up to [`max_samples`](Summary::max_samples) observations per interval are kept, chosen at
+/// random once there are more, so the quantiles of busy intervals are estimates as well. The
+/// count, sum, minimum and maximum are exact. Only the count and the sum are written for intervals
+/// without observations.
+///
+/// [`take_point`](Summary::take_point) returns the point of the observations since it was called
+/// the last time, so each point covers one interval.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::metrics::Summary;
+/// use influxdb::Query;
+///
+/// let latency = Summary::new("db_latency").add_tag("query", "users_by_id");
+/// for millis in 1..=100 {
+///     latency.observe(f64::from(millis));
+/// }
+///
+/// assert_eq!(
+///     latency.take_point().build()?.get().split(' ').nth(1),
+///     Some("count=100i,sum=5050,min=1,max=100,p50=50,p95=95,p99=99")
+/// );
+/// # Ok::<(), influxdb::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Summary {
+    measurement: String,
+    tags: Vec<(String, Type)>,
+    quantiles: Vec<f64>,
+    max_samples: usize,
+    random: RandomState,
+    state: Mutex<State>,
+}
+
+/// Observations of the current interval
+#[derive(Debug, Clone, PartialEq)]
+struct State {
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+    samples: Vec<f64>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State {
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            samples: Vec::new(),
+        }
+    }
+}
+
+impl Summary {
+    /// Creates a summary written as points of `measurement`, keeping up to 1024 observations per
+    /// interval
+    pub fn new<S>(measurement: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Summary {
+            measurement: measurement.into(),
+            tags: Vec::new(),
+            quantiles: DEFAULT_QUANTILES.to_vec(),
+            max_samples: 1024,
+            random: RandomState::new(),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Adds a tag to the points of the summary
+    pub fn add_tag<S, I>(mut self, tag: S, value: I) -> Self
+    where
+        S: Into<String>,
+        I: Into<Type>,
+    {
+        self.tags.push((tag.into(), value.into()));
+        self
+    }
+
+    /// Sets the quantiles which are calculated, defaults to 0.5, 0.95 and 0.99
+    pub fn quantiles(mut self, quantiles: &[f64]) -> Self {
+        self.quantiles = normalize_quantiles(quantiles);
+        self
+    }
+
+    /// Sets the number of observations kept per interval, defaults to 1024
+    pub fn max_samples(mut self, max_samples: usize) -> Self {
+        self.max_samples = max_samples.max(1);
+        self
+    }
+
+    /// Records an observation, NaN and infinities are ignored
+    pub fn observe(&self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.count += 1;
+        state.sum += value;
+        state.min = state.min.min(value);
+        state.max = state.max.max(value);
+        if state.samples.len() < self.max_samples {
+            state.samples.push(value);
+            return;
+        }
+        // algorithm R: the nth observation replaces a kept one with a probability of max / n
+        let index = (self.draw(state.count) % state.count) as usize;
+        if let Some(sample) = state.samples.get_mut(index) {
+            *sample = value;
+        }
+    }
+
+    /// Records a duration in milliseconds
+    pub fn observe_duration(&self, duration: Duration) {
+        self.observe(millis(duration));
+    }
+
+    /// Returns the number of observations in the current interval
+    pub fn count(&self) -> u64 {
+        self.state.lock().unwrap().count
+    }
+
+    /// Returns the point of the observations since the last call and starts a new interval
+    pub fn take_point(&self) -> WriteQuery {
+        let state = mem::take(&mut *self.state.lock().unwrap());
+        self.point_of(state)
+    }
+
+    fn point_of(&self, mut state: State) -> WriteQuery {
+        let mut point = point(&self.measurement, &self.tags)
+            .add_field("count", state.count)
+            .add_field("sum", state.sum);
+        if state.count == 0 {
+            return point;
+        }
+        point = point
+            .add_field("min", state.min)
+            .add_field("max", state.max);
+        state.samples.sort_by(f64::total_cmp);
+        for quantile in &self.quantiles {
+            // nearest rank
+            let rank = (quantile * state.samples.len() as f64).ceil() as usize;
+            let value = state.samples[rank.clamp(1, state.samples.len()) - 1];
+            point = point.add_field(quantile_field(*quantile), value);
+        }
+        point
+    }
+
+    /// Returns a random number, the keys of `RandomState` are random, so are the hashes
+    fn draw(&self, count: u64) -> u64 {
+        let mut hasher = self.random.build_hasher();
+        hasher.write_u64(count);
+        hasher.finish()
+    }
+}
+
+impl ToPoint for Summary {
+    /// Returns the point of the observations of the current interval, without starting a new one
+    fn to_point(&self) -> WriteQuery {
+        let state = self.state.lock().unwrap().clone();
+        self.point_of(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Summary;
+    use crate::{Query, ToPoint};
+    use std::time::Duration;
+
+    fn fields(summary: &Summary) -> String {
+        let line = summary.to_point().build().unwrap().get();
+        line.split(' ').nth(1).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_summary() {
+        let summary = Summary::new("latency").quantiles(&[0.0, 0.25, 1.0]);
+        assert_eq!(fields(&summary), "count=0i,sum=0");
+
+        summary.observe_duration(Duration::from_millis(30));
+        for value in &[10.0, f64::NAN, 20.0, f64::INFINITY, 40.0, f64::NEG_INFINITY] {
+            summary.observe(*value);
+        }
+        assert_eq!(summary.count(), 4);
+        assert_eq!(
+            fields(&summary),
+            "count=4i,sum=100,min=10,max=40,p0=10,p25=10,p100=40"
+        );
+
+        summary.take_point();
+        assert_eq!(summary.count(), 0);
+    }
+
+    #[test]
+    fn test_max_samples() {
+        let summary = Summary::new("latency").max_samples(10);
+        for value in 0..1000 {
+            summary.observe(f64::from(value));
+        }
+        let state = summary.state.lock().unwrap().clone();
+        assert_eq!(state.samples.len(), 10);
+        assert!(state.samples.iter().any(|sample| *sample >= 10.0));
+        assert_eq!((state.count, state.min, state.max), (1000, 0.0, 999.0));
+    }
+}
@@ -0,0 +1,367 @@
+//! Tokenizer for InfluxQL
+//! https://docs.influxdata.com/influxdb/v1.8/query_language/spec/#tokens
+
+use super::QlError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    /// Unquoted identifier or keyword
+    Ident,
+    /// Double quoted identifier
+    QuotedIdent,
+    /// Single quoted string
+    String,
+    /// Regular expression delimited by forward slashes
+    Regex,
+    Number,
+    /// Number with a duration unit, e.g. `30m`
+    Duration,
+    /// Bind parameter, e.g. `$host`
+    BoundParam,
+    /// `:MEASUREMENT` in the target of an `INTO` clause, which refers to the source measurement
+    Backref,
+    LeftParen,
+    RightParen,
+    Comma,
+    Semicolon,
+    /// Any other operator or punctuation, e.g. `>=` or `::`
+    Operator,
+}
+
+/// A token, with its position in the query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Token<'a> {
+    pub(crate) kind: TokenKind,
+    pub(crate) text: &'a str,
+    pub(crate) offset: usize,
+}
+
+impl Token<'_> {
+    /// Returns whether the token is the given keyword, which are case insensitive
+    pub(crate) fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Ident && self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    /// Returns whether the token refers to the `time` column
+    pub(crate) fn is_time(&self) -> bool {
+        match self.kind {
+            TokenKind::Ident => self.text.eq_ignore_ascii_case("time"),
+            TokenKind::QuotedIdent => self.text == r#""time""#,
+            _ => false,
+        }
+    }
+
+    /// Returns whether the token is a value or a reference to one, which has to be followed by an
+    /// operator or keyword. A `/` after an operand divides it, instead of starting a regex.
+    pub(crate) fn is_operand(&self) -> bool {
+        match self.kind {
+            TokenKind::Ident => !KEYWORDS.iter().any(|keyword| self.is_keyword(keyword)),
+            TokenKind::QuotedIdent
+            | TokenKind::String
+            | TokenKind::Regex
+            | TokenKind::Number
+            | TokenKind::Duration
+            | TokenKind::BoundParam
+            | TokenKind::Backref
+            | TokenKind::RightParen => true,
+            _ => false,
+        }
+    }
+}
+
+/// Keywords of InfluxQL, which are not operands
+/// https://docs.influxdata.com/influxdb/v1.8/query_language/spec/#keywords
+pub(crate) const KEYWORDS: [&str; 79] = [
+    "ALL",
+    "ALTER",
+    "ANALYZE",
+    "AND",
+    "ANY",
+    "AS",
+    "ASC",
+    "BEGIN",
+    "BY",
+    "CARDINALITY",
+    "CREATE",
+    "CONTINUOUS",
+    "DATABASE",
+    "DATABASES",
+    "DEFAULT",
+    "DELETE",
+    "DESC",
+    "DESTINATIONS",
+    "DIAGNOSTICS",
+    "DISTINCT",
+    "DROP",
+    "DURATION",
+    "END",
+    "EVERY",
+    "EXACT",
+    "EXPLAIN",
+    "FIELD",
+    "FILL",
+    "FOR",
+    "FROM",
+    "GRANT",
+    "GRANTS",
+    "GROUP",
+    "GROUPS",
+    "IN",
+    "INF",
+    "INSERT",
+    "INTO",
+    "KEY",
+    "KEYS",
+    "KILL",
+    "LIMIT",
+    "MEASUREMENT",
+    "MEASUREMENTS",
+    "NAME",
+    "OFFSET",
+    "ON",
+    "OR",
+    "ORDER",
+    "PASSWORD",
+    "POLICIES",
+    "POLICY",
+    "PRIVILEGES",
+    "QUERIES",
+    "QUERY",
+    "READ",
+    "REPLICATION",
+    "RESAMPLE",
+    "RETENTION",
+    "REVOKE",
+    "SELECT",
+    "SERIES",
+    "SET",
+    "SHARD",
+    "SHARDS",
+    "SHOW",
+    "SLIMIT",
+    "SOFFSET",
+    "STATS",
+    "SUBSCRIPTION",
+    "SUBSCRIPTIONS",
+    "TAG",
+    "TO",
+    "TZ",
+    "USER",
+    "USERS",
+    "VALUES",
+    "WHERE",
+    "WITH",
+];
+
+const DURATION_UNITS: [&str; 9] = ["ns", "u", "µ", "ms", "s", "m", "h", "d", "w"];
+
+const OPERATORS: [&str; 19] = [
+    "=~", "!~", "!=", "<>", "<=", ">=", "::", "=", "<", ">", "+", "-", "*", "/", "%", "&", "|",
+    "^", ".",
+];
+
+/// Splits a query into tokens, skipping whitespace and comments
+pub(crate) fn tokenize(query: &str) -> Result<Vec<Token<'_>>, QlError> {
+    let mut tokens: Vec<Token<'_>> = Vec::new();
+    let mut offset = 0;
+    while let Some(c) = query[offset..].chars().next() {
+        let rest = &query[offset..];
+        if c.is_whitespace() {
+            offset += c.len_utf8();
+            continue;
+        }
+        if rest.starts_with("--") {
+            offset += rest.find('\n').unwrap_or(rest.len());
+            continue;
+        }
+        if let Some(comment) = rest.strip_prefix("/*") {
+            let end = comment
+                .find("*/")
+                .ok_or_else(|| QlError::new(query, offset, "unterminated comment"))?;
+            offset += end + 4;
+            continue;
+        }
+
+        let regex_allowed = !matches!(tokens.last(), Some(token) if token.is_operand());
+        let (kind, len) = match c {
+            '(' => (TokenKind::LeftParen, 1),
+            ')' => (TokenKind::RightParen, 1),
+            ',' => (TokenKind::Comma, 1),
+            ';' => (TokenKind::Semicolon, 1),
+            '\'' => (
+                TokenKind::String,
+                delimited(query, offset, '\'', "unterminated string")?,
+            ),
+            '"' => (
+                TokenKind::QuotedIdent,
+                delimited(query, offset, '"', "unterminated identifier")?,
+            ),
+            '/' if regex_allowed => (
+                TokenKind::Regex,
+                delimited(query, offset, '/', "unterminated regex")?,
+            ),
+            ':' if rest[1..].starts_with("MEASUREMENT")
+                && identifier_len(&rest[1..]) == "MEASUREMENT".len() =>
+            {
+                (TokenKind::Backref, 1 + "MEASUREMENT".len())
+            }
+            '$' => {
+                let len = 1 + identifier_len(&rest[1..]);
+                if len == 1 {
+                    return Err(QlError::new(
+                        query,
+                        offset,
+                        "expected a parameter name after $",
+                    ));
+                }
+                (TokenKind::BoundParam, len)
+            }
+            c if c.is_ascii_digit()
+                || (c == '.' && rest[1..].starts_with(|c: char| c.is_ascii_digit())) =>
+            {
+                let number = rest
+                    .find(|c: char| !c.is_ascii_digit() && c != '.')
+                    .unwrap_or(rest.len());
+                let unit = DURATION_UNITS
+                    .iter()
+                    .filter(|unit| rest[number..].starts_with(*unit))
+                    .map(|unit| unit.len())
+                    .filter(|len| identifier_len(&rest[number + len..]) == 0)
+                    .max();
+                match unit {
+                    Some(unit) => (TokenKind::Duration, number + unit),
+                    None => (TokenKind::Number, number),
+                }
+            }
+            c if c.is_alphabetic() || c == '_' => (TokenKind::Ident, identifier_len(rest)),
+            _ => match OPERATORS
+                .iter()
+                .find(|operator| rest.starts_with(*operator))
+            {
+                Some(operator) => (TokenKind::Operator, operator.len()),
+                None => {
+                    return Err(QlError::new(
+                        query,
+                        offset,
+                        format!("unexpected character {:?}", c),
+                    ))
+                }
+            },
+        };
+        tokens.push(Token {
+            kind,
+            text: &query[offset..offset + len],
+            offset,
+        });
+        offset += len;
+    }
+    Ok(tokens)
+}
+
+/// Returns the length of the identifier at the start of `text`
+fn identifier_len(text: &str) -> usize {
+    text.find(|c: char| !c.is_alphanumeric() && c != '_')
+        .unwrap_or(text.len())
+}
+
+/// Returns the length of the text delimited by `delimiter` at `offset`, including the delimiters
+fn delimited(query: &str, offset: usize, delimiter: char, error: &str) -> Result<usize, QlError> {
+    let mut chars = query[offset + 1..].char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '\n' if delimiter != '\'' => break,
+            c if c == delimiter => return Ok(i + 2),
+            _ => {}
+        }
+    }
+    Err(QlError::new(query, offset, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{tokenize, TokenKind::*};
+
+    #[test]
+    fn test_tokenize() {
+        let query = r#"SELECT mean("value") / 2 FROM /cpu.*/ WHERE host =~ /^web\/\d+$/ AND time > now() - 1h -- comment
+            GROUP BY time(10m) LIMIT $limit"#;
+        let tokens = tokenize(query)
+            .unwrap()
+            .into_iter()
+            .map(|token| (token.kind, token.text))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                (Ident, "SELECT"),
+                (Ident, "mean"),
+                (LeftParen, "("),
+                (QuotedIdent, r#""value""#),
+                (RightParen, ")"),
+                (Operator, "/"),
+                (Number, "2"),
+                (Ident, "FROM"),
+                (Regex, "/cpu.*/"),
+                (Ident, "WHERE"),
+                (Ident, "host"),
+                (Operator, "=~"),
+                (Regex, r#"/^web\/\d+$/"#),
+                (Ident, "AND"),
+                (Ident, "time"),
+                (Operator, ">"),
+                (Ident, "now"),
+                (LeftParen, "("),
+                (RightParen, ")"),
+                (Operator, "-"),
+                (Duration, "1h"),
+                (Ident, "GROUP"),
+                (Ident, "BY"),
+                (Ident, "time"),
+                (LeftParen, "("),
+                (Duration, "10m"),
+                (RightParen, ")"),
+                (Ident, "LIMIT"),
+                (BoundParam, "$limit"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_backref() {
+        let tokens = tokenize("SELECT * INTO db2..:MEASUREMENT FROM /.*/ GROUP BY *")
+            .unwrap()
+            .into_iter()
+            .map(|token| (token.kind, token.text))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            &tokens[2..9],
+            &[
+                (Ident, "INTO"),
+                (Ident, "db2"),
+                (Operator, "."),
+                (Operator, "."),
+                (Backref, ":MEASUREMENT"),
+                (Ident, "FROM"),
+                (Regex, "/.*/"),
+            ]
+        );
+
+        let tokens = tokenize("SELECT value::field FROM cpu").unwrap();
+        assert_eq!(tokens[2].text, "::");
+        assert!(tokenize("SELECT * INTO :MEASUREMENTS FROM cpu").is_err());
+    }
+
+    #[test]
+    fn test_unterminated() {
+        let error = tokenize("SELECT * FROM cpu WHERE host = 'web").unwrap_err();
+        assert_eq!(error.position(), 31);
+        assert_eq!(error.message(), "unterminated string");
+
+        assert!(tokenize(r#"SELECT "value FROM cpu"#).is_err());
+        assert!(tokenize("SELECT * FROM /cpu").is_err());
+        assert!(tokenize("SELECT * FROM cpu WHERE a ? b").is_err());
+    }
+}
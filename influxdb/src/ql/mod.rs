@@ -0,0 +1,737 @@
+//! Client side validation of InfluxQL statements
+//!
+//! The validation catches the mistakes which most often make the server reject a query, without
+//! implementing the full grammar of InfluxQL:
+//!
+//! - unterminated strings, identifiers, regexes and comments
+//! - unbalanced parentheses
+//! - statements not starting with a known keyword like `SELECT` or `SHOW`
+//! - misspelled keywords, i.e. two values or names following each other without an operator
+//! - `SELECT` statements without a `FROM` clause
+//! - `DELETE` statements without a condition on `time`, which would delete all points
+//!
+//! Errors point at the position in the query where the mistake was found.
+//! [`require_time_condition`] additionally rejects `SELECT` statements which read all points,
+//! [`bound_time`] restricts them to the recent past instead.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use influxdb::ql;
+//!
+//! assert!(ql::validate("SELECT mean(value) FROM cpu WHERE time > now() - 1h").is_ok());
+//!
+//! let error = ql::validate("SELECT mean(value) FROM cpu WHER time > now() - 1h").unwrap_err();
+//! assert_eq!(error.position(), 28);
+//! assert_eq!(error.to_string(), "expected an operator or keyword after cpu, found WHER at line 1, column 29");
+//! ```
+
+mod lexer;
+
+use chrono::Duration;
+use std::fmt;
+use std::ops::Range;
+
+use crate::query::influxql_term::InfluxQlTerm;
+use crate::Error;
+use lexer::{tokenize, Token, TokenKind};
+
+/// Keywords statements can start with
+const STATEMENT_KEYWORDS: [&str; 11] = [
+    "ALTER", "CREATE", "DELETE", "DROP", "EXPLAIN", "GRANT", "KILL", "REVOKE", "SELECT", "SET",
+    "SHOW",
+];
+
+/// Keywords ending the condition of a `SELECT` statement
+const CLAUSE_KEYWORDS: [&str; 8] = [
+    "GROUP", "ORDER", "LIMIT", "OFFSET", "SLIMIT", "SOFFSET", "FILL", "TZ",
+];
+
+/// A mistake in an InfluxQL query, with the position it was found at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QlError {
+    position: usize,
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+impl QlError {
+    pub(crate) fn new<S>(query: &str, position: usize, message: S) -> Self
+    where
+        S: Into<String>,
+    {
+        let before = &query[..position];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        QlError {
+            position,
+            line,
+            column: query[line_start..position].chars().count() + 1,
+            message: message.into(),
+        }
+    }
+
+    /// Returns the byte offset of the mistake in the query
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the line of the mistake, starting at one
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the column of the mistake in characters, starting at one
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Returns the description of the mistake, without its position
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for QlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.message, self.line, self.column
+        )
+    }
+}
+
+impl std::error::Error for QlError {}
+
+impl From<QlError> for Error {
+    fn from(error: QlError) -> Self {
+        Error::InvalidQueryError {
+            error: error.to_string(),
+        }
+    }
+}
+
+/// A statement of a validated query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statement {
+    keyword: String,
+    span: Range<usize>,
+    pub(crate) condition: Option<Range<usize>>,
+    pub(crate) sources_end: Option<usize>,
+    time_condition: bool,
+}
+
+impl Statement {
+    /// Returns the keyword the statement starts with in upper case, e.g. `SELECT`, ignoring a
+    /// leading `EXPLAIN` or `EXPLAIN ANALYZE`
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+
+    /// Returns the byte range of the statement in the query, without the separating semicolon
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns whether the `WHERE` clause of the statement has a condition on `time`, which
+    /// applies to all points, i.e. is not just one alternative of an `OR`
+    pub fn has_time_condition(&self) -> bool {
+        self.time_condition
+    }
+}
+
+/// Validates a query of one or more statements separated by semicolons
+///
+/// See the [module documentation](crate::ql) for the mistakes which are found.
+pub fn validate(query: &str) -> Result<(), QlError> {
+    statements(query).map(|_| ())
+}
+
+/// Validates a query and returns its statements
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::ql;
+///
+/// let statements = ql::statements("SHOW DATABASES; SELECT * FROM cpu").unwrap();
+/// assert_eq!(statements[1].keyword(), "SELECT");
+/// assert!(!statements[1].has_time_condition());
+/// ```
+pub fn statements(query: &str) -> Result<Vec<Statement>, QlError> {
+    let tokens = tokenize(query)?;
+    check_parentheses(query, &tokens)?;
+    check_operands(query, &tokens)?;
+
+    let mut statements = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::LeftParen => depth += 1,
+            TokenKind::RightParen => depth -= 1,
+            TokenKind::Semicolon if depth == 0 => {
+                if i > start {
+                    statements.push(statement(query, &tokens[start..i])?);
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if tokens.len() > start {
+        statements.push(statement(query, &tokens[start..])?);
+    }
+    if statements.is_empty() {
+        return Err(QlError::new(query, query.len(), "query has no statements"));
+    }
+    Ok(statements)
+}
+
+/// Fails if a `SELECT` statement of the query has no condition on `time`
+///
+/// Without a condition on time, a `SELECT` reads every shard of its measurements, which can take
+/// down a shared server. A condition on time which is one alternative of an `OR`, like in
+/// `WHERE host = 'a' OR time > now() - 1h`, doesn't count.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::ql;
+///
+/// assert!(ql::require_time_condition("SELECT * FROM cpu WHERE time > now() - 1h").is_ok());
+/// assert!(ql::require_time_condition("SHOW DATABASES; SELECT * FROM cpu").is_err());
+/// ```
+pub fn require_time_condition(query: &str) -> Result<(), QlError> {
+    for statement in statements(query)? {
+        if statement.keyword == "SELECT" && !statement.time_condition {
+            return Err(QlError::new(
+                query,
+                statement.span.start,
+                "SELECT without a condition on time reads all points",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Restricts the `SELECT` statements of the query which have no condition on `time` to the points
+/// newer than `duration`
+///
+/// # Examples
+///
+/// ```rust
+/// use chrono::Duration;
+/// use influxdb::ql;
+///
+/// let query = "SELECT * FROM cpu WHERE host = 'a' OR host = 'b' LIMIT 10; SELECT * FROM mem";
+/// assert_eq!(
+///     ql::bound_time(query, Duration::hours(1)).unwrap(),
+///     "SELECT * FROM cpu WHERE (host = 'a' OR host = 'b') AND time > now() - 1h LIMIT 10; \
+///      SELECT * FROM mem WHERE time > now() - 1h"
+/// );
+/// ```
+pub fn bound_time(query: &str, duration: Duration) -> Result<String, QlError> {
+    let bound = format!(
+        "time > now() - {}",
+        InfluxQlTerm::Duration(&duration).escape()
+    );
+    let mut bounded = query.to_string();
+    // from the last statement, so the positions of the earlier ones stay valid
+    for statement in statements(query)?.iter().rev() {
+        if statement.keyword != "SELECT" || statement.time_condition {
+            continue;
+        }
+        match (&statement.condition, statement.sources_end) {
+            (Some(condition), _) => {
+                bounded.insert_str(condition.end, &format!(") AND {}", bound));
+                bounded.insert(condition.start, '(');
+            }
+            (None, Some(sources_end)) => {
+                bounded.insert_str(sources_end, &format!(" WHERE {}", bound));
+            }
+            (None, None) => {}
+        }
+    }
+    Ok(bounded)
+}
+
+/// Substitutes the `$parameters` of the query with their values, which are already quoted
+///
+/// Parameters inside of strings, identifiers, regexes and comments aren't substituted. Fails if a
+/// parameter has no value.
+pub(crate) fn bind(query: &str, params: &[(String, String)]) -> Result<String, QlError> {
+    let mut bound = String::with_capacity(query.len());
+    let mut end = 0;
+    for token in tokenize(query)? {
+        if token.kind != TokenKind::BoundParam {
+            continue;
+        }
+        let name = &token.text[1..];
+        let (_, value) = params
+            .iter()
+            .rev()
+            .find(|(param, _)| param == name)
+            .ok_or_else(|| {
+                QlError::new(query, token.offset, format!("no value for {}", token.text))
+            })?;
+        bound.push_str(&query[end..token.offset]);
+        bound.push_str(value);
+        end = token.offset + token.text.len();
+    }
+    bound.push_str(&query[end..]);
+    Ok(bound)
+}
+
+/// Returns whether a statement of the query has an `INTO` clause, ignoring `into` in strings,
+/// identifiers, regexes and comments. Queries which can't be tokenized have none.
+#[cfg_attr(not(feature = "client"), allow(dead_code))]
+pub(crate) fn has_into_clause(query: &str) -> bool {
+    tokenize(query).is_ok_and(|tokens| tokens.iter().any(|token| token.is_keyword("INTO")))
+}
+
+/// Prefixes the measurements of the `FROM` and `INTO` clauses of the query, except the ones which
+/// already start with the prefix
+///
+/// Regexes can't be prefixed, as they might match measurements with other prefixes, so sources
+/// which are regexes fail. The users of `REVOKE ... FROM` aren't measurements and are kept.
+#[cfg_attr(not(feature = "client"), allow(dead_code))]
+pub(crate) fn prefix_measurements(query: &str, prefix: &str) -> Result<String, QlError> {
+    let tokens = tokenize(query)?;
+    let is_dot = |token: &Token<'_>| token.kind == TokenKind::Operator && token.text == ".";
+    let mut prefixed = String::with_capacity(query.len());
+    let mut end = 0;
+    let mut revoke = false;
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        if i == 0 || tokens[i - 1].kind == TokenKind::Semicolon {
+            revoke = token.is_keyword("REVOKE");
+        }
+        i += 1;
+        if revoke || !(token.is_keyword("FROM") || token.is_keyword("INTO")) {
+            continue;
+        }
+        // sources separated by commas, measurements may be qualified with their database and
+        // retention policy, e.g. `db..cpu`, subqueries are prefixed by their own clauses
+        while let Some(source) = tokens.get(i) {
+            while i + 1 < tokens.len() && (is_dot(&tokens[i]) || is_dot(&tokens[i + 1])) {
+                i += 1;
+            }
+            let measurement = tokens[i];
+            let name = match measurement.kind {
+                TokenKind::Ident if measurement.is_operand() => measurement.text.to_string(),
+                TokenKind::QuotedIdent => unquote(measurement.text),
+                TokenKind::Regex => {
+                    return Err(QlError::new(
+                        query,
+                        source.offset,
+                        "regex sources can't be prefixed",
+                    ))
+                }
+                _ => break,
+            };
+            if !name.starts_with(prefix) {
+                let name = format!("{}{}", prefix, name);
+                prefixed.push_str(&query[end..measurement.offset]);
+                prefixed.push_str(&InfluxQlTerm::Identifier(&name).escape());
+                end = measurement.offset + measurement.text.len();
+            }
+            i += 1;
+            match tokens.get(i) {
+                Some(token) if token.kind == TokenKind::Comma => i += 1,
+                _ => break,
+            }
+        }
+    }
+    prefixed.push_str(&query[end..]);
+    Ok(prefixed)
+}
+
+/// Returns the name of a double quoted identifier
+#[cfg_attr(not(feature = "client"), allow(dead_code))]
+fn unquote(identifier: &str) -> String {
+    let mut name = String::with_capacity(identifier.len());
+    let mut chars = identifier[1..identifier.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => name.extend(chars.next()),
+            c => name.push(c),
+        }
+    }
+    name
+}
+
+fn check_parentheses(query: &str, tokens: &[Token<'_>]) -> Result<(), QlError> {
+    let mut open = Vec::new();
+    for token in tokens {
+        match token.kind {
+            TokenKind::LeftParen => open.push(token.offset),
+            TokenKind::RightParen if open.pop().is_none() => {
+                return Err(QlError::new(query, token.offset, "unmatched )"));
+            }
+            _ => {}
+        }
+    }
+    match open.pop() {
+        Some(offset) => Err(QlError::new(query, offset, "unclosed (")),
+        None => Ok(()),
+    }
+}
+
+/// Finds operands following each other, which happens when a keyword is misspelled
+fn check_operands(query: &str, tokens: &[Token<'_>]) -> Result<(), QlError> {
+    for pair in tokens.windows(2) {
+        if pair[0].is_operand() && pair[1].is_operand() && pair[1].kind != TokenKind::RightParen {
+            return Err(QlError::new(
+                query,
+                pair[1].offset,
+                format!(
+                    "expected an operator or keyword after {}, found {}",
+                    pair[0].text, pair[1].text
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn statement(query: &str, mut tokens: &[Token<'_>]) -> Result<Statement, QlError> {
+    let first = tokens[0];
+    let end = tokens
+        .last()
+        .map_or(first.offset, |token| token.offset + token.text.len());
+    if first.is_keyword("EXPLAIN") {
+        tokens = &tokens[1..];
+        if matches!(tokens.first(), Some(token) if token.is_keyword("ANALYZE")) {
+            tokens = &tokens[1..];
+        }
+        if !matches!(tokens.first(), Some(token) if token.is_keyword("SELECT")) {
+            let offset = tokens.first().map_or(end, |token| token.offset);
+            return Err(QlError::new(query, offset, "expected SELECT after EXPLAIN"));
+        }
+    }
+
+    let keyword = tokens[0];
+    if !STATEMENT_KEYWORDS
+        .iter()
+        .any(|known| keyword.is_keyword(known))
+    {
+        return Err(QlError::new(
+            query,
+            keyword.offset,
+            format!(
+                "expected a statement like SELECT or SHOW, found {}",
+                keyword.text
+            ),
+        ));
+    }
+
+    let mut statement = Statement {
+        keyword: keyword.text.to_ascii_uppercase(),
+        span: first.offset..end,
+        condition: None,
+        sources_end: None,
+        time_condition: false,
+    };
+    let top_level = top_level(tokens);
+    let condition_start = top_level
+        .iter()
+        .position(|token| token.is_keyword("WHERE"))
+        .map(|position| position + 1);
+    if let Some(condition_start) = condition_start {
+        let condition_end = top_level[condition_start..]
+            .iter()
+            .position(|token| {
+                CLAUSE_KEYWORDS
+                    .iter()
+                    .any(|clause| token.is_keyword(clause))
+            })
+            .map_or(top_level.len(), |position| condition_start + position);
+        // the condition includes all tokens between its first and last top level token
+        let first = top_level.get(condition_start);
+        let last = top_level[..condition_end].last();
+        if let (Some(first), Some(last)) = (first, last) {
+            if first.offset <= last.offset {
+                let range = first.offset..last.offset + last.text.len();
+                let condition = tokens
+                    .iter()
+                    .filter(|token| range.contains(&token.offset))
+                    .copied()
+                    .collect::<Vec<_>>();
+                statement.time_condition = bounds_time(&condition);
+                statement.condition = Some(range);
+            }
+        }
+    }
+
+    match statement.keyword.as_str() {
+        "SELECT" => {
+            let from = top_level
+                .iter()
+                .position(|token| token.is_keyword("FROM"))
+                .ok_or_else(|| QlError::new(query, end, "SELECT requires a FROM clause"))?;
+            statement.sources_end = Some(sources_end(query, &top_level[from..], end)?);
+        }
+        "DELETE" if !statement.time_condition => {
+            return Err(QlError::new(
+                query,
+                keyword.offset,
+                "DELETE without a condition on time deletes all points",
+            ));
+        }
+        _ => {}
+    }
+    Ok(statement)
+}
+
+/// Returns whether the condition only holds for points in a time range, i.e. each of the
+/// alternatives joined by `OR` has a comparison of `time` joined by `AND`
+fn bounds_time(condition: &[Token<'_>]) -> bool {
+    split_top_level(condition, "OR").iter().all(|alternative| {
+        split_top_level(alternative, "AND").iter().any(|term| {
+            let parenthesized = top_level(term).len() == 2
+                && term.first().map(|token| token.kind) == Some(TokenKind::LeftParen);
+            if parenthesized {
+                bounds_time(&term[1..term.len() - 1])
+            } else {
+                top_level(term).iter().any(Token::is_time)
+            }
+        })
+    })
+}
+
+/// Splits the tokens at the keyword, except where it is inside of parentheses
+fn split_top_level<'a, 'b>(tokens: &'b [Token<'a>], keyword: &str) -> Vec<&'b [Token<'a>]> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::LeftParen => depth += 1,
+            TokenKind::RightParen => depth -= 1,
+            _ if depth == 0 && token.is_keyword(keyword) => {
+                parts.push(&tokens[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&tokens[start..]);
+    parts
+}
+
+/// Returns the tokens outside of parentheses, keeping the parentheses themselves
+fn top_level<'a>(tokens: &[Token<'a>]) -> Vec<Token<'a>> {
+    let mut depth = 0;
+    let mut top_level = Vec::new();
+    for token in tokens {
+        if token.kind == TokenKind::RightParen {
+            depth -= 1;
+        }
+        if depth == 0 {
+            top_level.push(*token);
+        }
+        if token.kind == TokenKind::LeftParen {
+            depth += 1;
+        }
+    }
+    top_level
+}
+
+/// Returns the end of the sources of a `FROM` clause, given the top level tokens starting at `FROM`
+fn sources_end(query: &str, tokens: &[Token<'_>], end: usize) -> Result<usize, QlError> {
+    let mut sources_end = None;
+    let mut expect_source = true;
+    for token in &tokens[1..] {
+        let is_source = match token.kind {
+            TokenKind::Ident => token.is_operand(),
+            TokenKind::QuotedIdent
+            | TokenKind::Regex
+            | TokenKind::BoundParam
+            | TokenKind::LeftParen
+            | TokenKind::RightParen => true,
+            _ => false,
+        };
+        match token.kind {
+            TokenKind::Comma => expect_source = true,
+            // separates database, retention policy and measurement
+            TokenKind::Operator if token.text == "." => {}
+            _ if is_source => expect_source = false,
+            _ => break,
+        }
+        sources_end = Some(token.offset + token.text.len());
+    }
+    match sources_end {
+        Some(sources_end) if !expect_source => Ok(sources_end),
+        _ => {
+            let offset = tokens.get(1).map_or(end, |token| token.offset);
+            Err(QlError::new(
+                query,
+                offset,
+                "expected a measurement after FROM",
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bound_time, prefix_measurements, require_time_condition, statements, validate};
+    use chrono::Duration;
+
+    fn error(query: &str) -> (usize, String) {
+        let error = validate(query).unwrap_err();
+        (error.position(), error.message().to_string())
+    }
+
+    #[test]
+    fn test_valid() {
+        for query in &[
+            "SELECT mean(value) FROM cpu WHERE time > now() - 1h GROUP BY time(10m), host fill(none)",
+            r#"SELECT "value" FROM "telegraf"."autogen"."cpu", /mem.*/ WHERE host =~ /^web/ ORDER BY time DESC LIMIT 10"#,
+            "SELECT max(v) FROM (SELECT v * 2 AS v FROM cpu) WHERE time > '2021-03-08T00:00:00Z' tz('Europe/Berlin')",
+            "SELECT value::field FROM db..cpu; SHOW TAG VALUES WITH KEY = host",
+            "CREATE RETENTION POLICY two_weeks ON test DURATION 2w REPLICATION 1 DEFAULT",
+            "DELETE FROM cpu WHERE time < '2021-01-01T00:00:00Z'",
+            "EXPLAIN ANALYZE SELECT count(*) FROM cpu;",
+            "SELECT * FROM cpu WHERE host = $host -- only one host",
+            "SELECT * INTO db2..:MEASUREMENT FROM /.*/ GROUP BY *",
+            r#"SELECT mean(*) INTO "downsampled"."autogen".:MEASUREMENT FROM cpu, mem WHERE time > now() - 1h GROUP BY time(5m), *"#,
+        ] {
+            assert!(validate(query).is_ok(), "{} should be valid: {:?}", query, validate(query));
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(
+            error("SELECT * FROM cpu WHER host = 'a'"),
+            (
+                18,
+                "expected an operator or keyword after cpu, found WHER".to_string()
+            )
+        );
+        assert_eq!(
+            error("SELECT mean(value FROM cpu"),
+            (11, "unclosed (".to_string())
+        );
+        assert_eq!(
+            error("SELET * FROM cpu"),
+            (
+                0,
+                "expected a statement like SELECT or SHOW, found SELET".to_string()
+            )
+        );
+        assert_eq!(
+            error("SHOW DATABASES; SELECT mean(value)"),
+            (34, "SELECT requires a FROM clause".to_string())
+        );
+        assert_eq!(
+            error("SELECT * FROM WHERE time > now()"),
+            (14, "expected a measurement after FROM".to_string())
+        );
+        assert_eq!(
+            error("DELETE FROM cpu WHERE host = 'a'"),
+            (
+                0,
+                "DELETE without a condition on time deletes all points".to_string()
+            )
+        );
+        assert!(validate("DELETE FROM cpu").is_err());
+        assert!(validate(" ; ").is_err());
+
+        let error = validate("SELECT *\nFROM cpu\nWHERE 'a").unwrap_err();
+        assert_eq!((error.line(), error.column()), (3, 7));
+    }
+
+    #[test]
+    fn test_time_condition() {
+        assert!(require_time_condition("SELECT * FROM cpu WHERE \"time\" > now() - 1h").is_ok());
+        assert!(require_time_condition("EXPLAIN SELECT * FROM cpu WHERE host = 'a'").is_err());
+        assert!(require_time_condition("SHOW TAG VALUES WITH KEY = host").is_ok());
+
+        // every alternative has to be bounded
+        let unbounded = "SELECT * FROM cpu WHERE host = 'a' OR time > now() - 1h";
+        assert!(require_time_condition(unbounded).is_err());
+        assert_eq!(
+            bound_time(unbounded, Duration::hours(1)).unwrap(),
+            "SELECT * FROM cpu WHERE (host = 'a' OR time > now() - 1h) AND time > now() - 1h"
+        );
+        assert!(require_time_condition(
+            "SELECT * FROM cpu WHERE (host = 'a' AND time > now() - 1h) OR time < '2020-01-01'"
+        )
+        .is_ok());
+        assert!(require_time_condition(
+            "SELECT * FROM cpu WHERE host = 'a' AND (time > now() - 1h OR host = 'b')"
+        )
+        .is_err());
+        assert!(validate("DELETE FROM cpu WHERE host = 'a' OR time < '2020-01-01'").is_err());
+        assert!(validate("DELETE FROM cpu WHERE host = 'a' AND time < '2020-01-01'").is_ok());
+
+        let error = require_time_condition("SHOW DATABASES; SELECT * FROM cpu").unwrap_err();
+        assert_eq!(error.position(), 16);
+    }
+
+    #[test]
+    fn test_bound_time() {
+        let bound = |query| bound_time(query, Duration::minutes(90)).unwrap();
+        assert_eq!(
+            bound("SELECT * FROM cpu GROUP BY host"),
+            "SELECT * FROM cpu WHERE time > now() - 90m GROUP BY host"
+        );
+        assert_eq!(
+            bound("SELECT max(v) FROM (SELECT v FROM cpu) WHERE host = 'a';"),
+            "SELECT max(v) FROM (SELECT v FROM cpu) WHERE (host = 'a') AND time > now() - 90m;"
+        );
+        let bounded = "SELECT * FROM cpu WHERE time > now() - 1h";
+        assert_eq!(bound(bounded), bounded);
+        assert_eq!(
+            bound("SELECT * INTO db2..:MEASUREMENT FROM /.*/ GROUP BY *"),
+            "SELECT * INTO db2..:MEASUREMENT FROM /.*/ WHERE time > now() - 90m GROUP BY *"
+        );
+    }
+
+    #[test]
+    fn test_statements() {
+        let query = "SELECT * FROM cpu WHERE (time > now() - 1h) LIMIT 1; SHOW DATABASES";
+        let statements = statements(query).unwrap();
+        assert_eq!(statements.len(), 2);
+        assert!(statements[0].has_time_condition());
+        assert_eq!(
+            &query[statements[0].condition.clone().unwrap()],
+            "(time > now() - 1h)"
+        );
+        assert_eq!(statements[0].sources_end, Some(17));
+        assert_eq!(&query[statements[1].span()], "SHOW DATABASES");
+    }
+
+    #[test]
+    fn test_prefix_measurements() {
+        let prefix = |query: &str| prefix_measurements(query, "acme_").unwrap();
+        assert_eq!(
+            prefix("SELECT * FROM cpu, acme_mem, \"disk \\\"io\\\"\" WHERE host = 'FROM x'"),
+            r#"SELECT * FROM "acme_cpu", acme_mem, "acme_disk \"io\"" WHERE host = 'FROM x'"#
+        );
+        assert_eq!(
+            prefix("SELECT max(v) INTO db..cpu_max FROM (SELECT v FROM db.\"autogen\".cpu)"),
+            r#"SELECT max(v) INTO db.."acme_cpu_max" FROM (SELECT v FROM db."autogen"."acme_cpu")"#
+        );
+        assert_eq!(
+            prefix("SELECT * INTO db..:MEASUREMENT FROM cpu; SHOW TAG KEYS FROM mem"),
+            r#"SELECT * INTO db..:MEASUREMENT FROM "acme_cpu"; SHOW TAG KEYS FROM "acme_mem""#
+        );
+        assert_eq!(
+            prefix("SHOW DATABASES; REVOKE ALL PRIVILEGES FROM jdoe"),
+            "SHOW DATABASES; REVOKE ALL PRIVILEGES FROM jdoe"
+        );
+        assert_eq!(
+            prefix_measurements("SELECT * FROM /cpu.*/", "acme_")
+                .unwrap_err()
+                .position(),
+            14
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! Annotations, points marking events like deploys, in the format queried by Grafana
+
+use crate::query::{Measurement, Timestamp};
+use crate::WriteQuery;
+
+/// Measurement annotations are written to, unless configured otherwise
+const DEFAULT_MEASUREMENT: &str = "events";
+
+/// An annotation, written as a point with the fields `title`, `text` and `tags`
+///
+/// The fields match the columns of a Grafana annotation query like
+/// `SELECT title, text, tags FROM events WHERE $timeFilter`. Tags are joined to a single field
+/// separated by commas, as Grafana splits them again.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::{Annotation, Query, Timestamp};
+///
+/// let annotation = Annotation::new(Timestamp::Seconds(1600000000), "Deployed v1.2.0")
+///     .text("Rolled out to all regions")
+///     .tag("deploy")
+///     .tag("backend");
+///
+/// assert_eq!(
+///     annotation.into_query().build().unwrap().get(),
+///     r#"events tags="deploy,backend",text="Rolled out to all regions",title="Deployed v1.2.0" 1600000000"#
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    timestamp: Timestamp,
+    measurement: Measurement,
+    title: String,
+    text: Option<String>,
+    tags: Vec<String>,
+}
+
+impl Annotation {
+    /// Creates an annotation with the given title at `timestamp`
+    pub fn new<S>(timestamp: Timestamp, title: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Annotation {
+            timestamp,
+            measurement: Measurement::new(DEFAULT_MEASUREMENT),
+            title: title.into(),
+            text: None,
+            tags: Vec::new(),
+        }
+    }
+
+    /// Sets the measurement the annotation is written to, defaults to `events`
+    pub fn measurement<M>(mut self, measurement: M) -> Self
+    where
+        M: Into<Measurement>,
+    {
+        self.measurement = measurement.into();
+        self
+    }
+
+    /// Sets the text describing the event
+    pub fn text<S>(mut self, text: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// Adds a tag to filter annotations by
+    pub fn tag<S>(mut self, tag: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Converts the annotation into the point written for it
+    pub fn into_query(self) -> WriteQuery {
+        let mut query = WriteQuery::new(self.timestamp, self.measurement);
+        if !self.tags.is_empty() {
+            query = query.add_field("tags", self.tags.join(","));
+        }
+        if let Some(text) = self.text {
+            query = query.add_field("text", text);
+        }
+        query.add_field("title", self.title)
+    }
+}
+
+impl From<Annotation> for WriteQuery {
+    fn from(annotation: Annotation) -> Self {
+        annotation.into_query()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Annotation;
+    use crate::{Query, Timestamp};
+
+    #[test]
+    fn test_annotation_without_text_and_tags() {
+        let query = Annotation::new(Timestamp::Milliseconds(10), "Restarted")
+            .measurement("deploys")
+            .into_query();
+        assert_eq!(
+            query.build().unwrap().get(),
+            r#"deploys title="Restarted" 10"#
+        );
+    }
+}
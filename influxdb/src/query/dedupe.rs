@@ -0,0 +1,97 @@
+//! Strategies to keep distinct events from overwriting each other, while retried writes stay idempotent
+//!
+//! InfluxDB identifies a point by its measurement, tag set and timestamp: writing a second point with
+//! the same identity overwrites the fields of the first. A [`DedupeStrategy`](crate::DedupeStrategy)
+//! changes the identity of a point once, when it is applied. Resubmitting the resulting
+//! [`WriteQuery`](crate::WriteQuery) after a failed or timed out write then overwrites the point
+//! with identical values instead of duplicating it.
+//!
+//! The client doesn't retry failed writes itself, so the strategy isn't part of its write paths:
+//! [`write_split`](crate::Client::write_split) and the batches of a
+//! [`BufferedWriter`](crate::BufferedWriter) send the points they were given unchanged, so points
+//! the strategy was applied to stay idempotent when they are resubmitted by the application.
+
+use lazy_static::lazy_static;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+lazy_static! {
+    static ref PROCESS_SEED: u64 = RandomState::new().build_hasher().finish();
+}
+static UNIQUE_KEY_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// How a [`WriteQuery`](crate::WriteQuery) is made distinguishable from other points with the same
+/// measurement, tags and timestamp, see [`WriteQuery::dedupe`](crate::WriteQuery::dedupe)
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::{DedupeStrategy, Query, Timestamp};
+/// use influxdb::InfluxDbWriteable;
+///
+/// let query = Timestamp::Seconds(1)
+///     .into_query("login")
+///     .add_field("user", "gero")
+///     .dedupe(&DedupeStrategy::TimestampJitter(1_000_000));
+///
+/// // retrying the same query writes the same point
+/// assert_eq!(query.clone().build().unwrap(), query.build().unwrap().get());
+/// ```
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum DedupeStrategy {
+    /// Adds a tag with the given key and a value unique to the point
+    ///
+    /// Every point becomes a series of its own, so this is only suitable for low volume events.
+    UniqueTag(String),
+    /// Adds a jitter of less than the given number of nanoseconds to the timestamp
+    ///
+    /// The jitter is derived from the measurement, tags, fields and timestamp of the point, so
+    /// points with different values rarely collide. The timestamp is converted to nanoseconds,
+    /// the jitter is lost if the [`Client`](crate::Client) downscales the precision of writes.
+    TimestampJitter(u64),
+}
+
+/// Returns a tag value which is unique across processes with very high probability
+pub(crate) fn unique_key() -> String {
+    let counter = UNIQUE_KEY_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{:016x}{:08x}", *PROCESS_SEED, counter)
+}
+
+/// Stable FNV-1a hash of the identity and values of a point
+pub(crate) fn content_hash<'a, I>(parts: I) -> u64
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut hash = OFFSET_BASIS;
+    for part in parts {
+        // separate the parts, so `("ab", "c")` and `("a", "bc")` hash differently
+        for byte in part.bytes().chain(std::iter::once(0)) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(PRIME);
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{content_hash, unique_key};
+
+    #[test]
+    fn test_unique_key() {
+        let key = unique_key();
+        assert_eq!(key.len(), 24);
+        assert_ne!(key, unique_key());
+    }
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(content_hash(vec![]), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(content_hash(vec!["a", "b"]), content_hash(vec!["a", "b"]));
+        assert_ne!(content_hash(vec!["ab", "c"]), content_hash(vec!["a", "bc"]));
+    }
+}
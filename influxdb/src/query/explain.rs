@@ -0,0 +1,317 @@
+//! Parsed output of `EXPLAIN` and `EXPLAIN ANALYZE`
+
+use std::time::Duration;
+
+/// Plan of a statement, as shown by `EXPLAIN`
+///
+/// The plan is a list of properties like `NUMBER OF SHARDS: 1`, for which the common ones have
+/// typed accessors.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::QueryPlan;
+///
+/// let plan = QueryPlan::parse(vec![
+///     "EXPRESSION: mean(usage_idle::float)",
+///     "NUMBER OF SHARDS: 2",
+///     "NUMBER OF SERIES: 8",
+/// ]);
+///
+/// assert_eq!(plan.get("EXPRESSION"), Some("mean(usage_idle::float)"));
+/// assert_eq!(plan.number_of_series(), Some(8));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct QueryPlan {
+    properties: Vec<(String, String)>,
+}
+
+impl QueryPlan {
+    /// Parses the rows of the `QUERY PLAN` column
+    pub fn parse<I, S>(rows: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        QueryPlan {
+            properties: rows
+                .into_iter()
+                .filter_map(|row| {
+                    let (key, value) = row.as_ref().split_once(": ")?;
+                    Some((key.trim().to_string(), value.trim().to_string()))
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns all properties, in the order they were shown
+    pub fn properties(&self) -> &[(String, String)] {
+        &self.properties
+    }
+
+    /// Returns the value of the first property with the given key, e.g. `EXPRESSION`
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|(property, _)| property == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the sum of the numeric properties with the given key, as statements reading from
+    /// several measurements show one plan per measurement
+    fn sum(&self, key: &str) -> Option<u64> {
+        self.properties
+            .iter()
+            .filter(|(property, _)| property == key)
+            .map(|(_, value)| value.parse::<u64>().ok())
+            .sum()
+    }
+
+    /// Returns the number of shards the statement reads from
+    pub fn number_of_shards(&self) -> Option<u64> {
+        self.sum("NUMBER OF SHARDS")
+    }
+
+    /// Returns the number of series the statement reads
+    pub fn number_of_series(&self) -> Option<u64> {
+        self.sum("NUMBER OF SERIES")
+    }
+
+    /// Returns the number of values read from the cache
+    pub fn cached_values(&self) -> Option<u64> {
+        self.sum("CACHED VALUES")
+    }
+
+    /// Returns the number of TSM files read
+    pub fn number_of_files(&self) -> Option<u64> {
+        self.sum("NUMBER OF FILES")
+    }
+
+    /// Returns the number of TSM blocks read
+    pub fn number_of_blocks(&self) -> Option<u64> {
+        self.sum("NUMBER OF BLOCKS")
+    }
+
+    /// Returns the size of the TSM blocks read, in bytes
+    pub fn size_of_blocks(&self) -> Option<u64> {
+        self.sum("SIZE OF BLOCKS")
+    }
+}
+
+/// Step of an executed plan, as shown by `EXPLAIN ANALYZE`
+///
+/// Every step has attributes like `execution_time` or `cursors_ref`, and the steps it consists of
+/// as children.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::PlanNode;
+/// use std::time::Duration;
+///
+/// let plan = PlanNode::parse(vec![
+///     ".",
+///     "└── select",
+///     "    ├── execution_time: 2.25ms",
+///     "    └── field_iterators",
+///     "        └── create_iterator",
+///     "            ├── labels",
+///     "            │   └── measurement: cpu",
+///     "            └── float_blocks_decoded: 431",
+/// ])
+/// .unwrap();
+///
+/// assert_eq!(plan.name(), "select");
+/// assert_eq!(plan.duration("execution_time"), Some(Duration::from_micros(2250)));
+/// let iterators = plan.find_all("create_iterator");
+/// assert_eq!(iterators[0].attribute("float_blocks_decoded"), Some("431"));
+/// assert_eq!(iterators[0].children()[0].attribute("measurement"), Some("cpu"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanNode {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<PlanNode>,
+}
+
+impl PlanNode {
+    /// Parses the rows of the `EXPLAIN ANALYZE` column into the tree they draw, returning its
+    /// first step, which is `select` for `SELECT` statements
+    pub fn parse<I, S>(rows: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut root = PlanNode::new(".");
+        for row in rows {
+            let row = row.as_ref();
+            let text = row.trim_start_matches(|c: char| !c.is_alphanumeric() && c != '_');
+            if text.is_empty() {
+                continue;
+            }
+            // the tree is drawn with four characters per level, the root being level zero
+            let depth = (row.chars().count() - text.chars().count()) / 4;
+            let parent = root.last_at_depth(depth.saturating_sub(1));
+            match text.split_once(": ") {
+                Some((key, value)) => parent
+                    .attributes
+                    .push((key.to_string(), value.trim().to_string())),
+                None => parent.children.push(PlanNode::new(text.trim())),
+            }
+        }
+        root.children.into_iter().next()
+    }
+
+    fn new(name: &str) -> Self {
+        PlanNode {
+            name: name.to_string(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Returns the most recently added node `depth` levels below this one, or the deepest one
+    fn last_at_depth(&mut self, depth: usize) -> &mut PlanNode {
+        if depth == 0 || self.children.is_empty() {
+            return self;
+        }
+        self.children.last_mut().unwrap().last_at_depth(depth - 1)
+    }
+
+    /// Returns the name of the step, e.g. `create_iterator`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns all attributes of the step, in the order they were shown
+    pub fn attributes(&self) -> &[(String, String)] {
+        &self.attributes
+    }
+
+    /// Returns the value of an attribute, e.g. `cursors_ref`
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the value of an attribute holding a duration, e.g. `planning_time`
+    pub fn duration(&self, key: &str) -> Option<Duration> {
+        parse_go_duration(self.attribute(key)?)
+    }
+
+    /// Returns the steps this step consists of
+    pub fn children(&self) -> &[PlanNode] {
+        &self.children
+    }
+
+    /// Returns this step and all steps below it with the given name, in the order they were shown
+    pub fn find_all(&self, name: &str) -> Vec<&PlanNode> {
+        let mut found = Vec::new();
+        if self.name == name {
+            found.push(self);
+        }
+        for child in &self.children {
+            found.extend(child.find_all(name));
+        }
+        found
+    }
+}
+
+/// Parses durations as formatted by Go, e.g. `1m2.5s` or `850µs`
+pub(crate) fn parse_go_duration(text: &str) -> Option<Duration> {
+    const UNITS: [(&str, f64); 8] = [
+        ("ns", 1.0),
+        ("us", 1e3),
+        ("µs", 1e3),
+        ("μs", 1e3),
+        ("ms", 1e6),
+        ("s", 1e9),
+        ("m", 60e9),
+        ("h", 3600e9),
+    ];
+
+    let mut rest = text.trim();
+    let mut nanos = 0.0;
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let number = rest[..number_end].parse::<f64>().ok()?;
+        rest = &rest[number_end..];
+        let unit_end = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let (_, per_unit) = UNITS.iter().find(|(unit, _)| *unit == &rest[..unit_end])?;
+        nanos += number * per_unit;
+        rest = &rest[unit_end..];
+    }
+    Some(Duration::from_nanos(nanos.round() as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_go_duration, PlanNode};
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_go_duration() {
+        assert_eq!(parse_go_duration("0s"), Some(Duration::from_secs(0)));
+        assert_eq!(parse_go_duration("850ns"), Some(Duration::from_nanos(850)));
+        assert_eq!(parse_go_duration("2µs"), Some(Duration::from_micros(2)));
+        assert_eq!(
+            parse_go_duration("20.639846ms"),
+            Some(Duration::from_nanos(20_639_846))
+        );
+        assert_eq!(
+            parse_go_duration("1m2.5s"),
+            Some(Duration::from_millis(62_500))
+        );
+        assert_eq!(parse_go_duration("1x"), None);
+    }
+
+    #[test]
+    fn test_parse_plan_node() {
+        let rows = r#".
+└── select
+    ├── execution_time: 2.25823ms
+    ├── planning_time: 18.381616ms
+    ├── total_time: 20.639846ms
+    └── field_iterators
+        ├── labels
+        │   └── statement: SELECT mean(usage_idle::float) FROM telegraf."default".cpu
+        └── expression
+            ├── labels
+            │   └── expr: mean(usage_idle::float)
+            └── create_iterator
+                ├── labels
+                │   ├── measurement: cpu
+                │   └── shard_id: 608
+                ├── cursors_ref: 779
+                ├── planning_time: 14.805277ms
+                └── execution_time: 0s"#;
+        let plan = PlanNode::parse(rows.lines()).unwrap();
+
+        assert_eq!(plan.name(), "select");
+        assert_eq!(
+            plan.duration("total_time"),
+            Some(Duration::from_nanos(20_639_846))
+        );
+        let names = plan.children()[0]
+            .children()
+            .iter()
+            .map(PlanNode::name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["labels", "expression"]);
+        assert_eq!(
+            plan.children()[0].children()[0].attribute("statement"),
+            Some(r#"SELECT mean(usage_idle::float) FROM telegraf."default".cpu"#)
+        );
+
+        let iterator = plan.find_all("create_iterator")[0];
+        assert_eq!(iterator.attribute("cursors_ref"), Some("779"));
+        assert_eq!(iterator.children()[0].attribute("shard_id"), Some("608"));
+        assert_eq!(iterator.duration("execution_time"), Some(Duration::ZERO));
+    }
+}
@@ -0,0 +1,274 @@
+//! InfluxQL functions which can be selected by a [`SelectQuery`](crate::SelectQuery)
+//!
+//! Functions can be nested, e.g. to compute the rate of change of an aggregate.
+//! See the [InfluxDB Docs](https://docs.influxdata.com/influxdb/v1.8/query_language/functions/)
+//! for the semantics of each function.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use chrono::Duration;
+//! use influxdb::functions::{derivative, mean, percentile};
+//! use influxdb::{Fill, Query, SelectQuery};
+//!
+//! let query = SelectQuery::new()
+//!     .field(derivative(mean("value"), Duration::seconds(1)))
+//!     .field(percentile("value", 95).alias("p95"))
+//!     .from("cpu")
+//!     .group_by_time(Duration::minutes(1))
+//!     .fill(Fill::Previous)
+//!     .build();
+//!
+//! assert_eq!(
+//!     query.unwrap(),
+//!     r#"SELECT derivative(mean("value"), 1s), percentile("value", 95) AS "p95" FROM "cpu" GROUP BY time(1m) fill(previous)"#
+//! );
+//! ```
+
+use crate::query::influxql_term::InfluxQlTerm;
+use chrono::Duration;
+use std::fmt;
+
+/// An expression in the selected columns of a [`SelectQuery`](crate::SelectQuery)
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Expression {
+    /// All fields and tags, `*`
+    Wildcard,
+    /// A field or tag key
+    Key(String),
+    /// An integer literal, e.g. the `N` of `top("value", N)`
+    Integer(i64),
+    /// A float literal
+    Float(f64),
+    /// A duration literal, e.g. the unit of `derivative("value", 1s)`
+    Duration(Duration),
+    /// A call of a function with arguments
+    Call(String, Vec<Expression>),
+    /// An expression which is returned under a different column name
+    Alias(Box<Expression>, String),
+}
+
+impl Expression {
+    /// Returns the result of the expression under the column `name`
+    pub fn alias<S>(self, name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Expression::Alias(Box::new(self), name.into())
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Expression::*;
+        match self {
+            Wildcard => write!(f, "*"),
+            Key(key) => write!(f, "{}", InfluxQlTerm::Identifier(key).escape()),
+            Integer(i) => write!(f, "{}", i),
+            Float(x) => write!(f, "{}", x),
+            Duration(d) => write!(f, "{}", InfluxQlTerm::Duration(d).escape()),
+            Call(function, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                write!(f, "{}({})", function, args)
+            }
+            Alias(expression, name) => write!(
+                f,
+                "{} AS {}",
+                expression,
+                InfluxQlTerm::Identifier(name).escape()
+            ),
+        }
+    }
+}
+
+impl From<&str> for Expression {
+    fn from(key: &str) -> Self {
+        Expression::Key(key.into())
+    }
+}
+
+impl From<String> for Expression {
+    fn from(key: String) -> Self {
+        Expression::Key(key)
+    }
+}
+
+impl From<&String> for Expression {
+    fn from(key: &String) -> Self {
+        Expression::Key(key.clone())
+    }
+}
+
+impl From<i64> for Expression {
+    fn from(i: i64) -> Self {
+        Expression::Integer(i)
+    }
+}
+
+impl From<f64> for Expression {
+    fn from(x: f64) -> Self {
+        Expression::Float(x)
+    }
+}
+
+impl From<Duration> for Expression {
+    fn from(d: Duration) -> Self {
+        Expression::Duration(d)
+    }
+}
+
+/// Calls an InfluxQL function which isn't available as a typed function in this module
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::functions::call;
+///
+/// assert_eq!(call("holt_winters", vec!["value".into(), 10.into(), 4.into()]).to_string(), r#"holt_winters("value", 10, 4)"#);
+/// ```
+pub fn call<S>(function: S, args: Vec<Expression>) -> Expression
+where
+    S: Into<String>,
+{
+    Expression::Call(function.into(), args)
+}
+
+macro_rules! unary_functions {
+    ( $( $(#[$attr:meta])* $name:ident ),+ ) => (
+        $(
+            $(#[$attr])*
+            pub fn $name<E>(expression: E) -> Expression
+            where
+                E: Into<Expression>,
+            {
+                call(stringify!($name), vec![expression.into()])
+            }
+        )+
+    )
+}
+
+macro_rules! n_functions {
+    ( $( $(#[$attr:meta])* $name:ident ),+ ) => (
+        $(
+            $(#[$attr])*
+            pub fn $name<E>(expression: E, n: i64) -> Expression
+            where
+                E: Into<Expression>,
+            {
+                call(stringify!($name), vec![expression.into(), Expression::Integer(n)])
+            }
+        )+
+    )
+}
+
+macro_rules! unit_functions {
+    ( $( $(#[$attr:meta])* $name:ident ),+ ) => (
+        $(
+            $(#[$attr])*
+            pub fn $name<E>(expression: E, unit: Duration) -> Expression
+            where
+                E: Into<Expression>,
+            {
+                call(stringify!($name), vec![expression.into(), Expression::Duration(unit)])
+            }
+        )+
+    )
+}
+
+unary_functions! {
+    /// Aggregation: number of non-null values
+    count,
+    /// Aggregation: list of unique values
+    distinct,
+    /// Aggregation: arithmetic mean
+    mean,
+    /// Aggregation: middle value of the sorted values
+    median,
+    /// Aggregation: most frequent value
+    mode,
+    /// Aggregation: difference between the maximum and minimum value
+    spread,
+    /// Aggregation: standard deviation
+    stddev,
+    /// Aggregation: sum
+    sum,
+    /// Selector: value with the oldest timestamp
+    first,
+    /// Selector: value with the newest timestamp
+    last,
+    /// Selector: greatest value
+    max,
+    /// Selector: lowest value
+    min,
+    /// Transformation: absolute value
+    abs,
+    /// Transformation: rounds up to the nearest integer
+    ceil,
+    /// Transformation: rounds down to the nearest integer
+    floor,
+    /// Transformation: rounds to the nearest integer
+    round,
+    /// Transformation: running total of subsequent values
+    cumulative_sum,
+    /// Transformation: difference between subsequent values
+    difference,
+    /// Transformation: non-negative difference between subsequent values
+    non_negative_difference
+}
+
+n_functions! {
+    /// Selector: smallest `n` values
+    bottom,
+    /// Selector: `n` greatest values
+    top,
+    /// Selector: value larger than `n` percent of the values
+    percentile,
+    /// Selector: random sample of `n` values
+    sample,
+    /// Transformation: rolling average over `n` subsequent values
+    moving_average
+}
+
+unit_functions! {
+    /// Aggregation: area under the curve, in `unit`
+    integral,
+    /// Transformation: rate of change between subsequent values per `unit`
+    derivative,
+    /// Transformation: non-negative rate of change between subsequent values per `unit`
+    non_negative_derivative,
+    /// Transformation: difference between subsequent timestamps in `unit`
+    elapsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_functions() {
+        assert_eq!(mean("value").to_string(), r#"mean("value")"#);
+        assert_eq!(count(Expression::Wildcard).to_string(), "count(*)");
+        assert_eq!(top("value", 3).to_string(), r#"top("value", 3)"#);
+        assert_eq!(
+            non_negative_derivative(max("bytes"), Duration::minutes(1)).to_string(),
+            r#"non_negative_derivative(max("bytes"), 1m)"#
+        );
+        assert_eq!(
+            moving_average(derivative(mean("value"), Duration::seconds(1)), 5).to_string(),
+            r#"moving_average(derivative(mean("value"), 1s), 5)"#
+        );
+        assert_eq!(
+            sum(r#"my "field""#).alias("total").to_string(),
+            r#"sum("my \"field\"") AS "total""#
+        );
+        assert_eq!(
+            call("log", vec!["value".into(), 2.5.into()]).to_string(),
+            r#"log("value", 2.5)"#
+        );
+    }
+}
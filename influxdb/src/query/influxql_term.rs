@@ -0,0 +1,161 @@
+/// InfluxQL quoting helper module.
+/// https://docs.influxdata.com/influxdb/v1.8/query_language/spec/#identifiers
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    pub static ref DOUBLE_QUOTES_SLASHES: Regex = Regex::new(r#"["\\]"#).unwrap();
+    pub static ref SINGLE_QUOTES_SLASHES: Regex = Regex::new(r#"['\\]"#).unwrap();
+}
+
+pub enum InfluxQlTerm<'a> {
+    Identifier(&'a str),     // double quote, escape double quotes, backslashes
+    StringLiteral(&'a str),  // single quote, escape single quotes, backslashes
+    Regex(&'a str),          // delimit with forward slashes, escape forward slashes in the pattern
+    Duration(&'a Duration),  // duration literal in the largest unit without loss, e.g. `90m`
+    Time(&'a DateTime<Utc>), // single quoted RFC3339 string
+}
+
+impl InfluxQlTerm<'_> {
+    pub fn escape(self) -> String {
+        use InfluxQlTerm::*;
+        match self {
+            Identifier(x) => format!(r#""{}""#, Self::escape_any(x, &DOUBLE_QUOTES_SLASHES)),
+            StringLiteral(x) => format!("'{}'", Self::escape_any(x, &SINGLE_QUOTES_SLASHES)),
+            Regex(x) => Self::escape_regex(x),
+            Duration(x) => Self::format_duration(x),
+            Time(x) => format!("'{}'", x.to_rfc3339_opts(SecondsFormat::AutoSi, true)),
+        }
+    }
+
+    fn format_duration(d: &Duration) -> String {
+        const UNITS: [(&str, i128); 8] = [
+            ("w", 7 * 24 * 60 * 60 * 1_000_000_000),
+            ("d", 24 * 60 * 60 * 1_000_000_000),
+            ("h", 60 * 60 * 1_000_000_000),
+            ("m", 60 * 1_000_000_000),
+            ("s", 1_000_000_000),
+            ("ms", 1_000_000),
+            ("u", 1_000),
+            ("ns", 1),
+        ];
+
+        // durations of more than ~292 years don't fit into i64 nanoseconds
+        let secs = d.num_seconds();
+        let subsec_nanos = (*d - Duration::seconds(secs))
+            .num_nanoseconds()
+            .unwrap_or_default();
+        let nanos = i128::from(secs) * 1_000_000_000 + i128::from(subsec_nanos);
+        if nanos == 0 {
+            return "0s".to_string();
+        }
+        let (unit, per_unit) = UNITS
+            .iter()
+            .find(|(_, per_unit)| nanos % per_unit == 0)
+            .unwrap_or(&("ns", 1));
+        format!("{}{}", nanos / per_unit, unit)
+    }
+
+    fn escape_regex(x: &str) -> String {
+        // already delimited, e.g. `/^cpu.*/`, the delimiters are replaced by the escaped pattern's
+        let x = match x.strip_prefix('/').and_then(|x| x.strip_suffix('/')) {
+            Some(pattern) => pattern,
+            None => x,
+        };
+        // the regex crate allows `/` inside patterns, InfluxQL requires it to be escaped
+        let mut escaped = String::with_capacity(x.len() + 2);
+        escaped.push('/');
+        let mut chars = x.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                // a trailing backslash must not escape the closing slash
+                '\\' => {
+                    escaped.push(c);
+                    escaped.push(chars.next().unwrap_or('\\'));
+                }
+                '/' => escaped.push_str(r#"\/"#),
+                _ => escaped.push(c),
+            }
+        }
+        escaped.push('/');
+        escaped
+    }
+
+    fn escape_any(s: &str, re: &Regex) -> String {
+        re.replace_all(s, r#"\$0"#).to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::query::influxql_term::InfluxQlTerm::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test() {
+        assert_eq!(Identifier("cpu").escape(), r#""cpu""#);
+        assert_eq!(Identifier("two weeks").escape(), r#""two weeks""#);
+        assert_eq!(Identifier(r#"my"db"#).escape(), r#""my\"db""#);
+        assert_eq!(Identifier(r#"back\slash"#).escape(), r#""back\\slash""#);
+        assert_eq!(Identifier("").escape(), r#""""#);
+
+        assert_eq!(
+            StringLiteral("Europe/Berlin").escape(),
+            r#"'Europe/Berlin'"#
+        );
+        assert_eq!(StringLiteral(r#"it's"#).escape(), r#"'it\'s'"#);
+        assert_eq!(StringLiteral(r#"a\b"#).escape(), r#"'a\\b'"#);
+
+        assert_eq!(Regex("/^cpu.*/").escape(), r#"/^cpu.*/"#);
+        assert_eq!(Regex(r#"^web\d+$"#).escape(), r#"/^web\d+$/"#);
+        assert_eq!(Regex("/var/log").escape(), r#"/\/var\/log/"#);
+        assert_eq!(Regex("a//b").escape(), r#"/a\/\/b/"#);
+        assert_eq!(Regex(r#"a\/b"#).escape(), r#"/a\/b/"#);
+        assert_eq!(Regex(r#"a\\/b"#).escape(), r#"/a\\\/b/"#);
+        // neither delimiters in the pattern nor a trailing backslash end the regex early
+        assert_eq!(
+            Regex(r#"/x/ OR "a" = 'b' OR "host" =~ /y/"#).escape(),
+            r#"/x\/ OR "a" = 'b' OR "host" =~ \/y/"#
+        );
+        assert_eq!(Regex(r"abc\").escape(), r"/abc\\/");
+        assert_eq!(Regex(r"/abc\/").escape(), r"/abc\\/");
+        assert_eq!(Regex("/").escape(), r"/\//");
+
+        assert_eq!(Duration(&chrono::Duration::weeks(2)).escape(), "2w");
+        assert_eq!(Duration(&chrono::Duration::days(3)).escape(), "3d");
+        assert_eq!(Duration(&chrono::Duration::hours(1)).escape(), "1h");
+        assert_eq!(Duration(&chrono::Duration::minutes(90)).escape(), "90m");
+        assert_eq!(Duration(&chrono::Duration::seconds(-15)).escape(), "-15s");
+        assert_eq!(
+            Duration(&chrono::Duration::milliseconds(1500)).escape(),
+            "1500ms"
+        );
+        assert_eq!(Duration(&chrono::Duration::microseconds(7)).escape(), "7u");
+        assert_eq!(Duration(&chrono::Duration::nanoseconds(1)).escape(), "1ns");
+        assert_eq!(Duration(&chrono::Duration::zero()).escape(), "0s");
+        // beyond i64 nanoseconds
+        assert_eq!(
+            Duration(&chrono::Duration::milliseconds(i64::MAX)).escape(),
+            "9223372036854775807ms"
+        );
+        assert_eq!(
+            Duration(&chrono::Duration::weeks(20_000)).escape(),
+            "20000w"
+        );
+        assert_eq!(
+            Duration(&(chrono::Duration::weeks(20_000) + chrono::Duration::microseconds(-1)))
+                .escape(),
+            "12095999999999999u"
+        );
+
+        assert_eq!(
+            Time(&Utc.with_ymd_and_hms(2021, 3, 8, 12, 30, 0).unwrap()).escape(),
+            "'2021-03-08T12:30:00Z'"
+        );
+        assert_eq!(
+            Time(&Utc.timestamp_nanos(1_500_000_000)).escape(),
+            "'1970-01-01T00:00:01.500Z'"
+        );
+    }
+}
@@ -1,74 +1,43 @@
 /// InfluxDB Line Protocol escaping helper module.
 /// https://docs.influxdata.com/influxdb/v1.7/write_protocols/line_protocol_tutorial/
+///
+/// The escaping itself is shared with the `no_std` `influxdb_line_protocol` crate.
 use crate::Type;
-use lazy_static::lazy_static;
-use regex::Regex;
-
-lazy_static! {
-    pub static ref COMMAS_SPACES: Regex = Regex::new("[, ]").unwrap();
-    pub static ref COMMAS_SPACES_EQUALS: Regex = Regex::new("[, =]").unwrap();
-    pub static ref QUOTES_SLASHES: Regex = Regex::new(r#"["\\]"#).unwrap();
-    pub static ref SLASHES: Regex = Regex::new(r#"(\\|,| |=|")"#).unwrap();
-}
+use influxdb_line_protocol as line_protocol;
+use std::fmt::Write;
 
 pub enum LineProtoTerm<'a> {
-    Measurement(&'a str), // escape commas, spaces
-    TagKey(&'a str),      // escape commas, equals, spaces
-    TagValue(&'a Type),   // escape commas, equals, spaces
-    FieldKey(&'a str),    // escape commas, equals, spaces
+    Measurement(&'a str), // escape commas, spaces, backslashes
+    TagKey(&'a str),      // escape commas, equals, spaces, quotes, backslashes
+    TagValue(&'a Type),   // escape commas, equals, spaces, quotes, backslashes
+    FieldKey(&'a str),    // escape commas, equals, spaces, quotes, backslashes
     FieldValue(&'a Type), // escape quotes, backslashes + quote
 }
 
 impl LineProtoTerm<'_> {
     pub fn escape(self) -> String {
         use LineProtoTerm::*;
+        let mut escaped = String::new();
         match self {
-            Measurement(x) => Self::escape_any(x, &*COMMAS_SPACES),
-            TagKey(x) | FieldKey(x) => Self::escape_any(x, &*COMMAS_SPACES_EQUALS),
-            FieldValue(x) => Self::escape_field_value(x),
-            TagValue(x) => Self::escape_tag_value(x),
-        }
-    }
-
-    fn escape_field_value(v: &Type) -> String {
-        use Type::*;
-        match v {
-            Boolean(v) => {
-                if *v {
-                    "true"
-                } else {
-                    "false"
-                }
-            }
-            .to_string(),
-            Float(v) => v.to_string(),
-            SignedInteger(v) => format!("{}i", v),
-            UnsignedInteger(v) => format!("{}i", v),
-            Text(v) => format!(r#""{}""#, Self::escape_any(v, &*QUOTES_SLASHES)),
+            Measurement(x) => line_protocol::write_measurement(&mut escaped, x),
+            TagKey(x) | FieldKey(x) => line_protocol::write_key(&mut escaped, x),
+            FieldValue(x) => Self::write_field_value(&mut escaped, x),
+            TagValue(x) => line_protocol::write_tag_value(&mut escaped, &x.to_string()),
         }
+        .expect("writing to a String can not fail");
+        escaped
     }
 
-    fn escape_tag_value(v: &Type) -> String {
+    fn write_field_value(w: &mut String, v: &Type) -> std::fmt::Result {
         use Type::*;
         match v {
-            Boolean(v) => {
-                if *v {
-                    "true"
-                } else {
-                    "false"
-                }
-            }
-            .to_string(),
-            Float(v) => format!(r#"{}"#, v),
-            SignedInteger(v) => format!(r#"{}"#, v),
-            UnsignedInteger(v) => format!(r#"{}"#, v),
-            Text(v) => Self::escape_any(v, &*SLASHES),
+            Boolean(v) => write!(w, "{}", v),
+            Float(v) => write!(w, "{}", v),
+            SignedInteger(v) => write!(w, "{}i", v),
+            UnsignedInteger(v) => write!(w, "{}i", v),
+            Text(v) => line_protocol::write_string_field(w, v),
         }
     }
-
-    fn escape_any(s: &str, re: &Regex) -> String {
-        re.replace_all(s, r#"\$0"#).to_string()
-    }
 }
 
 #[cfg(test)]
@@ -101,7 +70,8 @@ mod test {
         );
 
         assert_eq!(Measurement(r#"wea", ther"#).escape(), r#"wea"\,\ ther"#);
-        assert_eq!(TagKey(r#"locat\ ,=ion"#).escape(), r#"locat\\ \,\=ion"#);
+        assert_eq!(TagKey(r#"locat\ ,=ion"#).escape(), r#"locat\\\ \,\=ion"#);
+        assert_eq!(FieldKey(r#""quoted""#).escape(), r#"\"quoted\""#);
 
         assert_eq!(FieldValue(&Type::Boolean(true)).escape(), r#"true"#);
         assert_eq!(FieldValue(&Type::Boolean(false)).escape(), r#"false"#);
@@ -121,6 +91,44 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_field_value_roundtrip() {
+        use influxdb_line_protocol::FieldValue as Parsed;
+
+        let values = [
+            (Type::Boolean(true), Parsed::Boolean(true)),
+            (Type::Boolean(false), Parsed::Boolean(false)),
+            (Type::Float(1e-7), Parsed::Float(1e-7)),
+            (
+                Type::SignedInteger(i64::MIN),
+                Parsed::SignedInteger(i64::MIN),
+            ),
+            (Type::UnsignedInteger(7), Parsed::SignedInteger(7)),
+        ];
+        for (value, parsed) in values.iter() {
+            assert_eq!(
+                Parsed::parse(&FieldValue(value).escape()).as_ref(),
+                Ok(parsed)
+            );
+        }
+
+        for text in [
+            r#"\"#,
+            r#"ends with \"#,
+            r#"\""#,
+            r#"a "quoted" \ word"#,
+            "new\nline",
+        ] {
+            let escaped = FieldValue(&Type::Text(text.into())).escape();
+            assert_eq!(
+                Parsed::parse(&escaped),
+                Ok(Parsed::Text(text.into())),
+                "{}",
+                escaped
+            );
+        }
+    }
+
     #[test]
     fn test_empty_tag_value() {
         // InfluxDB doesn't support empty tag values. But that's a job
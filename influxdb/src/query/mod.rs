@@ -21,21 +21,44 @@
 //! ```
 
 use chrono::prelude::{DateTime, TimeZone, Utc};
-use std::convert::TryInto;
+use std::cmp::Ordering;
+use std::convert::{TryFrom, TryInto};
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+pub mod annotation;
 pub mod consts;
+pub mod dedupe;
+pub mod explain;
+pub mod functions;
+pub(crate) mod influxql_term;
 mod line_proto_term;
+pub mod naming_policy;
+pub mod param;
 pub mod read_query;
+pub mod select_query;
+pub mod timestamp_generator;
+pub mod to_point;
+pub mod visit;
+pub mod write_hook;
+pub mod write_limits;
 pub mod write_query;
 use std::fmt;
 
 use crate::{Error, ReadQuery, WriteQuery};
 use consts::{MILLIS_PER_SECOND, MINUTES_PER_HOUR, NANOS_PER_MILLI, SECONDS_PER_MINUTE};
+use influxql_term::InfluxQlTerm;
 
 #[cfg(feature = "derive")]
 pub use influxdb_derive::InfluxDbWriteable;
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+/// A point in time since the unix epoch, in a given [`Precision`](crate::Precision)
+///
+/// Timestamps are equal if their precision and value are, so `Timestamp::Seconds(1)` does not equal
+/// `Timestamp::Milliseconds(1000)`. Use [`same_instant`](Timestamp::same_instant) and
+/// [`cmp_instant`](Timestamp::cmp_instant) to compare the points in time they denote.
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Timestamp {
     Nanoseconds(u128),
     Microseconds(u128),
@@ -55,6 +78,391 @@ impl fmt::Display for Timestamp {
     }
 }
 
+impl Timestamp {
+    /// Returns the current time of the system clock, or the unix epoch if the clock is set before
+    /// it
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{InfluxDbWriteable, Timestamp};
+    ///
+    /// let point = Timestamp::now().into_query("weather").add_field("temperature", 82);
+    /// ```
+    pub fn now() -> Timestamp {
+        Timestamp::try_from(SystemTime::now()).unwrap_or(Timestamp::Nanoseconds(0))
+    }
+
+    /// Converts a monotonic [`Instant`](std::time::Instant) into a timestamp, using a reference
+    /// point where the `base` instant was taken at the wall clock time `base_time`
+    ///
+    /// Unlike timestamps taken from [`SystemTime`](std::time::SystemTime) for each point, timestamps
+    /// derived from the same base are not affected by adjustments of the system clock. Returns an
+    /// error if the instant is before the unix epoch or out of the range of
+    /// [`SystemTime`](std::time::SystemTime).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Timestamp;
+    /// use std::convert::TryFrom;
+    /// use std::time::{Duration, Instant, SystemTime};
+    ///
+    /// let (base, base_time) = (Instant::now(), SystemTime::now());
+    /// let timestamp = Timestamp::from_instant(base + Duration::from_millis(5), base, base_time)?;
+    ///
+    /// assert_eq!(timestamp, Timestamp::try_from(base_time + Duration::from_millis(5))?);
+    /// # Ok::<(), influxdb::Error>(())
+    /// ```
+    pub fn from_instant(
+        instant: Instant,
+        base: Instant,
+        base_time: SystemTime,
+    ) -> Result<Timestamp, Error> {
+        let system_time = if instant >= base {
+            base_time.checked_add(instant.duration_since(base))
+        } else {
+            base_time.checked_sub(base.duration_since(instant))
+        };
+        let system_time = system_time.ok_or_else(|| Error::TimestampOutOfRange {
+            error: "instant can not be represented as a system time".to_string(),
+        })?;
+        Timestamp::try_from(system_time)
+    }
+
+    /// Returns the [`Precision`](crate::Precision) of the timestamp
+    pub fn precision(&self) -> Precision {
+        use Timestamp::*;
+        match self {
+            Nanoseconds(_) => Precision::Nanoseconds,
+            Microseconds(_) => Precision::Microseconds,
+            Milliseconds(_) => Precision::Milliseconds,
+            Seconds(_) => Precision::Seconds,
+            Minutes(_) => Precision::Minutes,
+            Hours(_) => Precision::Hours,
+        }
+    }
+
+    /// Converts the timestamp to the given [`Precision`](crate::Precision)
+    ///
+    /// Converting to a coarser precision truncates the timestamp, converting to a finer one
+    /// saturates at `u128::MAX`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Precision, Timestamp};
+    ///
+    /// assert_eq!(Timestamp::Seconds(90).to_precision(Precision::Milliseconds), Timestamp::Milliseconds(90_000));
+    /// assert_eq!(Timestamp::Seconds(90).to_precision(Precision::Minutes), Timestamp::Minutes(1));
+    /// ```
+    pub fn to_precision(&self, precision: Precision) -> Timestamp {
+        use Timestamp::*;
+        let ts = if precision <= self.precision() {
+            self.checked_value_in(precision).unwrap_or(u128::MAX)
+        } else {
+            let divisor = precision.nanos_per_unit() / self.precision().nanos_per_unit();
+            match *self {
+                Nanoseconds(ts) | Microseconds(ts) | Milliseconds(ts) | Seconds(ts)
+                | Minutes(ts) | Hours(ts) => ts / divisor,
+            }
+        };
+        match precision {
+            Precision::Nanoseconds => Nanoseconds(ts),
+            Precision::Microseconds => Microseconds(ts),
+            Precision::Milliseconds => Milliseconds(ts),
+            Precision::Seconds => Seconds(ts),
+            Precision::Minutes => Minutes(ts),
+            Precision::Hours => Hours(ts),
+        }
+    }
+
+    /// Converts the timestamp to the given [`Precision`](crate::Precision) if it is finer,
+    /// truncating it
+    ///
+    /// Timestamps which are already as coarse as `max_precision` are returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Precision, Timestamp};
+    ///
+    /// assert_eq!(Timestamp::Nanoseconds(1_500_000_001).downscale(Precision::Milliseconds), Timestamp::Milliseconds(1500));
+    /// assert_eq!(Timestamp::Hours(1).downscale(Precision::Milliseconds), Timestamp::Hours(1));
+    /// ```
+    pub fn downscale(&self, max_precision: Precision) -> Timestamp {
+        if self.precision() < max_precision {
+            self.to_precision(max_precision)
+        } else {
+            *self
+        }
+    }
+
+    /// Truncates the timestamp to a multiple of the given [`Precision`](crate::Precision),
+    /// keeping its own precision
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Precision, Timestamp};
+    ///
+    /// assert_eq!(Timestamp::Milliseconds(90_500).truncate_to(Precision::Minutes), Timestamp::Milliseconds(60_000));
+    /// assert_eq!(Timestamp::Hours(1).truncate_to(Precision::Seconds), Timestamp::Hours(1));
+    /// ```
+    pub fn truncate_to(&self, precision: Precision) -> Timestamp {
+        self.downscale(precision).to_precision(self.precision())
+    }
+
+    /// Aligns the timestamp to the start of the `interval` it falls into, counted from the unix
+    /// epoch, e.g. to the start of a five minute window, keeping its precision. Intervals shorter
+    /// than the precision of the timestamp return it unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Timestamp;
+    /// use std::time::Duration;
+    ///
+    /// assert_eq!(Timestamp::Seconds(1_000).align_to(Duration::from_secs(300)), Timestamp::Seconds(900));
+    /// ```
+    pub fn align_to(&self, interval: Duration) -> Timestamp {
+        use Timestamp::*;
+        let units = interval.as_nanos() / self.precision().nanos_per_unit();
+        if units == 0 {
+            return *self;
+        }
+        match *self {
+            Nanoseconds(ts) => Nanoseconds(ts - ts % units),
+            Microseconds(ts) => Microseconds(ts - ts % units),
+            Milliseconds(ts) => Milliseconds(ts - ts % units),
+            Seconds(ts) => Seconds(ts - ts % units),
+            Minutes(ts) => Minutes(ts - ts % units),
+            Hours(ts) => Hours(ts - ts % units),
+        }
+    }
+
+    /// Returns whether both timestamps denote the same point in time, regardless of their
+    /// precision
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Timestamp;
+    ///
+    /// assert!(Timestamp::Seconds(1).same_instant(&Timestamp::Milliseconds(1000)));
+    /// assert_ne!(Timestamp::Seconds(1), Timestamp::Milliseconds(1000));
+    /// ```
+    pub fn same_instant(&self, other: &Timestamp) -> bool {
+        self.cmp_instant(other) == Ordering::Equal
+    }
+
+    /// Orders timestamps by the point in time they denote, regardless of their precision
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Timestamp;
+    ///
+    /// let mut timestamps = vec![Timestamp::Seconds(2), Timestamp::Milliseconds(1500)];
+    /// timestamps.sort_by(Timestamp::cmp_instant);
+    /// assert_eq!(timestamps, vec![Timestamp::Milliseconds(1500), Timestamp::Seconds(2)]);
+    /// ```
+    pub fn cmp_instant(&self, other: &Timestamp) -> Ordering {
+        let precision = self.precision().min(other.precision());
+        match (
+            self.checked_value_in(precision),
+            other.checked_value_in(precision),
+        ) {
+            (Some(ts), Some(other_ts)) => ts.cmp(&other_ts),
+            // only the coarser timestamp can overflow, it is the later one
+            (ts, other_ts) => other_ts.is_some().cmp(&ts.is_some()),
+        }
+    }
+
+    /// Adds a duration to the timestamp, returning `None` on overflow
+    ///
+    /// The result has the precision of the timestamp, or the coarsest finer precision which
+    /// represents the duration without loss.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Timestamp;
+    /// use std::time::Duration;
+    ///
+    /// assert_eq!(Timestamp::Seconds(10).checked_add(Duration::from_secs(5)), Some(Timestamp::Seconds(15)));
+    /// assert_eq!(Timestamp::Seconds(10).checked_add(Duration::from_millis(500)), Some(Timestamp::Milliseconds(10_500)));
+    /// ```
+    pub fn checked_add(&self, duration: Duration) -> Option<Timestamp> {
+        let nanos = self.checked_nanos()?.checked_add(duration.as_nanos())?;
+        Some(Timestamp::Nanoseconds(nanos).to_precision(self.precision_with(duration)))
+    }
+
+    /// Subtracts a duration from the timestamp, returning `None` if the result would be before
+    /// the unix epoch
+    ///
+    /// The precision of the result is chosen like for [`checked_add`](Timestamp::checked_add).
+    pub fn checked_sub(&self, duration: Duration) -> Option<Timestamp> {
+        let nanos = self.checked_nanos()?.checked_sub(duration.as_nanos())?;
+        Some(Timestamp::Nanoseconds(nanos).to_precision(self.precision_with(duration)))
+    }
+
+    /// Returns the duration elapsed from `earlier` to this timestamp, or `None` if `earlier` is
+    /// later
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Timestamp;
+    /// use std::time::Duration;
+    ///
+    /// assert_eq!(
+    ///     Timestamp::Seconds(2).checked_duration_since(Timestamp::Milliseconds(500)),
+    ///     Some(Duration::from_millis(1500))
+    /// );
+    /// ```
+    pub fn checked_duration_since(&self, earlier: Timestamp) -> Option<Duration> {
+        let nanos = self
+            .checked_nanos()?
+            .checked_sub(earlier.checked_nanos()?)?;
+        let secs = (nanos / 1_000_000_000).try_into().ok()?;
+        Some(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+    }
+
+    /// Returns the nanoseconds since the unix epoch, or `None` if they overflow
+    pub(crate) fn checked_nanos(&self) -> Option<u128> {
+        self.checked_value_in(Precision::Nanoseconds)
+    }
+
+    /// Returns the value of the timestamp in the given precision, which has to be as fine as its
+    /// own, or `None` if it overflows
+    fn checked_value_in(&self, precision: Precision) -> Option<u128> {
+        use Timestamp::*;
+        let factor = self.precision().nanos_per_unit() / precision.nanos_per_unit();
+        match *self {
+            Nanoseconds(ts) | Microseconds(ts) | Milliseconds(ts) | Seconds(ts) | Minutes(ts)
+            | Hours(ts) => ts.checked_mul(factor),
+        }
+    }
+
+    /// Returns the precision of the timestamp, refined so that `duration` can be added without loss
+    fn precision_with(&self, duration: Duration) -> Precision {
+        let nanos = duration.as_nanos();
+        let mut precision = self.precision();
+        while nanos % precision.nanos_per_unit() != 0 {
+            precision = precision.finer();
+        }
+        precision
+    }
+}
+
+impl Add<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    /// # Panics
+    ///
+    /// Panics on overflow, see [`checked_add`](Timestamp::checked_add)
+    fn add(self, duration: Duration) -> Timestamp {
+        self.checked_add(duration)
+            .expect("overflow when adding duration to timestamp")
+    }
+}
+
+impl AddAssign<Duration> for Timestamp {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+impl Sub<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    /// # Panics
+    ///
+    /// Panics if the result would be before the unix epoch, see
+    /// [`checked_sub`](Timestamp::checked_sub)
+    fn sub(self, duration: Duration) -> Timestamp {
+        self.checked_sub(duration)
+            .expect("overflow when subtracting duration from timestamp")
+    }
+}
+
+impl SubAssign<Duration> for Timestamp {
+    fn sub_assign(&mut self, duration: Duration) {
+        *self = *self - duration;
+    }
+}
+
+/// The precision of [`Timestamp`](crate::Timestamp)s sent to InfluxDB with a single write
+///
+/// Precisions are ordered from the finest to the coarsest.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Copy, Clone)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Precision {
+    Nanoseconds,
+    Microseconds,
+    Milliseconds,
+    Seconds,
+    Minutes,
+    Hours,
+}
+
+impl Precision {
+    /// Parses the precision modifier of a write, e.g. `ms`
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    pub(crate) fn from_modifier(modifier: &str) -> Option<Precision> {
+        use Precision::*;
+        match modifier {
+            "ns" => Some(Nanoseconds),
+            "u" => Some(Microseconds),
+            "ms" => Some(Milliseconds),
+            "s" => Some(Seconds),
+            "m" => Some(Minutes),
+            "h" => Some(Hours),
+            _ => None,
+        }
+    }
+
+    fn nanos_per_unit(self) -> u128 {
+        use Precision::*;
+        match self {
+            Nanoseconds => 1,
+            Microseconds => NANOS_PER_MILLI / 1000,
+            Milliseconds => NANOS_PER_MILLI,
+            Seconds => MILLIS_PER_SECOND * NANOS_PER_MILLI,
+            Minutes => SECONDS_PER_MINUTE * MILLIS_PER_SECOND * NANOS_PER_MILLI,
+            Hours => MINUTES_PER_HOUR * SECONDS_PER_MINUTE * MILLIS_PER_SECOND * NANOS_PER_MILLI,
+        }
+    }
+
+    /// Returns the next finer precision, nanoseconds being the finest
+    fn finer(self) -> Precision {
+        use Precision::*;
+        match self {
+            Nanoseconds | Microseconds => Nanoseconds,
+            Milliseconds => Microseconds,
+            Seconds => Milliseconds,
+            Minutes => Seconds,
+            Hours => Minutes,
+        }
+    }
+}
+
+impl fmt::Display for Precision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Precision::*;
+        let modifier = match self {
+            Nanoseconds => "ns",
+            Microseconds => "u",
+            Milliseconds => "ms",
+            Seconds => "s",
+            Minutes => "m",
+            Hours => "h",
+        };
+        write!(f, "{}", modifier)
+    }
+}
+
 impl From<Timestamp> for DateTime<Utc> {
     fn from(ts: Timestamp) -> DateTime<Utc> {
         match ts {
@@ -89,7 +497,203 @@ where
     T: TimeZone,
 {
     fn from(date_time: DateTime<T>) -> Self {
-        Timestamp::Nanoseconds(date_time.timestamp_nanos() as u128)
+        let nanos = date_time
+            .timestamp_nanos_opt()
+            .expect("value can not be represented in a timestamp with nanosecond precision.");
+        Timestamp::Nanoseconds(nanos as u128)
+    }
+}
+
+impl TryFrom<SystemTime> for Timestamp {
+    type Error = Error;
+
+    /// Converts the time into a timestamp with nanosecond precision, returns an error if it is
+    /// before the unix epoch
+    fn try_from(system_time: SystemTime) -> Result<Self, Self::Error> {
+        let since_epoch =
+            system_time
+                .duration_since(UNIX_EPOCH)
+                .map_err(|err| Error::TimestampOutOfRange {
+                    error: format!("{:?} before the unix epoch", err.duration()),
+                })?;
+        Ok(Timestamp::Nanoseconds(since_epoch.as_nanos()))
+    }
+}
+
+#[cfg(feature = "time03")]
+impl TryFrom<::time::OffsetDateTime> for Timestamp {
+    type Error = Error;
+
+    /// Converts the date and time into a timestamp with nanosecond precision, returns an error if
+    /// it is before the unix epoch
+    fn try_from(date_time: ::time::OffsetDateTime) -> Result<Self, Self::Error> {
+        let nanos = date_time.unix_timestamp_nanos().try_into().map_err(|_| {
+            Error::TimestampOutOfRange {
+                error: format!("{} is before the unix epoch", date_time),
+            }
+        })?;
+        Ok(Timestamp::Nanoseconds(nanos))
+    }
+}
+
+#[cfg(feature = "time03")]
+impl TryFrom<Timestamp> for ::time::OffsetDateTime {
+    type Error = Error;
+
+    /// Converts the timestamp into a date and time in UTC, returns an error if it is after the
+    /// range of `OffsetDateTime`
+    fn try_from(ts: Timestamp) -> Result<Self, Self::Error> {
+        ts.checked_nanos()
+            .and_then(|nanos| nanos.try_into().ok())
+            .and_then(|nanos| ::time::OffsetDateTime::from_unix_timestamp_nanos(nanos).ok())
+            .ok_or_else(|| Error::TimestampOutOfRange {
+                error: format!(
+                    "{} {} can not be represented as OffsetDateTime",
+                    ts,
+                    ts.precision()
+                ),
+            })
+    }
+}
+
+/// A measurement, optionally qualified with the database and retention policy it is stored in
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::Measurement;
+///
+/// let measurement = Measurement::new("cpu")
+///     .database("mydb")
+///     .retention_policy("two_weeks");
+///
+/// assert_eq!(measurement.to_string(), r#""mydb"."two_weeks"."cpu""#);
+/// ```
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Measurement {
+    name: String,
+    #[cfg_attr(feature = "use-serde", serde(default))]
+    regex: bool,
+    database: Option<String>,
+    retention_policy: Option<String>,
+}
+
+impl Measurement {
+    /// Creates a new [`Measurement`](crate::Measurement) which is not qualified with a database or retention policy
+    pub fn new<S>(name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Measurement {
+            name: name.into(),
+            regex: false,
+            database: None,
+            retention_policy: None,
+        }
+    }
+
+    /// Creates a new [`Measurement`](crate::Measurement) matching all measurements the regular expression matches
+    ///
+    /// The pattern may be given with or without the enclosing forward slashes.
+    /// Regular expressions can only be used to read data.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Measurement;
+    ///
+    /// assert_eq!(Measurement::regex("/^cpu.*/").to_string(), "/^cpu.*/");
+    /// assert_eq!(Measurement::regex("^cpu.*").to_string(), "/^cpu.*/");
+    /// ```
+    pub fn regex<S>(pattern: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Measurement {
+            regex: true,
+            ..Measurement::new(pattern)
+        }
+    }
+
+    /// Qualifies the [`Measurement`](crate::Measurement) with the database it is stored in
+    pub fn database<S>(mut self, database: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.database = Some(database.into());
+        self
+    }
+
+    /// Qualifies the [`Measurement`](crate::Measurement) with the retention policy it is stored in
+    pub fn retention_policy<S>(mut self, retention_policy: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.retention_policy = Some(retention_policy.into());
+        self
+    }
+
+    /// Returns the name of the measurement, or the pattern if it is a regular expression
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns whether the measurement is a regular expression
+    pub fn is_regex(&self) -> bool {
+        self.regex
+    }
+
+    /// Returns the database the measurement is qualified with
+    pub fn get_database(&self) -> Option<&str> {
+        self.database.as_deref()
+    }
+
+    /// Returns the retention policy the measurement is qualified with
+    pub fn get_retention_policy(&self) -> Option<&str> {
+        self.retention_policy.as_deref()
+    }
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(database) = &self.database {
+            // `"db".."measurement"` selects the default retention policy of `db`
+            write!(f, "{}.", InfluxQlTerm::Identifier(database).escape())?;
+            if self.retention_policy.is_none() {
+                write!(f, ".")?;
+            }
+        }
+        if let Some(retention_policy) = &self.retention_policy {
+            write!(
+                f,
+                "{}.",
+                InfluxQlTerm::Identifier(retention_policy).escape()
+            )?;
+        }
+        if self.regex {
+            write!(f, "{}", InfluxQlTerm::Regex(&self.name).escape())
+        } else {
+            write!(f, "{}", InfluxQlTerm::Identifier(&self.name).escape())
+        }
+    }
+}
+
+impl From<&str> for Measurement {
+    fn from(name: &str) -> Self {
+        Measurement::new(name)
+    }
+}
+
+impl From<String> for Measurement {
+    fn from(name: String) -> Self {
+        Measurement::new(name)
+    }
+}
+
+impl From<&String> for Measurement {
+    fn from(name: &String) -> Self {
+        Measurement::new(name.as_str())
     }
 }
 
@@ -113,6 +717,36 @@ pub trait Query {
     fn build(&self) -> Result<ValidQuery, Error>;
 
     fn get_type(&self) -> QueryType;
+
+    /// Builds the query with all timestamps converted to the given [`Precision`](crate::Precision)
+    ///
+    /// Only used for write queries, when the [`Client`](crate::Client) is configured with a
+    /// precision. Write queries which don't support the conversion return an error.
+    fn build_with_precision(&self, precision: Precision) -> Result<ValidQuery, Error> {
+        Err(Error::InvalidQueryError {
+            error: format!("query cannot be converted to precision {}", precision),
+        })
+    }
+
+    /// Returns the database a write should be sent to, if it differs from the one
+    /// the [`Client`](crate::Client) is using
+    fn get_database(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the retention policy a write should be sent to, if it is not the
+    /// default retention policy of the database
+    fn get_retention_policy(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the points a write query writes, so the [`Client`](crate::Client) can apply its
+    /// [`WriteHook`](crate::WriteHook)s to them
+    ///
+    /// Write queries returning `None` are written without applying the hooks.
+    fn points(&self) -> Option<&[WriteQuery]> {
+        None
+    }
 }
 
 pub trait InfluxDbWriteable {
@@ -183,9 +817,221 @@ mod tests {
     use super::consts::{
         MICROS_PER_NANO, MILLIS_PER_SECOND, MINUTES_PER_HOUR, NANOS_PER_MILLI, SECONDS_PER_MINUTE,
     };
-    use crate::query::{Timestamp, ValidQuery};
+    use crate::query::{Measurement, Precision, Timestamp, ValidQuery};
     use chrono::prelude::{DateTime, TimeZone, Utc};
-    use std::convert::TryInto;
+    use std::convert::{TryFrom, TryInto};
+    #[test]
+    #[cfg(feature = "time03")]
+    fn test_timestamp_from_offset_date_time() {
+        use time::macros::datetime;
+
+        let offset_date_time = datetime!(1970-01-01 00:00:01.5 UTC);
+        let timestamp = Timestamp::try_from(offset_date_time).unwrap();
+        assert_eq!(timestamp, Timestamp::Nanoseconds(1_500_000_000));
+        assert_eq!(
+            time::OffsetDateTime::try_from(timestamp).unwrap(),
+            offset_date_time
+        );
+        assert_eq!(
+            time::OffsetDateTime::try_from(Timestamp::Hours(1)).unwrap(),
+            datetime!(1970-01-01 01:00 UTC)
+        );
+        assert!(Timestamp::try_from(datetime!(1969-12-31 23:59:59 UTC)).is_err());
+        assert!(time::OffsetDateTime::try_from(Timestamp::Hours(u128::MAX)).is_err());
+    }
+
+    #[test]
+    fn test_timestamp_from_system_time() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let timestamp = Timestamp::try_from(UNIX_EPOCH + Duration::new(2, 500)).unwrap();
+        assert_eq!(timestamp, Timestamp::Nanoseconds(2_000_000_500));
+        assert!(Timestamp::try_from(UNIX_EPOCH - Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn test_timestamp_from_instant() {
+        use std::time::{Duration, Instant, UNIX_EPOCH};
+
+        let base = Instant::now() + Duration::from_secs(10);
+        let base_time = UNIX_EPOCH + Duration::from_secs(100);
+
+        assert_eq!(
+            Timestamp::from_instant(base + Duration::from_millis(1500), base, base_time).ok(),
+            Some(Timestamp::Nanoseconds(101_500_000_000))
+        );
+        assert_eq!(
+            Timestamp::from_instant(base - Duration::from_secs(1), base, base_time).ok(),
+            Some(Timestamp::Nanoseconds(99_000_000_000))
+        );
+        assert!(Timestamp::from_instant(base - Duration::from_secs(101), base, base_time).is_err());
+    }
+
+    #[test]
+    fn test_timestamp_to_precision() {
+        let ts = Timestamp::Milliseconds(90_500);
+        assert_eq!(ts.precision(), Precision::Milliseconds);
+        assert_eq!(
+            ts.to_precision(Precision::Nanoseconds),
+            Timestamp::Nanoseconds(90_500_000_000)
+        );
+        assert_eq!(
+            ts.to_precision(Precision::Microseconds),
+            Timestamp::Microseconds(90_500_000)
+        );
+        assert_eq!(ts.to_precision(Precision::Seconds), Timestamp::Seconds(90));
+        assert_eq!(ts.to_precision(Precision::Minutes), Timestamp::Minutes(1));
+        assert_eq!(ts.to_precision(Precision::Hours), Timestamp::Hours(0));
+        assert_eq!(
+            Timestamp::Hours(1).to_precision(Precision::Seconds),
+            Timestamp::Seconds(3600)
+        );
+        // beyond u128 nanoseconds
+        assert_eq!(
+            Timestamp::Hours(u128::MAX).to_precision(Precision::Minutes),
+            Timestamp::Minutes(u128::MAX)
+        );
+        assert_eq!(
+            Timestamp::Hours(u128::MAX).to_precision(Precision::Hours),
+            Timestamp::Hours(u128::MAX)
+        );
+        assert_eq!(
+            Timestamp::Seconds(u128::MAX).to_precision(Precision::Hours),
+            Timestamp::Hours(u128::MAX / 3600)
+        );
+    }
+
+    #[test]
+    fn test_timestamp_downscale() {
+        assert_eq!(
+            Timestamp::Nanoseconds(1_999_999_999).downscale(Precision::Seconds),
+            Timestamp::Seconds(1)
+        );
+        assert_eq!(
+            Timestamp::Milliseconds(1500).downscale(Precision::Milliseconds),
+            Timestamp::Milliseconds(1500)
+        );
+        assert_eq!(
+            Timestamp::Minutes(2).downscale(Precision::Seconds),
+            Timestamp::Minutes(2)
+        );
+    }
+
+    #[test]
+    fn test_timestamp_arithmetic() {
+        use std::time::Duration;
+
+        let mut ts = Timestamp::Seconds(60);
+        ts += Duration::from_secs(30);
+        assert_eq!(ts.precision(), Precision::Seconds);
+        assert_eq!(ts, Timestamp::Seconds(90));
+        assert_eq!(
+            (ts - Duration::from_micros(1)).precision(),
+            Precision::Microseconds
+        );
+        assert_eq!(
+            ts - Duration::from_micros(1),
+            Timestamp::Microseconds(89_999_999)
+        );
+        assert_eq!(
+            Timestamp::Hours(1).checked_sub(Duration::from_secs(3601)),
+            None
+        );
+        assert_eq!(
+            Timestamp::Nanoseconds(u128::MAX).checked_add(Duration::from_nanos(1)),
+            None
+        );
+        assert_eq!(
+            Timestamp::Minutes(1).checked_duration_since(Timestamp::Seconds(15)),
+            Some(Duration::from_secs(45))
+        );
+        assert_eq!(
+            Timestamp::Seconds(15).checked_duration_since(Timestamp::Minutes(1)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_timestamp_alignment() {
+        use std::time::Duration;
+
+        let ts = Timestamp::Nanoseconds(3_723_000_000_123);
+        assert_eq!(
+            ts.truncate_to(Precision::Hours),
+            Timestamp::Nanoseconds(3_600_000_000_000)
+        );
+        assert_eq!(
+            ts.truncate_to(Precision::Nanoseconds),
+            Timestamp::Nanoseconds(3_723_000_000_123)
+        );
+        assert_eq!(
+            ts.align_to(Duration::from_secs(300)),
+            Timestamp::Nanoseconds(3_600_000_000_000)
+        );
+        assert_eq!(
+            Timestamp::Minutes(7).align_to(Duration::from_secs(1)),
+            Timestamp::Minutes(7)
+        );
+        assert_eq!(
+            Timestamp::Hours(u128::MAX).align_to(Duration::from_secs(7200)),
+            Timestamp::Hours(u128::MAX - 1)
+        );
+    }
+
+    #[test]
+    fn test_timestamp_comparison() {
+        use std::cmp::Ordering;
+
+        assert_ne!(Timestamp::Seconds(1), Timestamp::Milliseconds(1000));
+        assert!(Timestamp::Seconds(1).same_instant(&Timestamp::Milliseconds(1000)));
+        assert!(!Timestamp::Seconds(1).same_instant(&Timestamp::Milliseconds(1001)));
+        assert_eq!(
+            Timestamp::Minutes(1).cmp_instant(&Timestamp::Seconds(59)),
+            Ordering::Greater
+        );
+        assert_eq!(
+            Timestamp::Nanoseconds(1).cmp_instant(&Timestamp::Hours(1)),
+            Ordering::Less
+        );
+        assert_eq!(
+            Timestamp::Hours(u128::MAX).cmp_instant(&Timestamp::Nanoseconds(u128::MAX)),
+            Ordering::Greater
+        );
+        assert_eq!(
+            Timestamp::Nanoseconds(u128::MAX).cmp_instant(&Timestamp::Hours(u128::MAX)),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_precision_modifier() {
+        for precision in &[
+            Precision::Nanoseconds,
+            Precision::Microseconds,
+            Precision::Milliseconds,
+            Precision::Seconds,
+            Precision::Minutes,
+            Precision::Hours,
+        ] {
+            assert_eq!(
+                Precision::from_modifier(&precision.to_string()),
+                Some(*precision)
+            );
+        }
+        assert_eq!(Precision::from_modifier("n"), None);
+        assert!(Precision::Nanoseconds < Precision::Hours);
+    }
+
+    #[test]
+    fn test_precision_display() {
+        assert_eq!(Precision::Nanoseconds.to_string(), "ns");
+        assert_eq!(Precision::Microseconds.to_string(), "u");
+        assert_eq!(Precision::Milliseconds.to_string(), "ms");
+        assert_eq!(Precision::Seconds.to_string(), "s");
+        assert_eq!(Precision::Minutes.to_string(), "m");
+        assert_eq!(Precision::Hours.to_string(), "h");
+    }
+
     #[test]
     fn test_equality_str() {
         assert_eq!(ValidQuery::from("hello"), "hello");
@@ -198,6 +1044,33 @@ mod tests {
         );
     }
     #[test]
+    fn test_format_measurement() {
+        assert_eq!(Measurement::new("cpu").to_string(), r#""cpu""#);
+        assert_eq!(
+            Measurement::new("cpu")
+                .retention_policy("two_weeks")
+                .to_string(),
+            r#""two_weeks"."cpu""#
+        );
+        assert_eq!(
+            Measurement::new("cpu").database("mydb").to_string(),
+            r#""mydb".."cpu""#
+        );
+        assert_eq!(
+            Measurement::new(r#"c"pu"#)
+                .database("my db")
+                .retention_policy("two.weeks")
+                .to_string(),
+            r#""my db"."two.weeks"."c\"pu""#
+        );
+        assert_eq!(
+            Measurement::regex("^cpu.*")
+                .retention_policy("two_weeks")
+                .to_string(),
+            r#""two_weeks"./^cpu.*/"#
+        );
+    }
+    #[test]
     fn test_format_for_timestamp_else() {
         assert!(format!("{}", Timestamp::Nanoseconds(100)) == "100");
     }
@@ -260,7 +1133,8 @@ mod tests {
     }
     #[test]
     fn test_timestamp_from_chrono_date() {
-        let timestamp_from_datetime: Timestamp = Utc.ymd(1970, 1, 1).and_hms(0, 0, 1).into();
+        let timestamp_from_datetime: Timestamp =
+            Utc.with_ymd_and_hms(1970, 1, 1, 0, 0, 1).unwrap().into();
         assert_eq!(
             Timestamp::Nanoseconds(MILLIS_PER_SECOND * NANOS_PER_MILLI),
             timestamp_from_datetime
@@ -0,0 +1,255 @@
+//! Naming conventions for measurements, tag keys and field keys
+//!
+//! A [`NamingPolicy`](crate::NamingPolicy) keeps the schema of many writers consistent. It is a
+//! [`WriteHook`](crate::WriteHook), so it is applied to every point a [`Client`](crate::Client)
+//! writes once added with [`Client::with_naming_policy`](crate::Client::with_naming_policy).
+
+use crate::{Error, WriteHook, WriteQuery};
+
+/// Rules the names of measurements, tag keys and field keys have to follow
+///
+/// Points which break a rule are rejected, unless [`normalize`](NamingPolicy::normalize) is set,
+/// in which case names are converted to snake_case before they are checked. The
+/// [`measurement_prefix`](NamingPolicy::measurement_prefix) is added to measurements which don't
+/// start with it yet.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::{InfluxDbWriteable, NamingPolicy, Query, Timestamp, WriteHook};
+///
+/// let policy = NamingPolicy::new()
+///     .snake_case()
+///     .max_length(32)
+///     .measurement_prefix("billing_");
+///
+/// let point = Timestamp::Seconds(1).into_query("invoices").add_field("total_cents", 1250);
+/// assert_eq!(policy.process(point)?.build()?, "billing_invoices total_cents=1250i 1");
+///
+/// let point = Timestamp::Seconds(1).into_query("invoices").add_field("TotalCents", 1250);
+/// assert!(policy.process(point).is_err());
+/// # Ok::<(), influxdb::Error>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NamingPolicy {
+    snake_case: bool,
+    normalize: bool,
+    max_length: Option<usize>,
+    allowed_characters: Option<String>,
+    measurement_prefix: Option<String>,
+}
+
+impl NamingPolicy {
+    /// Creates a policy without any rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requires names to be snake_case: lowercase ASCII letters, digits and underscores, starting
+    /// with a letter
+    pub fn snake_case(mut self) -> Self {
+        self.snake_case = true;
+        self
+    }
+
+    /// Converts names to snake_case instead of rejecting them, e.g. `CpuLoad` and `cpu-load` to
+    /// `cpu_load`
+    pub fn normalize(mut self) -> Self {
+        self.snake_case = true;
+        self.normalize = true;
+        self
+    }
+
+    /// Limits the length of names in bytes, including the measurement prefix
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Only allows names made of the given characters
+    pub fn allowed_characters<S>(mut self, characters: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.allowed_characters = Some(characters.into());
+        self
+    }
+
+    /// Prefixes every measurement which doesn't start with `prefix` yet
+    pub fn measurement_prefix<S>(mut self, prefix: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.measurement_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Checks the names of a point against the policy, without changing them
+    ///
+    /// Unlike [`process`](WriteHook::process), measurements without the prefix and names which
+    /// aren't snake_case are errors, even with [`normalize`](NamingPolicy::normalize) set.
+    pub fn verify(&self, point: &WriteQuery) -> Result<(), Error> {
+        let measurement = point.measurement().name();
+        if let Some(prefix) = &self.measurement_prefix {
+            if !measurement.starts_with(prefix.as_str()) {
+                return Err(invalid(format!(
+                    "measurement {:?} doesn't start with {:?}",
+                    measurement, prefix
+                )));
+            }
+        }
+        self.check("measurement", measurement)?;
+        for (key, _) in point.tags() {
+            self.check("tag key", key)?;
+        }
+        for (key, _) in point.fields() {
+            self.check("field key", key)?;
+        }
+        Ok(())
+    }
+
+    fn check(&self, kind: &str, name: &str) -> Result<(), Error> {
+        if self.snake_case && !is_snake_case(name) {
+            return Err(invalid(format!("{} {:?} is not snake_case", kind, name)));
+        }
+        if let Some(max_length) = self.max_length {
+            if name.len() > max_length {
+                return Err(invalid(format!(
+                    "{} {:?} is longer than {} bytes",
+                    kind, name, max_length
+                )));
+            }
+        }
+        if let Some(allowed) = &self.allowed_characters {
+            if let Some(c) = name.chars().find(|c| !allowed.contains(*c)) {
+                return Err(invalid(format!(
+                    "{} {:?} contains the disallowed character {:?}",
+                    kind, name, c
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn apply(&self, name: &mut String) {
+        if self.normalize && !is_snake_case(name) {
+            *name = to_snake_case(name);
+        }
+    }
+}
+
+impl WriteHook for NamingPolicy {
+    fn process(&self, mut point: WriteQuery) -> Result<WriteQuery, Error> {
+        let mut measurement = point.measurement().name().to_string();
+        self.apply(&mut measurement);
+        if let Some(prefix) = &self.measurement_prefix {
+            if !measurement.starts_with(prefix.as_str()) {
+                measurement.insert_str(0, prefix);
+            }
+        }
+        point = point.rename_measurement(measurement);
+        for (key, _) in point.tags_mut().iter_mut() {
+            self.apply(key);
+        }
+        for (key, _) in point.fields_mut().iter_mut() {
+            self.apply(key);
+        }
+        self.verify(&point)?;
+        Ok(point)
+    }
+}
+
+fn invalid(error: String) -> Error {
+    Error::InvalidQueryError { error }
+}
+
+fn is_snake_case(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Converts `CpuLoad`, `cpu-load` or `HTTPRequests` to `cpu_load` and `http_requests`
+fn to_snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (index, &c) in chars.iter().enumerate() {
+        if c.is_ascii_alphanumeric() {
+            let previous = index.checked_sub(1).map(|index| chars[index]);
+            let next = chars.get(index + 1);
+            let word_start = c.is_ascii_uppercase()
+                && match previous {
+                    Some(previous)
+                        if previous.is_ascii_lowercase() || previous.is_ascii_digit() =>
+                    {
+                        true
+                    }
+                    Some(previous) if previous.is_ascii_uppercase() => {
+                        matches!(next, Some(next) if next.is_ascii_lowercase())
+                    }
+                    _ => false,
+                };
+            if word_start && !snake.is_empty() && !snake.ends_with('_') {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else if !snake.is_empty() && !snake.ends_with('_') {
+            snake.push('_');
+        }
+    }
+    snake.truncate(snake.trim_end_matches('_').len());
+    snake
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_snake_case, to_snake_case, NamingPolicy};
+    use crate::{InfluxDbWriteable, Query, Timestamp, WriteHook};
+
+    #[test]
+    fn test_snake_case() {
+        assert!(is_snake_case("cpu_load_1m"));
+        assert!(!is_snake_case("CpuLoad"));
+        assert!(!is_snake_case("_cpu"));
+        assert!(!is_snake_case(""));
+
+        assert_eq!(to_snake_case("CpuLoad"), "cpu_load");
+        assert_eq!(to_snake_case("cpu-load"), "cpu_load");
+        assert_eq!(to_snake_case("HTTPRequests"), "http_requests");
+        assert_eq!(to_snake_case("requests5xx"), "requests5xx");
+        assert_eq!(to_snake_case("disk IO--read."), "disk_io_read");
+    }
+
+    #[test]
+    fn test_normalize() {
+        let policy = NamingPolicy::new().normalize().measurement_prefix("app_");
+        let point = Timestamp::Seconds(1)
+            .into_query("CpuLoad")
+            .add_tag("HostName", "web-1")
+            .add_field("user-time", 0.5);
+        assert_eq!(
+            policy.process(point).unwrap().build().unwrap(),
+            "app_cpu_load,host_name=web-1 user_time=0.5 1"
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let policy = NamingPolicy::new()
+            .max_length(8)
+            .allowed_characters("abcdefghijklmnopqrstuvwxyz_");
+        let point = Timestamp::Seconds(1)
+            .into_query("cpu")
+            .add_field("load", 0.5);
+        assert!(policy.verify(&point).is_ok());
+        assert!(policy
+            .verify(&point.clone().add_tag("hostname1", "a"))
+            .is_err());
+        assert!(policy.verify(&point.clone().add_tag("host1", "a")).is_err());
+        assert!(NamingPolicy::new()
+            .measurement_prefix("app_")
+            .verify(&point)
+            .is_err());
+    }
+}
@@ -0,0 +1,179 @@
+//! Values of the `$parameters` of a [`ReadQuery`](crate::ReadQuery), see
+//! [`ReadQuery::param`](crate::ReadQuery::param)
+
+use crate::query::influxql_term::InfluxQlTerm;
+use chrono::{DateTime, Duration, Utc};
+
+/// The value of a `$parameter` of a [`ReadQuery`](crate::ReadQuery), which is quoted according to
+/// its kind when it is substituted
+///
+/// Strings become string literals. Names of fields, tags or measurements have to be given as
+/// [identifiers](Param::identifier), patterns as [regular expressions](Param::regex).
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::{Param, Query, ReadQuery};
+///
+/// let query = ReadQuery::new("SELECT $field FROM cpu WHERE host =~ $hosts AND region = $region")
+///     .param("field", Param::identifier("usage idle"))
+///     .param("hosts", Param::regex("^web/[0-9]+$"))
+///     .param("region", "eu'west");
+///
+/// assert_eq!(
+///     query.build().unwrap(),
+///     r#"SELECT "usage idle" FROM cpu WHERE host =~ /^web\/[0-9]+$/ AND region = 'eu\'west'"#
+/// );
+/// ```
+#[derive(PartialEq, Debug, Clone)]
+pub enum Param {
+    /// A string literal
+    String(String),
+    /// The name of a field, tag or measurement
+    Identifier(String),
+    /// A regular expression, without the enclosing forward slashes
+    Regex(String),
+    /// An integer literal
+    Integer(i64),
+    /// An integer literal, which may be above `i64::MAX`
+    UnsignedInteger(u64),
+    /// A float literal, which has to be finite, always written with a decimal point
+    Float(f64),
+    /// `true` or `false`
+    Boolean(bool),
+    /// A duration literal, e.g. `90m`
+    Duration(Duration),
+    /// A point in time, as an RFC3339 string literal
+    Time(DateTime<Utc>),
+}
+
+impl Param {
+    /// Returns the name of a field, tag or measurement as a parameter
+    pub fn identifier<S>(name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Param::Identifier(name.into())
+    }
+
+    /// Returns a regular expression as a parameter
+    pub fn regex<S>(pattern: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Param::Regex(pattern.into())
+    }
+
+    /// Returns the parameter as InfluxQL, quoted so it can't change the rest of the query, or why
+    /// it can't be written as InfluxQL
+    pub(crate) fn to_influxql(&self) -> Result<String, String> {
+        use Param::*;
+        let influxql = match self {
+            String(s) => InfluxQlTerm::StringLiteral(s).escape(),
+            Identifier(name) => InfluxQlTerm::Identifier(name).escape(),
+            Regex(pattern) => InfluxQlTerm::Regex(pattern).escape(),
+            Integer(i) => i.to_string(),
+            UnsignedInteger(u) => u.to_string(),
+            Float(x) if !x.is_finite() => return Err(format!("{} is not a finite number", x)),
+            // without a decimal point, InfluxQL would read an integer
+            Float(x) if x.fract() == 0.0 => format!("{:.1}", x),
+            Float(x) => x.to_string(),
+            Boolean(b) => b.to_string(),
+            Duration(d) => InfluxQlTerm::Duration(d).escape(),
+            Time(t) => InfluxQlTerm::Time(t).escape(),
+        };
+        Ok(influxql)
+    }
+}
+
+impl From<&str> for Param {
+    fn from(s: &str) -> Self {
+        Param::String(s.to_string())
+    }
+}
+
+impl From<String> for Param {
+    fn from(s: String) -> Self {
+        Param::String(s)
+    }
+}
+
+impl From<&String> for Param {
+    fn from(s: &String) -> Self {
+        Param::String(s.clone())
+    }
+}
+
+macro_rules! from_impl {
+        ( $variant:ident => $( $typ:ident ),+ ) => (
+                $(
+                    impl From<$typ> for Param {
+                        fn from(b: $typ) -> Self {
+                            Param::$variant(b.into())
+                        }
+                    }
+                )+
+        )
+}
+from_impl! {Boolean => bool}
+from_impl! {Float => f32, f64}
+from_impl! {Integer => i8, i16, i32, i64, u8, u16, u32}
+from_impl! {UnsignedInteger => u64}
+
+impl From<Duration> for Param {
+    fn from(d: Duration) -> Self {
+        Param::Duration(d)
+    }
+}
+
+impl From<DateTime<Utc>> for Param {
+    fn from(t: DateTime<Utc>) -> Self {
+        Param::Time(t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Param;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn influxql<P: Into<Param>>(param: P) -> String {
+        param.into().to_influxql().unwrap()
+    }
+
+    #[test]
+    fn test_to_influxql() {
+        assert_eq!(influxql("it's"), r#"'it\'s'"#);
+        assert_eq!(influxql(Param::identifier(r#"a"b"#)), r#""a\"b""#);
+        assert_eq!(influxql(-3), "-3");
+        assert_eq!(influxql(u64::MAX), "18446744073709551615");
+        assert_eq!(influxql(0.5), "0.5");
+        assert_eq!(influxql(true), "true");
+        assert_eq!(influxql(Duration::minutes(90)), "90m");
+        assert_eq!(
+            influxql(Utc.with_ymd_and_hms(2021, 3, 8, 0, 0, 0).unwrap()),
+            "'2021-03-08T00:00:00Z'"
+        );
+    }
+
+    #[test]
+    fn test_floats() {
+        assert_eq!(influxql(1.0), "1.0");
+        assert_eq!(influxql(-2.0), "-2.0");
+        assert_eq!(influxql(1e21), "1000000000000000000000.0");
+        assert_eq!(influxql(1e-7), "0.0000001");
+        assert!(Param::from(f64::NAN).to_influxql().is_err());
+        assert!(Param::from(f64::INFINITY).to_influxql().is_err());
+        assert!(Param::from(f32::NEG_INFINITY).to_influxql().is_err());
+    }
+
+    #[test]
+    fn test_regex() {
+        assert_eq!(influxql(Param::regex("^a/b$")), r#"/^a\/b$/"#);
+        assert_eq!(influxql(Param::regex(r"\d+")), r"/\d+/");
+        // delimiters are replaced, and trailing backslashes can't end the regex early
+        assert_eq!(influxql(Param::regex("/a/")), "/a/");
+        assert_eq!(influxql(Param::regex("/a/ OR /b/")), r#"/a\/ OR \/b/"#);
+        assert_eq!(influxql(Param::regex(r"a\")), r"/a\\/");
+    }
+}
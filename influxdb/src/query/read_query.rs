@@ -3,11 +3,24 @@
 //! Can only be instantiated by using Query::raw_read_query
 
 use crate::query::{QueryType, ValidQuery};
-use crate::{Error, Query};
+use crate::{Error, Param, Query};
 
-#[derive(Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadQuery {
     queries: Vec<String>,
+    /// Names of parameters with their quoted values
+    #[cfg_attr(
+        feature = "use-serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    params: Vec<(String, String)>,
+    /// Why the value of a parameter can't be written as InfluxQL, which fails the query
+    #[cfg_attr(
+        feature = "use-serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    param_error: Option<String>,
 }
 
 impl ReadQuery {
@@ -18,6 +31,8 @@ impl ReadQuery {
     {
         ReadQuery {
             queries: vec![query.into()],
+            params: Vec::new(),
+            param_error: None,
         }
     }
 
@@ -29,11 +44,147 @@ impl ReadQuery {
         self.queries.push(query.into());
         self
     }
+
+    /// Sets the value of the parameter `$name` of the statements
+    ///
+    /// Parameters are substituted when the query is built, with their values quoted and escaped
+    /// according to their [kind](crate::Param), so they can't change the rest of the query.
+    /// Building fails if a parameter of a statement has no value, or if a value can't be written as
+    /// InfluxQL, e.g. a float which is NaN or infinite. Statements without parameters are left as
+    /// they are.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use chrono::Duration;
+    /// use influxdb::{Query, ReadQuery};
+    ///
+    /// let query = ReadQuery::new("SELECT mean(value) FROM cpu WHERE host = $host AND time > now() - $range")
+    ///     .param("host", "web'01")
+    ///     .param("range", Duration::hours(6));
+    ///
+    /// assert_eq!(
+    ///     query.build().unwrap(),
+    ///     r#"SELECT mean(value) FROM cpu WHERE host = 'web\'01' AND time > now() - 6h"#
+    /// );
+    /// assert!(ReadQuery::new("SELECT * FROM cpu WHERE host = $host").param("region", "eu").build().is_err());
+    /// ```
+    pub fn param<S, P>(mut self, name: S, value: P) -> Self
+    where
+        S: Into<String>,
+        P: Into<Param>,
+    {
+        let name = name.into();
+        match value.into().to_influxql() {
+            Ok(value) => self.params.push((name, value)),
+            Err(error) => {
+                self.param_error
+                    .get_or_insert_with(|| format!("invalid value of ${}: {}", name, error));
+            }
+        }
+        self
+    }
+
+    /// Returns the statements of the [`ReadQuery`], separated by semicolons
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::Query;
+    ///
+    /// let query = Query::raw_read_query("SELECT * FROM aachen").add_query("SELECT * FROM cologne");
+    ///
+    /// assert_eq!(query.statement(), "SELECT * FROM aachen;SELECT * FROM cologne");
+    /// assert_eq!(query.statements().len(), 2);
+    /// ```
+    pub fn statement(&self) -> String {
+        self.queries.join(";")
+    }
+
+    /// Returns the statements of the [`ReadQuery`], in the order they were added
+    pub fn statements(&self) -> &[String] {
+        &self.queries
+    }
+
+    /// Validates the statements of the [`ReadQuery`] without sending them, see [`ql`](crate::ql)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::ReadQuery;
+    ///
+    /// assert!(ReadQuery::new("SELECT * FROM cpu").validate().is_ok());
+    /// assert!(ReadQuery::new("SELECT * FROM cpu WHERE host = 'web").validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), Error> {
+        for query in &self.queries {
+            crate::ql::validate(query)?;
+        }
+        Ok(())
+    }
+
+    /// Returns a [`ReadQuery`] showing the plans of the statements, without running them
+    ///
+    /// The output can be parsed with [`Client::explain`](crate::Client::explain).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Query, ReadQuery};
+    ///
+    /// let query = ReadQuery::new("SELECT mean(usage_idle) FROM cpu").explain();
+    ///
+    /// assert_eq!(query.build().unwrap(), "EXPLAIN SELECT mean(usage_idle) FROM cpu");
+    /// ```
+    pub fn explain(&self) -> ReadQuery {
+        self.prefixed("EXPLAIN ")
+    }
+
+    /// Returns a [`ReadQuery`] running the statements and showing their plans, annotated with the
+    /// time spent and the data read by each step
+    ///
+    /// The output can be parsed with [`Client::explain_analyze`](crate::Client::explain_analyze).
+    pub fn explain_analyze(&self) -> ReadQuery {
+        self.prefixed("EXPLAIN ANALYZE ")
+    }
+
+    fn prefixed(&self, prefix: &str) -> ReadQuery {
+        ReadQuery {
+            queries: self
+                .queries
+                .iter()
+                .map(|query| {
+                    let query = query.trim_start();
+                    let explain = matches!(query.get(..7), Some(start) if start.eq_ignore_ascii_case("EXPLAIN"));
+                    if explain {
+                        query.to_string()
+                    } else {
+                        format!("{}{}", prefix, query)
+                    }
+                })
+                .collect(),
+            params: self.params.clone(),
+            param_error: self.param_error.clone(),
+        }
+    }
 }
 
 impl Query for ReadQuery {
     fn build(&self) -> Result<ValidQuery, Error> {
-        Ok(ValidQuery(self.queries.join(";")))
+        if let Some(error) = &self.param_error {
+            return Err(Error::InvalidQueryError {
+                error: error.clone(),
+            });
+        }
+        if self.params.is_empty() {
+            return Ok(ValidQuery(self.statement()));
+        }
+        let queries = self
+            .queries
+            .iter()
+            .map(|query| crate::ql::bind(query, &self.params))
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(ValidQuery(queries.join(";")))
     }
 
     fn get_type(&self) -> QueryType {
@@ -47,23 +198,89 @@ mod tests {
 
     #[test]
     fn test_read_builder_single_query() {
-        let query = Query::raw_read_query("SELECT * FROM aachen").build();
+        let query = <dyn Query>::raw_read_query("SELECT * FROM aachen").build();
 
         assert_eq!(query.unwrap(), "SELECT * FROM aachen");
     }
 
     #[test]
     fn test_read_builder_multi_query() {
-        let query = Query::raw_read_query("SELECT * FROM aachen")
+        let query = <dyn Query>::raw_read_query("SELECT * FROM aachen")
             .add_query("SELECT * FROM cologne")
             .build();
 
         assert_eq!(query.unwrap(), "SELECT * FROM aachen;SELECT * FROM cologne");
     }
 
+    #[test]
+    #[cfg(feature = "use-serde")]
+    fn test_serde_roundtrip() {
+        use crate::ReadQuery;
+
+        let query =
+            <dyn Query>::raw_read_query("SELECT * FROM aachen").add_query("SELECT * FROM cologne");
+        let json = serde_json::to_string(&query).unwrap();
+
+        assert_eq!(serde_json::from_str::<ReadQuery>(&json).unwrap(), query);
+    }
+
+    #[test]
+    fn test_params() {
+        use crate::{Param, ReadQuery};
+
+        let query = ReadQuery::new("SELECT * FROM $m WHERE host = $host AND note = '$host'")
+            .add_query("SELECT * FROM cpu -- $comment")
+            .param("m", Param::identifier("cpu"))
+            .param("host", "a' OR 1=1 --")
+            .explain();
+        assert_eq!(
+            query.build().unwrap(),
+            r#"EXPLAIN SELECT * FROM "cpu" WHERE host = 'a\' OR 1=1 --' AND note = '$host';EXPLAIN SELECT * FROM cpu -- $comment"#
+        );
+
+        let error = ReadQuery::new("SELECT * FROM cpu WHERE host = $host")
+            .param("region", "eu")
+            .build()
+            .unwrap_err();
+        assert!(error.to_string().contains("no value for $host"));
+
+        let error = ReadQuery::new("SELECT * FROM cpu WHERE load > $load")
+            .param("load", f64::NAN)
+            .build()
+            .unwrap_err();
+        assert!(error.to_string().contains("invalid value of $load: NaN"));
+        let query = ReadQuery::new("SELECT * FROM cpu WHERE load > $load AND count > $count")
+            .param("load", 1.0)
+            .param("count", u64::MAX);
+        assert_eq!(
+            query.build().unwrap(),
+            "SELECT * FROM cpu WHERE load > 1.0 AND count > 18446744073709551615"
+        );
+
+        let query = ReadQuery::new("SELECT * INTO db2..:MEASUREMENT FROM $m GROUP BY *")
+            .param("m", Param::identifier("cpu"));
+        assert_eq!(
+            query.build().unwrap(),
+            r#"SELECT * INTO db2..:MEASUREMENT FROM "cpu" GROUP BY *"#
+        );
+    }
+
+    #[test]
+    fn test_explain() {
+        let query = <dyn Query>::raw_read_query("SELECT * FROM aachen")
+            .add_query("EXPLAIN SELECT * FROM cologne")
+            .explain_analyze()
+            .build();
+
+        assert_eq!(
+            query.unwrap(),
+            "EXPLAIN ANALYZE SELECT * FROM aachen;EXPLAIN SELECT * FROM cologne"
+        );
+    }
+
     #[test]
     fn test_correct_query_type() {
-        let query = Query::raw_read_query("SELECT * FROM aachen");
+        let query = <dyn Query>::raw_read_query("SELECT * FROM aachen");
 
         assert_eq!(query.get_type(), QueryType::ReadQuery);
     }
@@ -0,0 +1,884 @@
+//! Select Query Builder returned by SelectQuery::new
+//!
+//! Builds `SELECT` statements with correctly quoted identifiers
+
+use crate::query::{
+    functions::Expression, influxql_term::InfluxQlTerm, visit::VisitorMut, Measurement, QueryType,
+    ValidQuery,
+};
+use crate::{Error, Query};
+use chrono::{DateTime, Duration, Utc};
+use std::fmt;
+
+/// A range of time to select points from
+///
+/// # Examples
+///
+/// ```rust
+/// use chrono::{Duration, TimeZone, Utc};
+/// use influxdb::{Query, SelectQuery, TimeRange};
+///
+/// let query = SelectQuery::new()
+///     .from("cpu")
+///     .where_time(TimeRange::last(Duration::hours(1)))
+///     .build();
+/// assert_eq!(query.unwrap(), r#"SELECT * FROM "cpu" WHERE time > now() - 1h"#);
+///
+/// let query = SelectQuery::new()
+///     .from("cpu")
+///     .where_time(TimeRange::between(
+///         Utc.with_ymd_and_hms(2021, 3, 8, 0, 0, 0).unwrap(),
+///         Utc.with_ymd_and_hms(2021, 3, 9, 0, 0, 0).unwrap(),
+///     ))
+///     .build();
+/// assert_eq!(
+///     query.unwrap(),
+///     r#"SELECT * FROM "cpu" WHERE time >= '2021-03-08T00:00:00Z' AND time <= '2021-03-09T00:00:00Z'"#
+/// );
+/// ```
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimeRange {
+    /// Points newer than the duration before the current time of the server
+    Last(Duration),
+    /// Points at or after the start and at or before the end
+    Between(DateTime<Utc>, DateTime<Utc>),
+    /// Points at or after the given time
+    Since(DateTime<Utc>),
+    /// Points before the given time
+    Before(DateTime<Utc>),
+}
+
+impl TimeRange {
+    /// Points newer than the duration before the current time of the server
+    pub fn last(duration: Duration) -> Self {
+        TimeRange::Last(duration)
+    }
+
+    /// Points at or after `start` and at or before `end`
+    pub fn between<T1, T2>(start: T1, end: T2) -> Self
+    where
+        T1: Into<DateTime<Utc>>,
+        T2: Into<DateTime<Utc>>,
+    {
+        TimeRange::Between(start.into(), end.into())
+    }
+
+    /// Points at or after `start`
+    pub fn since<T>(start: T) -> Self
+    where
+        T: Into<DateTime<Utc>>,
+    {
+        TimeRange::Since(start.into())
+    }
+
+    /// Points before `end`
+    pub fn before<T>(end: T) -> Self
+    where
+        T: Into<DateTime<Utc>>,
+    {
+        TimeRange::Before(end.into())
+    }
+}
+
+impl fmt::Display for TimeRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use TimeRange::*;
+        match self {
+            Last(duration) if *duration < Duration::zero() => write!(
+                f,
+                "time > now() + {}",
+                InfluxQlTerm::Duration(&-*duration).escape()
+            ),
+            Last(duration) => write!(
+                f,
+                "time > now() - {}",
+                InfluxQlTerm::Duration(duration).escape()
+            ),
+            Between(start, end) => write!(
+                f,
+                "time >= {} AND time <= {}",
+                InfluxQlTerm::Time(start).escape(),
+                InfluxQlTerm::Time(end).escape()
+            ),
+            Since(start) => write!(f, "time >= {}", InfluxQlTerm::Time(start).escape()),
+            Before(end) => write!(f, "time < {}", InfluxQlTerm::Time(end).escape()),
+        }
+    }
+}
+
+/// How to fill time intervals of a `GROUP BY time()` query which have no data
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Fill {
+    /// Reports `null` for intervals without data, the default of InfluxDB
+    Null,
+    /// Omits intervals without data
+    None,
+    /// Reports the given value for intervals without data
+    Value(f64),
+    /// Reports the value of the previous interval
+    Previous,
+    /// Interpolates linearly between the surrounding intervals
+    Linear,
+}
+
+impl fmt::Display for Fill {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Fill::*;
+        match self {
+            Null => write!(f, "fill(null)"),
+            None => write!(f, "fill(none)"),
+            Value(value) => write!(f, "fill({})", value),
+            Previous => write!(f, "fill(previous)"),
+            Linear => write!(f, "fill(linear)"),
+        }
+    }
+}
+
+/// A condition of the `WHERE` clause of a [`SelectQuery`](crate::SelectQuery)
+///
+/// The conditions of a query are joined with `AND`.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Condition {
+    /// The tag has the value
+    TagEquals(String, String),
+    /// The tag matches the regular expression
+    TagMatches(String, String),
+    /// The tag does not match the regular expression
+    TagNotMatches(String, String),
+    /// The time of the point is within the range
+    Time(TimeRange),
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Condition::*;
+        match self {
+            TagEquals(tag, value) => write!(
+                f,
+                "{} = {}",
+                InfluxQlTerm::Identifier(tag).escape(),
+                InfluxQlTerm::StringLiteral(value).escape()
+            ),
+            TagMatches(tag, pattern) => write!(
+                f,
+                "{} =~ {}",
+                InfluxQlTerm::Identifier(tag).escape(),
+                InfluxQlTerm::Regex(pattern).escape()
+            ),
+            TagNotMatches(tag, pattern) => write!(
+                f,
+                "{} !~ {}",
+                InfluxQlTerm::Identifier(tag).escape(),
+                InfluxQlTerm::Regex(pattern).escape()
+            ),
+            Time(time_range) => write!(f, "{}", time_range),
+        }
+    }
+}
+
+/// Internal Representation of a Select query that has not yet been built
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::{Measurement, Query, SelectQuery};
+///
+/// let query = SelectQuery::new()
+///     .field("value")
+///     .from(Measurement::new("cpu").database("mydb").retention_policy("two_weeks"))
+///     .build();
+///
+/// assert_eq!(query.unwrap(), r#"SELECT "value" FROM "mydb"."two_weeks"."cpu""#);
+/// ```
+#[derive(PartialEq, Debug, Clone, Default)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "use-serde", serde(default))]
+pub struct SelectQuery {
+    fields: Vec<Expression>,
+    into: Option<Measurement>,
+    measurements: Vec<Measurement>,
+    conditions: Vec<Condition>,
+    group_by_time: Option<Duration>,
+    group_by_all: bool,
+    group_by_tags: Vec<String>,
+    fill: Option<Fill>,
+    order_desc: bool,
+    limit: Option<u64>,
+    offset: Option<u64>,
+    slimit: Option<u64>,
+    soffset: Option<u64>,
+    timezone: Option<String>,
+}
+
+impl SelectQuery {
+    /// Creates a new [`SelectQuery`](crate::SelectQuery) selecting all fields and tags
+    pub fn new() -> Self {
+        SelectQuery::default()
+    }
+
+    /// Adds a field or tag, or a [function](crate::functions) of them, to the selected columns of the [`SelectQuery`](crate::SelectQuery)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::functions::max;
+    /// use influxdb::{Query, SelectQuery};
+    ///
+    /// let query = SelectQuery::new().field("host").field(max("value")).from("cpu").build();
+    ///
+    /// assert_eq!(query.unwrap(), r#"SELECT "host", max("value") FROM "cpu""#);
+    /// ```
+    pub fn field<E>(mut self, field: E) -> Self
+    where
+        E: Into<Expression>,
+    {
+        self.fields.push(field.into());
+        self
+    }
+
+    /// Writes the results of the [`SelectQuery`](crate::SelectQuery) into the measurement instead of returning them
+    ///
+    /// Queries with an `INTO` clause are sent to InfluxDB as `POST` requests.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use chrono::Duration;
+    /// use influxdb::functions::mean;
+    /// use influxdb::{Measurement, Query, SelectQuery};
+    ///
+    /// let query = SelectQuery::new()
+    ///     .field(mean("value").alias("value"))
+    ///     .into_measurement(Measurement::new("cpu_1h").database("mydb").retention_policy("one_year"))
+    ///     .from("cpu")
+    ///     .group_by_time(Duration::hours(1))
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     query.unwrap(),
+    ///     r#"SELECT mean("value") AS "value" INTO "mydb"."one_year"."cpu_1h" FROM "cpu" GROUP BY time(1h)"#
+    /// );
+    /// ```
+    pub fn into_measurement<M>(mut self, measurement: M) -> Self
+    where
+        M: Into<Measurement>,
+    {
+        self.into = Some(measurement.into());
+        self
+    }
+
+    /// Adds a measurement to select from to the [`SelectQuery`](crate::SelectQuery)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Measurement, Query, SelectQuery};
+    ///
+    /// SelectQuery::new()
+    ///     .from("cpu")
+    ///     .from(Measurement::new("cpu").retention_policy("two_weeks"))
+    ///     .build();
+    /// ```
+    pub fn from<M>(mut self, measurement: M) -> Self
+    where
+        M: Into<Measurement>,
+    {
+        self.measurements.push(measurement.into());
+        self
+    }
+
+    /// Adds all measurements matching the regular expression to the [`SelectQuery`](crate::SelectQuery)
+    ///
+    /// The pattern may be given with or without the enclosing forward slashes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Query, SelectQuery};
+    ///
+    /// let query = SelectQuery::new().from_regex("/^cpu.*/").build();
+    ///
+    /// assert_eq!(query.unwrap(), "SELECT * FROM /^cpu.*/");
+    /// ```
+    pub fn from_regex<S>(self, pattern: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.from(Measurement::regex(pattern))
+    }
+
+    /// Only selects points whose tag has the value
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Query, SelectQuery};
+    ///
+    /// let query = SelectQuery::new().from("cpu").where_tag("host", "server01").build();
+    ///
+    /// assert_eq!(query.unwrap(), r#"SELECT * FROM "cpu" WHERE "host" = 'server01'"#);
+    /// ```
+    pub fn where_tag<S1, S2>(mut self, tag: S1, value: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        self.conditions
+            .push(Condition::TagEquals(tag.into(), value.into()));
+        self
+    }
+
+    /// Only selects points whose tag matches the regular expression
+    ///
+    /// The pattern may be given with or without the enclosing forward slashes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Query, SelectQuery};
+    ///
+    /// let query = SelectQuery::new()
+    ///     .from("cpu")
+    ///     .where_tag_matches("host", r"/web\d+/")
+    ///     .build();
+    ///
+    /// assert_eq!(query.unwrap(), r#"SELECT * FROM "cpu" WHERE "host" =~ /web\d+/"#);
+    /// ```
+    pub fn where_tag_matches<S1, S2>(mut self, tag: S1, pattern: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        self.conditions
+            .push(Condition::TagMatches(tag.into(), pattern.into()));
+        self
+    }
+
+    /// Only selects points whose tag does not match the regular expression
+    ///
+    /// The pattern may be given with or without the enclosing forward slashes.
+    pub fn where_tag_not_matches<S1, S2>(mut self, tag: S1, pattern: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        self.conditions
+            .push(Condition::TagNotMatches(tag.into(), pattern.into()));
+        self
+    }
+
+    /// Only selects points within the [`TimeRange`](crate::TimeRange)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use chrono::Duration;
+    /// use influxdb::{Query, SelectQuery, TimeRange};
+    ///
+    /// let query = SelectQuery::new()
+    ///     .from("cpu")
+    ///     .where_time(TimeRange::last(Duration::minutes(90)))
+    ///     .build();
+    ///
+    /// assert_eq!(query.unwrap(), r#"SELECT * FROM "cpu" WHERE time > now() - 90m"#);
+    /// ```
+    pub fn where_time(mut self, time_range: TimeRange) -> Self {
+        self.conditions.push(Condition::Time(time_range));
+        self
+    }
+
+    /// Groups the results into time intervals of the given length
+    pub fn group_by_time(mut self, interval: Duration) -> Self {
+        self.group_by_time = Some(interval);
+        self
+    }
+
+    /// Groups the results by the values of the tag
+    pub fn group_by<S>(mut self, tag: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.group_by_tags.push(tag.into());
+        self
+    }
+
+    /// Groups the results by all tags, so that every series is returned separately
+    pub fn group_by_all(mut self) -> Self {
+        self.group_by_all = true;
+        self
+    }
+
+    /// Sets how time intervals without data are reported when grouping by time
+    pub fn fill(mut self, fill: Fill) -> Self {
+        self.fill = Some(fill);
+        self
+    }
+
+    /// Returns the newest points first, instead of the oldest
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Query, SelectQuery};
+    ///
+    /// // latest point of each series
+    /// let query = SelectQuery::new()
+    ///     .from("cpu")
+    ///     .group_by("host")
+    ///     .order_desc()
+    ///     .limit(1)
+    ///     .build();
+    ///
+    /// assert_eq!(query.unwrap(), r#"SELECT * FROM "cpu" GROUP BY "host" ORDER BY time DESC LIMIT 1"#);
+    /// ```
+    pub fn order_desc(mut self) -> Self {
+        self.order_desc = true;
+        self
+    }
+
+    /// Returns at most `n` points per series
+    pub fn limit(mut self, n: u64) -> Self {
+        self.limit = Some(n);
+        self
+    }
+
+    /// Skips the first `n` points of each series
+    pub fn offset(mut self, n: u64) -> Self {
+        self.offset = Some(n);
+        self
+    }
+
+    /// Returns at most `n` series
+    pub fn slimit(mut self, n: u64) -> Self {
+        self.slimit = Some(n);
+        self
+    }
+
+    /// Skips the first `n` series
+    pub fn soffset(mut self, n: u64) -> Self {
+        self.soffset = Some(n);
+        self
+    }
+
+    /// Returns timestamps in the time zone, e.g. `Europe/Berlin`, instead of UTC
+    pub fn tz<S>(mut self, timezone: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.timezone = Some(timezone.into());
+        self
+    }
+
+    /// Rewrites the [`SelectQuery`](crate::SelectQuery) with a [`VisitorMut`](crate::visit::VisitorMut)
+    pub fn rewrite<V>(mut self, visitor: &mut V) -> Self
+    where
+        V: VisitorMut + ?Sized,
+    {
+        visitor.visit_select(&mut self);
+        self
+    }
+
+    /// Returns the selected columns, all fields and tags are selected if there are none
+    pub fn fields(&self) -> &[Expression] {
+        &self.fields
+    }
+
+    /// Returns the selected columns for modification
+    pub fn fields_mut(&mut self) -> &mut Vec<Expression> {
+        &mut self.fields
+    }
+
+    /// Returns the measurement the results are written into, if any
+    pub fn get_into_measurement(&self) -> Option<&Measurement> {
+        self.into.as_ref()
+    }
+
+    pub(crate) fn get_into_measurement_mut(&mut self) -> Option<&mut Measurement> {
+        self.into.as_mut()
+    }
+
+    /// Returns the measurements which are selected from
+    pub fn measurements(&self) -> &[Measurement] {
+        &self.measurements
+    }
+
+    /// Returns the measurements which are selected from for modification
+    pub fn measurements_mut(&mut self) -> &mut Vec<Measurement> {
+        &mut self.measurements
+    }
+
+    /// Returns the conditions of the `WHERE` clause
+    pub fn conditions(&self) -> &[Condition] {
+        &self.conditions
+    }
+
+    /// Returns the conditions of the `WHERE` clause for modification
+    pub fn conditions_mut(&mut self) -> &mut Vec<Condition> {
+        &mut self.conditions
+    }
+
+    /// Returns the length of the time intervals the results are grouped into, if any
+    pub fn get_group_by_time(&self) -> Option<Duration> {
+        self.group_by_time
+    }
+
+    /// Returns the tags the results are grouped by
+    pub fn get_group_by_tags(&self) -> &[String] {
+        &self.group_by_tags
+    }
+
+    /// Returns the maximum number of points per series, if any
+    pub fn get_limit(&self) -> Option<u64> {
+        self.limit
+    }
+
+    /// Returns the maximum number of series, if any
+    pub fn get_slimit(&self) -> Option<u64> {
+        self.slimit
+    }
+}
+
+impl Query for SelectQuery {
+    fn build(&self) -> Result<ValidQuery, Error> {
+        if self.measurements.is_empty() {
+            return Err(Error::InvalidQueryError {
+                error: "measurements cannot be empty".to_string(),
+            });
+        }
+
+        let fields = if self.fields.is_empty() {
+            "*".to_string()
+        } else {
+            self.fields
+                .iter()
+                .map(|field| field.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+
+        let measurements = self
+            .measurements
+            .iter()
+            .map(|measurement| measurement.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let mut query = format!("SELECT {}", fields);
+
+        if let Some(into) = &self.into {
+            if into.is_regex() {
+                return Err(Error::InvalidQueryError {
+                    error: "cannot select into a regex measurement".to_string(),
+                });
+            }
+            query.push_str(&format!(" INTO {}", into));
+        }
+
+        query.push_str(&format!(" FROM {}", measurements));
+
+        if !self.conditions.is_empty() {
+            let conditions = self
+                .conditions
+                .iter()
+                .map(|condition| condition.to_string())
+                .collect::<Vec<String>>()
+                .join(" AND ");
+            query.push_str(&format!(" WHERE {}", conditions));
+        }
+
+        let group_by = self
+            .group_by_time
+            .iter()
+            .map(|interval| format!("time({})", InfluxQlTerm::Duration(interval).escape()))
+            .chain(self.group_by_all.then(|| "*".to_string()))
+            .chain(
+                self.group_by_tags
+                    .iter()
+                    .map(|tag| InfluxQlTerm::Identifier(tag).escape()),
+            )
+            .collect::<Vec<String>>();
+        if !group_by.is_empty() {
+            query.push_str(&format!(" GROUP BY {}", group_by.join(", ")));
+        }
+
+        if let Some(fill) = &self.fill {
+            query.push_str(&format!(" {}", fill));
+        }
+
+        if self.order_desc {
+            query.push_str(" ORDER BY time DESC");
+        }
+
+        let limits = [
+            ("LIMIT", self.limit),
+            ("OFFSET", self.offset),
+            ("SLIMIT", self.slimit),
+            ("SOFFSET", self.soffset),
+        ];
+        for (clause, n) in limits.iter() {
+            if let Some(n) = n {
+                query.push_str(&format!(" {} {}", clause, n));
+            }
+        }
+
+        if let Some(timezone) = &self.timezone {
+            query.push_str(&format!(
+                " tz({})",
+                InfluxQlTerm::StringLiteral(timezone).escape()
+            ));
+        }
+
+        Ok(ValidQuery(query))
+    }
+
+    fn get_type(&self) -> QueryType {
+        QueryType::ReadQuery
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::functions::{derivative, mean, percentile};
+    use crate::query::{Measurement, Query, QueryType, Timestamp};
+    use crate::{Fill, SelectQuery, TimeRange};
+    use chrono::{Duration, TimeZone, Utc};
+
+    #[test]
+    fn test_select_builder_no_measurement() {
+        let query = SelectQuery::new().field("value").build();
+
+        assert!(query.is_err(), "Query had no measurement");
+    }
+
+    #[test]
+    fn test_select_builder_all_fields() {
+        let query = SelectQuery::new().from("cpu").build();
+
+        assert_eq!(query.unwrap(), r#"SELECT * FROM "cpu""#);
+    }
+
+    #[test]
+    fn test_select_builder_multiple_fields() {
+        let query = SelectQuery::new()
+            .field("value")
+            .field("host")
+            .from("cpu")
+            .build();
+
+        assert_eq!(query.unwrap(), r#"SELECT "value", "host" FROM "cpu""#);
+    }
+
+    #[test]
+    fn test_select_builder_qualified_measurements() {
+        let query = SelectQuery::new()
+            .from(
+                Measurement::new("cpu")
+                    .database("mydb")
+                    .retention_policy("two_weeks"),
+            )
+            .from(Measurement::new("cpu").database("mydb"))
+            .from(Measurement::new("mem").retention_policy("autogen"))
+            .build();
+
+        assert_eq!(
+            query.unwrap(),
+            r#"SELECT * FROM "mydb"."two_weeks"."cpu", "mydb".."cpu", "autogen"."mem""#
+        );
+    }
+
+    #[test]
+    fn test_select_builder_regex() {
+        let query = SelectQuery::new()
+            .field("value")
+            .from_regex("/^cpu.*/")
+            .from(Measurement::regex("mem/.*").retention_policy("two_weeks"))
+            .where_tag_matches("host", r"/web\d+/")
+            .where_tag_not_matches("region", "^us-")
+            .build();
+
+        assert_eq!(
+            query.unwrap(),
+            r#"SELECT "value" FROM /^cpu.*/, "two_weeks"./mem\/.*/ WHERE "host" =~ /web\d+/ AND "region" !~ /^us-/"#
+        );
+    }
+
+    #[test]
+    fn test_select_builder_regex_injection() {
+        let query = SelectQuery::new()
+            .from("cpu")
+            .where_tag_matches("host", r#"/x/ OR "a" = 'b' OR "host" =~ /y/"#)
+            .where_tag_not_matches("region", r"us\")
+            .build();
+
+        assert_eq!(
+            query.unwrap(),
+            r#"SELECT * FROM "cpu" WHERE "host" =~ /x\/ OR "a" = 'b' OR "host" =~ \/y/ AND "region" !~ /us\\/"#
+        );
+    }
+
+    #[test]
+    fn test_select_builder_time_ranges() {
+        let query = SelectQuery::new()
+            .from("cpu")
+            .where_tag_matches("host", "^web")
+            .where_time(TimeRange::last(Duration::hours(1)))
+            .build();
+        assert_eq!(
+            query.unwrap(),
+            r#"SELECT * FROM "cpu" WHERE "host" =~ /^web/ AND time > now() - 1h"#
+        );
+
+        let query = SelectQuery::new()
+            .from("cpu")
+            .where_time(TimeRange::last(Duration::seconds(-30)))
+            .build();
+        assert_eq!(
+            query.unwrap(),
+            r#"SELECT * FROM "cpu" WHERE time > now() + 30s"#
+        );
+
+        let query = SelectQuery::new()
+            .from("cpu")
+            .where_time(TimeRange::since(Timestamp::Seconds(1)))
+            .where_time(TimeRange::before(
+                Utc.with_ymd_and_hms(2021, 3, 8, 12, 0, 0).unwrap(),
+            ))
+            .build();
+        assert_eq!(
+            query.unwrap(),
+            r#"SELECT * FROM "cpu" WHERE time >= '1970-01-01T00:00:01Z' AND time < '2021-03-08T12:00:00Z'"#
+        );
+    }
+
+    #[test]
+    fn test_select_builder_aggregates() {
+        let query = SelectQuery::new()
+            .field(derivative(mean("value"), Duration::seconds(1)))
+            .field(percentile("value", 99).alias("p99"))
+            .from("cpu")
+            .where_time(TimeRange::last(Duration::hours(6)))
+            .group_by_time(Duration::minutes(5))
+            .group_by("host")
+            .group_by("region")
+            .fill(Fill::Value(0.0))
+            .build();
+
+        assert_eq!(
+            query.unwrap(),
+            r#"SELECT derivative(mean("value"), 1s), percentile("value", 99) AS "p99" FROM "cpu" WHERE time > now() - 6h GROUP BY time(5m), "host", "region" fill(0)"#
+        );
+    }
+
+    #[test]
+    fn test_select_builder_into() {
+        let query = SelectQuery::new()
+            .field(mean("value"))
+            .into_measurement(Measurement::new("cpu_5m").retention_policy("one_year"))
+            .from("cpu")
+            .group_by_time(Duration::minutes(5))
+            .build();
+        assert_eq!(
+            query.unwrap(),
+            r#"SELECT mean("value") INTO "one_year"."cpu_5m" FROM "cpu" GROUP BY time(5m)"#
+        );
+
+        let query = SelectQuery::new()
+            .into_measurement(Measurement::regex("^cpu"))
+            .from("cpu")
+            .build();
+        assert!(query.is_err(), "Query selected into a regex measurement");
+    }
+
+    #[test]
+    fn test_select_builder_ordering_and_limits() {
+        let query = SelectQuery::new()
+            .field(mean("value"))
+            .from("cpu")
+            .where_time(TimeRange::last(Duration::days(1)))
+            .group_by_time(Duration::hours(1))
+            .group_by("host")
+            .fill(Fill::None)
+            .order_desc()
+            .limit(10)
+            .offset(5)
+            .slimit(3)
+            .soffset(6)
+            .tz("Europe/Berlin")
+            .build();
+
+        assert_eq!(
+            query.unwrap(),
+            r#"SELECT mean("value") FROM "cpu" WHERE time > now() - 1d GROUP BY time(1h), "host" fill(none) ORDER BY time DESC LIMIT 10 OFFSET 5 SLIMIT 3 SOFFSET 6 tz('Europe/Berlin')"#
+        );
+    }
+
+    #[test]
+    fn test_select_builder_group_by_all() {
+        let query = SelectQuery::new()
+            .from("cpu")
+            .group_by_all()
+            .order_desc()
+            .limit(1)
+            .build();
+        assert_eq!(
+            query.unwrap(),
+            r#"SELECT * FROM "cpu" GROUP BY * ORDER BY time DESC LIMIT 1"#
+        );
+
+        let query = SelectQuery::new()
+            .field(mean("value"))
+            .from("cpu")
+            .group_by_time(Duration::minutes(1))
+            .group_by_all()
+            .build();
+        assert_eq!(
+            query.unwrap(),
+            r#"SELECT mean("value") FROM "cpu" GROUP BY time(1m), *"#
+        );
+    }
+
+    #[test]
+    fn test_fill() {
+        assert_eq!(Fill::Null.to_string(), "fill(null)");
+        assert_eq!(Fill::None.to_string(), "fill(none)");
+        assert_eq!(Fill::Value(-1.5).to_string(), "fill(-1.5)");
+        assert_eq!(Fill::Previous.to_string(), "fill(previous)");
+        assert_eq!(Fill::Linear.to_string(), "fill(linear)");
+    }
+
+    #[test]
+    #[cfg(feature = "use-serde")]
+    fn test_serde_roundtrip() {
+        let query = SelectQuery::new()
+            .field(derivative(mean("value"), Duration::seconds(1)).alias("rate"))
+            .from(Measurement::new("cpu").retention_policy("two_weeks"))
+            .where_tag_matches("host", "^web")
+            .where_time(TimeRange::between(
+                Utc.with_ymd_and_hms(2021, 3, 8, 0, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2021, 3, 9, 0, 0, 0).unwrap(),
+            ))
+            .group_by_time(Duration::minutes(5))
+            .group_by("host")
+            .fill(Fill::Value(0.0))
+            .limit(10)
+            .tz("Europe/Berlin");
+        let json = serde_json::to_string(&query).unwrap();
+        let deserialized = serde_json::from_str::<SelectQuery>(&json).unwrap();
+        assert_eq!(deserialized.build().unwrap(), query.build().unwrap().get());
+
+        // omitted clauses take their defaults
+        let deserialized =
+            serde_json::from_str::<SelectQuery>(r#"{"measurements":[{"name":"cpu"}]}"#).unwrap();
+        assert_eq!(deserialized.build().unwrap(), r#"SELECT * FROM "cpu""#);
+    }
+
+    #[test]
+    fn test_correct_query_type() {
+        let query = SelectQuery::new().from("cpu");
+
+        assert_eq!(query.get_type(), QueryType::ReadQuery);
+    }
+}
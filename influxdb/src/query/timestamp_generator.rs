@@ -0,0 +1,123 @@
+//! Unique timestamps for high frequency events
+//!
+//! Points with the same measurement, tags and timestamp overwrite each other. When events are
+//! logged faster than the resolution of the system clock, their timestamps collide and all but the
+//! last event are silently lost. A [`TimestampGenerator`](crate::TimestampGenerator) hands out
+//! strictly increasing timestamps instead.
+
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::Timestamp;
+
+/// Generates strictly monotonically increasing [`Timestamp`](crate::Timestamp)s in nanoseconds
+///
+/// Every timestamp is the current time, or one nanosecond after the previous timestamp if the
+/// clock didn't advance or went backwards in the meantime. The generator can be shared between
+/// threads, the timestamps are unique across all of them.
+///
+/// Uniqueness only holds for a single generator, points written with timestamps of different
+/// generators or processes can still collide. Writes have to use
+/// [`Precision::Nanoseconds`](crate::Precision::Nanoseconds), coarser precisions truncate the
+/// timestamps and make them collide again.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::{InfluxDbWriteable, TimestampGenerator};
+/// use std::cmp::Ordering;
+///
+/// let timestamps = TimestampGenerator::new();
+/// let first = timestamps.next();
+/// let second = timestamps.next();
+/// assert_eq!(first.cmp_instant(&second), Ordering::Less);
+///
+/// let query = second.into_query("login").add_field("user", "gero");
+/// ```
+#[derive(Debug, Default)]
+pub struct TimestampGenerator {
+    last: AtomicU64,
+}
+
+impl TimestampGenerator {
+    /// Creates a generator which starts at the current time
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a timestamp later than all timestamps this generator returned before
+    ///
+    /// # Panics
+    ///
+    /// Panics if the system clock is set before the unix epoch, or after the year 2554, which can
+    /// not be represented in nanoseconds.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&self) -> Timestamp {
+        Timestamp::Nanoseconds(u128::from(self.next_after(now())))
+    }
+
+    fn next_after(&self, now: u64) -> u64 {
+        let mut last = self.last.load(Ordering::Relaxed);
+        loop {
+            let next = now.max(last + 1);
+            match self
+                .last
+                .compare_exchange_weak(last, next, Ordering::Relaxed, Ordering::Relaxed)
+            {
+                Ok(_) => return next,
+                Err(current) => last = current,
+            }
+        }
+    }
+}
+
+fn now() -> u64 {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("value can not be represented in a timestamp before the unix epoch.");
+    u64::try_from(since_epoch.as_nanos())
+        .expect("value can not be represented in a timestamp with nanosecond precision.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TimestampGenerator;
+    use crate::Timestamp;
+    use std::cmp::Ordering;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_next_after() {
+        let generator = TimestampGenerator::new();
+        assert_eq!(generator.next_after(100), 100);
+        assert_eq!(generator.next_after(100), 101);
+        // the clock went backwards
+        assert_eq!(generator.next_after(50), 102);
+        assert_eq!(generator.next_after(200), 200);
+    }
+
+    #[test]
+    fn test_unique_across_threads() {
+        let generator = Arc::new(TimestampGenerator::new());
+        let handles = (0..4)
+            .map(|_| {
+                let generator = generator.clone();
+                thread::spawn(move || (0..1000).map(|_| generator.next()).collect::<Vec<_>>())
+            })
+            .collect::<Vec<_>>();
+
+        let mut timestamps = Vec::new();
+        for handle in handles {
+            let generated = handle.join().unwrap();
+            assert!(generated
+                .windows(2)
+                .all(|pair| pair[0].cmp_instant(&pair[1]) == Ordering::Less));
+            timestamps.extend(generated);
+        }
+        timestamps.sort_by(Timestamp::cmp_instant);
+        timestamps.dedup();
+        assert_eq!(timestamps.len(), 4000);
+    }
+}
@@ -0,0 +1,68 @@
+//! Object-safe conversion of metric types into points
+
+use crate::WriteQuery;
+
+/// Converts a value into a point, which knows its measurement
+///
+/// Unlike [`InfluxDbWriteable`](crate::InfluxDbWriteable), the trait borrows the value and can be
+/// used as a trait object, so metrics of different types can be collected and written together.
+/// `#[derive(InfluxDbWriteable)]` implements it for structs with an
+/// `#[influxdb(measurement = "...")]` attribute.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::{InfluxDbWriteable, Query, Timestamp, ToPoint, WriteQuery};
+///
+/// struct CpuLoad(f64);
+/// struct Requests(u64);
+///
+/// impl ToPoint for CpuLoad {
+///     fn to_point(&self) -> WriteQuery {
+///         Timestamp::Seconds(1).into_query("cpu").add_field("load", self.0)
+///     }
+/// }
+///
+/// impl ToPoint for Requests {
+///     fn to_point(&self) -> WriteQuery {
+///         Timestamp::Seconds(1).into_query("http").add_field("requests", self.0)
+///     }
+/// }
+///
+/// let metrics: Vec<Box<dyn ToPoint>> = vec![Box::new(CpuLoad(0.5)), Box::new(Requests(12))];
+/// let batch = metrics.iter().map(|metric| metric.to_point()).collect::<Vec<_>>();
+///
+/// assert_eq!(
+///     batch.build()?.get(),
+///     "cpu load=0.5 1\nhttp requests=12i 1"
+/// );
+/// # Ok::<(), influxdb::Error>(())
+/// ```
+pub trait ToPoint {
+    /// Returns the point the value is written as
+    fn to_point(&self) -> WriteQuery;
+}
+
+impl ToPoint for WriteQuery {
+    fn to_point(&self) -> WriteQuery {
+        self.clone()
+    }
+}
+
+impl<T> ToPoint for &T
+where
+    T: ToPoint + ?Sized,
+{
+    fn to_point(&self) -> WriteQuery {
+        (**self).to_point()
+    }
+}
+
+impl<T> ToPoint for Box<T>
+where
+    T: ToPoint + ?Sized,
+{
+    fn to_point(&self) -> WriteQuery {
+        (**self).to_point()
+    }
+}
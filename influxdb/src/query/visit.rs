@@ -0,0 +1,245 @@
+//! Traversal of the syntax tree of a [`SelectQuery`](crate::SelectQuery)
+//!
+//! A [`Visitor`] inspects a query, a [`VisitorMut`] rewrites it, e.g. in a proxy which restricts
+//! the queries of its users before they are sent to InfluxDB. Each `visit_` method has a default
+//! implementation which visits the children of the node with the `walk_` function of the same
+//! name, implementations which override it call the `walk_` function to keep descending.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use chrono::Duration;
+//! use influxdb::visit::{self, VisitorMut};
+//! use influxdb::{Condition, Query, SelectQuery, TimeRange};
+//!
+//! /// Restricts queries to the series of a tenant and to the last week
+//! struct Restrict {
+//!     tenant: String,
+//! }
+//!
+//! impl VisitorMut for Restrict {
+//!     fn visit_select(&mut self, query: &mut SelectQuery) {
+//!         visit::walk_select_mut(self, query);
+//!         query
+//!             .conditions_mut()
+//!             .push(Condition::TagEquals("tenant".into(), self.tenant.clone()));
+//!         if query.get_limit().map_or(true, |limit| limit > 1000) {
+//!             *query = std::mem::take(query).limit(1000);
+//!         }
+//!     }
+//!
+//!     fn visit_time_range(&mut self, time_range: &mut TimeRange) {
+//!         let max = Duration::weeks(1);
+//!         if let TimeRange::Last(duration) = time_range {
+//!             if *duration > max {
+//!                 *time_range = TimeRange::last(max);
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! let query = SelectQuery::new()
+//!     .from("cpu")
+//!     .where_time(TimeRange::last(Duration::days(30)))
+//!     .rewrite(&mut Restrict { tenant: "acme".into() });
+//!
+//! assert_eq!(
+//!     query.build().unwrap(),
+//!     r#"SELECT * FROM "cpu" WHERE time > now() - 1w AND "tenant" = 'acme' LIMIT 1000"#
+//! );
+//! ```
+
+use crate::query::functions::Expression;
+use crate::query::select_query::{Condition, SelectQuery, TimeRange};
+use crate::query::Measurement;
+
+/// Inspects the nodes of a [`SelectQuery`](crate::SelectQuery), see the [module](self)
+pub trait Visitor {
+    /// Visits a query, its columns, measurements and conditions
+    fn visit_select(&mut self, query: &SelectQuery) {
+        walk_select(self, query);
+    }
+
+    /// Visits a selected column and the arguments of the functions in it
+    fn visit_expression(&mut self, expression: &Expression) {
+        walk_expression(self, expression);
+    }
+
+    /// Visits a measurement which is selected from or written into
+    fn visit_measurement(&mut self, _measurement: &Measurement) {}
+
+    /// Visits a condition of the `WHERE` clause and its time range
+    fn visit_condition(&mut self, condition: &Condition) {
+        walk_condition(self, condition);
+    }
+
+    /// Visits the time range of a condition
+    fn visit_time_range(&mut self, _time_range: &TimeRange) {}
+}
+
+/// Rewrites the nodes of a [`SelectQuery`](crate::SelectQuery) in place, see the
+/// [module](self)
+pub trait VisitorMut {
+    /// Visits a query, its columns, measurements and conditions
+    fn visit_select(&mut self, query: &mut SelectQuery) {
+        walk_select_mut(self, query);
+    }
+
+    /// Visits a selected column and the arguments of the functions in it
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        walk_expression_mut(self, expression);
+    }
+
+    /// Visits a measurement which is selected from or written into
+    fn visit_measurement(&mut self, _measurement: &mut Measurement) {}
+
+    /// Visits a condition of the `WHERE` clause and its time range
+    fn visit_condition(&mut self, condition: &mut Condition) {
+        walk_condition_mut(self, condition);
+    }
+
+    /// Visits the time range of a condition
+    fn visit_time_range(&mut self, _time_range: &mut TimeRange) {}
+}
+
+/// Visits the columns, the `INTO` measurement, the measurements and the conditions of a query
+pub fn walk_select<V>(visitor: &mut V, query: &SelectQuery)
+where
+    V: Visitor + ?Sized,
+{
+    for expression in query.fields() {
+        visitor.visit_expression(expression);
+    }
+    if let Some(into) = query.get_into_measurement() {
+        visitor.visit_measurement(into);
+    }
+    for measurement in query.measurements() {
+        visitor.visit_measurement(measurement);
+    }
+    for condition in query.conditions() {
+        visitor.visit_condition(condition);
+    }
+}
+
+/// Visits the arguments of a function call, or the expression of an alias
+pub fn walk_expression<V>(visitor: &mut V, expression: &Expression)
+where
+    V: Visitor + ?Sized,
+{
+    match expression {
+        Expression::Call(_, arguments) => {
+            for argument in arguments {
+                visitor.visit_expression(argument);
+            }
+        }
+        Expression::Alias(expression, _) => visitor.visit_expression(expression),
+        _ => {}
+    }
+}
+
+/// Visits the time range of a condition
+pub fn walk_condition<V>(visitor: &mut V, condition: &Condition)
+where
+    V: Visitor + ?Sized,
+{
+    if let Condition::Time(time_range) = condition {
+        visitor.visit_time_range(time_range);
+    }
+}
+
+/// Visits the columns, the `INTO` measurement, the measurements and the conditions of a query
+pub fn walk_select_mut<V>(visitor: &mut V, query: &mut SelectQuery)
+where
+    V: VisitorMut + ?Sized,
+{
+    for expression in query.fields_mut() {
+        visitor.visit_expression(expression);
+    }
+    if let Some(into) = query.get_into_measurement_mut() {
+        visitor.visit_measurement(into);
+    }
+    for measurement in query.measurements_mut() {
+        visitor.visit_measurement(measurement);
+    }
+    for condition in query.conditions_mut() {
+        visitor.visit_condition(condition);
+    }
+}
+
+/// Visits the arguments of a function call, or the expression of an alias
+pub fn walk_expression_mut<V>(visitor: &mut V, expression: &mut Expression)
+where
+    V: VisitorMut + ?Sized,
+{
+    match expression {
+        Expression::Call(_, arguments) => {
+            for argument in arguments {
+                visitor.visit_expression(argument);
+            }
+        }
+        Expression::Alias(expression, _) => visitor.visit_expression(expression),
+        _ => {}
+    }
+}
+
+/// Visits the time range of a condition
+pub fn walk_condition_mut<V>(visitor: &mut V, condition: &mut Condition)
+where
+    V: VisitorMut + ?Sized,
+{
+    if let Condition::Time(time_range) = condition {
+        visitor.visit_time_range(time_range);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Visitor, VisitorMut};
+    use crate::functions::{derivative, mean};
+    use crate::{Expression, Measurement, Query, SelectQuery};
+    use chrono::Duration;
+
+    #[derive(Default)]
+    struct Keys(Vec<String>);
+
+    impl Visitor for Keys {
+        fn visit_expression(&mut self, expression: &Expression) {
+            if let Expression::Key(key) = expression {
+                self.0.push(key.clone());
+            }
+            super::walk_expression(self, expression);
+        }
+    }
+
+    struct Database(&'static str);
+
+    impl VisitorMut for Database {
+        fn visit_measurement(&mut self, measurement: &mut Measurement) {
+            *measurement = measurement.clone().database(self.0);
+        }
+    }
+
+    fn query() -> SelectQuery {
+        SelectQuery::new()
+            .field("host")
+            .field(derivative(mean("value"), Duration::seconds(1)).alias("rate"))
+            .into_measurement("cpu_rate")
+            .from("cpu")
+    }
+
+    #[test]
+    fn test_visitor() {
+        let mut keys = Keys::default();
+        keys.visit_select(&query());
+        assert_eq!(keys.0, vec!["host", "value"]);
+    }
+
+    #[test]
+    fn test_visitor_mut() {
+        let query = query().rewrite(&mut Database("tenant_a"));
+        assert_eq!(
+            query.build().unwrap(),
+            r#"SELECT "host", derivative(mean("value"), 1s) AS "rate" INTO "tenant_a".."cpu_rate" FROM "tenant_a".."cpu""#
+        );
+    }
+}
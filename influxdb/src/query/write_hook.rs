@@ -0,0 +1,148 @@
+//! Hooks processing points before they are written
+//!
+//! Hooks are registered with [`Client::with_write_hook`](crate::Client::with_write_hook) and applied
+//! to every point the client writes, no matter whether it was written on its own, in a batch, as a
+//! stream or derived with `#[derive(InfluxDbWriteable)]`.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{Error, Type, WriteQuery};
+
+/// Processes a point before it is serialized to line protocol, e.g. to add tags, to enforce naming
+/// conventions or to prefix measurements with the name of a tenant
+///
+/// Returning an error aborts the write. Hooks are applied after the point was routed to its
+/// database and retention policy, so changing the qualification of its
+/// [`Measurement`](crate::Measurement) has no effect.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::{Error, InfluxDbWriteable, Timestamp, WriteHook, WriteQuery};
+///
+/// let tenant_prefix = |point: WriteQuery| {
+///     let name = format!("tenant_a_{}", point.measurement().name());
+///     Ok::<_, Error>(point.rename_measurement(name))
+/// };
+///
+/// let point = tenant_prefix.process(Timestamp::Seconds(1).into_query("weather"))?;
+/// assert_eq!(point.measurement().name(), "tenant_a_weather");
+/// # Ok::<(), Error>(())
+/// ```
+pub trait WriteHook: Send + Sync {
+    /// Returns the point which is written instead of `point`
+    fn process(&self, point: WriteQuery) -> Result<WriteQuery, Error>;
+}
+
+impl<F> WriteHook for F
+where
+    F: Fn(WriteQuery) -> Result<WriteQuery, Error> + Send + Sync,
+{
+    fn process(&self, point: WriteQuery) -> Result<WriteQuery, Error> {
+        self(point)
+    }
+}
+
+/// The processing a client applies to every point: first its default tags are added, then its
+/// hooks are applied in the order they were added
+#[derive(Clone, Default)]
+#[cfg_attr(not(feature = "client"), allow(dead_code))]
+pub(crate) struct WriteHooks {
+    default_tags: Arc<Vec<(String, Type)>>,
+    hooks: Arc<Vec<Arc<dyn WriteHook>>>,
+}
+
+#[cfg_attr(not(feature = "client"), allow(dead_code))]
+impl WriteHooks {
+    pub(crate) fn push(&mut self, hook: Arc<dyn WriteHook>) {
+        Arc::make_mut(&mut self.hooks).push(hook);
+    }
+
+    /// Sets a default tag, replacing an earlier default with the same key
+    pub(crate) fn set_default_tag(&mut self, key: String, value: Type) {
+        let default_tags = Arc::make_mut(&mut self.default_tags);
+        match default_tags.iter_mut().find(|(tag, _)| *tag == key) {
+            Some((_, default)) => *default = value,
+            None => default_tags.push((key, value)),
+        }
+    }
+
+    pub(crate) fn default_tags(&self) -> &[(String, Type)] {
+        &self.default_tags
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.default_tags.is_empty() && self.hooks.is_empty()
+    }
+
+    pub(crate) fn apply(&self, mut point: WriteQuery) -> Result<WriteQuery, Error> {
+        for (key, value) in self.default_tags.iter() {
+            if !point.tags().iter().any(|(tag, _)| tag == key) {
+                point = point.add_tag(key.as_str(), value.clone());
+            }
+        }
+        self.hooks
+            .iter()
+            .try_fold(point, |point, hook| hook.process(point))
+    }
+}
+
+impl fmt::Debug for WriteHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WriteHooks")
+            .field("default_tags", &self.default_tags)
+            .field("hooks", &self.hooks.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WriteHooks;
+    use crate::{Error, InfluxDbWriteable, Query, Timestamp, Type, WriteQuery};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_apply() {
+        let mut hooks = WriteHooks::default();
+        hooks.push(Arc::new(|point: WriteQuery| {
+            Ok(point.add_tag("region", "eu"))
+        }));
+        hooks.push(Arc::new(|point: WriteQuery| {
+            let name = format!("tenant_{}", point.measurement().name());
+            Ok(point.rename_measurement(name))
+        }));
+
+        let point = Timestamp::Seconds(1)
+            .into_query("weather")
+            .add_field("temperature", 82);
+        let point = hooks.apply(point).unwrap();
+        assert_eq!(point.measurement().name(), "tenant_weather");
+        assert_eq!(point.tags().len(), 1);
+
+        hooks.push(Arc::new(|_| {
+            Err(Error::InvalidQueryError {
+                error: "rejected".to_string(),
+            })
+        }));
+        assert!(hooks.apply(point).is_err());
+    }
+
+    #[test]
+    fn test_default_tags() {
+        let mut hooks = WriteHooks::default();
+        hooks.set_default_tag("host".to_string(), Type::Text("web-1".to_string()));
+        hooks.set_default_tag("env".to_string(), Type::Text("staging".to_string()));
+        hooks.set_default_tag("env".to_string(), Type::Text("prod".to_string()));
+
+        let point = Timestamp::Seconds(1)
+            .into_query("weather")
+            .add_tag("host", "web-2")
+            .add_field("temperature", 82);
+        assert_eq!(
+            hooks.apply(point).unwrap().build().unwrap(),
+            "weather,host=web-2,env=prod temperature=82i 1"
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! Limits of the size of points, checked before they are written
+
+use crate::{Error, Precision, Query, WriteQuery};
+
+/// Limits of the points of writes, like the server enforces them, see
+/// [`Client::with_write_limits`](crate::Client::with_write_limits)
+///
+/// Points which exceed a limit fail with an
+/// [`InvalidQueryError`](crate::Error::InvalidQueryError) naming their measurement, before
+/// anything is sent. Limits can also be checked where points are built, with
+/// [`check`](WriteLimits::check).
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::{InfluxDbWriteable, Timestamp, WriteLimits};
+///
+/// let limits = WriteLimits::new().max_fields(2).max_line_length(1024 * 1024);
+/// let point = Timestamp::Seconds(1)
+///     .into_query("weather")
+///     .add_field("temperature", 82)
+///     .add_field("humidity", 40)
+///     .add_field("pressure", 1013);
+///
+/// let error = limits.check(&point).unwrap_err();
+/// assert_eq!(
+///     error.to_string(),
+///     "query is invalid: point of `weather` has 3 fields, more than the limit of 2"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WriteLimits {
+    max_fields: Option<usize>,
+    max_tags: Option<usize>,
+    max_line_length: Option<usize>,
+}
+
+impl WriteLimits {
+    /// Creates limits which don't limit anything yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of fields of a point
+    pub fn max_fields(mut self, max_fields: usize) -> Self {
+        self.max_fields = Some(max_fields);
+        self
+    }
+
+    /// Sets the maximum number of tags of a point
+    pub fn max_tags(mut self, max_tags: usize) -> Self {
+        self.max_tags = Some(max_tags);
+        self
+    }
+
+    /// Sets the maximum length of the line of a point in bytes, e.g. 1 MiB
+    ///
+    /// Lines are measured with timestamps in nanoseconds, the longest they can be written with.
+    pub fn max_line_length(mut self, max_line_length: usize) -> Self {
+        self.max_line_length = Some(max_line_length);
+        self
+    }
+
+    /// Fails if the point exceeds a limit
+    pub fn check(&self, point: &WriteQuery) -> Result<(), Error> {
+        let exceeded = |what: String, limit: usize| {
+            Err(Error::InvalidQueryError {
+                error: format!(
+                    "point of `{}` has {}, more than the limit of {}",
+                    point.measurement().name(),
+                    what,
+                    limit
+                ),
+            })
+        };
+        if let Some(limit) = self
+            .max_fields
+            .filter(|limit| point.fields().len() > *limit)
+        {
+            return exceeded(format!("{} fields", point.fields().len()), limit);
+        }
+        if let Some(limit) = self.max_tags.filter(|limit| point.tags().len() > *limit) {
+            return exceeded(format!("{} tags", point.tags().len()), limit);
+        }
+        if let Some(limit) = self.max_line_length {
+            let length = point
+                .build_with_precision(Precision::Nanoseconds)?
+                .get()
+                .len();
+            if length > limit {
+                return exceeded(format!("a line of {} bytes", length), limit);
+            }
+        }
+        Ok(())
+    }
+
+    /// Fails if any point of a batch exceeds a limit
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    pub(crate) fn check_all(&self, points: &[WriteQuery]) -> Result<(), Error> {
+        points.iter().try_for_each(|point| self.check(point))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WriteLimits;
+    use crate::{InfluxDbWriteable, Timestamp};
+
+    #[test]
+    fn test_check() {
+        let point = Timestamp::Seconds(1)
+            .into_query("weather")
+            .add_tag("city", "berlin")
+            .add_field("temperature", 82);
+        // `weather,city=berlin temperature=82i 1000000000`
+        let length = 46;
+
+        assert!(WriteLimits::new().check(&point).is_ok());
+        assert!(WriteLimits::new()
+            .max_fields(1)
+            .max_tags(1)
+            .max_line_length(length)
+            .check(&point)
+            .is_ok());
+
+        let message = |limits: WriteLimits| limits.check(&point).unwrap_err().to_string();
+        assert_eq!(
+            message(WriteLimits::new().max_tags(0)),
+            "query is invalid: point of `weather` has 1 tags, more than the limit of 0"
+        );
+        assert_eq!(
+            message(WriteLimits::new().max_line_length(length - 1)),
+            "query is invalid: point of `weather` has a line of 46 bytes, more than the limit of 45"
+        );
+        assert!(WriteLimits::new()
+            .max_fields(0)
+            .check_all(&[point.clone(), point])
+            .is_err());
+    }
+}
@@ -2,10 +2,20 @@
 //!
 //! Can only be instantiated by using Query::write_query
 
+use crate::query::dedupe::{self, DedupeStrategy};
 use crate::query::line_proto_term::LineProtoTerm;
-use crate::query::{QueryType, ValidQuery};
+use crate::query::{Measurement, Precision, QueryType, ValidQuery};
 use crate::{Error, Query, Timestamp};
+use std::borrow::Cow;
+use std::convert::TryFrom;
 use std::fmt::{Display, Formatter};
+use std::num::{
+    NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8,
+};
+use std::panic::Location;
+
+/// Values of string fields longer than this are rejected by InfluxDB
+const MAX_STRING_FIELD_LENGTH: usize = 64 * 1024;
 
 pub trait WriteType {
     fn add_to(self, tag: String, fields_or_tags: &mut Vec<(String, Type)>);
@@ -26,27 +36,135 @@ impl<T: Into<Type>> WriteType for Option<T> {
     }
 }
 
+impl<'a, T> WriteType for &'a Option<T>
+where
+    &'a T: Into<Type>,
+{
+    fn add_to(self, tag: String, fields_or_tags: &mut Vec<(String, Type)>) {
+        if let Some(val) = self {
+            val.add_to(tag, fields_or_tags);
+        }
+    }
+}
+
 /// Internal Representation of a Write query that has not yet been built
-#[derive(Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WriteQuery {
     fields: Vec<(String, Type)>,
     tags: Vec<(String, Type)>,
-    measurement: String,
+    measurement: Measurement,
     timestamp: Timestamp,
+    #[cfg_attr(feature = "use-serde", serde(skip))]
+    strict: bool,
+    /// The first invalid tag or field added in strict mode, with the location it was added at
+    #[cfg_attr(feature = "use-serde", serde(skip))]
+    invalid: Option<String>,
 }
 
 impl WriteQuery {
     /// Creates a new [`WriteQuery`](crate::query::write_query::WriteQuery)
-    pub fn new<S>(timestamp: Timestamp, measurement: S) -> Self
+    ///
+    /// If the [`Measurement`](crate::Measurement) is qualified with a database or retention policy,
+    /// the point is written there instead of the database of the [`Client`](crate::Client).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Measurement, Query, Timestamp, WriteQuery};
+    ///
+    /// let measurement = Measurement::new("cpu").database("mydb").retention_policy("two_weeks");
+    /// let query = WriteQuery::new(Timestamp::Hours(11), measurement).add_field("load", 0.5);
+    ///
+    /// assert_eq!(query.get_retention_policy(), Some("two_weeks"));
+    /// assert_eq!(query.build().unwrap(), "cpu load=0.5 11");
+    /// ```
+    pub fn new<M>(timestamp: Timestamp, measurement: M) -> Self
     where
-        S: Into<String>,
+        M: Into<Measurement>,
     {
         WriteQuery {
             fields: vec![],
             tags: vec![],
             measurement: measurement.into(),
             timestamp,
+            strict: false,
+            invalid: None,
+        }
+    }
+
+    /// Creates a validated [`WriteQuery`](crate::WriteQuery) from its tags and fields, e.g. for
+    /// points whose shape is only known at runtime
+    ///
+    /// Tags and fields are validated like with [`try_add_tag`](crate::WriteQuery::try_add_tag) and
+    /// [`try_add_field`](crate::WriteQuery::try_add_field), and at least one field is required.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Query, Timestamp, Type, WriteQuery};
+    /// use std::collections::HashMap;
+    ///
+    /// let mut fields = HashMap::new();
+    /// fields.insert("temperature", Type::Float(82.5));
+    ///
+    /// let query = WriteQuery::from_parts("weather", vec![("city", "berlin")], fields, Timestamp::Hours(11))?;
+    /// assert_eq!(query.build()?, "weather,city=berlin temperature=82.5 11");
+    /// # Ok::<(), influxdb::Error>(())
+    /// ```
+    pub fn from_parts<M, T, TK, TV, F, FK, FV>(
+        measurement: M,
+        tags: T,
+        fields: F,
+        timestamp: Timestamp,
+    ) -> Result<Self, Error>
+    where
+        M: Into<Measurement>,
+        T: IntoIterator<Item = (TK, TV)>,
+        TK: Into<String>,
+        TV: Into<Type>,
+        F: IntoIterator<Item = (FK, FV)>,
+        FK: Into<String>,
+        FV: Into<Type>,
+    {
+        let mut query = WriteQuery::new(timestamp, measurement);
+        for (tag, value) in tags {
+            query = query.try_add_tag(tag, value.into())?;
         }
+        for (field, value) in fields {
+            query = query.try_add_field(field, value.into())?;
+        }
+        if query.fields.is_empty() {
+            return Err(Error::InvalidQueryError {
+                error: "fields cannot be empty".to_string(),
+            });
+        }
+        Ok(query)
+    }
+
+    /// Validates every tag and field added from now on with
+    /// [`add_tag`](crate::WriteQuery::add_tag) and [`add_field`](crate::WriteQuery::add_field)
+    ///
+    /// The first invalid tag or field fails [`build`](crate::Query::build), with the source location
+    /// it was added at. See [`try_add_field`](crate::WriteQuery::try_add_field) for the validation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{InfluxDbWriteable, Query, Timestamp};
+    ///
+    /// let query = Timestamp::Seconds(1)
+    ///     .into_query("weather")
+    ///     .strict()
+    ///     .add_field("temperature", f64::NAN);
+    ///
+    /// let error = query.build().unwrap_err().to_string();
+    /// assert!(error.contains("non-finite value NaN"), "{}", error);
+    /// assert!(error.contains("(added at "), "{}", error);
+    /// ```
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
     }
 
     /// Adds a field to the [`WriteQuery`](crate::WriteQuery)
@@ -59,15 +177,50 @@ impl WriteQuery {
     ///
     /// Timestamp::Nanoseconds(0).into_query("measurement").add_field("field1", 5).build();
     /// ```
+    #[track_caller]
     pub fn add_field<S, F>(mut self, field: S, value: F) -> Self
     where
         S: Into<String>,
         F: WriteType,
     {
+        let added = self.fields.len();
         value.add_to(field.into(), &mut self.fields);
+        if self.strict && self.invalid.is_none() {
+            if let Err(error) = validate(Kind::Field, &self.fields, added) {
+                self.invalid = Some(format!("{} (added at {})", error, Location::caller()));
+            }
+        }
         self
     }
 
+    /// Adds a field to the [`WriteQuery`](crate::WriteQuery), failing right away if it is invalid
+    ///
+    /// Keys have to be non-empty, can not be `time` or contain line breaks, and must not have
+    /// been added before. Floats have to be finite, unsigned integers can not exceed `i64::MAX`
+    /// and strings can not exceed 64 KiB.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{InfluxDbWriteable, Timestamp};
+    ///
+    /// let query = Timestamp::Seconds(1).into_query("weather");
+    /// assert!(query.clone().try_add_field("temperature", 82).is_ok());
+    /// assert!(query.clone().try_add_field("time", 82).is_err());
+    /// assert!(query.try_add_field("temperature", f64::INFINITY).is_err());
+    /// ```
+    pub fn try_add_field<S, F>(mut self, field: S, value: F) -> Result<Self, Error>
+    where
+        S: Into<String>,
+        F: WriteType,
+    {
+        let added = self.fields.len();
+        value.add_to(field.into(), &mut self.fields);
+        validate(Kind::Field, &self.fields, added)
+            .map_err(|error| Error::InvalidQueryError { error })?;
+        Ok(self)
+    }
+
     /// Adds a tag to the [`WriteQuery`](crate::WriteQuery)
     ///
     /// Please note that a [`WriteQuery`](crate::WriteQuery) requires at least one field. Composing a query with
@@ -83,29 +236,156 @@ impl WriteQuery {
     ///     .into_query("measurement")
     ///     .add_tag("field1", 5); // calling `.build()` now would result in a `Err(Error::InvalidQueryError)`
     /// ```
+    #[track_caller]
     pub fn add_tag<S, I>(mut self, tag: S, value: I) -> Self
     where
         S: Into<String>,
         I: WriteType,
     {
+        let added = self.tags.len();
         value.add_to(tag.into(), &mut self.tags);
+        if self.strict && self.invalid.is_none() {
+            if let Err(error) = validate(Kind::Tag, &self.tags, added) {
+                self.invalid = Some(format!("{} (added at {})", error, Location::caller()));
+            }
+        }
+        self
+    }
+
+    /// Adds a tag to the [`WriteQuery`](crate::WriteQuery), failing right away if it is invalid
+    ///
+    /// Keys are validated like for [`try_add_field`](crate::WriteQuery::try_add_field), values
+    /// can not be empty or contain line breaks.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{InfluxDbWriteable, Timestamp};
+    ///
+    /// let query = Timestamp::Seconds(1).into_query("weather");
+    /// assert!(query.clone().try_add_tag("city", "berlin").is_ok());
+    /// assert!(query.try_add_tag("city", "").is_err());
+    /// ```
+    pub fn try_add_tag<S, I>(mut self, tag: S, value: I) -> Result<Self, Error>
+    where
+        S: Into<String>,
+        I: WriteType,
+    {
+        let added = self.tags.len();
+        value.add_to(tag.into(), &mut self.tags);
+        validate(Kind::Tag, &self.tags, added)
+            .map_err(|error| Error::InvalidQueryError { error })?;
+        Ok(self)
+    }
+
+    /// Makes the point distinguishable from other points with the same measurement, tags and timestamp
+    ///
+    /// Apply the [`DedupeStrategy`](crate::DedupeStrategy) once, when the point is created: clones of
+    /// the returned query, e.g. resubmitted after a failed write, write the very same point.
+    pub fn dedupe(mut self, strategy: &DedupeStrategy) -> Self {
+        match strategy {
+            DedupeStrategy::UniqueTag(key) => self.add_tag(key.as_str(), dedupe::unique_key()),
+            DedupeStrategy::TimestampJitter(max_jitter) if *max_jitter > 0 => {
+                // with the precision, so `Seconds(1)` and `Milliseconds(1)` hash differently
+                let timestamp = format!("{}{}", self.timestamp, self.timestamp.precision());
+                let values = self
+                    .tags
+                    .iter()
+                    .chain(self.fields.iter())
+                    .map(|(key, value)| format!("{}={}", key, value))
+                    .collect::<Vec<String>>();
+                let hash = dedupe::content_hash(
+                    std::iter::once(self.measurement.name())
+                        .chain(values.iter().map(String::as_str))
+                        .chain(std::iter::once(timestamp.as_str())),
+                );
+                if let Timestamp::Nanoseconds(nanos) =
+                    self.timestamp.to_precision(Precision::Nanoseconds)
+                {
+                    self.timestamp = Timestamp::Nanoseconds(nanos + u128::from(hash % max_jitter));
+                }
+                self
+            }
+            DedupeStrategy::TimestampJitter(_) => self,
+        }
+    }
+
+    /// Changes the name of the measurement the point is written to, keeping the database and
+    /// retention policy it is qualified with
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Measurement, Query, Timestamp, WriteQuery};
+    ///
+    /// let query = WriteQuery::new(Timestamp::Hours(11), Measurement::new("cpu").database("mydb"))
+    ///     .add_field("load", 0.5)
+    ///     .rename_measurement("host_cpu");
+    ///
+    /// assert_eq!(query.get_database(), Some("mydb"));
+    /// assert_eq!(query.build().unwrap(), "host_cpu load=0.5 11");
+    /// ```
+    pub fn rename_measurement<S>(mut self, name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.measurement.name = name.into();
         self
     }
 
+    /// Returns the measurement the point is written to
+    pub fn measurement(&self) -> &Measurement {
+        &self.measurement
+    }
+
+    /// Returns the tags of the point, in the order they were added
+    pub fn tags(&self) -> &[(String, Type)] {
+        &self.tags
+    }
+
+    /// Returns the fields of the point, in the order they were added
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{Timestamp, Type};
+    /// use influxdb::InfluxDbWriteable;
+    ///
+    /// let query = Timestamp::Hours(11).into_query("weather").add_field("temperature", 82);
+    ///
+    /// assert_eq!(query.measurement().name(), "weather");
+    /// assert_eq!(query.fields(), &[("temperature".to_string(), Type::SignedInteger(82))]);
+    /// assert_eq!(query.timestamp(), Timestamp::Hours(11));
+    /// ```
+    pub fn fields(&self) -> &[(String, Type)] {
+        &self.fields
+    }
+
+    pub(crate) fn tags_mut(&mut self) -> &mut Vec<(String, Type)> {
+        &mut self.tags
+    }
+
+    pub(crate) fn fields_mut(&mut self) -> &mut Vec<(String, Type)> {
+        &mut self.fields
+    }
+
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    pub(crate) fn timestamp_mut(&mut self) -> &mut Timestamp {
+        &mut self.timestamp
+    }
+
+    /// Returns the timestamp of the point
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
     pub fn get_precision(&self) -> String {
-        let modifier = match self.timestamp {
-            Timestamp::Nanoseconds(_) => "ns",
-            Timestamp::Microseconds(_) => "u",
-            Timestamp::Milliseconds(_) => "ms",
-            Timestamp::Seconds(_) => "s",
-            Timestamp::Minutes(_) => "m",
-            Timestamp::Hours(_) => "h",
-        };
-        modifier.to_string()
+        self.timestamp.precision().to_string()
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Type {
     Boolean(bool),
     Float(f64),
@@ -143,29 +423,106 @@ from_impl! {Boolean => bool}
 from_impl! {Float => f32, f64}
 from_impl! {SignedInteger => i8, i16, i32, i64}
 from_impl! {UnsignedInteger => u8, u16, u32, u64}
-from_impl! {Text => String}
+from_impl! {Text => String, char}
+macro_rules! from_non_zero_impl {
+        ( $variant:ident => $( $typ:ident ),+ ) => (
+                $(
+                    impl From<$typ> for Type {
+                        fn from(b: $typ) -> Self {
+                            Type::$variant(b.get().into())
+                        }
+                    }
+                )+
+        )
+}
+from_non_zero_impl! {SignedInteger => NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64}
+from_non_zero_impl! {UnsignedInteger => NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64}
+// `usize` and `isize` are converted losslessly, which requires them to be at most 64 bits wide
+const _: () = assert!(std::mem::size_of::<usize>() <= std::mem::size_of::<u64>());
+impl From<usize> for Type {
+    fn from(b: usize) -> Self {
+        Type::UnsignedInteger(b as u64)
+    }
+}
+impl From<isize> for Type {
+    fn from(b: isize) -> Self {
+        Type::SignedInteger(b as i64)
+    }
+}
 impl From<&str> for Type {
     fn from(b: &str) -> Self {
         Type::Text(b.into())
     }
 }
+impl From<Cow<'_, str>> for Type {
+    fn from(b: Cow<'_, str>) -> Self {
+        Type::Text(b.into_owned())
+    }
+}
+/// References are cloned, e.g. a `&String` into the one allocation of a [`Type::Text`]
 impl<T> From<&T> for Type
 where
-    T: Copy + Into<Type>,
+    T: Clone + Into<Type>,
 {
     fn from(t: &T) -> Self {
-        (*t).into()
+        t.clone().into()
     }
 }
 
 impl Query for WriteQuery {
     fn build(&self) -> Result<ValidQuery, Error> {
+        self.build_with_timestamp(self.timestamp)
+    }
+
+    fn build_with_precision(&self, precision: Precision) -> Result<ValidQuery, Error> {
+        self.build_with_timestamp(self.timestamp.to_precision(precision))
+    }
+
+    fn get_type(&self) -> QueryType {
+        QueryType::WriteQuery(self.get_precision())
+    }
+
+    fn get_database(&self) -> Option<&str> {
+        self.measurement.get_database()
+    }
+
+    fn get_retention_policy(&self) -> Option<&str> {
+        self.measurement.get_retention_policy()
+    }
+
+    fn points(&self) -> Option<&[WriteQuery]> {
+        Some(std::slice::from_ref(self))
+    }
+}
+
+impl WriteQuery {
+    fn build_with_timestamp(&self, timestamp: Timestamp) -> Result<ValidQuery, Error> {
+        if let Some(error) = &self.invalid {
+            return Err(Error::InvalidQueryError {
+                error: error.clone(),
+            });
+        }
+
         if self.fields.is_empty() {
             return Err(Error::InvalidQueryError {
                 error: "fields cannot be empty".to_string(),
             });
         }
 
+        if self.measurement.is_regex() {
+            return Err(Error::InvalidQueryError {
+                error: "cannot write to a regex measurement".to_string(),
+            });
+        }
+
+        // the line protocol has no representation of NaN and infinity, and unsigned integers are
+        // written as signed ones
+        for (field, value) in &self.fields {
+            if let Some(error) = invalid_field_value(field, value) {
+                return Err(Error::InvalidQueryError { error });
+            }
+        }
+
         let mut tags = self
             .tags
             .iter()
@@ -196,45 +553,399 @@ impl Query for WriteQuery {
             .join(",");
 
         Ok(ValidQuery(format!(
-            "{measurement}{tags} {fields}{time}",
-            measurement = LineProtoTerm::Measurement(&self.measurement).escape(),
+            "{measurement}{tags} {fields} {time}",
+            measurement = LineProtoTerm::Measurement(self.measurement.name()).escape(),
             tags = tags,
             fields = fields,
-            time = format!(" {}", self.timestamp)
+            time = timestamp
         )))
     }
+}
 
-    fn get_type(&self) -> QueryType {
-        QueryType::WriteQuery(self.get_precision())
+/// Returns why the value of a field can't be written, if it can't
+fn invalid_field_value(field: &str, value: &Type) -> Option<String> {
+    match value {
+        Type::Float(float) if !float.is_finite() => {
+            Some(format!("field {:?} has non-finite value {}", field, float))
+        }
+        Type::UnsignedInteger(integer) if i64::try_from(*integer).is_err() => Some(format!(
+            "field {:?} has value {}, which exceeds the largest integer {} of the line protocol",
+            field,
+            integer,
+            i64::MAX
+        )),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Tag,
+    Field,
+}
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Kind::Tag => write!(f, "tag"),
+            Kind::Field => write!(f, "field"),
+        }
     }
 }
 
+/// Validates the tags or fields from index `added` on, which were just added to `entries`
+fn validate(kind: Kind, entries: &[(String, Type)], added: usize) -> Result<(), String> {
+    let (existing, added) = entries.split_at(added);
+    for (key, value) in added {
+        if key.is_empty() {
+            return Err(format!("{} key cannot be empty", kind));
+        }
+        if key == "time" {
+            return Err(format!("time is not allowed as {} key", kind));
+        }
+        if key.contains(&['\n', '\r'][..]) {
+            return Err(format!("{} key {:?} cannot contain line breaks", kind, key));
+        }
+        if existing.iter().any(|(existing, _)| existing == key) {
+            return Err(format!("{} key {:?} was already added", kind, key));
+        }
+        if let Kind::Field = kind {
+            if let Some(error) = invalid_field_value(key, value) {
+                return Err(error);
+            }
+        }
+        match (kind, value) {
+            (Kind::Tag, Type::Float(float)) if !float.is_finite() => {
+                return Err(format!("tag {:?} has non-finite value {}", key, float));
+            }
+            (Kind::Tag, Type::Text(text)) if text.is_empty() => {
+                return Err(format!("tag {:?} cannot have an empty value", key));
+            }
+            (Kind::Tag, Type::Text(text)) if text.contains(&['\n', '\r'][..]) => {
+                return Err(format!("tag {:?} cannot contain line breaks", key));
+            }
+            (Kind::Field, Type::Text(text)) if text.len() > MAX_STRING_FIELD_LENGTH => {
+                return Err(format!(
+                    "field {:?} exceeds the maximum length of {} bytes",
+                    key, MAX_STRING_FIELD_LENGTH
+                ));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// A single write carries the timestamps of one precision, so the timestamps of a batch with mixed
+/// precisions are converted to the finest of them, which is lossless
 impl Query for Vec<WriteQuery> {
     fn build(&self) -> Result<ValidQuery, Error> {
-        let mut qlines = Vec::new();
-
-        for q in self {
-            let valid_query = q.build()?;
-            qlines.push(valid_query.0);
+        match batch_precision(self) {
+            Some(precision) => build_batch(self, |q| q.build_with_precision(precision)),
+            None => build_batch(self, |q| q.build()),
         }
+    }
 
-        Ok(ValidQuery(qlines.join("\n")))
+    fn build_with_precision(&self, precision: Precision) -> Result<ValidQuery, Error> {
+        build_batch(self, |q| q.build_with_precision(precision))
     }
 
     fn get_type(&self) -> QueryType {
         QueryType::WriteQuery(
-            self.get(0)
-                .map(|q| q.get_precision())
+            batch_precision(self)
+                .map(|precision| precision.to_string())
                 // use "ms" as placeholder if query is empty
                 .unwrap_or_else(|| "ms".to_owned()),
         )
     }
+
+    fn get_database(&self) -> Option<&str> {
+        self.first().and_then(|q| q.get_database())
+    }
+
+    fn get_retention_policy(&self) -> Option<&str> {
+        self.first().and_then(|q| q.get_retention_policy())
+    }
+
+    fn points(&self) -> Option<&[WriteQuery]> {
+        Some(self)
+    }
+}
+
+/// Returns the finest precision of the timestamps of a batch, if it isn't empty
+pub(crate) fn batch_precision(queries: &[WriteQuery]) -> Option<Precision> {
+    queries.iter().map(|q| q.timestamp.precision()).min()
+}
+
+fn build_batch<F>(queries: &[WriteQuery], build: F) -> Result<ValidQuery, Error>
+where
+    F: Fn(&WriteQuery) -> Result<ValidQuery, Error>,
+{
+    let mut qlines = Vec::new();
+
+    // a single request can only be routed to a single database and retention policy
+    let first = queries.first();
+    if queries.iter().any(|q| {
+        q.get_database() != first.and_then(|q| q.get_database())
+            || q.get_retention_policy() != first.and_then(|q| q.get_retention_policy())
+    }) {
+        return Err(Error::InvalidQueryError {
+            error: "all queries of a batch must target the same database and retention policy"
+                .to_string(),
+        });
+    }
+
+    for q in queries {
+        let valid_query = build(q)?;
+        qlines.push(valid_query.0);
+    }
+
+    Ok(ValidQuery(qlines.join("\n")))
 }
 
 #[cfg(test)]
 mod tests {
     use crate::query::{InfluxDbWriteable, Query, Timestamp};
 
+    #[test]
+    fn test_try_add() {
+        let query = Timestamp::Hours(11)
+            .into_query("weather")
+            .try_add_tag("city", "berlin")
+            .and_then(|query| query.try_add_field("temperature", 82))
+            .and_then(|query| query.try_add_field("raining", None::<bool>))
+            .unwrap();
+        assert_eq!(
+            query.build().unwrap(),
+            "weather,city=berlin temperature=82i 11"
+        );
+
+        for invalid in &[
+            query.clone().try_add_tag("city", "hamburg"),
+            query.clone().try_add_tag("", "x"),
+            query.clone().try_add_tag("city\n", "x"),
+            query.clone().try_add_tag("region", "eu\nwest"),
+            query.clone().try_add_field("time", 1),
+            query.clone().try_add_field("humidity", f64::NAN),
+            query
+                .clone()
+                .try_add_field("notes", "x".repeat(64 * 1024 + 1)),
+        ] {
+            assert!(invalid.is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_from_parts() {
+        use crate::{Type, WriteQuery};
+        use std::collections::BTreeMap;
+
+        let fields = vec![
+            ("load", Type::Float(0.5)),
+            ("cores", Type::UnsignedInteger(8)),
+        ]
+        .into_iter()
+        .collect::<BTreeMap<_, _>>();
+        let query = WriteQuery::from_parts(
+            "cpu",
+            vec![("host".to_string(), "web-1".to_string())],
+            fields,
+            Timestamp::Seconds(1),
+        )
+        .unwrap();
+        assert_eq!(query.build().unwrap(), "cpu,host=web-1 cores=8i,load=0.5 1");
+
+        let no_tags = Vec::<(&str, &str)>::new();
+        assert!(WriteQuery::from_parts(
+            "cpu",
+            no_tags.clone(),
+            vec![("load", 0.5)],
+            Timestamp::Seconds(1)
+        )
+        .is_ok());
+        assert!(WriteQuery::from_parts(
+            "cpu",
+            no_tags.clone(),
+            Vec::<(&str, f64)>::new(),
+            Timestamp::Seconds(1)
+        )
+        .is_err());
+        assert!(WriteQuery::from_parts(
+            "cpu",
+            vec![("host", "")],
+            vec![("load", 0.5)],
+            Timestamp::Seconds(1)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_strict() {
+        let lenient = Timestamp::Hours(11)
+            .into_query("weather")
+            .add_tag("city", "")
+            .add_field("temperature", 82);
+        assert!(lenient.build().is_ok());
+
+        let strict = Timestamp::Hours(11)
+            .into_query("weather")
+            .strict()
+            .add_field("temperature", 82)
+            .add_tag("city", "")
+            .add_field("temperature", 83);
+        let error = strict.build().unwrap_err().to_string();
+        assert!(
+            error.contains("tag \"city\" cannot have an empty value"),
+            "{}",
+            error
+        );
+        assert!(error.contains(file!()), "{}", error);
+    }
+
+    #[test]
+    fn test_non_finite_fields() {
+        for value in &[f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let query = Timestamp::Hours(11)
+                .into_query("weather")
+                .add_field("temperature", *value);
+            assert!(query.build().is_err(), "{}", value);
+        }
+        // tag values are written as text
+        let query = Timestamp::Hours(11)
+            .into_query("weather")
+            .add_tag("reading", f64::NAN)
+            .add_field("temperature", 82);
+        assert_eq!(
+            query.build().unwrap(),
+            "weather,reading=NaN temperature=82i 11"
+        );
+    }
+
+    #[test]
+    fn test_unsigned_fields_beyond_i64() {
+        let query = Timestamp::Hours(11)
+            .into_query("weather")
+            .add_field("stations", i64::MAX as u64);
+        assert_eq!(
+            query.build().unwrap(),
+            "weather stations=9223372036854775807i 11"
+        );
+
+        let query = Timestamp::Hours(11)
+            .into_query("weather")
+            .add_field("stations", i64::MAX as u64 + 1);
+        assert!(query.build().is_err());
+        assert!(Timestamp::Hours(11)
+            .into_query("weather")
+            .try_add_field("stations", u64::MAX)
+            .is_err());
+
+        // tag values are written as text
+        let query = Timestamp::Hours(11)
+            .into_query("weather")
+            .add_tag("station", u64::MAX)
+            .add_field("temperature", 82);
+        assert_eq!(
+            query.build().unwrap(),
+            "weather,station=18446744073709551615 temperature=82i 11"
+        );
+    }
+
+    /// The examples of the line protocol reference and tutorial,
+    /// https://docs.influxdata.com/influxdb/v1.8/write_protocols/line_protocol_reference/
+    #[test]
+    fn test_line_protocol_reference() {
+        use crate::Type;
+        use influxdb_line_protocol::Point;
+
+        let point = |measurement: &str, tags: &[(&str, &str)], fields: Vec<(&str, Type)>| {
+            let mut point =
+                Timestamp::Nanoseconds(1_465_839_830_100_400_200).into_query(measurement);
+            for (tag, value) in tags {
+                point = point.add_tag(*tag, *value);
+            }
+            for (field, value) in fields {
+                point = point.add_field(field, value);
+            }
+            point.build().unwrap().get()
+        };
+        let midwest = [("location", "us-midwest")];
+        let cases = vec![
+            (
+                point(
+                    "weather",
+                    &midwest,
+                    vec![("temperature", Type::Float(82.0))],
+                ),
+                "weather,location=us-midwest temperature=82 1465839830100400200",
+            ),
+            (
+                point(
+                    "weather",
+                    &midwest,
+                    vec![("temperature", Type::SignedInteger(82))],
+                ),
+                "weather,location=us-midwest temperature=82i 1465839830100400200",
+            ),
+            (
+                point(
+                    "weather",
+                    &midwest,
+                    vec![("temperature", "too warm".into())],
+                ),
+                r#"weather,location=us-midwest temperature="too warm" 1465839830100400200"#,
+            ),
+            (
+                point("weather", &midwest, vec![("too_hot", Type::Boolean(true))]),
+                "weather,location=us-midwest too_hot=true 1465839830100400200",
+            ),
+            (
+                point(
+                    "weather",
+                    &[("location", "us,midwest")],
+                    vec![("temperature", Type::Float(82.0))],
+                ),
+                r#"weather,location=us\,midwest temperature=82 1465839830100400200"#,
+            ),
+            (
+                point(
+                    "weather",
+                    &midwest,
+                    vec![("temp=rature", Type::Float(82.0))],
+                ),
+                r#"weather,location=us-midwest temp\=rature=82 1465839830100400200"#,
+            ),
+            (
+                point(
+                    "weather",
+                    &[("location place", "us-midwest")],
+                    vec![("temperature", Type::Float(82.0))],
+                ),
+                r#"weather,location\ place=us-midwest temperature=82 1465839830100400200"#,
+            ),
+            (
+                point(
+                    "wea, ther",
+                    &midwest,
+                    vec![("temperature", Type::Float(82.0))],
+                ),
+                r#"wea\,\ ther,location=us-midwest temperature=82 1465839830100400200"#,
+            ),
+            (
+                point(
+                    "weather",
+                    &midwest,
+                    vec![("temperature", r#"too"hot""#.into())],
+                ),
+                r#"weather,location=us-midwest temperature="too\"hot\"" 1465839830100400200"#,
+            ),
+        ];
+        for (built, expected) in cases {
+            assert_eq!(built, expected);
+            assert!(Point::parse(&built).is_ok(), "{}", built);
+        }
+    }
+
     #[test]
     fn test_write_builder_empty_query() {
         let query = Timestamp::Hours(5)
@@ -255,6 +966,146 @@ mod tests {
         assert_eq!(query.unwrap(), "weather temperature=82i 11");
     }
 
+    #[test]
+    fn test_dedupe_unique_tag() {
+        use crate::DedupeStrategy;
+
+        let strategy = DedupeStrategy::UniqueTag("event_id".to_string());
+        let q0 = Timestamp::Hours(11)
+            .into_query("weather")
+            .add_field("temperature", 82)
+            .dedupe(&strategy);
+        let q1 = Timestamp::Hours(11)
+            .into_query("weather")
+            .add_field("temperature", 82)
+            .dedupe(&strategy);
+
+        assert_eq!(q0.tags()[0].0, "event_id");
+        assert_ne!(q0.tags(), q1.tags());
+        assert_eq!(q0.clone().build().unwrap(), q0.build().unwrap().get());
+    }
+
+    #[test]
+    fn test_dedupe_timestamp_jitter() {
+        use crate::DedupeStrategy;
+
+        let strategy = DedupeStrategy::TimestampJitter(1000);
+        let query = |temperature| {
+            Timestamp::Seconds(11)
+                .into_query("weather")
+                .add_field("temperature", temperature)
+                .dedupe(&strategy)
+        };
+
+        let (q0, q1) = (query(82), query(65));
+        for q in &[&q0, &q1] {
+            match q.timestamp() {
+                Timestamp::Nanoseconds(nanos) => {
+                    assert!((11_000_000_000..11_000_001_000).contains(&nanos))
+                }
+                timestamp => panic!("unexpected timestamp {:?}", timestamp),
+            }
+        }
+        assert_eq!(q0.timestamp(), query(82).timestamp());
+        assert_ne!(q0.timestamp(), q1.timestamp());
+
+        let jitter = |timestamp: Timestamp| {
+            let query = timestamp.into_query("weather").add_field("temperature", 82);
+            let jittered = query.dedupe(&strategy).timestamp();
+            jittered.checked_nanos().unwrap() - timestamp.checked_nanos().unwrap()
+        };
+        assert_ne!(
+            jitter(Timestamp::Seconds(1)),
+            jitter(Timestamp::Milliseconds(1))
+        );
+
+        let unchanged = Timestamp::Seconds(11)
+            .into_query("weather")
+            .dedupe(&DedupeStrategy::TimestampJitter(0));
+        assert_eq!(unchanged.timestamp(), Timestamp::Seconds(11));
+    }
+
+    #[test]
+    fn test_accessors() {
+        use crate::Type;
+
+        let query = Timestamp::Hours(11)
+            .into_query("weather")
+            .add_tag("location", "us-midwest")
+            .add_field("temperature", 82)
+            .add_field("wind_strength", 3.7);
+
+        assert_eq!(query.measurement().name(), "weather");
+        assert_eq!(
+            query.tags(),
+            &[("location".to_string(), Type::Text("us-midwest".to_string()))]
+        );
+        assert_eq!(
+            query.fields(),
+            &[
+                ("temperature".to_string(), Type::SignedInteger(82)),
+                ("wind_strength".to_string(), Type::Float(3.7))
+            ]
+        );
+        assert_eq!(query.timestamp(), Timestamp::Hours(11));
+    }
+
+    #[test]
+    fn test_type_from() {
+        use crate::Type;
+        use std::num::{NonZeroI8, NonZeroU32};
+
+        assert_eq!(Type::from('°'), Type::Text("°".to_string()));
+        assert_eq!(Type::from(&-3i64), Type::SignedInteger(-3));
+        assert_eq!(
+            Type::from(NonZeroU32::new(7).unwrap()),
+            Type::UnsignedInteger(7)
+        );
+        assert_eq!(
+            Type::from(&NonZeroI8::new(-1).unwrap()),
+            Type::SignedInteger(-1)
+        );
+        assert_eq!(
+            Type::from(usize::MAX),
+            Type::UnsignedInteger(usize::MAX as u64)
+        );
+        assert_eq!(
+            Type::from(&isize::MIN),
+            Type::SignedInteger(isize::MIN as i64)
+        );
+
+        let query = Timestamp::Hours(11)
+            .into_query("weather")
+            .add_tag("unit", 'F')
+            .add_field("stations", 12usize);
+        assert_eq!(
+            query.build().unwrap().get(),
+            "weather,unit=F stations=12i 11"
+        );
+    }
+
+    #[test]
+    fn test_add_references() {
+        let city = "Berlin".to_string();
+        let description = Some("sunny".to_string());
+        let warning: Option<String> = None;
+        let query = Timestamp::Hours(11)
+            .into_query("weather")
+            .add_tag("city", &city)
+            .add_field("description", &description)
+            .add_field("warning", &warning)
+            .add_field("station", &city);
+        assert_eq!(
+            query.build().unwrap().get(),
+            r#"weather,city=Berlin description="sunny",station="Berlin" 11"#
+        );
+        // the values are still owned by the caller
+        assert_eq!(
+            (city.as_str(), description.as_deref()),
+            ("Berlin", Some("sunny"))
+        );
+    }
+
     #[test]
     fn test_write_builder_multiple_fields() {
         let query = Timestamp::Hours(11)
@@ -334,10 +1185,117 @@ mod tests {
 
         assert!(query.is_ok(), "Query was empty");
         let query_res = query.unwrap().get();
-        #[allow(clippy::print_literal)]
         assert_eq!(
             query_res,
-            r#"wea\,\ ther=,location=us-midwest,loc\,\ \="ation=us\,\ \"mid\=west temperature=82i,"temp\=era\,t\ ure"="too\"\\\\hot",float=82 11"#
+            r#"wea\,\ ther=,location=us-midwest,loc\,\ \=\"ation=us\,\ \"mid\=west temperature=82i,\"temp\=era\,t\ ure\"="too\"\\\\hot",float=82 11"#
+        );
+    }
+
+    #[test]
+    fn test_qualified_measurement() {
+        use crate::{Measurement, WriteQuery};
+
+        let query = WriteQuery::new(
+            Timestamp::Hours(11),
+            Measurement::new("weather")
+                .database("mydb")
+                .retention_policy("two_weeks"),
+        )
+        .add_field("temperature", 82);
+
+        assert_eq!(query.get_database(), Some("mydb"));
+        assert_eq!(query.get_retention_policy(), Some("two_weeks"));
+        assert_eq!(query.build().unwrap(), "weather temperature=82i 11");
+    }
+
+    #[test]
+    fn test_regex_measurement() {
+        use crate::{Measurement, WriteQuery};
+
+        let query = WriteQuery::new(Timestamp::Hours(11), Measurement::regex("^weather.*"))
+            .add_field("temperature", 82)
+            .build();
+
+        assert!(query.is_err(), "Query wrote to a regex measurement");
+    }
+
+    #[test]
+    fn test_batch_mixed_retention_policies() {
+        use crate::{Measurement, WriteQuery};
+
+        let q0 = Timestamp::Hours(11)
+            .into_query("weather")
+            .add_field("temperature", 82);
+
+        let q1 = WriteQuery::new(
+            Timestamp::Hours(12),
+            Measurement::new("weather").retention_policy("two_weeks"),
+        )
+        .add_field("temperature", 65);
+
+        assert!(vec![q0, q1].build().is_err());
+    }
+
+    #[test]
+    fn test_batch_with_precision() {
+        use crate::Precision;
+
+        let q0 = Timestamp::Seconds(11)
+            .into_query("weather")
+            .add_field("temperature", 82);
+
+        let q1 = Timestamp::Nanoseconds(12_345_678_901)
+            .into_query("weather")
+            .add_field("temperature", 65);
+
+        let query = vec![q0, q1].build_with_precision(Precision::Milliseconds);
+
+        assert!(query.is_ok(), "Query was empty");
+        assert_eq!(
+            query.unwrap(),
+            "weather temperature=82i 11000\nweather temperature=65i 12345"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "use-serde")]
+    fn test_serde_roundtrip() {
+        use crate::{Measurement, WriteQuery};
+
+        let query = WriteQuery::new(
+            Timestamp::Nanoseconds(1_500_000_000_000_000_000),
+            Measurement::new("weather").retention_policy("two_weeks"),
+        )
+        .add_tag("location", "us-midwest")
+        .add_field("temperature", 82)
+        .add_field("raining", true)
+        .add_field("wind_strength", 3.7);
+        let json = serde_json::to_string(&query).unwrap();
+        let deserialized = serde_json::from_str::<WriteQuery>(&json).unwrap();
+
+        assert_eq!(deserialized, query);
+        assert_eq!(deserialized.build().unwrap(), query.build().unwrap().get());
+    }
+
+    #[test]
+    fn test_batch_mixed_precisions() {
+        use crate::query::QueryType;
+
+        let batch = vec![
+            Timestamp::Hours(1)
+                .into_query("weather")
+                .add_field("temperature", 82),
+            Timestamp::Milliseconds(3_600_500)
+                .into_query("weather")
+                .add_field("temperature", 83),
+            Timestamp::Seconds(3601)
+                .into_query("weather")
+                .add_field("temperature", 84),
+        ];
+        assert_eq!(batch.get_type(), QueryType::WriteQuery("ms".to_owned()));
+        assert_eq!(
+            batch.build().unwrap(),
+            "weather temperature=82i 3600000\nweather temperature=83i 3600500\nweather temperature=84i 3601000"
         );
     }
 
@@ -0,0 +1,243 @@
+//! Results of read queries which are processed chunk by chunk, while the server streams them
+
+use futures::io::{AsyncBufRead, AsyncBufReadExt, BufReader};
+use futures::prelude::*;
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::client::request_id::REQUEST_ID;
+use crate::query_result::{QueryResult, Row, Series};
+use crate::{Client, Error, Query, QueryType};
+
+type Chunks = Pin<Box<dyn Stream<Item = Result<QueryResult, Error>> + Send>>;
+
+/// The result of a read query as a [`Stream`](futures::Stream) of chunks, returned by
+/// [`Client::query_chunks`](crate::Client::query_chunks)
+///
+/// Each chunk is a [`QueryResult`](crate::QueryResult) of its own, a series which is split across
+/// chunks continues in the next one. The rows of the chunks can be filtered, mapped and folded
+/// while they arrive, so a large result never has to be held in memory as a whole.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use influxdb::{Client, ReadQuery, Value};
+///
+/// # #[async_std::main]
+/// # async fn main() -> Result<(), influxdb::Error> {
+/// let client = Client::new("http://localhost:8086", "test");
+/// let query = ReadQuery::new("SELECT temperature FROM weather");
+///
+/// // the number of readings above 30 degrees of each city
+/// let hot = client
+///     .query_chunks(&query, 10_000)
+///     .await?
+///     .filter(|row| matches!(row.get("temperature"), Some(Value::Float(t)) if *t > 30.0))
+///     .fold(0, |count, _| count + 1)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ChunkedResult {
+    chunks: Chunks,
+}
+
+impl ChunkedResult {
+    pub(crate) fn new<S>(chunks: S) -> Self
+    where
+        S: Stream<Item = Result<QueryResult, Error>> + Send + 'static,
+    {
+        ChunkedResult {
+            chunks: Box::pin(chunks),
+        }
+    }
+
+    /// Reads the chunks of a response, one JSON object per line
+    pub(crate) fn from_reader<R>(reader: R, request_id: String) -> Self
+    where
+        R: AsyncBufRead + Send + Unpin + 'static,
+    {
+        ChunkedResult::new(reader.lines().filter_map(move |line| {
+            let chunk = match line {
+                Ok(line) if line.trim().is_empty() => None,
+                Ok(line) => Some(QueryResult::parse(&line).map(|mut chunk| {
+                    chunk.request_id = Some(request_id.clone());
+                    chunk
+                })),
+                Err(err) => Some(Err(Error::ConnectionError {
+                    error: err.to_string(),
+                    source: crate::error::source(err),
+                })),
+            };
+            future::ready(
+                chunk.map(|chunk| chunk.map_err(|error| error.with_request_id(&request_id))),
+            )
+        }))
+    }
+
+    /// Keeps the rows for which the predicate returns `true`, dropping series without rows
+    pub fn filter<F>(self, mut predicate: F) -> ChunkedResult
+    where
+        F: FnMut(&Row<'_>) -> bool + Send + 'static,
+    {
+        ChunkedResult::new(self.chunks.map_ok(move |mut chunk| {
+            for statement in &mut chunk.statements {
+                statement.series.retain_mut(|series| {
+                    let values = std::mem::take(&mut series.values);
+                    series.values = values
+                        .into_iter()
+                        .filter(|values| predicate(&Row::new(series, values)))
+                        .collect();
+                    !series.values.is_empty()
+                });
+            }
+            chunk
+        }))
+    }
+
+    /// Maps the rows of each chunk, returning a stream with the mapped rows of each chunk
+    pub fn map_rows<F, T>(self, mut f: F) -> impl Stream<Item = Result<Vec<T>, Error>> + Send
+    where
+        F: FnMut(Row<'_>) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        self.chunks
+            .map_ok(move |chunk| chunk.series().flat_map(Series::rows).map(&mut f).collect())
+    }
+
+    /// Folds the rows of all chunks into a single value, failing with the first error
+    pub async fn fold<T, F>(self, init: T, mut f: F) -> Result<T, Error>
+    where
+        F: FnMut(T, Row<'_>) -> T,
+    {
+        let mut chunks = self.chunks;
+        let mut accumulator = init;
+        while let Some(chunk) = chunks.next().await {
+            accumulator = chunk?.rows().fold(accumulator, &mut f);
+        }
+        Ok(accumulator)
+    }
+}
+
+impl Stream for ChunkedResult {
+    type Item = Result<QueryResult, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.chunks.as_mut().poll_next(cx)
+    }
+}
+
+impl fmt::Debug for ChunkedResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChunkedResult").finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    /// Sends a read query with `chunked=true` and returns its result chunk by chunk, as the server
+    /// streams it, see [`ChunkedResult`](crate::ChunkedResult)
+    ///
+    /// The server sends series in chunks of up to `chunk_size` rows. Fails if the server answers
+    /// with an error status, errors while reading the result are items of the stream.
+    pub async fn query_chunks<Q>(&self, q: &Q, chunk_size: usize) -> Result<ChunkedResult, Error>
+    where
+        Q: Query,
+    {
+        if let QueryType::WriteQuery(_) = q.get_type() {
+            return Err(Error::InvalidQueryError {
+                error: "only read queries return results".to_string(),
+            });
+        }
+        let request_id = self.next_request_id();
+        let request = self
+            .with_headers(
+                self.read_request(
+                    q.build()?.get(),
+                    &[("chunked", "true"), ("chunk_size", &chunk_size.to_string())],
+                )?
+                .header(REQUEST_ID, request_id.as_str()),
+            )
+            .build();
+        let mut response = self
+            .send_request(request)
+            .await
+            .map_err(|error| error.with_request_id(&request_id))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.body_string().await.unwrap_or_default();
+            return Err(Error::DatabaseError {
+                error: format!("influxdb error: \"{}\"", body),
+                status: Some(status.into()),
+                request_id: Some(request_id),
+            });
+        }
+        Ok(ChunkedResult::from_reader(
+            BufReader::new(response),
+            request_id,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChunkedResult;
+    use crate::query_result::Value;
+    use futures::executor::block_on;
+    use futures::io::Cursor;
+    use futures::prelude::*;
+
+    fn chunks() -> ChunkedResult {
+        let body = concat!(
+            r#"{"results":[{"statement_id":0,"series":[{"name":"weather","tags":{"city":"berlin"},"columns":["time","temperature"],"values":[["2021-03-08T12:30:00Z",21.5],["2021-03-08T12:31:00Z",32.5]],"partial":true}],"partial":true}]}"#,
+            "\n",
+            r#"{"results":[{"statement_id":0,"series":[{"name":"weather","tags":{"city":"berlin"},"columns":["time","temperature"],"values":[["2021-03-08T12:32:00Z",31.0]]}]}]}"#,
+            "\n\n",
+        );
+        ChunkedResult::from_reader(Cursor::new(body.as_bytes().to_vec()), "1".to_string())
+    }
+
+    fn hot(row: &crate::query_result::Row<'_>) -> bool {
+        matches!(row.get("temperature"), Some(Value::Float(t)) if *t > 30.0)
+    }
+
+    #[test]
+    fn test_chunks() {
+        let chunks = block_on(chunks().try_collect::<Vec<_>>()).unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1].rows().count(), 1);
+        assert_eq!(chunks[1].request_id.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn test_filter_fold() {
+        let count = block_on(chunks().filter(hot).fold(0, |count, _| count + 1)).unwrap();
+        assert_eq!(count, 2);
+
+        let sum = block_on(chunks().fold(0.0, |sum, row| {
+            sum + row.get_as::<f64>("temperature").unwrap()
+        }))
+        .unwrap();
+        assert_eq!(sum, 85.0);
+    }
+
+    #[test]
+    fn test_map_rows() {
+        let cities = block_on(
+            chunks()
+                .filter(|row| !hot(row))
+                .map_rows(|row| row.tags()["city"].clone())
+                .try_collect::<Vec<_>>(),
+        )
+        .unwrap();
+        assert_eq!(cities, vec![vec!["berlin".to_string()], vec![]]);
+    }
+
+    #[test]
+    fn test_error() {
+        let body = b"{\"error\":\"query timeout\"}\n".to_vec();
+        let mut chunks = ChunkedResult::from_reader(Cursor::new(body), "1".to_string());
+        let error = block_on(chunks.next()).unwrap().unwrap_err();
+        assert_eq!(error.request_id(), Some("1"));
+    }
+}
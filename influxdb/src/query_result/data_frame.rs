@@ -0,0 +1,172 @@
+//! Conversion of query results into Polars data frames
+
+use polars_core::prelude::{
+    Column as FrameColumn, DataFrame, DataType, NamedFrom, Series as FrameSeries, TimeUnit,
+    TimeZone,
+};
+use std::convert::TryFrom;
+
+use crate::query_result::table::{Column, ColumnType, Table};
+use crate::query_result::{QueryResult, Series, Value};
+use crate::Error;
+
+impl QueryResult {
+    /// Converts the series of all statements into a single Polars [`DataFrame`]
+    ///
+    /// The data frame has the union of the columns of all series, followed by their names in the
+    /// `name` column and their tags as string columns. Rows of series which lack a column are null in it. Columns mixing integers
+    /// and floats are `Float64`, columns mixing other types are `String`. The `time` column is a
+    /// `Datetime` in nanoseconds and UTC.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::QueryResult;
+    ///
+    /// let result = QueryResult::parse(
+    ///     r#"{"results":[{"statement_id":0,"series":[{"name":"weather","tags":{"city":"berlin"},"columns":["time","temperature"],"values":[["2021-03-08T12:30:00Z",82]]}]}]}"#,
+    /// )
+    /// .unwrap();
+    /// let data_frame = result.to_data_frame().unwrap();
+    ///
+    /// assert_eq!(data_frame.shape(), (1, 4));
+    /// ```
+    pub fn to_data_frame(&self) -> Result<DataFrame, Error> {
+        data_frame(Table::of(self.series()))
+    }
+}
+
+impl Series {
+    /// Converts the series into a Polars [`DataFrame`], see
+    /// [`QueryResult::to_data_frame`](crate::QueryResult::to_data_frame)
+    pub fn to_data_frame(&self) -> Result<DataFrame, Error> {
+        data_frame(Table::of(std::iter::once(self)))
+    }
+}
+
+impl TryFrom<&QueryResult> for DataFrame {
+    type Error = Error;
+
+    fn try_from(result: &QueryResult) -> Result<Self, Self::Error> {
+        result.to_data_frame()
+    }
+}
+
+fn data_frame(table: Table) -> Result<DataFrame, Error> {
+    let columns = table
+        .columns
+        .into_iter()
+        .map(column)
+        .collect::<Result<Vec<_>, _>>()?;
+    DataFrame::new(columns).map_err(conversion_error)
+}
+
+fn column(column: Column) -> Result<FrameColumn, Error> {
+    let name = column.name.as_str().into();
+    let values = column.values;
+    let series = match column.column_type {
+        ColumnType::Null => FrameSeries::full_null(name, values.len(), &DataType::Null),
+        ColumnType::Boolean => FrameSeries::new(
+            name,
+            convert(values, |value| match value {
+                Value::Boolean(value) => Some(value),
+                _ => None,
+            }),
+        ),
+        ColumnType::Integer => FrameSeries::new(
+            name,
+            convert(values, |value| match value {
+                Value::Integer(value) => Some(value),
+                _ => None,
+            }),
+        ),
+        ColumnType::UnsignedInteger => FrameSeries::new(
+            name,
+            convert(values, |value| match value {
+                Value::Integer(value) => u64::try_from(value).ok(),
+                Value::UnsignedInteger(value) => Some(value),
+                _ => None,
+            }),
+        ),
+        ColumnType::Float => FrameSeries::new(
+            name,
+            convert(values, |value| match value {
+                Value::Float(value) => Some(value),
+                Value::Integer(value) => Some(value as f64),
+                Value::UnsignedInteger(value) => Some(value as f64),
+                _ => None,
+            }),
+        ),
+        ColumnType::Timestamp => FrameSeries::new(
+            name,
+            convert(values, |value| match value {
+                Value::Timestamp(value) => value.timestamp_nanos_opt(),
+                _ => None,
+            }),
+        )
+        .cast(&DataType::Datetime(
+            TimeUnit::Nanoseconds,
+            Some(TimeZone::UTC),
+        ))
+        .map_err(conversion_error)?,
+        ColumnType::String => FrameSeries::new(
+            name,
+            convert(values, |value| match value {
+                Value::Null => None,
+                value => Some(value.to_string()),
+            }),
+        ),
+    };
+    Ok(series.into())
+}
+
+fn convert<T, F>(values: Vec<Value>, f: F) -> Vec<Option<T>>
+where
+    F: Fn(Value) -> Option<T>,
+{
+    values.into_iter().map(f).collect()
+}
+
+fn conversion_error<E: std::fmt::Display>(err: E) -> Error {
+    Error::DeserializationError {
+        error: format!("could not convert to data frame: {}", err),
+        source: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::query_result::QueryResult;
+    use polars_core::prelude::{DataType, TimeUnit, TimeZone};
+
+    #[test]
+    fn test_to_data_frame() {
+        let result = QueryResult::parse(
+            r#"{"results":[{"statement_id":0,"series":[
+                {"name":"weather","tags":{"city":"berlin"},"columns":["time","temperature","raining"],"values":[["1970-01-01T00:00:01Z",3,true],["1970-01-01T00:00:02Z",3.5,false]]},
+                {"name":"weather","tags":{"city":"paris"},"columns":["time","temperature"],"values":[["1970-01-01T00:00:03Z",null]]}
+            ]}]}"#,
+        )
+        .unwrap();
+        let data_frame = result.to_data_frame().unwrap();
+
+        assert_eq!(data_frame.shape(), (3, 5));
+        assert_eq!(
+            data_frame.dtypes(),
+            vec![
+                DataType::Datetime(TimeUnit::Nanoseconds, Some(TimeZone::UTC)),
+                DataType::Float64,
+                DataType::Boolean,
+                DataType::String,
+                DataType::String,
+            ]
+        );
+        let temperature = data_frame.column("temperature").unwrap().f64().unwrap();
+        assert_eq!(temperature.get(1), Some(3.5));
+        assert_eq!(temperature.get(2), None);
+        let raining = data_frame.column("raining").unwrap().bool().unwrap();
+        assert_eq!(raining.get(2), None);
+        let name = data_frame.column("name").unwrap().str().unwrap();
+        assert_eq!(name.get(2), Some("weather"));
+    }
+}
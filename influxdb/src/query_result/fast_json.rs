@@ -0,0 +1,114 @@
+//! Parsing of the rows of query results straight into [`Value`](crate::Value)s, enabled with the
+//! `fast-json` feature
+//!
+//! The rows are the bulk of most responses. Without this feature every value of a row is parsed
+//! into a `serde_json::Value` first, which is converted afterwards.
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::query_result::Value;
+
+/// A value of a row, parsed without an intermediate `serde_json::Value`
+#[derive(Debug)]
+pub(super) struct Cell(Value);
+
+impl Cell {
+    /// Returns the value, parsing strings of the `time` column as timestamps
+    pub(super) fn into_value(self, time_column: bool) -> Value {
+        match self.0 {
+            Value::String(value) if time_column => Value::time(value),
+            value => value,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Cell {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(CellVisitor).map(Cell)
+    }
+}
+
+struct CellVisitor;
+
+impl<'de> Visitor<'de> for CellVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
+        Ok(Value::Boolean(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+        Ok(Value::Integer(value))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
+        Ok(i64::try_from(value).map_or(Value::UnsignedInteger(value), Value::Integer))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
+        Ok(Value::Float(value))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Value, E> {
+        Ok(Value::String(value.to_string()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Value, E> {
+        Ok(Value::String(value))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    // arrays and objects don't occur in rows, they are kept as JSON like without the feature
+
+    fn visit_seq<A>(self, seq: A) -> Result<Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let value = serde_json::Value::deserialize(de::value::SeqAccessDeserializer::new(seq))?;
+        Ok(Value::String(value.to_string()))
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let value = serde_json::Value::deserialize(de::value::MapAccessDeserializer::new(map))?;
+        Ok(Value::String(value.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cell;
+    use crate::query_result::Value;
+
+    #[test]
+    fn test_cells() {
+        let row = r#"["2021-03-08T12:30:00Z", 42, 0.5, 18446744073709551615, true, null, "up", [1], {"a": 1}]"#;
+        let cells = serde_json::from_str::<Vec<Cell>>(row).unwrap();
+        let json = serde_json::from_str::<Vec<serde_json::Value>>(row).unwrap();
+
+        for (index, (cell, json)) in cells.into_iter().zip(json).enumerate() {
+            assert_eq!(
+                cell.into_value(index == 0),
+                Value::from_json(json, index == 0)
+            );
+        }
+    }
+}
@@ -0,0 +1,307 @@
+//! Merging the results of several queries into one set of series, joined on their tags and time
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::query_result::{QueryResult, Series, StatementResult, Value};
+use crate::Error;
+#[cfg(feature = "client")]
+use crate::{Client, Query};
+
+/// Which times of the series of the merged results are kept, see
+/// [`QueryResult::merge`](crate::QueryResult::merge)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeJoin {
+    /// Keeps the times which all results have a row at
+    Inner,
+    /// Keeps the times which any result has a row at, the columns of the other results are `null`
+    Outer,
+}
+
+/// The rows of the series of one result with the same tags, by time
+type Rows = BTreeMap<i64, (Value, Vec<Value>)>;
+
+impl QueryResult {
+    /// Merges the results of several queries into series with the same tags, joining their rows on
+    /// time
+    ///
+    /// Each result is given with a label, the columns of its series are prefixed with the label,
+    /// e.g. `cpu.mean`. The merged series are named after the labels, their first column is
+    /// `time`. Series of a result which have the same tags, e.g. of different measurements, are
+    /// merged into one. Rows without a time are skipped. Fails if a statement of a result failed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{QueryResult, TimeJoin, Value};
+    ///
+    /// let cpu = QueryResult::parse(
+    ///     r#"{"results":[{"statement_id":0,"series":[{"name":"cpu","tags":{"host":"a"},"columns":["time","mean"],"values":[[1,0.5],[2,0.7]]}]}]}"#,
+    /// )?;
+    /// let mem = QueryResult::parse(
+    ///     r#"{"results":[{"statement_id":0,"series":[{"name":"mem","tags":{"host":"a"},"columns":["time","mean"],"values":[[2,42]]}]}]}"#,
+    /// )?;
+    ///
+    /// let merged = QueryResult::merge(&[("cpu", &cpu), ("mem", &mem)], TimeJoin::Inner)?;
+    /// let series = merged.series().next().unwrap();
+    /// assert_eq!(series.columns, vec!["time", "cpu.mean", "mem.mean"]);
+    /// assert_eq!(series.values, vec![vec![Value::Integer(2), Value::Float(0.7), Value::Integer(42)]]);
+    /// # Ok::<(), influxdb::Error>(())
+    /// ```
+    pub fn merge(results: &[(&str, &QueryResult)], join: TimeJoin) -> Result<QueryResult, Error> {
+        let mut columns = Vec::with_capacity(results.len());
+        let mut rows = Vec::with_capacity(results.len());
+        for (_, result) in results {
+            if let Some(error) = result
+                .statements
+                .iter()
+                .find_map(|statement| statement.error.as_ref())
+            {
+                return Err(Error::DatabaseError {
+                    error: error.clone(),
+                    status: None,
+                    request_id: result.request_id.clone(),
+                });
+            }
+            let result_columns = value_columns(result);
+            rows.push(series_rows(result, &result_columns));
+            columns.push(result_columns);
+        }
+
+        let tag_sets = rows
+            .iter()
+            .flat_map(|rows| rows.keys())
+            .collect::<BTreeSet<_>>();
+        let name = results
+            .iter()
+            .map(|(label, _)| *label)
+            .collect::<Vec<_>>()
+            .join(",");
+        let mut merged_columns = vec!["time".to_string()];
+        for ((label, _), result_columns) in results.iter().zip(&columns) {
+            merged_columns.extend(
+                result_columns
+                    .iter()
+                    .map(|column| format!("{}.{}", label, column)),
+            );
+        }
+
+        let mut series = Vec::new();
+        for tags in tag_sets {
+            let tag_rows = rows.iter().map(|rows| rows.get(tags)).collect::<Vec<_>>();
+            let times = match join {
+                TimeJoin::Inner => {
+                    let mut times = match tag_rows.first() {
+                        Some(Some(first)) => first.keys().copied().collect::<BTreeSet<_>>(),
+                        _ => BTreeSet::new(),
+                    };
+                    for rows in &tag_rows {
+                        times.retain(|time| rows.is_some_and(|rows| rows.contains_key(time)));
+                    }
+                    times
+                }
+                TimeJoin::Outer => tag_rows
+                    .iter()
+                    .flatten()
+                    .flat_map(|rows| rows.keys().copied())
+                    .collect(),
+            };
+            if times.is_empty() {
+                continue;
+            }
+            let values = times
+                .into_iter()
+                .map(|time| {
+                    let mut row = vec![Value::Null];
+                    for (rows, result_columns) in tag_rows.iter().zip(&columns) {
+                        match rows.and_then(|rows| rows.get(&time)) {
+                            Some((time_value, values)) => {
+                                if row[0].is_null() {
+                                    row[0] = time_value.clone();
+                                }
+                                row.extend(values.iter().cloned());
+                            }
+                            None => row.extend(result_columns.iter().map(|_| Value::Null)),
+                        }
+                    }
+                    row
+                })
+                .collect();
+            series.push(Series {
+                name: name.clone(),
+                tags: tags.clone(),
+                columns: merged_columns.clone(),
+                values,
+            });
+        }
+
+        Ok(QueryResult {
+            statements: vec![StatementResult {
+                statement_id: 0,
+                series,
+                error: None,
+            }],
+            request_id: None,
+        })
+    }
+}
+
+/// Returns the columns of all series of a result, except `time`, in the order they first appear
+fn value_columns(result: &QueryResult) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    for column in result.series().flat_map(|series| series.columns.iter()) {
+        if column != "time" && !columns.contains(column) {
+            columns.push(column.clone());
+        }
+    }
+    columns
+}
+
+/// Returns the rows of the series of a result by their tags, with the values in the order of
+/// `columns`
+fn series_rows(
+    result: &QueryResult,
+    columns: &[String],
+) -> BTreeMap<BTreeMap<String, String>, Rows> {
+    let mut rows = BTreeMap::<_, Rows>::new();
+    for series in result.series() {
+        let time_column = match series.column_index("time") {
+            Some(time_column) => time_column,
+            None => continue,
+        };
+        let indexes = columns
+            .iter()
+            .map(|column| series.column_index(column))
+            .collect::<Vec<_>>();
+        let tag_rows = rows.entry(series.tags.clone()).or_default();
+        for values in &series.values {
+            let time_value = match values.get(time_column) {
+                Some(time_value) => time_value,
+                None => continue,
+            };
+            let time = match time_value {
+                Value::Timestamp(time) => time.timestamp_nanos_opt(),
+                Value::Integer(time) => Some(*time),
+                _ => None,
+            };
+            let time = match time {
+                Some(time) => time,
+                None => continue,
+            };
+            let (_, row) = tag_rows
+                .entry(time)
+                .or_insert_with(|| (time_value.clone(), vec![Value::Null; columns.len()]));
+            for (merged, index) in row.iter_mut().zip(&indexes) {
+                if let Some(value) = index.and_then(|index| values.get(index)) {
+                    if !value.is_null() {
+                        *merged = value.clone();
+                    }
+                }
+            }
+        }
+    }
+    rows
+}
+
+#[cfg(feature = "client")]
+impl Client {
+    /// Sends several read queries concurrently and merges their results, see
+    /// [`QueryResult::merge`](crate::QueryResult::merge)
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::{Client, ReadQuery, TimeJoin};
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("http://localhost:8086", "telegraf");
+    /// let cpu = ReadQuery::new("SELECT mean(usage_idle) FROM cpu WHERE time > now() - 1h GROUP BY time(1m), host");
+    /// let mem = ReadQuery::new("SELECT mean(used_percent) FROM mem WHERE time > now() - 1h GROUP BY time(1m), host");
+    ///
+    /// let merged = client
+    ///     .query_merged(&[("cpu", &cpu), ("mem", &mem)], TimeJoin::Outer)
+    ///     .await?;
+    /// for row in merged.rows() {
+    ///     println!("{:?} {:?} {:?}", row.tags(), row.get("cpu.mean"), row.get("mem.mean"));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn query_merged<Q>(
+        &self,
+        queries: &[(&str, &Q)],
+        join: TimeJoin,
+    ) -> Result<QueryResult, Error>
+    where
+        Q: Query,
+    {
+        let results =
+            futures::future::try_join_all(queries.iter().map(|(_, q)| self.query_result(*q)))
+                .await?;
+        let labeled = queries
+            .iter()
+            .zip(&results)
+            .map(|((label, _), result)| (*label, result))
+            .collect::<Vec<_>>();
+        QueryResult::merge(&labeled, join)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TimeJoin;
+    use crate::query_result::{QueryResult, Value};
+
+    fn result(body: &str) -> QueryResult {
+        QueryResult::parse(body).unwrap()
+    }
+
+    #[test]
+    fn test_merge() {
+        let cpu = result(
+            r#"{"results":[{"statement_id":0,"series":[
+                {"name":"cpu","tags":{"host":"a"},"columns":["time","mean"],"values":[["2021-03-08T00:00:00Z",1],["2021-03-08T00:01:00Z",2]]},
+                {"name":"cpu","tags":{"host":"b"},"columns":["time","mean"],"values":[["2021-03-08T00:00:00Z",3]]}
+            ]}]}"#,
+        );
+        let mem = result(
+            r#"{"results":[{"statement_id":0,"series":[
+                {"name":"mem","tags":{"host":"a"},"columns":["time","used","free"],"values":[["2021-03-08T00:01:00Z",10,null],["2021-03-08T00:02:00Z",11,5]]}
+            ]}]}"#,
+        );
+        let inputs = [("cpu", &cpu), ("mem", &mem)];
+
+        let inner = QueryResult::merge(&inputs, TimeJoin::Inner).unwrap();
+        let series = inner.series().collect::<Vec<_>>();
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].name, "cpu,mem");
+        assert_eq!(
+            series[0].columns,
+            ["time", "cpu.mean", "mem.used", "mem.free"]
+        );
+        assert_eq!(
+            series[0].values[0][1..],
+            [Value::Integer(2), Value::Integer(10), Value::Null]
+        );
+
+        let outer = QueryResult::merge(&inputs, TimeJoin::Outer).unwrap();
+        let series = outer.series().collect::<Vec<_>>();
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].values.len(), 3);
+        assert_eq!(
+            series[0].values[2][1..],
+            [Value::Null, Value::Integer(11), Value::Integer(5)]
+        );
+        assert_eq!(series[1].tags["host"], "b");
+        assert_eq!(
+            series[1].values[0][1..],
+            [Value::Integer(3), Value::Null, Value::Null]
+        );
+    }
+
+    #[test]
+    fn test_merge_error() {
+        let failed = result(r#"{"results":[{"statement_id":0,"error":"database not found"}]}"#);
+        assert!(QueryResult::merge(&[("a", &failed)], TimeJoin::Outer).is_err());
+    }
+}
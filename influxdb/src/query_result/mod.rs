@@ -0,0 +1,264 @@
+//! Structured results of read queries, which don't require to deserialize into custom types
+//!
+//! ```rust,no_run
+//! # #[cfg(feature = "client")]
+//! use influxdb::{Client, ReadQuery};
+//! use std::convert::TryFrom;
+//!
+//! # #[cfg(feature = "client")]
+//! # #[async_std::main]
+//! # async fn main() -> Result<(), influxdb::Error> {
+//! let client = Client::new("http://localhost:8086", "test");
+//! let result = client
+//!     .query_result(&ReadQuery::new("SELECT temperature FROM weather"))
+//!     .await?;
+//! for row in result.rows() {
+//!     let temperature = f64::try_from(row.get("temperature").unwrap())?;
+//!     println!("{}: {}", row.name(), temperature);
+//! }
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "client"))]
+//! # fn main() {}
+//! ```
+
+#[cfg(feature = "client")]
+mod chunked;
+#[cfg(feature = "polars")]
+mod data_frame;
+#[cfg(feature = "fast-json")]
+mod fast_json;
+mod merge;
+#[cfg(feature = "arrow")]
+mod record_batch;
+mod row;
+#[cfg(feature = "client")]
+mod streaming;
+#[cfg(any(feature = "arrow", feature = "polars"))]
+mod table;
+mod value;
+
+#[cfg(feature = "client")]
+pub use chunked::ChunkedResult;
+pub use merge::TimeJoin;
+pub use row::Row;
+pub use value::Value;
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::Error;
+#[cfg(feature = "client")]
+use crate::{Client, Query};
+
+/// Result of a query, with one [`StatementResult`](crate::query_result::StatementResult) per
+/// statement
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct QueryResult {
+    pub statements: Vec<StatementResult>,
+    /// The ID of the request which returned the result, see
+    /// [`Client::with_request_id`](crate::Client::with_request_id)
+    pub request_id: Option<String>,
+}
+
+/// Result of a single statement of a query
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StatementResult {
+    pub statement_id: usize,
+    pub series: Vec<Series>,
+    /// The error of the statement, which doesn't affect the other statements of the query
+    pub error: Option<String>,
+}
+
+/// A series returned by a statement, with its rows of values in the order of its columns
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Series {
+    pub name: String,
+    pub tags: BTreeMap<String, String>,
+    pub columns: Vec<String>,
+    pub values: Vec<Vec<Value>>,
+}
+
+impl Series {
+    /// Returns the index of a column in the rows of values
+    pub fn column_index(&self, column: &str) -> Option<usize> {
+        self.columns.iter().position(|name| name == column)
+    }
+}
+
+impl QueryResult {
+    /// Parses the JSON response of InfluxDB to a query
+    ///
+    /// Strings in the `time` column are parsed as [`Timestamp`](crate::Value::Timestamp)s. An
+    /// error of the whole query is returned as [`DatabaseError`](crate::Error::DatabaseError),
+    /// errors of statements are kept in their [`StatementResult`](crate::query_result::StatementResult).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::{QueryResult, Value};
+    ///
+    /// let result = QueryResult::parse(
+    ///     r#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","temperature"],"values":[["2021-03-08T12:30:00Z",82]]}]}]}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let series = result.series().next().unwrap();
+    /// assert_eq!(series.name, "weather");
+    /// assert_eq!(series.values[0][1], Value::Integer(82));
+    /// ```
+    pub fn parse(body: &str) -> Result<Self, Error> {
+        let response: RawResponse =
+            serde_json::from_str(body).map_err(|err| Error::DeserializationError {
+                error: format!("could not parse query result: {}", err),
+                source: crate::error::source(err),
+            })?;
+        if let Some(error) = response.error {
+            return Err(Error::DatabaseError {
+                error,
+                status: None,
+                request_id: None,
+            });
+        }
+
+        let statements = response
+            .results
+            .into_iter()
+            .map(|result| StatementResult {
+                statement_id: result.statement_id,
+                series: result.series.into_iter().map(Series::from).collect(),
+                error: result.error,
+            })
+            .collect();
+        Ok(QueryResult {
+            statements,
+            request_id: None,
+        })
+    }
+
+    /// Returns the series of all statements
+    pub fn series(&self) -> impl Iterator<Item = &Series> {
+        self.statements
+            .iter()
+            .flat_map(|statement| statement.series.iter())
+    }
+}
+
+#[derive(Deserialize)]
+struct RawResponse {
+    #[serde(default)]
+    results: Vec<RawStatementResult>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawStatementResult {
+    #[serde(default)]
+    statement_id: usize,
+    #[serde(default)]
+    series: Vec<RawSeries>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawSeries {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    tags: BTreeMap<String, String>,
+    #[serde(default)]
+    columns: Vec<String>,
+    #[cfg(not(feature = "fast-json"))]
+    #[serde(default)]
+    values: Vec<Vec<serde_json::Value>>,
+    #[cfg(feature = "fast-json")]
+    #[serde(default)]
+    values: Vec<Vec<fast_json::Cell>>,
+}
+
+impl From<RawSeries> for Series {
+    fn from(series: RawSeries) -> Self {
+        let time_column = series.columns.iter().position(|column| column == "time");
+        #[cfg(not(feature = "fast-json"))]
+        let convert = Value::from_json;
+        #[cfg(feature = "fast-json")]
+        let convert = fast_json::Cell::into_value;
+        let values = series
+            .values
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .enumerate()
+                    .map(|(index, value)| convert(value, Some(index) == time_column))
+                    .collect()
+            })
+            .collect();
+        Series {
+            name: series.name,
+            tags: series.tags,
+            columns: series.columns,
+            values,
+        }
+    }
+}
+
+#[cfg(feature = "client")]
+impl Client {
+    /// Sends a read query and returns its structured [`QueryResult`](crate::QueryResult)
+    pub async fn query_result<Q>(&self, q: &Q) -> Result<QueryResult, Error>
+    where
+        Q: Query,
+    {
+        let request_id = self.next_request_id();
+        let body = self.query_with_request_id(q, &request_id).await?;
+        let mut result =
+            QueryResult::parse(&body).map_err(|error| error.with_request_id(&request_id))?;
+        result.request_id = Some(request_id);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QueryResult, Value};
+    use crate::Error;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_parse() {
+        let result = QueryResult::parse(
+            r#"{"results":[
+                {"statement_id":0,"series":[{"name":"weather","tags":{"city":"berlin"},"columns":["time","temperature","raining"],"values":[["2021-03-08T12:30:00Z",3.5,null]]}]},
+                {"statement_id":1,"error":"database not found: missing"}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(result.statements.len(), 2);
+        assert_eq!(
+            result.statements[1].error.as_deref(),
+            Some("database not found: missing")
+        );
+        let series = result.series().collect::<Vec<_>>();
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].tags["city"], "berlin");
+        assert_eq!(series[0].column_index("raining"), Some(2));
+        assert_eq!(
+            series[0].values[0],
+            vec![
+                Value::Timestamp(Utc.with_ymd_and_hms(2021, 3, 8, 12, 30, 0).unwrap()),
+                Value::Float(3.5),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        match QueryResult::parse(r#"{"error":"error parsing query"}"#) {
+            Err(Error::DatabaseError { error, .. }) => assert_eq!(error, "error parsing query"),
+            result => panic!("unexpected result {:?}", result),
+        }
+        assert!(QueryResult::parse("<html>").is_err());
+    }
+}
@@ -0,0 +1,172 @@
+//! Conversion of query results into Arrow record batches
+
+use arrow_array::{
+    ArrayRef, BooleanArray, Float64Array, Int64Array, NullArray, RecordBatch, StringArray,
+    TimestampNanosecondArray, UInt64Array,
+};
+use arrow_schema::{Field, Schema};
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use crate::query_result::table::{Column, ColumnType, Table};
+use crate::query_result::{QueryResult, Series, Value};
+use crate::Error;
+
+impl QueryResult {
+    /// Converts the series of all statements into a single Arrow [`RecordBatch`]
+    ///
+    /// The batch has the union of the columns of all series, followed by their names in the
+    /// `name` column and their tags as string columns. Rows of series which lack a column are null in it. Columns mixing integers and
+    /// floats are `Float64`, columns mixing other types are `Utf8`. The `time` column is a
+    /// `Timestamp(Nanosecond, "UTC")`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::QueryResult;
+    ///
+    /// let result = QueryResult::parse(
+    ///     r#"{"results":[{"statement_id":0,"series":[{"name":"weather","tags":{"city":"berlin"},"columns":["time","temperature"],"values":[["2021-03-08T12:30:00Z",82]]}]}]}"#,
+    /// )
+    /// .unwrap();
+    /// let batch = result.to_record_batch().unwrap();
+    ///
+    /// assert_eq!(batch.num_rows(), 1);
+    /// assert_eq!(batch.schema().field(2).name(), "name");
+    /// assert_eq!(batch.schema().field(3).name(), "city");
+    /// ```
+    pub fn to_record_batch(&self) -> Result<RecordBatch, Error> {
+        record_batch(Table::of(self.series()))
+    }
+}
+
+impl Series {
+    /// Converts the series into an Arrow [`RecordBatch`], see
+    /// [`QueryResult::to_record_batch`](crate::QueryResult::to_record_batch)
+    pub fn to_record_batch(&self) -> Result<RecordBatch, Error> {
+        record_batch(Table::of(std::iter::once(self)))
+    }
+}
+
+impl TryFrom<&QueryResult> for RecordBatch {
+    type Error = Error;
+
+    fn try_from(result: &QueryResult) -> Result<Self, Self::Error> {
+        result.to_record_batch()
+    }
+}
+
+fn record_batch(table: Table) -> Result<RecordBatch, Error> {
+    let (fields, arrays): (Vec<Field>, Vec<ArrayRef>) =
+        table.columns.into_iter().map(array).unzip();
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays).map_err(|err| {
+        Error::DeserializationError {
+            error: format!("could not convert to record batch: {}", err),
+            source: crate::error::source(err),
+        }
+    })
+}
+
+fn array(column: Column) -> (Field, ArrayRef) {
+    let values = column.values;
+    let array: ArrayRef = match column.column_type {
+        ColumnType::Null => Arc::new(NullArray::new(values.len())),
+        ColumnType::Boolean => Arc::new(BooleanArray::from(convert(values, |value| match value {
+            Value::Boolean(value) => Some(value),
+            _ => None,
+        }))),
+        ColumnType::Integer => Arc::new(Int64Array::from(convert(values, |value| match value {
+            Value::Integer(value) => Some(value),
+            _ => None,
+        }))),
+        ColumnType::UnsignedInteger => {
+            Arc::new(UInt64Array::from(convert(values, |value| match value {
+                Value::Integer(value) => u64::try_from(value).ok(),
+                Value::UnsignedInteger(value) => Some(value),
+                _ => None,
+            })))
+        }
+        ColumnType::Float => Arc::new(Float64Array::from(convert(values, |value| match value {
+            Value::Float(value) => Some(value),
+            Value::Integer(value) => Some(value as f64),
+            Value::UnsignedInteger(value) => Some(value as f64),
+            _ => None,
+        }))),
+        ColumnType::Timestamp => Arc::new(
+            TimestampNanosecondArray::from(convert(values, |value| match value {
+                Value::Timestamp(value) => value.timestamp_nanos_opt(),
+                _ => None,
+            }))
+            .with_timezone("UTC"),
+        ),
+        ColumnType::String => Arc::new(StringArray::from(convert(values, |value| match value {
+            Value::Null => None,
+            value => Some(value.to_string()),
+        }))),
+    };
+    let field = Field::new(column.name, array.data_type().clone(), true);
+    (field, array)
+}
+
+fn convert<T, F>(values: Vec<Value>, f: F) -> Vec<Option<T>>
+where
+    F: Fn(Value) -> Option<T>,
+{
+    values.into_iter().map(f).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::query_result::{QueryResult, Value};
+    use arrow_array::{Array, Float64Array, TimestampNanosecondArray};
+    use arrow_schema::{DataType, TimeUnit};
+
+    #[test]
+    fn test_to_record_batch() {
+        let result = QueryResult::parse(
+            r#"{"results":[{"statement_id":0,"series":[
+                {"name":"weather","tags":{"city":"berlin"},"columns":["time","temperature"],"values":[["1970-01-01T00:00:01Z",3],["1970-01-01T00:00:02Z",3.5]]},
+                {"name":"weather","tags":{"city":"paris"},"columns":["time","temperature"],"values":[["1970-01-01T00:00:03Z",null]]}
+            ]}]}"#,
+        )
+        .unwrap();
+        let batch = result.to_record_batch().unwrap();
+
+        let schema = batch.schema();
+        let types = schema
+            .fields()
+            .iter()
+            .map(|field| (field.name().as_str(), field.data_type().clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![
+                (
+                    "time",
+                    DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into()))
+                ),
+                ("temperature", DataType::Float64),
+                ("name", DataType::Utf8),
+                ("city", DataType::Utf8),
+            ]
+        );
+
+        let time = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<TimestampNanosecondArray>()
+            .unwrap();
+        assert_eq!(time.value(2), 3_000_000_000);
+        let temperature = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(temperature.value(0), 3.0);
+        assert!(temperature.is_null(2));
+
+        let series = result.series().next().unwrap();
+        assert_eq!(series.to_record_batch().unwrap().num_rows(), 2);
+        assert_eq!(series.values[1][1], Value::Float(3.5));
+    }
+}
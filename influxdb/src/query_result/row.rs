@@ -0,0 +1,120 @@
+//! Rows of a [`QueryResult`](crate::QueryResult), borrowed from their series
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+use crate::query_result::{QueryResult, Series, Value};
+use crate::Error;
+
+/// A row of a series, borrowing its values from the [`QueryResult`](crate::QueryResult)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Row<'a> {
+    series: &'a Series,
+    values: &'a [Value],
+}
+
+impl<'a> Row<'a> {
+    pub(crate) fn new(series: &'a Series, values: &'a [Value]) -> Self {
+        Row { series, values }
+    }
+
+    /// Returns the name of the series the row belongs to
+    pub fn name(&self) -> &'a str {
+        &self.series.name
+    }
+
+    /// Returns the tags of the series the row belongs to
+    pub fn tags(&self) -> &'a BTreeMap<String, String> {
+        &self.series.tags
+    }
+
+    /// Returns the names of the columns, in the order of the values
+    pub fn columns(&self) -> &'a [String] {
+        &self.series.columns
+    }
+
+    /// Returns the values of the row, in the order of the columns
+    pub fn values(&self) -> &'a [Value] {
+        self.values
+    }
+
+    /// Returns the value of a column, `None` if the series has no such column
+    pub fn get(&self, column: &str) -> Option<&'a Value> {
+        self.values.get(self.series.column_index(column)?)
+    }
+
+    /// Returns the value of a column converted to a Rust type
+    ///
+    /// Returns an error if the series has no such column, or its value cannot be converted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::QueryResult;
+    ///
+    /// let result = QueryResult::parse(
+    ///     r#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","temperature","city"],"values":[["2021-03-08T12:30:00Z",82,"berlin"],["2021-03-08T12:31:00Z",null,"paris"]]}]}]}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// for row in result.rows() {
+    ///     let city: String = row.get_as("city")?;
+    ///     let temperature: Option<f64> = row.get_as("temperature")?;
+    ///     println!("{}: {:?}", city, temperature);
+    /// }
+    /// # Ok::<(), influxdb::Error>(())
+    /// ```
+    pub fn get_as<T>(&self, column: &str) -> Result<T, Error>
+    where
+        T: TryFrom<&'a Value, Error = Error>,
+    {
+        let value = self
+            .get(column)
+            .ok_or_else(|| Error::DeserializationError {
+                error: format!("no column {} in series {}", column, self.series.name),
+                source: None,
+            })?;
+        T::try_from(value)
+    }
+}
+
+impl Series {
+    /// Returns the rows of the series, without copying their values
+    pub fn rows(&self) -> impl Iterator<Item = Row<'_>> {
+        self.values.iter().map(move |values| Row::new(self, values))
+    }
+}
+
+impl QueryResult {
+    /// Returns the rows of all series of all statements, without copying their values
+    pub fn rows(&self) -> impl Iterator<Item = Row<'_>> {
+        self.series().flat_map(Series::rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::query_result::{QueryResult, Value};
+
+    #[test]
+    fn test_rows() {
+        let result = QueryResult::parse(
+            r#"{"results":[{"statement_id":0,"series":[
+                {"name":"weather","tags":{"city":"berlin"},"columns":["time","temperature"],"values":[["1970-01-01T00:00:01Z",3],["1970-01-01T00:00:02Z",4]]},
+                {"name":"weather","tags":{"city":"paris"},"columns":["time","temperature"],"values":[["1970-01-01T00:00:01Z",9]]}
+            ]}]}"#,
+        )
+        .unwrap();
+
+        let rows = result.rows().collect::<Vec<_>>();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[2].name(), "weather");
+        assert_eq!(rows[2].tags()["city"], "paris");
+        assert_eq!(rows[1].columns(), ["time", "temperature"]);
+        assert_eq!(rows[1].get("temperature"), Some(&Value::Integer(4)));
+        assert_eq!(rows[1].get("humidity"), None);
+        assert_eq!(rows[0].get_as::<i64>("temperature").unwrap(), 3);
+        assert!(rows[0].get_as::<i64>("humidity").is_err());
+        assert!(rows[0].get_as::<bool>("temperature").is_err());
+    }
+}
@@ -0,0 +1,560 @@
+//! Results of read queries which aren't chunked, parsed incrementally while they arrive
+
+use futures::io::{AsyncRead, AsyncReadExt};
+use futures::stream;
+use std::collections::VecDeque;
+use std::mem;
+
+use crate::client::request_id::REQUEST_ID;
+use crate::query_result::{ChunkedResult, QueryResult};
+use crate::{Client, Error, Query, QueryType};
+
+const RESULTS: &[u8] = b"\"results\"";
+const SERIES: &[u8] = b"\"series\"";
+const VALUES: &[u8] = b"\"values\"";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    Key,
+    Colon,
+    Value,
+    Comma,
+}
+
+/// An object or array of the response which is parsed incrementally
+#[derive(Debug)]
+struct Container {
+    object: bool,
+    expect: Expect,
+    /// Key of the member of an object which is parsed, with its quotes
+    key: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Progress {
+    More,
+    /// The value ended with the byte
+    Done,
+    /// The value ended before the byte, which belongs to its container
+    Ended,
+}
+
+/// A value which is copied verbatim, a key, a member of an object or a row
+#[derive(Debug)]
+struct Raw {
+    bytes: Vec<u8>,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl Raw {
+    fn new(byte: u8) -> Self {
+        Raw {
+            bytes: vec![byte],
+            depth: matches!(byte, b'{' | b'[') as usize,
+            in_string: byte == b'"',
+            escaped: false,
+        }
+    }
+
+    fn push(&mut self, byte: u8) -> Progress {
+        if self.in_string {
+            self.bytes.push(byte);
+            if self.escaped {
+                self.escaped = false;
+            } else if byte == b'\\' {
+                self.escaped = true;
+            } else if byte == b'"' {
+                self.in_string = false;
+                if self.depth == 0 {
+                    return Progress::Done;
+                }
+            }
+            return Progress::More;
+        }
+        match byte {
+            b'"' => self.in_string = true,
+            b'{' | b'[' => self.depth += 1,
+            b'}' | b']' if self.depth > 0 => {
+                self.depth -= 1;
+                if self.depth == 0 {
+                    self.bytes.push(byte);
+                    return Progress::Done;
+                }
+            }
+            b',' | b'}' | b']' | b' ' | b'\t' | b'\r' | b'\n' if self.depth == 0 => {
+                return Progress::Ended
+            }
+            _ => {}
+        }
+        self.bytes.push(byte);
+        Progress::More
+    }
+}
+
+/// Splits the JSON response to a read query into chunks of up to `rows_per_chunk` rows while it
+/// is read, like the server splits results with `chunked=true`
+///
+/// Only the rows of the series which is read and the other members of its result and series are
+/// held in memory. The chunks are validated by [`QueryResult::parse`](crate::QueryResult::parse).
+#[derive(Debug)]
+pub(crate) struct StreamingParser {
+    rows_per_chunk: usize,
+    started: bool,
+    stack: Vec<Container>,
+    raw: Option<Raw>,
+    /// Members of the response, of the result and of the series which are parsed, except the
+    /// ones with their results, series and rows
+    members: [Vec<Vec<u8>>; 3],
+    rows: Vec<Vec<u8>>,
+    rows_emitted: bool,
+    series_emitted: bool,
+    emitted: bool,
+    chunks: Vec<Vec<u8>>,
+}
+
+impl StreamingParser {
+    pub(crate) fn new(rows_per_chunk: usize) -> Self {
+        StreamingParser {
+            rows_per_chunk: rows_per_chunk.max(1),
+            started: false,
+            stack: Vec::new(),
+            raw: None,
+            members: Default::default(),
+            rows: Vec::new(),
+            rows_emitted: false,
+            series_emitted: false,
+            emitted: false,
+            chunks: Vec::new(),
+        }
+    }
+
+    /// Parses the next bytes of the response, returning the chunks completed by them
+    pub(crate) fn feed(&mut self, bytes: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+        for byte in bytes {
+            self.byte(*byte)?;
+        }
+        Ok(mem::take(&mut self.chunks))
+    }
+
+    /// Fails if the response ended before it was complete
+    pub(crate) fn finish(self) -> Result<Vec<Vec<u8>>, Error> {
+        if !self.started || !self.stack.is_empty() || self.raw.is_some() {
+            return Err(Error::DeserializationError {
+                error: "could not parse query result: the response ended before it was complete"
+                    .to_string(),
+                source: None,
+            });
+        }
+        Ok(self.chunks)
+    }
+
+    fn byte(&mut self, byte: u8) -> Result<(), Error> {
+        if let Some(mut raw) = self.raw.take() {
+            match raw.push(byte) {
+                Progress::More => {
+                    self.raw = Some(raw);
+                    return Ok(());
+                }
+                Progress::Done => return self.complete(raw.bytes),
+                // the byte ending a number or literal belongs to the container
+                Progress::Ended => self.complete(raw.bytes)?,
+            }
+        }
+        if byte.is_ascii_whitespace() {
+            return Ok(());
+        }
+
+        let depth = self.stack.len();
+        let top = match self.stack.last_mut() {
+            Some(top) => top,
+            None if byte == b'{' && !self.started => {
+                self.started = true;
+                self.open(true);
+                return Ok(());
+            }
+            None => return Err(unexpected(byte)),
+        };
+        match (top.object, top.expect, byte) {
+            (true, Expect::Key, b'"') => self.raw = Some(Raw::new(byte)),
+            (true, Expect::Key, b'}') | (true, Expect::Comma, b'}') => self.close(),
+            (true, Expect::Colon, b':') => top.expect = Expect::Value,
+            (true, Expect::Comma, b',') => top.expect = Expect::Key,
+            (false, Expect::Value, b']') | (false, Expect::Comma, b']') => self.close(),
+            (false, Expect::Comma, b',') => top.expect = Expect::Value,
+            (_, Expect::Value, _) => {
+                // results, series and rows are streamed, everything else is copied
+                let streamed = match depth {
+                    1 => top.key == RESULTS && byte == b'[',
+                    3 => top.key == SERIES && byte == b'[',
+                    5 => top.key == VALUES && byte == b'[',
+                    2 | 4 => byte == b'{',
+                    _ => false,
+                };
+                if streamed {
+                    self.open(byte == b'{');
+                } else {
+                    self.raw = Some(Raw::new(byte));
+                }
+            }
+            _ => return Err(unexpected(byte)),
+        }
+        Ok(())
+    }
+
+    fn open(&mut self, object: bool) {
+        match self.stack.len() {
+            2 => {
+                self.members[1].clear();
+                self.series_emitted = false;
+            }
+            4 => {
+                self.members[2].clear();
+                self.rows.clear();
+                self.rows_emitted = false;
+            }
+            _ => {}
+        }
+        self.stack.push(Container {
+            object,
+            expect: if object { Expect::Key } else { Expect::Value },
+            key: Vec::new(),
+        });
+    }
+
+    fn close(&mut self) {
+        let depth = self.stack.len();
+        self.stack.pop();
+        if let Some(parent) = self.stack.last_mut() {
+            parent.expect = Expect::Comma;
+        }
+        match depth {
+            5 if !self.rows.is_empty() || !self.rows_emitted => self.emit_rows(),
+            3 if !self.series_emitted
+                || self.members[1]
+                    .iter()
+                    .any(|member| member.starts_with(b"\"error\"")) =>
+            {
+                let result = object(self.members[1].iter().map(Vec::as_slice));
+                self.emit([b"{\"results\":[", &result[..], b"]}"].concat());
+            }
+            1 if !self.members[0].is_empty() || !self.emitted => {
+                let response = object(self.members[0].iter().map(Vec::as_slice));
+                self.emit(response);
+            }
+            _ => {}
+        }
+    }
+
+    fn complete(&mut self, bytes: Vec<u8>) -> Result<(), Error> {
+        let depth = self.stack.len();
+        let top = self.stack.last_mut().ok_or_else(|| unexpected(bytes[0]))?;
+        if top.expect == Expect::Key {
+            top.key = bytes;
+            top.expect = Expect::Colon;
+            return Ok(());
+        }
+        top.expect = Expect::Comma;
+        match depth {
+            1 | 3 | 5 => {
+                let member = [&top.key[..], b":", &bytes[..]].concat();
+                self.members[depth / 2].push(member);
+            }
+            6 => {
+                if self.rows.len() >= self.rows_per_chunk {
+                    self.emit_rows();
+                }
+                self.rows.push(bytes);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Emits the rows read so far with the members of their series and result
+    fn emit_rows(&mut self) {
+        let rows = [
+            b"\"values\":[",
+            &mem::take(&mut self.rows).join(&b","[..])[..],
+            b"]",
+        ]
+        .concat();
+        let series = object(
+            self.members[2]
+                .iter()
+                .map(Vec::as_slice)
+                .chain(Some(&rows[..])),
+        );
+        let series = [b"\"series\":[", &series[..], b"]"].concat();
+        let result = object(
+            self.members[1]
+                .iter()
+                .map(Vec::as_slice)
+                .chain(Some(&series[..])),
+        );
+        self.emit([b"{\"results\":[", &result[..], b"]}"].concat());
+        self.rows_emitted = true;
+        self.series_emitted = true;
+    }
+
+    fn emit(&mut self, chunk: Vec<u8>) {
+        self.chunks.push(chunk);
+        self.emitted = true;
+    }
+}
+
+fn object<'a, I>(members: I) -> Vec<u8>
+where
+    I: Iterator<Item = &'a [u8]>,
+{
+    let mut object = vec![b'{'];
+    for (index, member) in members.enumerate() {
+        if index > 0 {
+            object.push(b',');
+        }
+        object.extend_from_slice(member);
+    }
+    object.push(b'}');
+    object
+}
+
+fn unexpected(byte: u8) -> Error {
+    Error::DeserializationError {
+        error: format!(
+            "could not parse query result: unexpected {:?}",
+            char::from(byte)
+        ),
+        source: None,
+    }
+}
+
+fn parse_chunk(chunk: Vec<u8>, request_id: &str) -> Result<QueryResult, Error> {
+    let chunk = String::from_utf8(chunk).map_err(|err| Error::DeserializationError {
+        error: "response could not be converted to UTF-8".to_string(),
+        source: crate::error::source(err),
+    })?;
+    let mut result =
+        QueryResult::parse(&chunk).map_err(|error| error.with_request_id(request_id))?;
+    result.request_id = Some(request_id.to_string());
+    Ok(result)
+}
+
+struct Reading<R> {
+    reader: R,
+    parser: Option<StreamingParser>,
+    chunks: VecDeque<Vec<u8>>,
+    request_id: String,
+}
+
+impl ChunkedResult {
+    /// Parses a response which isn't chunked while it is read, see
+    /// [`Client::query_stream`](crate::Client::query_stream)
+    pub(crate) fn from_json_reader<R>(reader: R, request_id: String, rows_per_chunk: usize) -> Self
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+    {
+        let reading = Reading {
+            reader,
+            parser: Some(StreamingParser::new(rows_per_chunk)),
+            chunks: VecDeque::new(),
+            request_id,
+        };
+        ChunkedResult::new(stream::unfold(reading, |mut reading| async move {
+            loop {
+                if let Some(chunk) = reading.chunks.pop_front() {
+                    let chunk = parse_chunk(chunk, &reading.request_id);
+                    return Some((chunk, reading));
+                }
+                reading.parser.as_ref()?;
+                let mut buffer = [0; 8192];
+                let chunks = match reading.reader.read(&mut buffer).await {
+                    Ok(0) => reading.parser.take().map(StreamingParser::finish),
+                    Ok(read) => reading
+                        .parser
+                        .as_mut()
+                        .map(|parser| parser.feed(&buffer[..read])),
+                    Err(err) => Some(Err(Error::ConnectionError {
+                        error: err.to_string(),
+                        source: crate::error::source(err),
+                    })),
+                };
+                match chunks {
+                    Some(Ok(chunks)) => reading.chunks.extend(chunks),
+                    Some(Err(error)) => {
+                        reading.parser = None;
+                        let error = error.with_request_id(&reading.request_id);
+                        return Some((Err(error), reading));
+                    }
+                    None => return None,
+                }
+            }
+        }))
+    }
+}
+
+impl Client {
+    /// Sends a read query and parses its result while it arrives, returning it in chunks of up to
+    /// `rows_per_chunk` rows, see [`ChunkedResult`](crate::ChunkedResult)
+    ///
+    /// Unlike [`query_chunks`](crate::Client::query_chunks), the query is sent without
+    /// `chunked=true`, e.g. for servers or proxies which don't support chunked results. The first
+    /// rows are available before the whole response is received, and only the rows of a chunk are
+    /// held in memory at once.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::{Client, ReadQuery};
+    ///
+    /// # #[async_std::main]
+    /// # async fn main() -> Result<(), influxdb::Error> {
+    /// let client = Client::new("http://localhost:8086", "test");
+    /// let query = ReadQuery::new("SELECT temperature FROM weather");
+    ///
+    /// let rows = client
+    ///     .query_stream(&query, 1000)
+    ///     .await?
+    ///     .fold(0, |count, _| count + 1)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn query_stream<Q>(
+        &self,
+        q: &Q,
+        rows_per_chunk: usize,
+    ) -> Result<ChunkedResult, Error>
+    where
+        Q: Query,
+    {
+        if let QueryType::WriteQuery(_) = q.get_type() {
+            return Err(Error::InvalidQueryError {
+                error: "only read queries return results".to_string(),
+            });
+        }
+        let request_id = self.next_request_id();
+        let request = self
+            .with_headers(
+                self.read_request(q.build()?.get(), &[])?
+                    .header(REQUEST_ID, request_id.as_str()),
+            )
+            .build();
+        let mut response = self
+            .send_request(request)
+            .await
+            .map_err(|error| error.with_request_id(&request_id))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.body_string().await.unwrap_or_default();
+            return Err(Error::DatabaseError {
+                error: format!("influxdb error: \"{}\"", body),
+                status: Some(status.into()),
+                request_id: Some(request_id),
+            });
+        }
+        Ok(ChunkedResult::from_json_reader(
+            response,
+            request_id,
+            rows_per_chunk,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StreamingParser;
+    use crate::query_result::{ChunkedResult, QueryResult};
+    use crate::Error;
+    use futures::executor::block_on;
+    use futures::io::Cursor;
+    use futures::prelude::*;
+
+    const BODY: &str = r#"{"results":[
+        {"statement_id":0,"series":[
+            {"name":"weather","tags":{"city":"berlin"},"columns":["time","note"],"values":[
+                ["2021-03-08T12:30:00Z","a \"quoted\" ], note"],
+                ["2021-03-08T12:31:00Z","back\\slash"],
+                ["2021-03-08T12:32:00Z",null]
+            ]},
+            {"name":"weather","tags":{"city":"paris"},"columns":["time","note"],"values":[]}
+        ]},
+        {"statement_id":1,"error":"database not found: missing"}
+    ]}"#;
+
+    fn split(body: &str, piece: usize, rows_per_chunk: usize) -> Vec<QueryResult> {
+        let mut parser = StreamingParser::new(rows_per_chunk);
+        let mut chunks = Vec::new();
+        for bytes in body.as_bytes().chunks(piece) {
+            chunks.extend(parser.feed(bytes).unwrap());
+        }
+        chunks.extend(parser.finish().unwrap());
+        chunks
+            .into_iter()
+            .map(|chunk| QueryResult::parse(std::str::from_utf8(&chunk).unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_streaming_parser() {
+        let expected = QueryResult::parse(BODY).unwrap();
+        for piece in [1, 7, BODY.len()] {
+            let chunks = split(BODY, piece, 2);
+            // two chunks of the first series, one of the second and the error of the statement
+            assert_eq!(chunks.len(), 4);
+            assert_eq!(
+                chunks
+                    .iter()
+                    .map(|chunk| chunk.rows().count())
+                    .sum::<usize>(),
+                3
+            );
+            assert_eq!(chunks[1].statements[0].series[0].tags["city"], "berlin");
+            assert_eq!(chunks[2].statements[0].series[0].tags["city"], "paris");
+            assert_eq!(chunks[3].statements[0], expected.statements[1]);
+
+            let rows = chunks
+                .iter()
+                .flat_map(|chunk| chunk.series())
+                .flat_map(|series| series.values.clone())
+                .collect::<Vec<_>>();
+            assert_eq!(rows, expected.statements[0].series[0].values);
+        }
+    }
+
+    #[test]
+    fn test_streaming_parser_errors() {
+        let chunks = split(r#"{"results":[{"statement_id":0}]}"#, 3, 10);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].statements.len(), 1);
+
+        let mut parser = StreamingParser::new(10);
+        let chunk = parser.feed(br#"{"error":"error parsing query"}"#).unwrap();
+        assert!(matches!(
+            QueryResult::parse(std::str::from_utf8(&chunk[0]).unwrap()),
+            Err(Error::DatabaseError { .. })
+        ));
+
+        let mut parser = StreamingParser::new(10);
+        parser
+            .feed(br#"{"results":[{"statement_id":0,"series":[{"#)
+            .unwrap();
+        assert!(parser.finish().is_err());
+        assert!(StreamingParser::new(10).feed(b"<html>").is_err());
+        assert!(StreamingParser::new(10).finish().is_err());
+    }
+
+    #[test]
+    fn test_from_json_reader() {
+        let reader = Cursor::new(BODY.as_bytes().to_vec());
+        let chunks = ChunkedResult::from_json_reader(reader, "1".to_string(), 1);
+        let count = block_on(chunks.fold(0, |count, _| count + 1)).unwrap();
+        assert_eq!(count, 3);
+
+        let reader = Cursor::new(br#"{"results":[{"#.to_vec());
+        let mut chunks = ChunkedResult::from_json_reader(reader, "1".to_string(), 1);
+        let error = block_on(chunks.next()).unwrap().unwrap_err();
+        assert_eq!(error.to_string(), "http protocol error: could not parse query result: the response ended before it was complete");
+        assert!(block_on(chunks.next()).is_none());
+    }
+}
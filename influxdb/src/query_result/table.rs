@@ -0,0 +1,264 @@
+//! Flattening of series into a single table, for the conversions into data frames
+
+use crate::query_result::{Series, Value};
+
+/// The type of a column, which all of its values can be converted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ColumnType {
+    /// All values are null
+    Null,
+    Boolean,
+    Integer,
+    /// Integers of which some are above `i64::MAX`, and none negative
+    UnsignedInteger,
+    Float,
+    Timestamp,
+    /// Values of different types are converted to strings
+    String,
+}
+
+impl ColumnType {
+    fn of(value: &Value) -> Self {
+        match value {
+            Value::Null => ColumnType::Null,
+            Value::Boolean(_) => ColumnType::Boolean,
+            Value::Integer(_) => ColumnType::Integer,
+            Value::UnsignedInteger(_) => ColumnType::UnsignedInteger,
+            Value::Float(_) => ColumnType::Float,
+            Value::Timestamp(_) => ColumnType::Timestamp,
+            Value::String(_) => ColumnType::String,
+        }
+    }
+
+    fn merge(self, other: ColumnType) -> Self {
+        match (self, other) {
+            (ColumnType::Null, other) | (other, ColumnType::Null) => other,
+            (ColumnType::Integer, ColumnType::UnsignedInteger)
+            | (ColumnType::UnsignedInteger, ColumnType::Integer) => ColumnType::UnsignedInteger,
+            (ColumnType::Integer, ColumnType::Float)
+            | (ColumnType::Float, ColumnType::Integer)
+            | (ColumnType::UnsignedInteger, ColumnType::Float)
+            | (ColumnType::Float, ColumnType::UnsignedInteger) => ColumnType::Float,
+            (this, other) if this == other => this,
+            _ => ColumnType::String,
+        }
+    }
+}
+
+/// The column with the names of the series
+const NAME_COLUMN: &str = "name";
+
+pub(crate) struct Column {
+    pub(crate) name: String,
+    pub(crate) column_type: ColumnType,
+    pub(crate) values: Vec<Value>,
+}
+
+/// The rows of several series, with the union of their columns and tags
+///
+/// Columns appear in the order they are first seen, the name of the series as the string column
+/// `name` and its tags as string columns after the columns of their series, so rows of different
+/// measurements can be told apart. A column or tag called `name` of a series takes the place of its
+/// name. Rows of series which lack a column are null in it.
+pub(crate) struct Table {
+    pub(crate) columns: Vec<Column>,
+}
+
+impl Table {
+    pub(crate) fn of<'a, I>(series: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Series>,
+    {
+        let mut columns: Vec<Column> = Vec::new();
+        let mut rows = 0;
+        for series in series {
+            let has_name = series.column_index(NAME_COLUMN).is_none()
+                && !series.tags.contains_key(NAME_COLUMN);
+            let name = Some(NAME_COLUMN.to_string()).filter(|_| has_name);
+            let names = series
+                .columns
+                .iter()
+                .chain(name.iter())
+                .chain(series.tags.keys())
+                .collect::<Vec<_>>();
+            let indices = names
+                .iter()
+                .map(
+                    |name| match columns.iter().position(|column| column.name == **name) {
+                        Some(index) => index,
+                        None => {
+                            columns.push(Column {
+                                name: name.to_string(),
+                                column_type: ColumnType::Null,
+                                values: vec![Value::Null; rows],
+                            });
+                            columns.len() - 1
+                        }
+                    },
+                )
+                .collect::<Vec<_>>();
+
+            for row in &series.values {
+                let name = Some(Value::String(series.name.clone())).filter(|_| has_name);
+                let tags = series.tags.values().map(|tag| Value::String(tag.clone()));
+                let values = row.iter().cloned().chain(name).chain(tags);
+                for column in &mut columns {
+                    column.values.push(Value::Null);
+                }
+                for (index, value) in indices.iter().zip(values) {
+                    columns[*index].values[rows] = value;
+                }
+                rows += 1;
+            }
+        }
+
+        for column in &mut columns {
+            column.column_type = column
+                .values
+                .iter()
+                .map(ColumnType::of)
+                .fold(ColumnType::Null, ColumnType::merge);
+            let negative = |value: &Value| matches!(value, Value::Integer(value) if *value < 0);
+            if column.column_type == ColumnType::UnsignedInteger
+                && column.values.iter().any(negative)
+            {
+                column.column_type = ColumnType::Float;
+            }
+        }
+        Table { columns }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ColumnType, Table};
+    use crate::query_result::{Series, Value};
+
+    #[test]
+    fn test_table() {
+        let berlin = Series {
+            name: "weather".to_string(),
+            tags: vec![("city".to_string(), "berlin".to_string())]
+                .into_iter()
+                .collect(),
+            columns: vec!["temperature".to_string()],
+            values: vec![vec![Value::Integer(3)], vec![Value::Float(3.5)]],
+        };
+        let rain = Series {
+            name: "rain".to_string(),
+            tags: Default::default(),
+            columns: vec!["raining".to_string(), "temperature".to_string()],
+            values: vec![vec![
+                Value::Boolean(true),
+                Value::String("warm".to_string()),
+            ]],
+        };
+
+        let table = Table::of(vec![&berlin, &rain]);
+        let columns = table
+            .columns
+            .iter()
+            .map(|column| {
+                (
+                    column.name.as_str(),
+                    column.column_type,
+                    column.values.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            columns,
+            vec![
+                (
+                    "temperature",
+                    ColumnType::String,
+                    vec![
+                        Value::Integer(3),
+                        Value::Float(3.5),
+                        Value::String("warm".to_string())
+                    ]
+                ),
+                (
+                    "name",
+                    ColumnType::String,
+                    vec![
+                        Value::String("weather".to_string()),
+                        Value::String("weather".to_string()),
+                        Value::String("rain".to_string())
+                    ]
+                ),
+                (
+                    "city",
+                    ColumnType::String,
+                    vec![
+                        Value::String("berlin".to_string()),
+                        Value::String("berlin".to_string()),
+                        Value::Null
+                    ]
+                ),
+                (
+                    "raining",
+                    ColumnType::Boolean,
+                    vec![Value::Null, Value::Null, Value::Boolean(true)]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsigned_columns() {
+        let series = Series {
+            name: "counters".to_string(),
+            tags: Default::default(),
+            columns: vec!["requests".to_string(), "delta".to_string()],
+            values: vec![
+                vec![Value::Integer(3), Value::Integer(-1)],
+                vec![
+                    Value::UnsignedInteger(u64::MAX),
+                    Value::UnsignedInteger(u64::MAX),
+                ],
+            ],
+        };
+
+        let table = Table::of(vec![&series]);
+        let types = table
+            .columns
+            .iter()
+            .map(|column| column.column_type)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![
+                ColumnType::UnsignedInteger,
+                ColumnType::Float,
+                ColumnType::String
+            ]
+        );
+    }
+
+    #[test]
+    fn test_name_column() {
+        let series = Series {
+            name: "hosts".to_string(),
+            tags: vec![("name".to_string(), "server01".to_string())]
+                .into_iter()
+                .collect(),
+            columns: vec!["load".to_string()],
+            values: vec![vec![Value::Float(0.5)]],
+        };
+
+        let table = Table::of(vec![&series]);
+        let names = table
+            .columns
+            .iter()
+            .map(|column| (column.name.as_str(), column.values.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                ("load", vec![Value::Float(0.5)]),
+                ("name", vec![Value::String("server01".to_string())]),
+            ]
+        );
+    }
+}
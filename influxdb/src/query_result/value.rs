@@ -0,0 +1,216 @@
+//! Values of the columns of a [`QueryResult`](crate::QueryResult)
+
+use chrono::{DateTime, Utc};
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+
+use crate::Error;
+
+/// A value of a column, as returned by InfluxDB
+///
+/// JSON doesn't distinguish between integers and floats without a fractional part, so a float
+/// column may contain [`Integer`](crate::Value::Integer)s. Converting them to `f64` succeeds.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::Value;
+/// use std::convert::TryFrom;
+///
+/// assert_eq!(f64::try_from(Value::Integer(3)).unwrap(), 3.0);
+/// assert_eq!(String::try_from(Value::String("up".to_string())).unwrap(), "up");
+/// assert!(bool::try_from(&Value::Float(0.5)).is_err());
+/// assert_eq!(Option::<i64>::try_from(Value::Null).unwrap(), None);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Float(f64),
+    Integer(i64),
+    /// An integer above `i64::MAX`, as returned for unsigned fields, smaller ones are
+    /// [`Integer`](crate::Value::Integer)s
+    UnsignedInteger(u64),
+    String(String),
+    Boolean(bool),
+    /// The series has no value in this column at the time of the row
+    Null,
+    /// The value of the `time` column
+    Timestamp(DateTime<Utc>),
+}
+
+impl Value {
+    /// Returns whether the value is [`Null`](crate::Value::Null)
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// Returns the name of the kind of the value, used in conversion errors
+    fn kind(&self) -> &'static str {
+        match self {
+            Value::Float(_) => "float",
+            Value::Integer(_) => "integer",
+            Value::UnsignedInteger(_) => "unsigned integer",
+            Value::String(_) => "string",
+            Value::Boolean(_) => "boolean",
+            Value::Null => "null",
+            Value::Timestamp(_) => "timestamp",
+        }
+    }
+
+    /// Converts a value of the JSON response, parsing strings of the `time` column as timestamps
+    #[cfg_attr(feature = "fast-json", allow(dead_code))]
+    pub(crate) fn from_json(value: serde_json::Value, time_column: bool) -> Self {
+        match value {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(value) => Value::Boolean(value),
+            serde_json::Value::Number(number) => match (number.as_i64(), number.as_u64()) {
+                (Some(integer), _) => Value::Integer(integer),
+                (None, Some(integer)) => Value::UnsignedInteger(integer),
+                (None, None) => Value::Float(number.as_f64().unwrap_or(f64::NAN)),
+            },
+            serde_json::Value::String(value) if time_column => Value::time(value),
+            serde_json::Value::String(value) => Value::String(value),
+            value => Value::String(value.to_string()),
+        }
+    }
+
+    /// Parses a string of the `time` column as timestamp, keeping it if it isn't one
+    pub(crate) fn time(value: String) -> Self {
+        match DateTime::parse_from_rfc3339(&value) {
+            Ok(time) => Value::Timestamp(time.with_timezone(&Utc)),
+            Err(_) => Value::String(value),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Float(value) => write!(f, "{}", value),
+            Value::Integer(value) => write!(f, "{}", value),
+            Value::UnsignedInteger(value) => write!(f, "{}", value),
+            Value::String(value) => write!(f, "{}", value),
+            Value::Boolean(value) => write!(f, "{}", value),
+            Value::Null => Ok(()),
+            Value::Timestamp(value) => write!(f, "{}", value.to_rfc3339()),
+        }
+    }
+}
+
+fn mismatch(expected: &str, value: &Value) -> Error {
+    Error::DeserializationError {
+        error: format!("expected {}, found {}", expected, value.kind()),
+        source: None,
+    }
+}
+
+macro_rules! try_from_value {
+    ($typ:ty, $expected:literal, $($pattern:pat $(if $guard:expr)? => $converted:expr),+) => {
+        impl TryFrom<&Value> for $typ {
+            type Error = Error;
+
+            fn try_from(value: &Value) -> Result<Self, Self::Error> {
+                match value {
+                    $($pattern $(if $guard)? => Ok($converted),)+
+                    value => Err(mismatch($expected, value)),
+                }
+            }
+        }
+
+        impl TryFrom<Value> for $typ {
+            type Error = Error;
+
+            fn try_from(value: Value) -> Result<Self, Self::Error> {
+                <$typ>::try_from(&value)
+            }
+        }
+
+        impl TryFrom<&Value> for Option<$typ> {
+            type Error = Error;
+
+            fn try_from(value: &Value) -> Result<Self, Self::Error> {
+                match value {
+                    Value::Null => Ok(None),
+                    value => <$typ>::try_from(value).map(Some),
+                }
+            }
+        }
+
+        impl TryFrom<Value> for Option<$typ> {
+            type Error = Error;
+
+            fn try_from(value: Value) -> Result<Self, Self::Error> {
+                Option::<$typ>::try_from(&value)
+            }
+        }
+    };
+}
+
+try_from_value!(
+    f64,
+    "float",
+    Value::Float(value) => *value,
+    Value::Integer(value) => *value as f64,
+    Value::UnsignedInteger(value) => *value as f64
+);
+try_from_value!(i64, "integer", Value::Integer(value) => *value);
+try_from_value!(
+    u64,
+    "unsigned integer",
+    Value::Integer(value) if *value >= 0 => *value as u64,
+    Value::UnsignedInteger(value) => *value
+);
+try_from_value!(bool, "boolean", Value::Boolean(value) => *value);
+try_from_value!(String, "string", Value::String(value) => value.clone());
+try_from_value!(DateTime<Utc>, "timestamp", Value::Timestamp(value) => *value);
+
+#[cfg(test)]
+mod tests {
+    use super::Value;
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_from_json() {
+        assert_eq!(Value::from_json(json!(42), false), Value::Integer(42));
+        assert_eq!(
+            Value::from_json(json!(u64::MAX), false),
+            Value::UnsignedInteger(u64::MAX)
+        );
+        assert_eq!(Value::from_json(json!(0.5), false), Value::Float(0.5));
+        assert_eq!(Value::from_json(json!(true), false), Value::Boolean(true));
+        assert_eq!(Value::from_json(json!(null), true), Value::Null);
+        assert_eq!(
+            Value::from_json(json!("2021-03-08T12:30:00Z"), true),
+            Value::Timestamp(Utc.with_ymd_and_hms(2021, 3, 8, 12, 30, 0).unwrap())
+        );
+        assert_eq!(
+            Value::from_json(json!("2021-03-08T12:30:00Z"), false),
+            Value::String("2021-03-08T12:30:00Z".to_string())
+        );
+    }
+
+    #[test]
+    fn test_try_from() {
+        assert_eq!(i64::try_from(Value::Integer(-3)).unwrap(), -3);
+        assert_eq!(u64::try_from(Value::Integer(3)).unwrap(), 3);
+        assert!(u64::try_from(Value::Integer(-3)).is_err());
+        assert_eq!(
+            u64::try_from(Value::UnsignedInteger(u64::MAX)).unwrap(),
+            u64::MAX
+        );
+        assert!(i64::try_from(Value::UnsignedInteger(u64::MAX)).is_err());
+        assert!(i64::try_from(Value::Float(0.5)).is_err());
+        assert!(f64::try_from(Value::Null).is_err());
+        assert_eq!(
+            Option::<bool>::try_from(Value::Boolean(false)).unwrap(),
+            Some(false)
+        );
+
+        let error = String::try_from(&Value::Integer(1)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "http protocol error: expected string, found integer"
+        );
+    }
+}
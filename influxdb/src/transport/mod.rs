@@ -0,0 +1,11 @@
+//! Transports writing line protocol to other processes than InfluxDB, e.g. to a Telegraf agent
+//! which takes care of buffering and retrying the writes
+//!
+//! The transports only depend on the standard library, so they are available without the
+//! `client` feature.
+
+mod tcp;
+mod telegraf;
+
+pub use tcp::TcpWriter;
+pub use telegraf::TelegrafWriter;
@@ -0,0 +1,268 @@
+//! Writing newline delimited line protocol over a persistent TCP connection
+
+use std::io::{self, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::{Error, Precision, Query, QueryType};
+
+/// Buffered lines are sent once they exceed this size
+const DEFAULT_FLUSH_SIZE: usize = 64 * 1024;
+/// Up to this size, lines are kept while the connection is down
+const DEFAULT_MAX_BUFFER_SIZE: usize = 8 * 1024 * 1024;
+
+/// Writes line protocol over a TCP connection, e.g. to the `socket_listener` of Telegraf or to a
+/// relay, reconnecting when the connection fails
+///
+/// Points are buffered and sent once the buffer exceeds the [flush
+/// size](TcpWriter::with_flush_size) or when [`flush`](TcpWriter::flush) is called. A failed send
+/// is retried once on a new connection, if that fails as well, the lines stay buffered and are
+/// sent with the next flush. While the connection is down, the oldest lines are dropped once the
+/// buffer exceeds its [maximum size](TcpWriter::with_max_buffer_size).
+///
+/// Lines may be sent twice, if a connection fails in the middle of a send. Writing a point twice
+/// is harmless to InfluxDB, which keeps one point per series and timestamp. Timestamps are sent in
+/// nanoseconds. The remaining lines are flushed when the writer is dropped.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use influxdb::transport::TcpWriter;
+/// use influxdb::{InfluxDbWriteable, Timestamp};
+///
+/// let mut writer = TcpWriter::connect("localhost:8094")?;
+/// let point = Timestamp::Seconds(1).into_query("weather").add_field("temperature", 82);
+/// writer.write(&point)?;
+/// writer.flush()?;
+/// # Ok::<(), influxdb::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct TcpWriter {
+    address: String,
+    stream: Option<TcpStream>,
+    buffer: Vec<u8>,
+    flush_size: usize,
+    max_buffer_size: usize,
+    timeout: Option<Duration>,
+    dropped_lines: u64,
+}
+
+impl TcpWriter {
+    /// Creates a writer which connects to `address`, e.g. `localhost:8094`, with its first flush
+    pub fn new<S>(address: S) -> Self
+    where
+        S: Into<String>,
+    {
+        TcpWriter {
+            address: address.into(),
+            stream: None,
+            buffer: Vec::new(),
+            flush_size: DEFAULT_FLUSH_SIZE,
+            max_buffer_size: DEFAULT_MAX_BUFFER_SIZE,
+            timeout: None,
+            dropped_lines: 0,
+        }
+    }
+
+    /// Creates a writer which is connected to `address` right away, failing if it can't connect
+    pub fn connect<S>(address: S) -> Result<Self, Error>
+    where
+        S: Into<String>,
+    {
+        let mut writer = TcpWriter::new(address);
+        writer.stream = Some(writer.open()?);
+        Ok(writer)
+    }
+
+    /// Sets the size of buffered lines from which they are sent, defaults to 64 KiB
+    ///
+    /// A size of zero sends every write right away.
+    pub fn with_flush_size(mut self, flush_size: usize) -> Self {
+        self.flush_size = flush_size;
+        self
+    }
+
+    /// Sets the size up to which lines are kept while the connection is down, defaults to 8 MiB
+    pub fn with_max_buffer_size(mut self, max_buffer_size: usize) -> Self {
+        self.max_buffer_size = max_buffer_size;
+        self
+    }
+
+    /// Sets the timeout of connecting and of sending, by default the operating system decides
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Buffers a [`WriteQuery`](crate::WriteQuery), or a batch of them, and sends the buffered
+    /// lines if they exceed the flush size
+    ///
+    /// Fails if the lines couldn't be sent, they are retried with the next flush.
+    pub fn write<Q>(&mut self, q: &Q) -> Result<(), Error>
+    where
+        Q: Query + ?Sized,
+    {
+        if let QueryType::ReadQuery = q.get_type() {
+            return Err(Error::InvalidQueryError {
+                error: "read queries can not be written over TCP".to_string(),
+            });
+        }
+        let lines = q.build_with_precision(Precision::Nanoseconds)?.get();
+        self.buffer.extend_from_slice(lines.as_bytes());
+        self.buffer.push(b'\n');
+        self.truncate();
+        if self.buffer.len() >= self.flush_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Sends the buffered lines, reconnecting once if the connection failed
+    pub fn flush(&mut self) -> Result<(), Error> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let mut result = Ok(());
+        for _ in 0..2 {
+            result = self.send();
+            if result.is_ok() {
+                self.buffer.clear();
+                return Ok(());
+            }
+            self.stream = None;
+        }
+        result.map_err(Error::from)
+    }
+
+    /// Returns the number of bytes which are buffered and not sent yet
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the number of lines which were dropped because the buffer was full
+    pub fn dropped_lines(&self) -> u64 {
+        self.dropped_lines
+    }
+
+    fn send(&mut self) -> io::Result<()> {
+        let stream = match &mut self.stream {
+            Some(stream) => stream,
+            None => self.stream.insert(self.open()?),
+        };
+        stream.write_all(&self.buffer)?;
+        stream.flush()
+    }
+
+    fn open(&self) -> io::Result<TcpStream> {
+        let timeout = match self.timeout {
+            Some(timeout) => timeout,
+            None => return TcpStream::connect(self.address.as_str()),
+        };
+        let mut last_error = None;
+        for address in self.address.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&address, timeout) {
+                Ok(stream) => {
+                    stream.set_write_timeout(Some(timeout))?;
+                    return Ok(stream);
+                }
+                Err(err) => last_error = Some(err),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "address resolved to nothing")
+        }))
+    }
+
+    /// Drops the oldest lines if the buffer exceeds its maximum size
+    fn truncate(&mut self) {
+        let excess = match self.buffer.len().checked_sub(self.max_buffer_size) {
+            Some(excess) if excess > 0 => excess,
+            _ => return,
+        };
+        // cut after the line the excess ends in
+        let cut = self.buffer[excess - 1..]
+            .iter()
+            .position(|byte| *byte == b'\n')
+            .map_or(self.buffer.len(), |position| excess + position);
+        self.dropped_lines += self.buffer[..cut]
+            .iter()
+            .filter(|byte| **byte == b'\n')
+            .count() as u64;
+        self.buffer.drain(..cut);
+    }
+}
+
+impl Drop for TcpWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TcpWriter;
+    use crate::{InfluxDbWriteable, Timestamp, WriteQuery};
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    fn point(seconds: u128) -> WriteQuery {
+        Timestamp::Seconds(seconds)
+            .into_query("weather")
+            .add_field("temperature", 82)
+    }
+
+    #[test]
+    fn test_buffering() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut writer = TcpWriter::connect(listener.local_addr().unwrap().to_string())
+            .unwrap()
+            .with_flush_size(50);
+        let (mut stream, _) = listener.accept().unwrap();
+
+        writer.write(&point(1)).unwrap();
+        assert_eq!(writer.buffered(), 35);
+        writer.write(&point(2)).unwrap();
+        assert_eq!(writer.buffered(), 0);
+        writer.write(&point(3)).unwrap();
+        drop(writer);
+
+        let mut received = String::new();
+        stream.read_to_string(&mut received).unwrap();
+        assert_eq!(
+            received,
+            "weather temperature=82i 1000000000\nweather temperature=82i 2000000000\nweather temperature=82i 3000000000\n"
+        );
+    }
+
+    #[test]
+    fn test_reconnect() {
+        let address = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let mut writer = TcpWriter::new(address.to_string());
+        writer.write(&point(1)).unwrap();
+        assert!(writer.flush().is_err());
+        assert_eq!(writer.buffered(), 35);
+
+        let listener = TcpListener::bind(address).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.buffered(), 0);
+        let (mut stream, _) = listener.accept().unwrap();
+        drop(writer);
+        let mut received = String::new();
+        stream.read_to_string(&mut received).unwrap();
+        assert_eq!(received, "weather temperature=82i 1000000000\n");
+    }
+
+    #[test]
+    fn test_max_buffer_size() {
+        let mut writer = TcpWriter::new("127.0.0.1:0").with_max_buffer_size(80);
+        for seconds in 1..=3 {
+            writer.write(&point(seconds)).unwrap();
+        }
+        assert_eq!(writer.buffered(), 70);
+        assert_eq!(writer.dropped_lines(), 1);
+        writer.buffer.clear();
+    }
+}
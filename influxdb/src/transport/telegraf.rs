@@ -0,0 +1,228 @@
+//! Writing to the `socket_listener` input of Telegraf
+
+use std::io::{self, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+#[cfg(unix)]
+use std::os::unix::net::{UnixDatagram, UnixStream};
+
+use crate::{Error, Precision, Query, QueryType};
+
+/// Datagrams are kept below the MTU of most networks, so they aren't fragmented
+const DEFAULT_MAX_DATAGRAM_SIZE: usize = 1400;
+
+/// Writes points to the [`socket_listener`](https://github.com/influxdata/telegraf/tree/master/plugins/inputs/socket_listener)
+/// input of a local Telegraf agent, which buffers and retries the writes to InfluxDB
+///
+/// Telegraf has to parse the `influx` data format. Timestamps are sent in nanoseconds, the
+/// precision the parser expects. Writes block until the points were handed to the socket. To
+/// buffer points and to reconnect over TCP, use a [`TcpWriter`](crate::transport::TcpWriter).
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use influxdb::transport::TelegrafWriter;
+/// use influxdb::{InfluxDbWriteable, Timestamp};
+///
+/// let mut telegraf = TelegrafWriter::connect("udp://localhost:8094")?;
+/// let point = Timestamp::Seconds(1).into_query("weather").add_field("temperature", 82);
+/// telegraf.write(&point)?;
+/// # Ok::<(), influxdb::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct TelegrafWriter {
+    socket: Socket,
+    max_datagram_size: usize,
+}
+
+#[derive(Debug)]
+enum Socket {
+    Tcp(TcpStream),
+    Udp(UdpSocket),
+    #[cfg(unix)]
+    Unix(UnixStream),
+    #[cfg(unix)]
+    Unixgram(UnixDatagram),
+}
+
+impl TelegrafWriter {
+    /// Connects to the `service_address` of a `socket_listener`
+    ///
+    /// Supported are `tcp://`, `udp://` and, on Unix, `unix://` and `unixgram://` addresses, e.g.
+    /// `tcp://localhost:8094` or `unixgram:///tmp/telegraf.sock`. Addresses without a host, like
+    /// `udp://:8094`, connect to `localhost`.
+    pub fn connect(address: &str) -> Result<Self, Error> {
+        let invalid = || Error::UrlConstructionError {
+            error: format!("invalid socket_listener address: {}", address),
+        };
+        let (scheme, target) = address.split_once("://").ok_or_else(invalid)?;
+        let host = |target: &str| {
+            if target.starts_with(':') {
+                format!("localhost{}", target)
+            } else {
+                target.to_string()
+            }
+        };
+        let socket = match scheme {
+            "tcp" | "tcp4" | "tcp6" => Socket::Tcp(TcpStream::connect(host(target))?),
+            "udp" | "udp4" | "udp6" => {
+                let remote = host(target).to_socket_addrs()?.next().ok_or_else(invalid)?;
+                let local = match remote {
+                    SocketAddr::V4(_) => "0.0.0.0:0",
+                    SocketAddr::V6(_) => "[::]:0",
+                };
+                let socket = UdpSocket::bind(local)?;
+                socket.connect(remote)?;
+                Socket::Udp(socket)
+            }
+            #[cfg(unix)]
+            "unix" => Socket::Unix(UnixStream::connect(target)?),
+            #[cfg(unix)]
+            "unixgram" => {
+                let socket = UnixDatagram::unbound()?;
+                socket.connect(target)?;
+                Socket::Unixgram(socket)
+            }
+            _ => return Err(invalid()),
+        };
+        Ok(TelegrafWriter {
+            socket,
+            max_datagram_size: DEFAULT_MAX_DATAGRAM_SIZE,
+        })
+    }
+
+    /// Sets the size up to which lines are sent in one datagram over UDP or Unix datagram
+    /// sockets, defaults to 1400 bytes
+    ///
+    /// Lines which are larger are sent in a datagram of their own.
+    pub fn with_max_datagram_size(mut self, max_datagram_size: usize) -> Self {
+        self.max_datagram_size = max_datagram_size;
+        self
+    }
+
+    /// Writes a [`WriteQuery`](crate::WriteQuery), or a batch of them
+    pub fn write<Q>(&mut self, q: &Q) -> Result<(), Error>
+    where
+        Q: Query + ?Sized,
+    {
+        if let QueryType::ReadQuery = q.get_type() {
+            return Err(Error::InvalidQueryError {
+                error: "read queries can not be written to Telegraf".to_string(),
+            });
+        }
+        let lines = q.build_with_precision(Precision::Nanoseconds)?.get();
+        let max_datagram_size = self.max_datagram_size;
+        match &mut self.socket {
+            Socket::Tcp(stream) => write_lines(stream, &lines)?,
+            Socket::Udp(socket) => {
+                for datagram in datagrams(&lines, max_datagram_size) {
+                    socket.send(datagram.as_bytes())?;
+                }
+            }
+            #[cfg(unix)]
+            Socket::Unix(stream) => write_lines(stream, &lines)?,
+            #[cfg(unix)]
+            Socket::Unixgram(socket) => {
+                for datagram in datagrams(&lines, max_datagram_size) {
+                    socket.send(datagram.as_bytes())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes newline terminated lines to a stream
+fn write_lines<W>(stream: &mut W, lines: &str) -> io::Result<()>
+where
+    W: Write,
+{
+    let mut buffer = String::with_capacity(lines.len() + 1);
+    buffer.push_str(lines);
+    buffer.push('\n');
+    stream.write_all(buffer.as_bytes())?;
+    stream.flush()
+}
+
+/// Splits lines into datagrams of whole lines, which are at most `max_size` bytes unless a single
+/// line is larger
+fn datagrams(lines: &str, max_size: usize) -> Vec<&str> {
+    let mut datagrams = Vec::new();
+    // the datagram spans `start..end`, the next line begins at `offset`
+    let (mut start, mut end, mut offset) = (0, 0, 0);
+    for line in lines.split('\n') {
+        let line_end = offset + line.len();
+        if end > start && line_end - start > max_size {
+            datagrams.push(&lines[start..end]);
+            start = offset;
+        }
+        end = line_end;
+        offset = line_end + 1;
+    }
+    if end > start {
+        datagrams.push(&lines[start..end]);
+    }
+    datagrams
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{datagrams, TelegrafWriter};
+    use crate::{InfluxDbWriteable, Timestamp};
+    use std::io::Read;
+    use std::net::{TcpListener, UdpSocket};
+
+    fn points() -> Vec<crate::WriteQuery> {
+        (1..=3)
+            .map(|i| {
+                Timestamp::Seconds(i)
+                    .into_query("weather")
+                    .add_field("temperature", 82)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_datagrams() {
+        let lines = "a b=1 1\na b=2 2\na b=3 3";
+        assert_eq!(datagrams(lines, 100), vec![lines]);
+        assert_eq!(datagrams(lines, 15), vec!["a b=1 1\na b=2 2", "a b=3 3"]);
+        assert_eq!(datagrams(lines, 3), vec!["a b=1 1", "a b=2 2", "a b=3 3"]);
+    }
+
+    #[test]
+    fn test_udp() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = format!("udp://{}", listener.local_addr().unwrap());
+        let mut telegraf = TelegrafWriter::connect(&address)
+            .unwrap()
+            .with_max_datagram_size(80);
+        telegraf.write(&points()).unwrap();
+
+        let mut buffer = [0; 100];
+        let received = listener.recv(&mut buffer).unwrap();
+        assert_eq!(
+            std::str::from_utf8(&buffer[..received]).unwrap(),
+            "weather temperature=82i 1000000000\nweather temperature=82i 2000000000"
+        );
+    }
+
+    #[test]
+    fn test_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("tcp://{}", listener.local_addr().unwrap());
+        let mut telegraf = TelegrafWriter::connect(&address).unwrap();
+        let (mut stream, _) = listener.accept().unwrap();
+        telegraf.write(&points()[0]).unwrap();
+        drop(telegraf);
+
+        let mut received = String::new();
+        stream.read_to_string(&mut received).unwrap();
+        assert_eq!(received, "weather temperature=82i 1000000000\n");
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(TelegrafWriter::connect("localhost:8094").is_err());
+        assert!(TelegrafWriter::connect("http://localhost:8094").is_err());
+    }
+}
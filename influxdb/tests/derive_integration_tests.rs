@@ -6,6 +6,8 @@ use influxdb::InfluxDbWriteable;
 
 use chrono::{DateTime, Utc};
 use influxdb::{Query, Timestamp};
+#[cfg(feature = "derive")]
+use std::borrow::Cow;
 
 #[cfg(feature = "use-serde")]
 use serde::Deserialize;
@@ -25,6 +27,7 @@ struct WeatherReading {
 
 #[derive(Debug)]
 #[cfg_attr(feature = "use-serde", derive(Deserialize))]
+#[allow(dead_code)]
 struct WeatherReadingWithoutIgnored {
     time: DateTime<Utc>,
     pressure: i32,
@@ -47,6 +50,98 @@ fn test_build_query() {
     );
 }
 
+#[cfg(feature = "derive")]
+#[derive(InfluxDbWriteable)]
+#[influxdb(tag_fields(host, region), measurement = "server_load")]
+struct ServerLoad {
+    time: DateTime<Utc>,
+    host: String,
+    region: String,
+    load: f64,
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn test_build_query_with_tag_fields() {
+    let server_load = ServerLoad {
+        time: Timestamp::Hours(1).into(),
+        host: "web-1".to_string(),
+        region: "eu".to_string(),
+        load: 0.5,
+    };
+    let query = server_load.into_query("server_load");
+    assert_eq!(
+        query.build().unwrap().get(),
+        "server_load,host=web-1,region=eu load=0.5 3600000000000"
+    );
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn test_to_point_trait_objects() {
+    use influxdb::{ToPoint, WriteQuery};
+
+    let server_load = ServerLoad {
+        time: Timestamp::Hours(1).into(),
+        host: "web-1".to_string(),
+        region: "eu".to_string(),
+        load: 0.5,
+    };
+    let metrics: Vec<Box<dyn ToPoint>> = vec![
+        Box::new(server_load),
+        Box::new(
+            Timestamp::Nanoseconds(3_600_000_000_000)
+                .into_query("uptime")
+                .add_field("seconds", 3600),
+        ),
+    ];
+    let batch = metrics
+        .iter()
+        .map(|metric| metric.to_point())
+        .collect::<Vec<WriteQuery>>();
+    assert_eq!(
+        batch.build().unwrap().get(),
+        "server_load,host=web-1,region=eu load=0.5 3600000000000\nuptime seconds=3600i 3600000000000"
+    );
+}
+
+#[cfg(feature = "derive")]
+#[derive(InfluxDbWriteable)]
+#[influxdb(borrowed)]
+struct Request<'a> {
+    time: DateTime<Utc>,
+    #[influxdb(tag)]
+    path: Cow<'a, str>,
+    #[influxdb(tag)]
+    method: &'a str,
+    user_agent: String,
+    referrer: Option<String>,
+    duration_ms: u64,
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn test_build_query_borrowed() {
+    let request = Request {
+        time: Timestamp::Hours(1).into(),
+        path: Cow::Borrowed("/api"),
+        method: "GET",
+        user_agent: "curl".to_string(),
+        referrer: Some("/".to_string()),
+        duration_ms: 12,
+    };
+    let expected = r#"request,path=/api,method=GET user_agent="curl",referrer="/",duration_ms=12i 3600000000000"#;
+    assert_eq!(
+        (&request).into_query("request").build().unwrap().get(),
+        expected
+    );
+    // the request can still be used after it was written
+    assert_eq!(
+        request.into_query("request").build().unwrap().get(),
+        expected
+    );
+}
+
 #[cfg(feature = "derive")]
 /// INTEGRATION TEST
 ///
@@ -101,8 +196,9 @@ async fn test_write_and_read_option() {
                 .query(&weather_reading.into_query("weather_reading".to_string()))
                 .await;
             assert_result_ok(&write_result);
-            let query =
-                Query::raw_read_query("SELECT time, pressure, wind_strength FROM weather_reading");
+            let query = <dyn Query>::raw_read_query(
+                "SELECT time, pressure, wind_strength FROM weather_reading",
+            );
             let result = client.json_query(query).await.and_then(|mut db_result| {
                 println!("{:?}", db_result);
                 db_result.deserialize_next::<WeatherReadingWithoutIgnored>()
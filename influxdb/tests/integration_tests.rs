@@ -23,6 +23,11 @@ async fn test_ping_influx_db_async_std() {
     assert!(!build.is_empty(), "Build should not be empty");
     assert!(!version.is_empty(), "Build should not be empty");
 
+    assert_eq!(
+        client.server_version().map(|v| v.to_string()),
+        Some(version.clone())
+    );
+
     println!("build: {} version: {}", build, version);
 }
 
@@ -52,7 +57,7 @@ async fn test_connection_error() {
     let test_name = "test_connection_error";
     let client =
         Client::new("http://127.0.0.1:10086", test_name).with_auth("nopriv_user", "password");
-    let read_query = Query::raw_read_query("SELECT * FROM weather");
+    let read_query = <dyn Query>::raw_read_query("SELECT * FROM weather");
     let read_result = client.query(&read_query).await;
     assert_result_err(&read_result);
     match read_result {
@@ -78,7 +83,7 @@ async fn test_authed_write_and_read() {
                 Client::new("http://127.0.0.1:9086", TEST_NAME).with_auth("admin", "password");
             let query = format!("CREATE DATABASE {}", TEST_NAME);
             client
-                .query(&Query::raw_read_query(query))
+                .query(&<dyn Query>::raw_read_query(query))
                 .await
                 .expect("could not setup db");
 
@@ -90,7 +95,7 @@ async fn test_authed_write_and_read() {
             let write_result = client.query(&write_query).await;
             assert_result_ok(&write_result);
 
-            let read_query = Query::raw_read_query("SELECT * FROM weather");
+            let read_query = <dyn Query>::raw_read_query("SELECT * FROM weather");
             let read_result = client.query(&read_query).await;
             assert_result_ok(&read_result);
             assert!(
@@ -104,7 +109,7 @@ async fn test_authed_write_and_read() {
             let query = format!("DROP DATABASE {}", TEST_NAME);
 
             client
-                .query(&Query::raw_read_query(query))
+                .query(&<dyn Query>::raw_read_query(query))
                 .await
                 .expect("could not clean up db");
         },
@@ -126,7 +131,7 @@ async fn test_wrong_authed_write_and_read() {
                 Client::new("http://127.0.0.1:9086", TEST_NAME).with_auth("admin", "password");
             let query = format!("CREATE DATABASE {}", TEST_NAME);
             client
-                .query(&Query::raw_read_query(query))
+                .query(&<dyn Query>::raw_read_query(query))
                 .await
                 .expect("could not setup db");
 
@@ -145,7 +150,7 @@ async fn test_wrong_authed_write_and_read() {
                 ),
             }
 
-            let read_query = Query::raw_read_query("SELECT * FROM weather");
+            let read_query = <dyn Query>::raw_read_query("SELECT * FROM weather");
             let read_result = client.query(&read_query).await;
             assert_result_err(&read_result);
             match read_result {
@@ -158,7 +163,7 @@ async fn test_wrong_authed_write_and_read() {
 
             let client = Client::new("http://127.0.0.1:9086", TEST_NAME)
                 .with_auth("nopriv_user", "password");
-            let read_query = Query::raw_read_query("SELECT * FROM weather");
+            let read_query = <dyn Query>::raw_read_query("SELECT * FROM weather");
             let read_result = client.query(&read_query).await;
             assert_result_err(&read_result);
             match read_result {
@@ -174,7 +179,7 @@ async fn test_wrong_authed_write_and_read() {
                 Client::new("http://127.0.0.1:9086", TEST_NAME).with_auth("admin", "password");
             let query = format!("DROP DATABASE {}", TEST_NAME);
             client
-                .query(&Query::raw_read_query(query))
+                .query(&<dyn Query>::raw_read_query(query))
                 .await
                 .expect("could not clean up db");
         },
@@ -196,7 +201,7 @@ async fn test_non_authed_write_and_read() {
                 Client::new("http://127.0.0.1:9086", TEST_NAME).with_auth("admin", "password");
             let query = format!("CREATE DATABASE {}", TEST_NAME);
             client
-                .query(&Query::raw_read_query(query))
+                .query(&<dyn Query>::raw_read_query(query))
                 .await
                 .expect("could not setup db");
             let non_authed_client = Client::new("http://127.0.0.1:9086", TEST_NAME);
@@ -213,7 +218,7 @@ async fn test_non_authed_write_and_read() {
                 ),
             }
 
-            let read_query = Query::raw_read_query("SELECT * FROM weather");
+            let read_query = <dyn Query>::raw_read_query("SELECT * FROM weather");
             let read_result = non_authed_client.query(&read_query).await;
             assert_result_err(&read_result);
             match read_result {
@@ -229,7 +234,7 @@ async fn test_non_authed_write_and_read() {
                 Client::new("http://127.0.0.1:9086", TEST_NAME).with_auth("admin", "password");
             let query = format!("DROP DATABASE {}", TEST_NAME);
             client
-                .query(&Query::raw_read_query(query))
+                .query(&<dyn Query>::raw_read_query(query))
                 .await
                 .expect("could not clean up db");
         },
@@ -255,7 +260,7 @@ async fn test_write_and_read_field() {
             let write_result = client.query(&write_query).await;
             assert_result_ok(&write_result);
 
-            let read_query = Query::raw_read_query("SELECT * FROM weather");
+            let read_query = <dyn Query>::raw_read_query("SELECT * FROM weather");
             let read_result = client.query(&read_query).await;
             assert_result_ok(&read_result);
             assert!(
@@ -304,8 +309,9 @@ async fn test_write_and_read_option() {
                     temperature: i32,
                 }
 
-                let query =
-                    Query::raw_read_query("SELECT time, temperature, wind_strength FROM weather");
+                let query = <dyn Query>::raw_read_query(
+                    "SELECT time, temperature, wind_strength FROM weather",
+                );
                 let result = client
                     .json_query(query)
                     .await
@@ -361,7 +367,7 @@ async fn test_json_query() {
                 temperature: i32,
             }
 
-            let query = Query::raw_read_query("SELECT * FROM weather");
+            let query = <dyn Query>::raw_read_query("SELECT * FROM weather");
             let result = client
                 .json_query(query)
                 .await
@@ -419,7 +425,7 @@ async fn test_json_query_tagged() {
                 temperature: i32,
             }
 
-            let query = Query::raw_read_query("SELECT * FROM weather GROUP BY location");
+            let query = <dyn Query>::raw_read_query("SELECT * FROM weather GROUP BY location");
             let result = client.json_query(query).await.and_then(|mut db_result| {
                 db_result.deserialize_next_tagged::<WeatherMeta, Weather>()
             });
@@ -447,6 +453,153 @@ async fn test_json_query_tagged() {
     .await;
 }
 
+/// INTEGRATION TEST
+///
+/// This test case tests whether the newest point of each series is returned by last_values
+#[async_std::test]
+#[cfg(feature = "use-serde")]
+#[cfg(not(tarpaulin_include))]
+async fn test_last_values() {
+    use serde::Deserialize;
+    use std::collections::HashMap;
+
+    const TEST_NAME: &str = "test_last_values";
+
+    run_test(
+        || async move {
+            create_db(TEST_NAME).await.expect("could not setup db");
+
+            let client = create_client(TEST_NAME);
+            let write_query = vec![
+                Timestamp::Hours(11)
+                    .into_query("weather")
+                    .add_tag("location", "London")
+                    .add_field("temperature", 82),
+                Timestamp::Hours(12)
+                    .into_query("weather")
+                    .add_tag("location", "London")
+                    .add_field("temperature", 65),
+                Timestamp::Hours(11)
+                    .into_query("weather")
+                    .add_tag("location", "Berlin")
+                    .add_field("temperature", 70),
+            ];
+            let write_result = client.query(&write_query).await;
+            assert_result_ok(&write_result);
+
+            #[derive(Deserialize, Debug, PartialEq)]
+            struct Weather {
+                time: String,
+                temperature: i32,
+            }
+
+            let result = client
+                .last_values::<HashMap<String, String>, Weather, _>("weather", &[])
+                .await;
+            assert_result_ok(&result);
+            let mut result = result.unwrap();
+            result.sort_by(|a, b| a.tags["location"].cmp(&b.tags["location"]));
+
+            assert_eq!(result.len(), 2);
+            assert_eq!(
+                result[0].values,
+                vec![Weather {
+                    time: "1970-01-01T11:00:00Z".to_string(),
+                    temperature: 70
+                }]
+            );
+            assert_eq!(
+                result[1].values,
+                vec![Weather {
+                    time: "1970-01-01T12:00:00Z".to_string(),
+                    temperature: 65
+                }]
+            );
+        },
+        || async move {
+            delete_db(TEST_NAME).await.expect("could not clean up db");
+        },
+    )
+    .await;
+}
+
+/// INTEGRATION TEST
+///
+/// This test case tests whether a MultiWrite writes the same points to all of its targets
+#[async_std::test]
+#[cfg(not(tarpaulin_include))]
+async fn test_multi_write() {
+    use influxdb::MultiWrite;
+
+    const TEST_NAME: &str = "test_multi_write";
+    const MIRROR_NAME: &str = "test_multi_write_mirror";
+
+    run_test(
+        || async move {
+            create_db(TEST_NAME).await.expect("could not setup db");
+            create_db(MIRROR_NAME).await.expect("could not setup db");
+
+            let multi_write = MultiWrite::new()
+                .add_target(create_client(TEST_NAME))
+                .add_target(create_client(MIRROR_NAME))
+                .add_target(create_client(TEST_NAME).with_retention_policy("doesnotexist"));
+            let write_query = Timestamp::Hours(11)
+                .into_query("weather")
+                .add_field("temperature", 82);
+            let results = multi_write.query(&write_query).await;
+            assert_eq!(results.len(), 3);
+            assert_result_ok(&results[0]);
+            assert_result_ok(&results[1]);
+            assert_result_err(&results[2]);
+
+            for name in &[TEST_NAME, MIRROR_NAME] {
+                let read_query = <dyn Query>::raw_read_query("SELECT * FROM weather");
+                let read_result = create_client(*name).query(&read_query).await;
+                assert_result_ok(&read_result);
+                assert!(read_result.unwrap().contains("82"));
+            }
+        },
+        || async move {
+            delete_db(TEST_NAME).await.expect("could not clean up db");
+            delete_db(MIRROR_NAME).await.expect("could not clean up db");
+        },
+    )
+    .await;
+}
+
+/// INTEGRATION TEST
+///
+/// This test case tests whether a batch exceeding the chunked threshold is streamed to the server
+#[async_std::test]
+#[cfg(not(tarpaulin_include))]
+async fn test_write_stream() {
+    const TEST_NAME: &str = "test_write_stream";
+
+    run_test(
+        || async move {
+            create_db(TEST_NAME).await.expect("could not setup db");
+
+            let client = create_client(TEST_NAME).with_chunked_threshold(64);
+            let points = (0..1000).map(|i| {
+                Timestamp::Seconds(i)
+                    .into_query("weather")
+                    .add_field("temperature", 82)
+            });
+            let write_result = client.write_stream(points).await;
+            assert_result_ok(&write_result);
+
+            let read_query = <dyn Query>::raw_read_query("SELECT count(temperature) FROM weather");
+            let read_result = client.query(&read_query).await;
+            assert_result_ok(&read_result);
+            assert!(read_result.unwrap().contains("1000"));
+        },
+        || async move {
+            delete_db(TEST_NAME).await.expect("could not clean up db");
+        },
+    )
+    .await;
+}
+
 /// INTEGRATION TEST
 ///
 /// This test case tests whether JSON can be decoded from a InfluxDB response and wether that JSON
@@ -485,7 +638,7 @@ async fn test_json_query_vec() {
                 temperature: i32,
             }
 
-            let query = Query::raw_read_query("SELECT * FROM temperature_vec");
+            let query = <dyn Query>::raw_read_query("SELECT * FROM temperature_vec");
             let result = client
                 .json_query(query)
                 .await
@@ -542,7 +695,7 @@ async fn test_serde_multi_query() {
 
             let result = client
                 .json_query(
-                    Query::raw_read_query("SELECT * FROM temperature")
+                    <dyn Query>::raw_read_query("SELECT * FROM temperature")
                         .add_query("SELECT * FROM humidity"),
                 )
                 .await
@@ -586,7 +739,9 @@ async fn test_serde_multi_query() {
 async fn test_wrong_query_errors() {
     let client = create_client("test_name");
     let result = client
-        .json_query(Query::raw_read_query("CREATE DATABASE this_should_fail"))
+        .json_query(<dyn Query>::raw_read_query(
+            "CREATE DATABASE this_should_fail",
+        ))
         .await;
     assert!(
         result.is_err(),
@@ -29,7 +29,7 @@ where
     let test_name = name.into();
     let query = format!("CREATE DATABASE {}", test_name);
     create_client(test_name)
-        .query(&Query::raw_read_query(query))
+        .query(&<dyn Query>::raw_read_query(query))
         .await
 }
 
@@ -41,7 +41,7 @@ where
     let test_name = name.into();
     let query = format!("DROP DATABASE {}", test_name);
     create_client(test_name)
-        .query(&Query::raw_read_query(query))
+        .query(&<dyn Query>::raw_read_query(query))
         .await
 }
 
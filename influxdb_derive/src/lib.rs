@@ -9,6 +9,14 @@ fn krate() -> TokenStream2 {
     quote!(::influxdb)
 }
 
+/// Implements `InfluxDbWriteable` for a struct with named fields
+///
+/// The `time` field is the timestamp of the point, all other fields are written as fields unless
+/// they are marked with `#[influxdb(tag)]` or skipped with `#[influxdb(ignore)]`. On the struct,
+/// `#[influxdb(tag_fields(host, region))]` marks several fields as tags at once and
+/// `#[influxdb(borrowed)]` implements the trait for references to the struct as well, cloning
+/// just the values of the fields. `#[influxdb(measurement = "cpu")]` implements `ToPoint` as well,
+/// which writes the cloned fields to the given measurement.
 #[proc_macro_derive(InfluxDbWriteable, attributes(influxdb))]
 pub fn derive_writeable(tokens: TokenStream) -> TokenStream {
     expand_writeable(tokens)
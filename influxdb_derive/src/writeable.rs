@@ -1,7 +1,10 @@
 use proc_macro::TokenStream;
-use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use proc_macro2::TokenTree;
 use quote::{format_ident, quote};
-use syn::{parse_macro_input, Field, Fields, Ident, ItemStruct};
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Field, Fields, Ident, ItemStruct, Lit, LitStr, Meta,
+    NestedMeta,
+};
 
 #[derive(Debug)]
 struct WriteableField {
@@ -72,46 +75,187 @@ impl From<Field> for WriteableField {
     }
 }
 
+/// Options of the struct, e.g. `#[influxdb(tag_fields(host, region), borrowed, measurement = "cpu")]`
+#[derive(Debug, Default)]
+struct StructOptions {
+    /// Fields which are written as tags
+    tag_fields: Vec<Ident>,
+    /// Whether to implement the trait for references to the struct as well
+    borrowed: bool,
+    /// Measurement of the points of `ToPoint`, which is only implemented if it is set
+    measurement: Option<LitStr>,
+}
+
+impl StructOptions {
+    fn parse(attrs: &[Attribute]) -> syn::Result<StructOptions> {
+        let mut options = StructOptions::default();
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("influxdb")) {
+            let nested = match attr.parse_meta()? {
+                Meta::List(list) => list.nested,
+                meta => return Err(syn::Error::new_spanned(meta, "expected #[influxdb(...)]")),
+            };
+            for meta in nested {
+                match meta {
+                    NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("tag_fields") => {
+                        for field in list.nested {
+                            match field {
+                                NestedMeta::Meta(Meta::Path(path))
+                                    if path.get_ident().is_some() =>
+                                {
+                                    options.tag_fields.push(path.get_ident().unwrap().clone())
+                                }
+                                field => {
+                                    return Err(syn::Error::new_spanned(
+                                        field,
+                                        "expected a field name",
+                                    ))
+                                }
+                            }
+                        }
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("borrowed") => {
+                        options.borrowed = true
+                    }
+                    NestedMeta::Meta(Meta::NameValue(name_value))
+                        if name_value.path.is_ident("measurement") =>
+                    {
+                        match name_value.lit {
+                            Lit::Str(measurement) => options.measurement = Some(measurement),
+                            lit => {
+                                return Err(syn::Error::new_spanned(
+                                    lit,
+                                    "expected the measurement as a string",
+                                ))
+                            }
+                        }
+                    }
+                    meta => return Err(syn::Error::new_spanned(
+                        meta,
+                        "expected tag_fields(...), borrowed or measurement = \"...\" on the struct",
+                    )),
+                }
+            }
+        }
+        Ok(options)
+    }
+}
+
 pub fn expand_writeable(tokens: TokenStream) -> TokenStream {
     let krate = super::krate();
     let input = parse_macro_input!(tokens as ItemStruct);
     let ident = input.ident;
     let generics = input.generics;
+    let options = match StructOptions::parse(&input.attrs) {
+        Ok(options) => options,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let tag_fields = &options.tag_fields;
 
     let time_field = format_ident!("time");
     #[allow(clippy::cmp_owned)] // that's not how idents work clippy
-    let fields: Vec<TokenStream2> = match input.fields {
-        Fields::Named(fields) => fields
-            .named
-            .into_iter()
-            .map(WriteableField::from)
-            .filter(|field| !field.is_ignore)
-            .filter(|field| field.ident.to_string() != time_field.to_string())
-            .map(|field| {
-                let ident = field.ident;
-                #[allow(clippy::match_bool)]
-                match field.is_tag {
-                    true => quote!(query.add_tag(stringify!(#ident), self.#ident)),
-                    false => quote!(query.add_field(stringify!(#ident), self.#ident)),
-                }
-            })
-            .collect(),
+    let fields: Vec<(Ident, bool)> = match input.fields {
+        Fields::Named(fields) => {
+            let fields = fields
+                .named
+                .into_iter()
+                .map(WriteableField::from)
+                .collect::<Vec<_>>();
+            if let Some(unknown) = tag_fields
+                .iter()
+                .find(|tag| !fields.iter().any(|field| field.ident == **tag))
+            {
+                return syn::Error::new_spanned(unknown, "no field with this name")
+                    .to_compile_error()
+                    .into();
+            }
+            fields
+        }
         _ => panic!("a struct without named fields is not supported"),
-    };
+    }
+    .into_iter()
+    .filter(|field| !field.is_ignore)
+    .filter(|field| field.ident.to_string() != time_field.to_string())
+    .map(|field| {
+        let is_tag = field.is_tag || tag_fields.contains(&field.ident);
+        (field.ident, is_tag)
+    })
+    .collect();
 
-    let output = quote! {
-        impl #generics #krate::InfluxDbWriteable for #ident #generics
-        {
+    // writes the fields of `self` to the measurement `name`, tags and fields are passed by reference
+    // if `self` is borrowed, the time is cloned
+    let build_query = |borrowed: bool| {
+        let time = if borrowed {
+            quote!(::core::clone::Clone::clone(&self.#time_field))
+        } else {
+            quote!(self.#time_field)
+        };
+        let fields = fields.iter().map(|(ident, is_tag)| {
+            let value = if borrowed {
+                quote!(&self.#ident)
+            } else {
+                quote!(self.#ident)
+            };
+            #[allow(clippy::match_bool)]
+            match is_tag {
+                true => quote!(query.add_tag(stringify!(#ident), #value)),
+                false => quote!(query.add_field(stringify!(#ident), #value)),
+            }
+        });
+        quote! {
+            let timestamp : #krate::Timestamp = #time.into();
+            let mut query = timestamp.into_query(name);
+            #(
+                query = #fields;
+            )*
+            query
+        }
+    };
+    let into_query = |borrowed: bool| {
+        let body = build_query(borrowed);
+        quote! {
             fn into_query<I: Into<String>>(self, name : I) -> #krate::WriteQuery
             {
-                let timestamp : #krate::Timestamp = self.#time_field.into();
-                let mut query = timestamp.into_query(name);
-                #(
-                    query = #fields;
-                )*
-                query
+                #body
             }
         }
     };
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let owned = into_query(false);
+    let mut output = quote! {
+        impl #impl_generics #krate::InfluxDbWriteable for #ident #ty_generics #where_clause
+        {
+            #owned
+        }
+    };
+
+    if options.borrowed {
+        let mut ref_generics = generics.clone();
+        ref_generics.params.insert(0, parse_quote!('__influxdb_ref));
+        let (ref_impl_generics, _, _) = ref_generics.split_for_impl();
+        let borrowed = into_query(true);
+        output.extend(quote! {
+            impl #ref_impl_generics #krate::InfluxDbWriteable for &'__influxdb_ref #ident #ty_generics
+                #where_clause
+            {
+                #borrowed
+            }
+        });
+    }
+
+    if let Some(measurement) = options.measurement {
+        let body = build_query(true);
+        output.extend(quote! {
+            impl #impl_generics #krate::ToPoint for #ident #ty_generics #where_clause
+            {
+                fn to_point(&self) -> #krate::WriteQuery
+                {
+                    use #krate::InfluxDbWriteable;
+                    let name = #measurement;
+                    #body
+                }
+            }
+        });
+    }
     output.into()
 }
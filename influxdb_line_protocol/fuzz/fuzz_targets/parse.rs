@@ -0,0 +1,23 @@
+//! Parses arbitrary lines, and checks that the lines which parse are written back unchanged
+//!
+//! Run with `cargo +nightly fuzz run parse` in `influxdb_line_protocol`.
+
+#![no_main]
+
+use influxdb_line_protocol::{Error, Point};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|line: &str| {
+    let point = match Point::parse(line) {
+        Ok(point) => point,
+        Err(_) => return,
+    };
+    let written = match point.to_line() {
+        Ok(written) => written,
+        // unsigned integers beyond the signed range are parsed, but can't be written
+        Err(Error::InvalidFieldValue) => return,
+        Err(err) => panic!("{} parsed, but can't be written: {}", line, err),
+    };
+    let reparsed = Point::parse(&written).expect("written lines parse");
+    assert_eq!(reparsed.to_line().as_ref(), Ok(&written));
+});
@@ -0,0 +1,707 @@
+//! Formats points in the InfluxDB line protocol, without the standard library
+//!
+//! This crate is `#![no_std]` and only needs `alloc`, so embedded data loggers can format points
+//! which a gateway forwards to InfluxDB later. The [`influxdb`](https://crates.io/crates/influxdb)
+//! client escapes its writes with the same functions.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use influxdb_line_protocol::Point;
+//!
+//! let line = Point::new("weather")
+//!     .add_tag("location", "us-midwest")
+//!     .add_field("temperature", 82)
+//!     .add_field("description", "sunny")
+//!     .timestamp(1_465_839_830_100_400_200)
+//!     .to_line()
+//!     .unwrap();
+//!
+//! assert_eq!(line, r#"weather,location=us-midwest temperature=82i,description="sunny" 1465839830100400200"#);
+//! ```
+//!
+//! The `write_*` functions escape single terms into any [`core::fmt::Write`], e.g. a fixed size
+//! buffer, without building a [`Point`](crate::Point) first. [`Point::parse`](crate::Point::parse)
+//! reads lines back, e.g. to check what a gateway forwards.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+/// The value of a field
+#[derive(PartialEq, Debug, Clone)]
+pub enum FieldValue {
+    Boolean(bool),
+    Float(f64),
+    SignedInteger(i64),
+    /// Written with the `i` suffix of signed integers, which InfluxDB 1.x requires, so it can't
+    /// exceed `i64::MAX`
+    UnsignedInteger(u64),
+    Text(String),
+}
+
+macro_rules! from_impl {
+        ( $variant:ident => $( $typ:ident ),+ ) => (
+                $(
+                    impl From<$typ> for FieldValue {
+                        fn from(b: $typ) -> Self {
+                            FieldValue::$variant(b.into())
+                        }
+                    }
+                )+
+        )
+}
+from_impl! {Boolean => bool}
+from_impl! {Float => f32, f64}
+from_impl! {SignedInteger => i8, i16, i32, i64}
+from_impl! {UnsignedInteger => u8, u16, u32, u64}
+from_impl! {Text => String}
+impl From<&str> for FieldValue {
+    fn from(b: &str) -> Self {
+        FieldValue::Text(b.into())
+    }
+}
+
+impl FieldValue {
+    /// Parses a value as it appears in the field set of a line
+    ///
+    /// Accepts floats, integers with the `i` suffix, unsigned integers with the `u` suffix,
+    /// booleans as `t`, `T`, `true`, `True`, `TRUE`, `f`, `F`, `false`, `False` or `FALSE`, and
+    /// strings in double quotes, in which `\"` and `\\` are unescaped and other backslashes are
+    /// kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb_line_protocol::FieldValue;
+    ///
+    /// assert_eq!(FieldValue::parse("T"), Ok(FieldValue::Boolean(true)));
+    /// assert_eq!(FieldValue::parse("-3i"), Ok(FieldValue::SignedInteger(-3)));
+    /// assert_eq!(FieldValue::parse(r#""say \"hi\"""#), Ok(FieldValue::Text(r#"say "hi""#.into())));
+    /// assert!(FieldValue::parse("NaN").is_err());
+    /// ```
+    pub fn parse(value: &str) -> Result<FieldValue, Error> {
+        use FieldValue::*;
+        match value {
+            "t" | "T" | "true" | "True" | "TRUE" => return Ok(Boolean(true)),
+            "f" | "F" | "false" | "False" | "FALSE" => return Ok(Boolean(false)),
+            _ => {}
+        }
+        if let Some(quoted) = value.strip_prefix('"') {
+            return parse_string_field(quoted).map(Text);
+        }
+        if let Some(integer) = value.strip_suffix('i') {
+            return integer
+                .parse()
+                .map(SignedInteger)
+                .map_err(|_| Error::InvalidFieldValue);
+        }
+        if let Some(integer) = value.strip_suffix('u') {
+            return integer
+                .parse()
+                .map(UnsignedInteger)
+                .map_err(|_| Error::InvalidFieldValue);
+        }
+        // Rust also parses `inf` and `NaN`, which the line protocol doesn't have
+        let starts_like_number = value
+            .trim_start_matches(&['-', '+'][..])
+            .starts_with(|c: char| c.is_ascii_digit() || c == '.');
+        match value.parse::<f64>() {
+            Ok(float) if starts_like_number && float.is_finite() => Ok(Float(float)),
+            _ => Err(Error::InvalidFieldValue),
+        }
+    }
+
+    /// Writes the value as it appears in the field set of a line
+    pub fn write_to<W: Write>(&self, w: &mut W) -> fmt::Result {
+        use FieldValue::*;
+        match self {
+            Boolean(v) => write!(w, "{}", v),
+            Float(v) => write!(w, "{}", v),
+            SignedInteger(v) => write!(w, "{}i", v),
+            UnsignedInteger(v) => write!(w, "{}i", v),
+            Text(v) => write_string_field(w, v),
+        }
+    }
+}
+
+/// Error of formatting or parsing a [`Point`](crate::Point)
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Error {
+    /// The point has no fields, which the line protocol requires
+    NoFields,
+    /// The measurement, a key or a tag value is empty or contains a line break, or the
+    /// measurement starts with `#`, which would make the line a comment
+    InvalidName,
+    /// The writer failed, e.g. because its buffer is full
+    Format,
+    /// A float field is infinite or NaN, which the line protocol can't represent
+    NonFiniteFloat,
+    /// A field value couldn't be parsed, or an unsigned integer exceeds the range of the signed
+    /// integers it is written as
+    InvalidFieldValue,
+    /// A line couldn't be parsed
+    InvalidLine,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoFields => write!(f, "fields cannot be empty"),
+            Error::InvalidName => write!(
+                f,
+                "measurements, keys and tag values must be non-empty single lines"
+            ),
+            Error::Format => write!(f, "could not write line"),
+            Error::NonFiniteFloat => write!(f, "float fields must be finite"),
+            Error::InvalidFieldValue => write!(f, "invalid field value"),
+            Error::InvalidLine => write!(f, "invalid line"),
+        }
+    }
+}
+
+impl From<fmt::Error> for Error {
+    fn from(_: fmt::Error) -> Self {
+        Error::Format
+    }
+}
+
+/// A single point, formatted as one line of the line protocol
+///
+/// Points without a timestamp are stamped by the server when they are written.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Point {
+    measurement: String,
+    tags: Vec<(String, String)>,
+    fields: Vec<(String, FieldValue)>,
+    timestamp: Option<u128>,
+}
+
+impl Point {
+    /// Creates a point of the given measurement
+    pub fn new<M>(measurement: M) -> Self
+    where
+        M: Into<String>,
+    {
+        Point {
+            measurement: measurement.into(),
+            tags: Vec::new(),
+            fields: Vec::new(),
+            timestamp: None,
+        }
+    }
+
+    /// Adds a tag to the point
+    pub fn add_tag<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.tags.push((key.into(), value.into()));
+        self
+    }
+
+    /// Adds a field to the point
+    pub fn add_field<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<FieldValue>,
+    {
+        self.fields.push((key.into(), value.into()));
+        self
+    }
+
+    /// Sets the timestamp of the point, in the precision the line is written with later
+    pub fn timestamp(mut self, timestamp: u128) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Parses a single line, without a trailing newline
+    ///
+    /// Escaped characters are unescaped like they are escaped by [`write_to`](Point::write_to).
+    /// Backslashes which don't escape anything are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb_line_protocol::Point;
+    ///
+    /// let point = Point::parse(r#"weather,city=New\ York temperature=82i,sky="clear" 11"#)?;
+    /// let expected = Point::new("weather")
+    ///     .add_tag("city", "New York")
+    ///     .add_field("temperature", 82)
+    ///     .add_field("sky", "clear")
+    ///     .timestamp(11);
+    ///
+    /// assert_eq!(point, expected);
+    /// assert_eq!(point.to_line()?, r#"weather,city=New\ York temperature=82i,sky="clear" 11"#);
+    /// # Ok::<(), influxdb_line_protocol::Error>(())
+    /// ```
+    pub fn parse(line: &str) -> Result<Point, Error> {
+        let (measurement, mut rest) = split_term(line, &[',', ' ']);
+        let mut point = Point::new(unescape(measurement, MEASUREMENT_ESCAPES));
+
+        while let Some(tag) = rest.strip_prefix(',') {
+            let (key, tag) = split_term(tag, &[',', '=', ' ']);
+            let tag = tag.strip_prefix('=').ok_or(Error::InvalidLine)?;
+            let (value, tag) = split_term(tag, &[',', '=', ' ']);
+            point = point.add_tag(
+                unescape(key, KEY_ESCAPES),
+                unescape(value, TAG_VALUE_ESCAPES),
+            );
+            rest = tag;
+        }
+
+        let mut separator = ' ';
+        while let Some(field) = rest.strip_prefix(separator) {
+            let (key, field) = split_term(field, &[',', '=', ' ']);
+            let field = field.strip_prefix('=').ok_or(Error::InvalidLine)?;
+            let (value, field) = if field.starts_with('"') {
+                split_string_field(field)?
+            } else {
+                split_term(field, &[',', ' '])
+            };
+            point = point.add_field(unescape(key, KEY_ESCAPES), FieldValue::parse(value)?);
+            rest = field;
+            separator = ',';
+        }
+
+        if let Some(timestamp) = rest.strip_prefix(' ') {
+            if !timestamp.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(Error::InvalidLine);
+            }
+            point.timestamp = Some(timestamp.parse().map_err(|_| Error::InvalidLine)?);
+        } else if !rest.is_empty() {
+            return Err(Error::InvalidLine);
+        }
+        point.validate()?;
+        Ok(point)
+    }
+
+    /// Returns the measurement of the point
+    pub fn measurement(&self) -> &str {
+        &self.measurement
+    }
+
+    /// Returns the tags of the point, in the order they were added
+    pub fn tags(&self) -> &[(String, String)] {
+        &self.tags
+    }
+
+    /// Returns the fields of the point, in the order they were added
+    pub fn fields(&self) -> &[(String, FieldValue)] {
+        &self.fields
+    }
+
+    /// Returns the timestamp of the point, if it has one
+    pub fn get_timestamp(&self) -> Option<u128> {
+        self.timestamp
+    }
+
+    /// Fails if the point can't be written as a valid line
+    fn validate(&self) -> Result<(), Error> {
+        if self.fields.is_empty() {
+            return Err(Error::NoFields);
+        }
+        if self
+            .fields
+            .iter()
+            .any(|(_, value)| matches!(value, FieldValue::Float(float) if !float.is_finite()))
+        {
+            return Err(Error::NonFiniteFloat);
+        }
+        if self.fields.iter().any(
+            |(_, value)| matches!(value, FieldValue::UnsignedInteger(u) if *u > i64::MAX as u64),
+        ) {
+            return Err(Error::InvalidFieldValue);
+        }
+        let invalid = |name: &str| name.is_empty() || name.contains(&['\n', '\r'][..]);
+        if invalid(&self.measurement)
+            || self.measurement.starts_with('#')
+            || self
+                .tags
+                .iter()
+                .any(|(key, value)| invalid(key) || invalid(value))
+            || self.fields.iter().any(|(key, _)| invalid(key))
+        {
+            return Err(Error::InvalidName);
+        }
+        Ok(())
+    }
+
+    /// Writes the point as a line, without a trailing newline
+    pub fn write_to<W: Write>(&self, w: &mut W) -> Result<(), Error> {
+        self.validate()?;
+
+        write_measurement(w, &self.measurement)?;
+        for (key, value) in &self.tags {
+            w.write_char(',')?;
+            write_key(w, key)?;
+            w.write_char('=')?;
+            write_tag_value(w, value)?;
+        }
+        for (index, (key, value)) in self.fields.iter().enumerate() {
+            w.write_char(if index == 0 { ' ' } else { ',' })?;
+            write_key(w, key)?;
+            w.write_char('=')?;
+            value.write_to(w)?;
+        }
+        if let Some(timestamp) = self.timestamp {
+            write!(w, " {}", timestamp)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the point as a line, without a trailing newline
+    pub fn to_line(&self) -> Result<String, Error> {
+        let mut line = String::new();
+        self.write_to(&mut line)?;
+        Ok(line)
+    }
+}
+
+const MEASUREMENT_ESCAPES: &[char] = &['\\', ',', ' '];
+const KEY_ESCAPES: &[char] = &['\\', ',', '=', ' ', '"'];
+const TAG_VALUE_ESCAPES: &[char] = &['\\', ',', ' ', '=', '"'];
+
+/// Writes a measurement name, escaping commas, spaces and backslashes
+pub fn write_measurement<W: Write>(w: &mut W, measurement: &str) -> fmt::Result {
+    write_escaped(w, measurement, MEASUREMENT_ESCAPES)
+}
+
+/// Writes a tag or field key, escaping commas, equal signs, spaces, quotes and backslashes
+pub fn write_key<W: Write>(w: &mut W, key: &str) -> fmt::Result {
+    write_escaped(w, key, KEY_ESCAPES)
+}
+
+/// Writes a tag value, escaping commas, equal signs, spaces, quotes and backslashes
+pub fn write_tag_value<W: Write>(w: &mut W, value: &str) -> fmt::Result {
+    write_escaped(w, value, TAG_VALUE_ESCAPES)
+}
+
+/// Writes a string field value in double quotes, escaping quotes and backslashes
+pub fn write_string_field<W: Write>(w: &mut W, value: &str) -> fmt::Result {
+    w.write_char('"')?;
+    write_escaped(w, value, &['"', '\\'])?;
+    w.write_char('"')
+}
+
+/// Unescapes a string field value after its opening quote, which has to end with the closing quote
+fn parse_string_field(quoted: &str) -> Result<String, Error> {
+    let mut text = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if chars.as_str().is_empty() => return Ok(text),
+            '"' => return Err(Error::InvalidFieldValue),
+            '\\' => match chars.next() {
+                Some(escaped @ ('"' | '\\')) => text.push(escaped),
+                Some(other) => {
+                    text.push('\\');
+                    text.push(other);
+                }
+                None => return Err(Error::InvalidFieldValue),
+            },
+            _ => text.push(c),
+        }
+    }
+    // the closing quote is missing
+    Err(Error::InvalidFieldValue)
+}
+
+/// Splits off the term before the first unescaped delimiter
+fn split_term<'a>(s: &'a str, delimiters: &[char]) -> (&'a str, &'a str) {
+    let mut chars = s.char_indices();
+    while let Some((index, c)) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if delimiters.contains(&c) {
+            return s.split_at(index);
+        }
+    }
+    (s, "")
+}
+
+/// Splits off a string field value, including its quotes
+fn split_string_field(s: &str) -> Result<(&str, &str), Error> {
+    let mut chars = s.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '"' => return Ok(s.split_at(index + 1)),
+            _ => {}
+        }
+    }
+    Err(Error::InvalidFieldValue)
+}
+
+/// Removes the backslashes before `escaped` characters
+fn unescape(s: &str, escaped: &[char]) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some(next) if escaped.contains(&next) => unescaped.push(next),
+            Some(next) => {
+                unescaped.push('\\');
+                unescaped.push(next);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+fn write_escaped<W: Write>(w: &mut W, s: &str, special: &[char]) -> fmt::Result {
+    let mut unescaped = 0;
+    for (index, c) in s.char_indices() {
+        if special.contains(&c) {
+            w.write_str(&s[unescaped..index])?;
+            w.write_char('\\')?;
+            unescaped = index;
+        }
+    }
+    w.write_str(&s[unescaped..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_key, write_measurement, write_string_field, write_tag_value};
+    use super::{Error, FieldValue, Point};
+    use alloc::string::String;
+
+    fn escaped(write: fn(&mut String, &str) -> core::fmt::Result, s: &str) -> String {
+        let mut escaped = String::new();
+        write(&mut escaped, s).unwrap();
+        escaped
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escaped(write_measurement, "wea ther,1"), r#"wea\ ther\,1"#);
+        assert_eq!(escaped(write_measurement, "a=b"), "a=b");
+        assert_eq!(escaped(write_measurement, r#"trailing\"#), r#"trailing\\"#);
+        assert_eq!(escaped(write_key, "a b,c=d"), r#"a\ b\,c\=d"#);
+        assert_eq!(escaped(write_key, r#"a\ b"#), r#"a\\\ b"#);
+        assert_eq!(escaped(write_tag_value, r#"a\b "c""#), r#"a\\b\ \"c\""#);
+        assert_eq!(
+            escaped(write_string_field, r#"say "hi"\"#),
+            r#""say \"hi\"\\""#
+        );
+        assert_eq!(escaped(write_string_field, "äö, ü"), r#""äö, ü""#);
+    }
+
+    #[test]
+    fn test_point() {
+        let point = Point::new("weather")
+            .add_tag("city", "New York")
+            .add_field("temperature", 82.5)
+            .add_field("raining", false)
+            .add_field("visitors", 3_u32);
+        assert_eq!(
+            point.to_line().unwrap(),
+            r#"weather,city=New\ York temperature=82.5,raining=false,visitors=3i"#
+        );
+        assert_eq!(
+            point.timestamp(11).to_line().unwrap(),
+            r#"weather,city=New\ York temperature=82.5,raining=false,visitors=3i 11"#
+        );
+        assert_eq!(Point::new("weather").to_line(), Err(Error::NoFields));
+    }
+
+    #[test]
+    fn test_invalid_names() {
+        let point = |measurement: &str, key: &str, value: &str| {
+            Point::new(measurement)
+                .add_tag(key, value)
+                .add_field("temperature", 82)
+                .to_line()
+        };
+        assert!(point("weather", "city", "berlin").is_ok());
+        for invalid in [
+            point("", "city", "berlin"),
+            point("#weather", "city", "berlin"),
+            point("weather\n", "city", "berlin"),
+            point("weather", "", "berlin"),
+            point("weather", "city", ""),
+            point("weather", "city", "ber\r\nlin"),
+        ] {
+            assert_eq!(invalid, Err(Error::InvalidName));
+        }
+    }
+
+    #[test]
+    fn test_parse_point() {
+        let lines = [
+            (
+                "weather temperature=82",
+                Point::new("weather").add_field("temperature", 82.0),
+            ),
+            (
+                r#"wea\ ther\,\\,a\=b=c\ d\\,e=\"f\" g\,h="i,j=k l\"",m=-1i 0"#,
+                Point::new(r#"wea ther,\"#)
+                    .add_tag("a=b", r#"c d\"#)
+                    .add_tag("e", r#""f""#)
+                    .add_field("g,h", r#"i,j=k l""#)
+                    .add_field("m", -1_i64)
+                    .timestamp(0),
+            ),
+            // backslashes which don't escape anything are kept
+            (
+                r#"C:\dir,k\x=v\ny f=t"#,
+                Point::new(r#"C:\dir"#)
+                    .add_tag(r#"k\x"#, r#"v\ny"#)
+                    .add_field("f", true),
+            ),
+        ];
+        for (line, point) in lines.iter() {
+            assert_eq!(Point::parse(line).as_ref(), Ok(point), "{}", line);
+        }
+
+        let invalid = [
+            ("", Error::NoFields),
+            ("weather", Error::NoFields),
+            ("weather,city=berlin", Error::NoFields),
+            ("#weather temperature=82", Error::InvalidName),
+            ("weather,city= temperature=82", Error::InvalidName),
+            ("weather,city temperature=82", Error::InvalidLine),
+            ("weather temperature", Error::InvalidLine),
+            ("weather temperature=82 11 12", Error::InvalidLine),
+            ("weather temperature=82 -11", Error::InvalidLine),
+            ("weather temperature=82 ", Error::InvalidLine),
+            ("weather  temperature=82", Error::InvalidLine),
+            ("weather temperature=82,", Error::InvalidLine),
+            ("weather,city=ber=lin temperature=82", Error::InvalidLine),
+            (r#"weather sky="clear"#, Error::InvalidFieldValue),
+            ("weather temperature=hot", Error::InvalidFieldValue),
+        ];
+        for (line, error) in invalid.iter() {
+            assert_eq!(Point::parse(line).as_ref(), Err(error), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_parse_field_value() {
+        use FieldValue::*;
+        let valid = [
+            ("t", Boolean(true)),
+            ("T", Boolean(true)),
+            ("true", Boolean(true)),
+            ("True", Boolean(true)),
+            ("TRUE", Boolean(true)),
+            ("f", Boolean(false)),
+            ("F", Boolean(false)),
+            ("false", Boolean(false)),
+            ("False", Boolean(false)),
+            ("FALSE", Boolean(false)),
+            ("1", Float(1.0)),
+            ("-1.5", Float(-1.5)),
+            (".5", Float(0.5)),
+            ("1e+78", Float(1e78)),
+            ("1.0e-06", Float(1e-6)),
+            ("0i", SignedInteger(0)),
+            ("-9223372036854775808i", SignedInteger(i64::MIN)),
+            ("18446744073709551615u", UnsignedInteger(u64::MAX)),
+            (r#""""#, Text(String::new())),
+            (r#""too hot""#, Text("too hot".into())),
+            (r#""a,b=c d""#, Text("a,b=c d".into())),
+            (r#""\"""#, Text(r#"""#.into())),
+            (r#""\\\\""#, Text(r#"\\"#.into())),
+            (r#""C:\\dir\file""#, Text(r#"C:\dir\file"#.into())),
+            ("\"line\nbreak\"", Text("line\nbreak".into())),
+        ];
+        for (text, value) in valid.iter() {
+            assert_eq!(FieldValue::parse(text).as_ref(), Ok(value), "{}", text);
+        }
+
+        let invalid = [
+            "",
+            "tru",
+            "yes",
+            "1.5i",
+            "1x",
+            "9223372036854775808i",
+            "-1u",
+            "NaN",
+            "inf",
+            "-infinity",
+            r#""open"#,
+            r#""a"b""#,
+            r#""ends with \""#,
+            "'single'",
+        ];
+        for text in invalid.iter() {
+            assert_eq!(
+                FieldValue::parse(text),
+                Err(Error::InvalidFieldValue),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_field_value_roundtrip() {
+        use FieldValue::*;
+        let values = [
+            Boolean(true),
+            Boolean(false),
+            Float(0.0),
+            Float(-273.15),
+            Float(1e300),
+            Float(f64::MIN_POSITIVE),
+            SignedInteger(i64::MAX),
+            UnsignedInteger(u64::MAX),
+            Text(r#"say "hi"\"#.into()),
+            Text(r#"\\"\"#.into()),
+            Text("ä,ö ü=\n".into()),
+        ];
+        for value in values.iter() {
+            let mut line = String::new();
+            value.write_to(&mut line).unwrap();
+            let parsed = match value {
+                // written with the `i` suffix for InfluxDB 1.x
+                UnsignedInteger(u) => {
+                    assert_eq!(line, alloc::format!("{}i", u));
+                    continue;
+                }
+                _ => FieldValue::parse(&line),
+            };
+            assert_eq!(parsed.as_ref(), Ok(value), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_non_finite_float() {
+        let point = Point::new("weather").add_field("temperature", f64::NAN);
+        assert_eq!(point.to_line(), Err(Error::NonFiniteFloat));
+        let point = Point::new("weather").add_field("temperature", f64::INFINITY);
+        assert_eq!(point.to_line(), Err(Error::NonFiniteFloat));
+    }
+
+    #[test]
+    fn test_write_to_full_buffer() {
+        struct Fixed(usize);
+        impl core::fmt::Write for Fixed {
+            fn write_str(&mut self, s: &str) -> core::fmt::Result {
+                self.0 = self.0.checked_sub(s.len()).ok_or(core::fmt::Error)?;
+                Ok(())
+            }
+        }
+
+        let point = Point::new("weather").add_field("temperature", FieldValue::Float(82.0));
+        assert_eq!(point.write_to(&mut Fixed(8)), Err(Error::Format));
+        assert_eq!(point.write_to(&mut Fixed(64)), Ok(()));
+    }
+}
@@ -0,0 +1,117 @@
+use influxdb_line_protocol::{Error, FieldValue, Point};
+use proptest::prelude::*;
+
+/// Characters with a meaning in the line protocol, which are generated more often than others
+const SPECIAL: &[char] = &[',', '=', ' ', '"', '\\', '#', '\t', 'é', '🦀'];
+
+fn term() -> impl Strategy<Value = String> {
+    let c = prop_oneof![
+        3 => proptest::sample::select(SPECIAL),
+        1 => any::<char>().prop_filter("line break", |c| *c != '\n' && *c != '\r'),
+    ];
+    proptest::collection::vec(c, 1..12).prop_map(|chars| chars.into_iter().collect())
+}
+
+fn measurement() -> impl Strategy<Value = String> {
+    term().prop_filter("comment", |m| !m.starts_with('#'))
+}
+
+fn field_value() -> impl Strategy<Value = FieldValue> {
+    prop_oneof![
+        any::<bool>().prop_map(FieldValue::Boolean),
+        any::<f64>()
+            .prop_filter("finite", |f| f.is_finite())
+            .prop_map(FieldValue::Float),
+        any::<i64>().prop_map(FieldValue::SignedInteger),
+        // unlike their own strings, strings of fields can span lines
+        any::<String>().prop_map(FieldValue::Text),
+        term().prop_map(FieldValue::Text),
+    ]
+}
+
+prop_compose! {
+    fn point()(
+        measurement in measurement(),
+        tags in proptest::collection::vec((term(), term()), 0..4),
+        fields in proptest::collection::vec((term(), field_value()), 1..4),
+        timestamp in proptest::option::of(any::<u64>()),
+    ) -> Point {
+        let mut point = Point::new(measurement);
+        for (key, value) in tags {
+            point = point.add_tag(key, value);
+        }
+        for (key, value) in fields {
+            point = point.add_field(key, value);
+        }
+        match timestamp {
+            Some(timestamp) => point.timestamp(timestamp.into()),
+            None => point,
+        }
+    }
+}
+
+/// Returns the positions of the characters of the line which aren't escaped or in a string field
+fn structure(line: &str) -> Vec<(usize, char)> {
+    let mut structure = Vec::new();
+    let (mut escaped, mut quoted, mut fields) = (false, false, false);
+    for (index, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '"' && fields {
+            quoted = !quoted;
+        } else if !quoted {
+            if c == ' ' {
+                fields = true;
+            }
+            structure.push((index, c));
+        }
+    }
+    structure
+}
+
+proptest! {
+    #[test]
+    fn build_parse_roundtrip(point in point()) {
+        let line = point.to_line().unwrap();
+        prop_assert_eq!(Point::parse(&line), Ok(point));
+    }
+
+    #[test]
+    fn lines_follow_the_grammar(point in point()) {
+        let line = point.to_line().unwrap();
+        let structure = structure(&line);
+        // equal signs don't have to be escaped in measurements
+        let delimiters = structure
+            .iter()
+            .skip_while(|(_, c)| *c != ',' && *c != ' ')
+            .filter(|(_, c)| [',', '=', ' '].contains(c))
+            .count();
+
+        // one delimiter after the measurement and between the tags, and one between their keys
+        // and values, the same for the fields, and one before the timestamp
+        let tags = point.tags().len();
+        let fields = point.fields().len();
+        let timestamp = point.get_timestamp().is_some() as usize;
+        prop_assert_eq!(delimiters, 2 * tags + 2 * fields + timestamp);
+        prop_assert!(!line.starts_with('#'));
+        // line breaks can only be part of a string field
+        prop_assert!(structure.iter().all(|(_, c)| *c != '\n' && *c != '\r'));
+    }
+
+    #[test]
+    fn unsigned_integers_are_written_as_signed(value in any::<u64>()) {
+        let line = Point::new("m").add_field("f", value).to_line();
+        if value <= i64::MAX as u64 {
+            prop_assert_eq!(line, Ok(format!("m f={}i", value)));
+        } else {
+            prop_assert_eq!(line, Err(Error::InvalidFieldValue));
+        }
+    }
+
+    #[test]
+    fn parsing_never_panics(line in "\\PC*") {
+        let _ = Point::parse(&line);
+    }
+}